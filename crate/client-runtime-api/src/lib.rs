@@ -12,8 +12,8 @@ use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
 
 use selendra_primitives::{
 	AccountId, ApiError as AlephApiError, AuraId, AuthorityId as AlephId, Balance, Block, Nonce,
-	SessionAuthorityData, SessionCommittee, SessionIndex, SessionValidatorError,
-	Version as FinalityVersion,
+	SessionAuthorityData, SessionCommittee, SessionIndex, SessionPeriodHistory,
+	SessionValidatorError, Version as FinalityVersion,
 };
 
 use fp_rpc::TransactionStatus;
@@ -155,6 +155,10 @@ pub mod mock_runtime {
 				unimplemented!()
 			}
 
+			fn session_period_history() -> SessionPeriodHistory {
+				unimplemented!()
+			}
+
 			fn authorities() -> Vec<AlephId> {
 				unimplemented!()
 			}
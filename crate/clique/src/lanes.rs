@@ -0,0 +1,299 @@
+use std::{
+	collections::VecDeque,
+	fmt::{Display, Error as FmtError, Formatter},
+	sync::{Arc, Mutex, MutexGuard},
+};
+
+use log::trace;
+use tokio::sync::Notify;
+
+use crate::{metrics::Metrics, LOG_TARGET};
+
+/// How urgently data should be delivered to a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Priority {
+	/// Small, latency-critical messages.
+	High,
+	/// The default. When the connection cannot keep up, new messages are rejected once the
+	/// queue is full, instead of waiting without limit.
+	Normal,
+}
+
+const PRIORITIES: [Priority; 2] = [Priority::High, Priority::Normal];
+
+impl Priority {
+	fn index(&self) -> usize {
+		match self {
+			Priority::High => 0,
+			Priority::Normal => 1,
+		}
+	}
+
+	/// How many messages of this priority are sent in a single scheduling round, if there are
+	/// messages of other priorities waiting as well.
+	fn weight(&self) -> usize {
+		match self {
+			Priority::High => 8,
+			Priority::Normal => 4,
+		}
+	}
+
+	/// How many messages of this priority can wait to be sent through a single connection.
+	fn capacity(&self) -> usize {
+		match self {
+			Priority::High => 1024,
+			Priority::Normal => 4096,
+		}
+	}
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Priority::High => "high",
+			Priority::Normal => "normal",
+		}
+	}
+}
+
+/// Error when queueing data to be sent through a connection.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LaneError {
+	/// The connection is closed.
+	Closed,
+	/// Too much data of this priority is already waiting.
+	Full(Priority),
+}
+
+impl Display for LaneError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use LaneError::*;
+		match self {
+			Closed => write!(f, "connection closed"),
+			Full(priority) => write!(f, "{} priority queue full", priority.label()),
+		}
+	}
+}
+
+struct Lanes<D> {
+	queues: [VecDeque<D>; 2],
+	credits: [usize; 2],
+	sender_alive: bool,
+	receiver_alive: bool,
+	metrics: Metrics,
+}
+
+impl<D> Lanes<D> {
+	fn push(&mut self, data: D, priority: Priority) -> Result<(), LaneError> {
+		if !self.receiver_alive {
+			return Err(LaneError::Closed);
+		}
+		let queue = &mut self.queues[priority.index()];
+		if queue.len() >= priority.capacity() {
+			trace!(target: LOG_TARGET, "Rejecting a {} priority message.", priority.label());
+			self.metrics.report_dropped(priority);
+			return Err(LaneError::Full(priority));
+		}
+		queue.push_back(data);
+		self.metrics.report_enqueued(priority);
+		Ok(())
+	}
+
+	/// Weighted round robin: every priority gets to send as many messages as its weight in a
+	/// round, higher priorities first. A new round starts once no waiting message has credits left.
+	fn pop(&mut self) -> Option<D> {
+		if self.queues.iter().all(VecDeque::is_empty) {
+			return None;
+		}
+		let priority = match PRIORITIES.iter().find(|priority| self.can_send(priority)) {
+			Some(priority) => *priority,
+			None => {
+				self.credits = PRIORITIES.map(|priority| priority.weight());
+				*PRIORITIES.iter().find(|priority| self.can_send(priority))?
+			},
+		};
+		self.credits[priority.index()] -= 1;
+		self.metrics.report_dequeued(priority);
+		self.queues[priority.index()].pop_front()
+	}
+
+	fn can_send(&self, priority: &Priority) -> bool {
+		self.credits[priority.index()] > 0 && !self.queues[priority.index()].is_empty()
+	}
+}
+
+impl<D> Drop for Lanes<D> {
+	fn drop(&mut self) {
+		for priority in PRIORITIES {
+			for _ in 0..self.queues[priority.index()].len() {
+				self.metrics.report_dequeued(priority);
+			}
+		}
+	}
+}
+
+struct Shared<D> {
+	lanes: Mutex<Lanes<D>>,
+	notify: Notify,
+}
+
+impl<D> Shared<D> {
+	fn lanes(&self) -> MutexGuard<'_, Lanes<D>> {
+		// The lanes stay consistent even if a holder of the lock panicked.
+		self.lanes.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+/// Queues data to be sent through a single connection. The connection gets closed when this is
+/// dropped.
+pub struct LaneSender<D> {
+	shared: Arc<Shared<D>>,
+}
+
+impl<D> LaneSender<D> {
+	pub fn send(&self, data: D, priority: Priority) -> Result<(), LaneError> {
+		self.shared.lanes().push(data, priority)?;
+		self.shared.notify.notify_one();
+		Ok(())
+	}
+
+	/// Whether the connection is closed.
+	pub fn is_closed(&self) -> bool {
+		!self.shared.lanes().receiver_alive
+	}
+}
+
+impl<D> Drop for LaneSender<D> {
+	fn drop(&mut self) {
+		self.shared.lanes().sender_alive = false;
+		self.shared.notify.notify_one();
+	}
+}
+
+/// Takes the data to be sent through a single connection out of the queues, in the order given by
+/// the weighted scheduling.
+pub struct LaneReceiver<D> {
+	shared: Arc<Shared<D>>,
+}
+
+impl<D> LaneReceiver<D> {
+	/// The next message to send, or `None` if the sender is gone and all the queues are drained.
+	/// This method is cancellation safe.
+	pub async fn next(&mut self) -> Option<D> {
+		loop {
+			{
+				let mut lanes = self.shared.lanes();
+				if let Some(data) = lanes.pop() {
+					return Some(data);
+				}
+				if !lanes.sender_alive {
+					return None;
+				}
+			}
+			self.shared.notify.notified().await;
+		}
+	}
+}
+
+impl<D> Drop for LaneReceiver<D> {
+	fn drop(&mut self) {
+		self.shared.lanes().receiver_alive = false;
+	}
+}
+
+/// Creates the priority lanes of a single connection.
+pub fn channel<D>(metrics: Metrics) -> (LaneSender<D>, LaneReceiver<D>) {
+	let shared = Arc::new(Shared {
+		lanes: Mutex::new(Lanes {
+			queues: Default::default(),
+			credits: [0; 2],
+			sender_alive: true,
+			receiver_alive: true,
+			metrics,
+		}),
+		notify: Notify::new(),
+	});
+	(LaneSender { shared: shared.clone() }, LaneReceiver { shared })
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::FutureExt;
+	use prometheus_endpoint::Registry;
+
+	use super::{channel, LaneError, Priority};
+	use crate::metrics::Metrics;
+
+	fn dropped(registry: &Registry, priority: Priority) -> f64 {
+		registry
+			.gather()
+			.iter()
+			.filter(|family| family.get_name() == "clique_network_dropped_messages")
+			.flat_map(|family| family.get_metric().to_vec())
+			.find(|metric| {
+				metric.get_label().iter().any(|label| label.get_value() == priority.label())
+			})
+			.map(|metric| metric.get_counter().get_value())
+			.unwrap_or_default()
+	}
+
+	#[tokio::test]
+	async fn weighs_priorities() {
+		let (sender, mut receiver) = channel(Metrics::noop());
+		for i in 0..20 {
+			sender.send(("normal", i), Priority::Normal).expect("should queue");
+			sender.send(("high", i), Priority::High).expect("should queue");
+		}
+		let mut round = Vec::new();
+		for _ in 0..12 {
+			round.push(receiver.next().await.expect("should receive").0);
+		}
+		let mut expected = vec!["high"; 8];
+		expected.extend(["normal"; 4]);
+		assert_eq!(round, expected);
+		assert_eq!(receiver.next().await, Some(("high", 8)), "a new round should start");
+	}
+
+	#[tokio::test]
+	async fn sends_lower_priorities_when_higher_are_empty() {
+		let (sender, mut receiver) = channel(Metrics::noop());
+		for i in 0..10 {
+			sender.send(i, Priority::Normal).expect("should queue");
+		}
+		for i in 0..10 {
+			assert_eq!(receiver.next().await, Some(i));
+		}
+		assert!(receiver.next().now_or_never().is_none(), "nothing should be waiting");
+	}
+
+	#[tokio::test]
+	async fn rejects_messages_when_full() {
+		let registry = Registry::new();
+		let metrics = Metrics::new(Some(registry.clone())).expect("the metrics should register");
+		let (sender, mut receiver) = channel(metrics);
+		for i in 0..Priority::Normal.capacity() {
+			sender.send(i, Priority::Normal).expect("should queue");
+		}
+		assert_eq!(sender.send(0, Priority::Normal), Err(LaneError::Full(Priority::Normal)));
+		for i in 0..Priority::High.capacity() {
+			sender.send(i, Priority::High).expect("should queue");
+		}
+		assert_eq!(sender.send(0, Priority::High), Err(LaneError::Full(Priority::High)));
+		assert_eq!(dropped(&registry, Priority::Normal), 1.0);
+		assert_eq!(dropped(&registry, Priority::High), 1.0);
+		assert_eq!(receiver.next().await, Some(0), "queued messages should stay intact");
+	}
+
+	#[tokio::test]
+	async fn closes_both_ways() {
+		let (sender, mut receiver) = channel(Metrics::noop());
+		sender.send(1, Priority::Normal).expect("should queue");
+		drop(sender);
+		assert_eq!(receiver.next().await, Some(1), "queued data should still be delivered");
+		assert_eq!(receiver.next().await, None);
+
+		let (sender, receiver) = channel(Metrics::noop());
+		assert!(!sender.is_closed());
+		drop(receiver);
+		assert!(sender.is_closed());
+		assert_eq!(sender.send(1, Priority::Normal), Err(LaneError::Closed));
+	}
+}
@@ -12,6 +12,7 @@ use tokio::io::{AsyncRead, AsyncWrite};
 mod crypto;
 mod incoming;
 mod io;
+mod lanes;
 mod manager;
 pub mod metrics;
 pub mod mock;
@@ -23,6 +24,8 @@ mod service;
 mod testing;
 
 pub use crypto::{PublicKey, SecretKey};
+pub use lanes::Priority;
+pub use manager::ManagerStatus;
 pub use rate_limiting::{RateLimitingDialer, RateLimitingListener};
 pub use service::{Service, SpawnHandleExt, SpawnHandleT};
 
@@ -87,9 +90,10 @@ pub trait Network<PK: PublicKey, A: Data, D: Data>: Send + 'static {
 	/// Remove the peer from the set of connected peers and close the connection.
 	fn remove_connection(&mut self, peer: PK);
 
-	/// Send a message to a single peer.
+	/// Send a message to a single peer. Messages of higher priority overtake the ones of lower
+	/// priority waiting to be sent to the same peer.
 	/// This function should be implemented in a non-blocking manner.
-	fn send(&self, data: D, recipient: PK);
+	fn send(&self, data: D, recipient: PK, priority: Priority);
 
 	/// Receive a message from the network.
 	/// This method's implementation must be cancellation safe.
@@ -3,9 +3,11 @@ use std::{
 	fmt::{Display, Error as FmtError, Formatter},
 };
 
-use futures::channel::mpsc;
-
-use crate::{metrics::Metrics, Data, PeerId, PublicKey};
+use crate::{
+	lanes::{LaneError, LaneSender},
+	metrics::Metrics,
+	Data, PeerId, Priority, PublicKey,
+};
 
 mod direction;
 use direction::DirectedPeers;
@@ -17,6 +19,8 @@ pub enum SendError {
 	ConnectionClosed,
 	/// Peer not added to the manager
 	PeerNotFound,
+	/// Too much data of this priority waiting for the peer
+	QueueFull(Priority),
 }
 
 impl Display for SendError {
@@ -25,6 +29,7 @@ impl Display for SendError {
 		match self {
 			ConnectionClosed => write!(f, "worker dead"),
 			PeerNotFound => write!(f, "peer not found"),
+			QueueFull(priority) => write!(f, "{} priority queue full", priority.label()),
 		}
 	}
 }
@@ -40,6 +45,8 @@ pub enum AddResult {
 	Replaced,
 }
 
+/// Which of the peers we want to be connected to actually are.
+#[derive(Clone)]
 pub struct ManagerStatus<PK: PublicKey + PeerId> {
 	outgoing_peers: HashSet<PK>,
 	missing_outgoing: HashSet<PK>,
@@ -69,6 +76,16 @@ impl<PK: PublicKey + PeerId> ManagerStatus<PK> {
 		ManagerStatus { incoming_peers, missing_incoming, outgoing_peers, missing_outgoing }
 	}
 
+	/// Whether we have a working connection with the peer.
+	pub fn is_connected(&self, peer_id: &PK) -> bool {
+		self.incoming_peers.contains(peer_id) || self.outgoing_peers.contains(peer_id)
+	}
+
+	/// How many peers we want to be connected to, whether we are or not.
+	pub fn wanted_peers(&self) -> usize {
+		self.wanted_incoming() + self.wanted_outgoing()
+	}
+
 	fn wanted_incoming(&self) -> usize {
 		self.incoming_peers.len() + self.missing_incoming.len()
 	}
@@ -153,7 +170,7 @@ pub struct Manager<PK: PublicKey + PeerId, A: Data, D: Data> {
 	// Which peers we want to be connected with, and which way.
 	wanted: DirectedPeers<PK, A>,
 	// This peers we are connected with. We ensure that this is always a subset of what we want.
-	have: HashMap<PK, mpsc::UnboundedSender<D>>,
+	have: HashMap<PK, LaneSender<D>>,
 }
 
 impl<PK: PublicKey + PeerId, A: Data, D: Data> Manager<PK, A, D> {
@@ -181,11 +198,7 @@ impl<PK: PublicKey + PeerId, A: Data, D: Data> Manager<PK, A, D> {
 	}
 
 	/// Add an established connection with a known peer, but only if the peer is among the peers we want to be connected to.
-	pub fn add_connection(
-		&mut self,
-		peer_id: PK,
-		data_for_network: mpsc::UnboundedSender<D>,
-	) -> AddResult {
+	pub fn add_connection(&mut self, peer_id: PK, data_for_network: LaneSender<D>) -> AddResult {
 		use AddResult::*;
 		if !self.wanted.interested(&peer_id) {
 			return Uninterested;
@@ -206,12 +219,15 @@ impl<PK: PublicKey + PeerId, A: Data, D: Data> Manager<PK, A, D> {
 	/// Send data to a peer.
 	/// Returns error if there is no outgoing connection to the peer,
 	/// or if the connection is dead.
-	pub fn send_to(&mut self, peer_id: &PK, data: D) -> Result<(), SendError> {
+	pub fn send_to(&mut self, peer_id: &PK, data: D, priority: Priority) -> Result<(), SendError> {
 		self.have
 			.get(peer_id)
 			.ok_or(SendError::PeerNotFound)?
-			.unbounded_send(data)
-			.map_err(|_| SendError::ConnectionClosed)
+			.send(data, priority)
+			.map_err(|e| match e {
+				LaneError::Closed => SendError::ConnectionClosed,
+				LaneError::Full(priority) => SendError::QueueFull(priority),
+			})
 	}
 
 	/// A status of the manager, to be displayed somewhere.
@@ -226,12 +242,12 @@ impl<PK: PublicKey + PeerId, A: Data, D: Data> Manager<PK, A, D> {
 
 #[cfg(test)]
 mod tests {
-	use futures::{channel::mpsc, StreamExt};
-
 	use super::{AddResult::*, Manager, SendError};
 	use crate::{
+		lanes,
 		metrics::Metrics,
 		mock::{key, MockPublicKey},
+		Priority,
 	};
 
 	type Data = String;
@@ -275,12 +291,12 @@ mod tests {
 			Manager::<MockPublicKey, Address, Data>::new(listening_id.clone(), Metrics::noop());
 		let data = String::from("DATA");
 		let address = String::from("43.43.43.43:43000");
-		let (tx, _rx) = mpsc::unbounded();
+		let (tx, _rx) = lanes::channel(Metrics::noop());
 		// try add unknown peer
 		assert_eq!(connecting_manager.add_connection(listening_id.clone(), tx), Uninterested);
 		// sending should fail
 		assert_eq!(
-			connecting_manager.send_to(&listening_id, data.clone()),
+			connecting_manager.send_to(&listening_id, data.clone(), Priority::Normal),
 			Err(SendError::PeerNotFound)
 		);
 		// add peer, this time for real
@@ -294,16 +310,20 @@ mod tests {
 			assert!(connecting_manager.add_peer(listening_id.clone(), address.clone()));
 		}
 		// add outgoing to connecting
-		let (tx, mut rx) = mpsc::unbounded();
+		let (tx, mut rx) = lanes::channel(Metrics::noop());
 		assert_eq!(connecting_manager.add_connection(listening_id.clone(), tx), Added);
 		// send and receive connecting
-		assert!(connecting_manager.send_to(&listening_id, data.clone()).is_ok());
+		assert!(connecting_manager
+			.send_to(&listening_id, data.clone(), Priority::Normal)
+			.is_ok());
 		assert_eq!(data, rx.next().await.expect("should receive"));
 		// add incoming to listening
-		let (tx, mut rx) = mpsc::unbounded();
+		let (tx, mut rx) = lanes::channel(Metrics::noop());
 		assert_eq!(listening_manager.add_connection(connecting_id.clone(), tx), Added);
 		// send and receive listening
-		assert!(listening_manager.send_to(&connecting_id, data.clone()).is_ok());
+		assert!(listening_manager
+			.send_to(&connecting_id, data.clone(), Priority::Normal)
+			.is_ok());
 		assert_eq!(data, rx.next().await.expect("should receive"));
 		// remove peer
 		listening_manager.remove_peer(&connecting_id);
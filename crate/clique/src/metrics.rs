@@ -1,4 +1,10 @@
-use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
+use std::fmt::Display;
+
+use prometheus_endpoint::{
+	register, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64,
+};
+
+use crate::Priority;
 
 #[derive(Clone)]
 pub enum Metrics {
@@ -7,10 +13,32 @@ pub enum Metrics {
 		missing_incoming_connections: Gauge<U64>,
 		outgoing_connections: Gauge<U64>,
 		missing_outgoing_connections: Gauge<U64>,
+		bytes_sent: CounterVec<U64>,
+		bytes_received: CounterVec<U64>,
+		queue_depth: GaugeVec<U64>,
+		dropped_messages: CounterVec<U64>,
 	},
 	Noop,
 }
 
+/// Kinds of messages exchanged over a connection, counted separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+	Data,
+	Heartbeat,
+}
+
+impl MessageKind {
+	const ALL: [MessageKind; 2] = [MessageKind::Data, MessageKind::Heartbeat];
+
+	fn label(&self) -> &'static str {
+		match self {
+			MessageKind::Data => "data",
+			MessageKind::Heartbeat => "heartbeat",
+		}
+	}
+}
+
 pub enum Event {
 	NewOutgoing,
 	NewIncoming,
@@ -54,6 +82,40 @@ impl Metrics {
 					)?,
 					&registry,
 				)?,
+				bytes_sent: register(
+					CounterVec::new(
+						Opts::new("clique_network_bytes_sent", "bytes sent to a peer"),
+						&["peer", "message"],
+					)?,
+					&registry,
+				)?,
+				bytes_received: register(
+					CounterVec::new(
+						Opts::new("clique_network_bytes_received", "bytes received from a peer"),
+						&["peer", "message"],
+					)?,
+					&registry,
+				)?,
+				queue_depth: register(
+					GaugeVec::new(
+						Opts::new(
+							"clique_network_queue_depth",
+							"messages waiting to be sent, across all connections",
+						),
+						&["priority"],
+					)?,
+					&registry,
+				)?,
+				dropped_messages: register(
+					CounterVec::new(
+						Opts::new(
+							"clique_network_dropped_messages",
+							"messages dropped because too many were waiting to be sent",
+						),
+						&["priority"],
+					)?,
+					&registry,
+				)?,
 			}),
 			None => Ok(Metrics::Noop),
 		}
@@ -70,6 +132,7 @@ impl Metrics {
 			outgoing_connections,
 			missing_incoming_connections,
 			missing_outgoing_connections,
+			..
 		} = self
 		{
 			match event {
@@ -96,4 +159,86 @@ impl Metrics {
 			}
 		}
 	}
+
+	/// Counts bytes of a message of the given kind sent to the peer.
+	pub fn report_bytes_sent(&self, peer: &impl Display, kind: MessageKind, bytes: usize) {
+		if let Metrics::Prometheus { bytes_sent, .. } = self {
+			bytes_sent
+				.with_label_values(&[&peer.to_string(), kind.label()])
+				.inc_by(bytes as u64);
+		}
+	}
+
+	/// Counts bytes of a message of the given kind received from the peer.
+	pub fn report_bytes_received(&self, peer: &impl Display, kind: MessageKind, bytes: usize) {
+		if let Metrics::Prometheus { bytes_received, .. } = self {
+			bytes_received
+				.with_label_values(&[&peer.to_string(), kind.label()])
+				.inc_by(bytes as u64);
+		}
+	}
+
+	/// Removes the counters of the peer, should be called once the connection with it ends.
+	pub fn remove_peer(&self, peer: &impl Display) {
+		if let Metrics::Prometheus { bytes_sent, bytes_received, .. } = self {
+			let peer = peer.to_string();
+			for kind in MessageKind::ALL {
+				let _ = bytes_sent.remove_label_values(&[&peer, kind.label()]);
+				let _ = bytes_received.remove_label_values(&[&peer, kind.label()]);
+			}
+		}
+	}
+
+	pub fn report_enqueued(&self, priority: Priority) {
+		if let Metrics::Prometheus { queue_depth, .. } = self {
+			queue_depth.with_label_values(&[priority.label()]).inc();
+		}
+	}
+
+	pub fn report_dequeued(&self, priority: Priority) {
+		if let Metrics::Prometheus { queue_depth, .. } = self {
+			queue_depth.with_label_values(&[priority.label()]).dec();
+		}
+	}
+
+	pub fn report_dropped(&self, priority: Priority) {
+		if let Metrics::Prometheus { dropped_messages, .. } = self {
+			dropped_messages.with_label_values(&[priority.label()]).inc();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use prometheus_endpoint::Registry;
+
+	use super::{MessageKind, Metrics};
+
+	fn peers_with_counters(registry: &Registry) -> Vec<String> {
+		let mut peers: Vec<_> = registry
+			.gather()
+			.iter()
+			.filter(|family| family.get_name().starts_with("clique_network_bytes"))
+			.flat_map(|family| family.get_metric().to_vec())
+			.flat_map(|metric| metric.get_label().to_vec())
+			.filter(|label| label.get_name() == "peer")
+			.map(|label| label.get_value().to_string())
+			.collect();
+		peers.sort();
+		peers.dedup();
+		peers
+	}
+
+	#[test]
+	fn removes_counters_of_disconnected_peers() {
+		let registry = Registry::new();
+		let metrics = Metrics::new(Some(registry.clone())).expect("the metrics should register");
+		metrics.report_bytes_sent(&"alice", MessageKind::Data, 10);
+		metrics.report_bytes_received(&"alice", MessageKind::Heartbeat, 4);
+		metrics.report_bytes_sent(&"bob", MessageKind::Heartbeat, 4);
+		assert_eq!(peers_with_counters(&registry), ["alice", "bob"]);
+
+		metrics.remove_peer(&"alice");
+		assert_eq!(peers_with_counters(&registry), ["bob"]);
+	}
 }
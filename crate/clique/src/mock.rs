@@ -23,7 +23,7 @@ use tokio::{
 use crate::{
 	protocols::{ProtocolError, ResultForService},
 	AddressingInformation, ConnectionInfo, Data, Dialer, Listener, Network, NetworkIdentity,
-	PeerAddressInfo, PeerId, PublicKey, SecretKey, Splittable, LOG_TARGET,
+	PeerAddressInfo, PeerId, Priority, PublicKey, SecretKey, Splittable, LOG_TARGET,
 };
 
 #[derive(Hash, Debug, Clone, PartialEq, Eq)]
@@ -333,7 +333,7 @@ pub fn random_invalid_address() -> MockAddressingInformation {
 pub struct MockNetwork<D: Data> {
 	pub add_connection: Channel<(MockPublicKey, MockAddressingInformation)>,
 	pub remove_connection: Channel<MockPublicKey>,
-	pub send: Channel<(D, MockPublicKey)>,
+	pub send: Channel<(D, MockPublicKey, Priority)>,
 	pub next: Channel<D>,
 }
 
@@ -347,8 +347,8 @@ impl<D: Data> Network<MockPublicKey, MockAddressingInformation, D> for MockNetwo
 		self.remove_connection.send(peer);
 	}
 
-	fn send(&self, data: D, recipient: MockPublicKey) {
-		self.send.send((data, recipient));
+	fn send(&self, data: D, recipient: MockPublicKey, priority: Priority) {
+		self.send.send((data, recipient, priority));
 	}
 
 	async fn next(&mut self) -> Option<D> {
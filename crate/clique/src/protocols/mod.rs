@@ -4,6 +4,7 @@ use futures::channel::{mpsc, oneshot};
 
 use crate::{
 	io::{ReceiveError, SendError},
+	lanes::LaneSender,
 	metrics::Metrics,
 	Data, PublicKey, SecretKey, Splittable,
 };
@@ -18,9 +19,9 @@ pub use negotiation::{protocol, ProtocolNegotiationError};
 pub type Version = u32;
 
 /// What connections send back to the service after they become established. Starts with a public
-/// key of the remote node, followed by a channel for sending data to that node, with None if the
+/// key of the remote node, followed by the queues of data to send to that node, with None if the
 /// connection was unsuccessful and should be reestablished.
-pub type ResultForService<PK, D> = (PK, Option<mpsc::UnboundedSender<D>>);
+pub type ResultForService<PK, D> = (PK, Option<LaneSender<D>>);
 
 /// Defines the protocol for communication. Currently single variant, but left in case of protocol change.
 #[derive(Debug, PartialEq, Eq)]
@@ -1,7 +1,6 @@
-use futures::{
-	channel::{mpsc, oneshot},
-	StreamExt,
-};
+use std::mem::size_of;
+
+use futures::channel::{mpsc, oneshot};
 use log::{debug, info, trace};
 use parity_scale_codec::{Decode, Encode};
 use tokio::{
@@ -11,7 +10,8 @@ use tokio::{
 
 use crate::{
 	io::{receive_data, send_data},
-	metrics::{Event, Metrics},
+	lanes::{self, LaneReceiver},
+	metrics::{Event, MessageKind, Metrics},
 	protocols::{
 		handshake::{v0_handshake_incoming, v0_handshake_outgoing},
 		ProtocolError, ResultForService,
@@ -28,6 +28,20 @@ enum Message<D: Data> {
 	Heartbeat,
 }
 
+impl<D: Data> Message<D> {
+	/// Bytes the message takes on the wire, including the length prefix.
+	fn wire_size(&self) -> usize {
+		self.encoded_size() + size_of::<u32>()
+	}
+
+	fn kind(&self) -> MessageKind {
+		match self {
+			Message::Data(_) => MessageKind::Data,
+			Message::Heartbeat => MessageKind::Heartbeat,
+		}
+	}
+}
+
 async fn check_authorization<SK: SecretKey>(
 	authorization_requests_sender: mpsc::UnboundedSender<(SK::PublicKey, oneshot::Sender<bool>)>,
 	public_key: SK::PublicKey,
@@ -41,7 +55,9 @@ async fn check_authorization<SK: SecretKey>(
 
 async fn sending<PK: PublicKey, D: Data, S: AsyncWrite + Unpin + Send>(
 	mut sender: S,
-	mut data_from_user: mpsc::UnboundedReceiver<D>,
+	mut data_from_user: LaneReceiver<D>,
+	public_key: &PK,
+	metrics: &Metrics,
 ) -> Result<(), ProtocolError<PK>> {
 	use Message::*;
 	loop {
@@ -53,15 +69,19 @@ async fn sending<PK: PublicKey, D: Data, S: AsyncWrite + Unpin + Send>(
 			},
 			_ => Heartbeat,
 		};
+		let (kind, size) = (to_send.kind(), to_send.wire_size());
 		sender = timeout(MAX_MISSED_HEARTBEATS * HEARTBEAT_TIMEOUT, send_data(sender, to_send))
 			.await
 			.map_err(|_| ProtocolError::SendTimeout)??;
+		metrics.report_bytes_sent(public_key, kind, size);
 	}
 }
 
 async fn receiving<PK: PublicKey, D: Data, S: AsyncRead + Unpin + Send>(
 	mut stream: S,
 	data_for_user: mpsc::UnboundedSender<D>,
+	public_key: &PK,
+	metrics: &Metrics,
 ) -> Result<(), ProtocolError<PK>> {
 	use Message::*;
 	loop {
@@ -70,6 +90,7 @@ async fn receiving<PK: PublicKey, D: Data, S: AsyncRead + Unpin + Send>(
 				.await
 				.map_err(|_| ProtocolError::CardiacArrest)??;
 		stream = old_stream;
+		metrics.report_bytes_received(public_key, message.kind(), message.wire_size());
 		match message {
 			Data(data) => data_for_user
 				.unbounded_send(data)
@@ -87,11 +108,13 @@ async fn manage_connection<
 >(
 	sender: S,
 	receiver: R,
-	data_from_user: mpsc::UnboundedReceiver<D>,
+	data_from_user: LaneReceiver<D>,
 	data_for_user: mpsc::UnboundedSender<D>,
+	public_key: &PK,
+	metrics: &Metrics,
 ) -> Result<(), ProtocolError<PK>> {
-	let sending = sending(sender, data_from_user);
-	let receiving = receiving(receiver, data_for_user);
+	let sending = sending(sender, data_from_user, public_key, metrics);
+	let receiving = receiving(receiver, data_for_user, public_key, metrics);
 	tokio::select! {
 		result = receiving => result,
 		result = sending => result,
@@ -115,7 +138,7 @@ pub async fn outgoing<SK: SecretKey, D: Data, S: Splittable>(
 		target: LOG_TARGET,
 		"Outgoing handshake with {} finished successfully.", public_key
 	);
-	let (data_for_network, data_from_user) = mpsc::unbounded();
+	let (data_for_network, data_from_user) = lanes::channel(metrics.clone());
 	result_for_parent
 		.unbounded_send((public_key.clone(), Some(data_for_network)))
 		.map_err(|_| ProtocolError::NoParentConnection)?;
@@ -125,8 +148,11 @@ pub async fn outgoing<SK: SecretKey, D: Data, S: Splittable>(
 		target: LOG_TARGET,
 		"Starting worker for communicating with {}.", public_key
 	);
-	let result = manage_connection(sender, receiver, data_from_user, data_for_user).await;
+	let result =
+		manage_connection(sender, receiver, data_from_user, data_for_user, &public_key, &metrics)
+			.await;
 	metrics.report_event(DisconnectedOutgoing);
+	metrics.remove_peer(&public_key);
 	result
 }
 
@@ -153,7 +179,7 @@ pub async fn incoming<SK: SecretKey, D: Data, S: Splittable>(
 		return Err(ProtocolError::NotAuthorized);
 	}
 
-	let (data_for_network, data_from_user) = mpsc::unbounded();
+	let (data_for_network, data_from_user) = lanes::channel(metrics.clone());
 	result_for_parent
 		.unbounded_send((public_key.clone(), Some(data_for_network)))
 		.map_err(|_| ProtocolError::NoParentConnection)?;
@@ -162,8 +188,11 @@ pub async fn incoming<SK: SecretKey, D: Data, S: Splittable>(
 		target: LOG_TARGET,
 		"Starting worker for communicating with {}.", public_key
 	);
-	let result = manage_connection(sender, receiver, data_from_user, data_for_user).await;
+	let result =
+		manage_connection(sender, receiver, data_from_user, data_for_user, &public_key, &metrics)
+			.await;
 	metrics.report_event(DisconnectedIncoming);
+	metrics.remove_peer(&public_key);
 	result
 }
 
@@ -181,7 +210,7 @@ mod tests {
 			v1::{incoming, outgoing},
 			ProtocolError,
 		},
-		Data,
+		Data, Priority,
 	};
 
 	fn prepare<D: Data>() -> MockPrelims<D> {
@@ -283,10 +312,10 @@ mod tests {
 				let (_, maybe_data_for_outgoing) = result.expect("the channel shouldn't be dropped");
 				let data_for_outgoing = maybe_data_for_outgoing.expect("successfully connected");
 				data_for_outgoing
-					.unbounded_send(vec![4, 3, 43])
+					.send(vec![4, 3, 43], Priority::Normal)
 					.expect("should send");
 				data_for_outgoing
-					.unbounded_send(vec![2, 1, 3, 7])
+					.send(vec![2, 1, 3, 7], Priority::Normal)
 					.expect("should send");
 				data_for_outgoing
 			},
@@ -298,10 +327,10 @@ mod tests {
 				let (_, maybe_data_for_incoming) = result.expect("the channel shouldn't be dropped");
 				let data_for_incoming = maybe_data_for_incoming.expect("successfully connected");
 				data_for_incoming
-					.unbounded_send(vec![5, 4, 44])
+					.send(vec![5, 4, 44], Priority::Normal)
 					.expect("should send");
 				data_for_incoming
-					.unbounded_send(vec![3, 2, 4, 8])
+					.send(vec![3, 2, 4, 8], Priority::Normal)
 					.expect("should send");
 				data_for_incoming
 			},
@@ -419,7 +448,7 @@ mod tests {
 				let (_, maybe_data_for_outgoing) = result.expect("the channel shouldn't be dropped");
 				let data_for_outgoing = maybe_data_for_outgoing.expect("successfully connected");
 				data_for_outgoing
-					.unbounded_send(vec![2, 1, 3, 7])
+					.send(vec![2, 1, 3, 7], Priority::Normal)
 					.expect("should send");
 				data_for_outgoing
 			},
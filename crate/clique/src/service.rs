@@ -17,11 +17,12 @@ use tokio::time;
 
 use crate::{
 	incoming::incoming,
-	manager::{AddResult, Manager},
+	lanes::LaneSender,
+	manager::{AddResult, Manager, ManagerStatus, SendError},
 	metrics::Metrics,
 	outgoing::outgoing,
 	protocols::ResultForService,
-	Data, Dialer, Listener, Network, PeerId, PublicKey, SecretKey, LOG_TARGET,
+	Data, Dialer, Listener, Network, PeerId, Priority, PublicKey, SecretKey, LOG_TARGET,
 };
 
 const STATUS_REPORT_INTERVAL: Duration = Duration::from_secs(20);
@@ -29,7 +30,7 @@ const STATUS_REPORT_INTERVAL: Duration = Duration::from_secs(20);
 enum ServiceCommand<PK: PublicKey, D: Data, A: Data> {
 	AddConnection(PK, A),
 	DelConnection(PK),
-	SendData(D, PK),
+	SendData(D, PK, Priority),
 }
 
 struct ServiceInterface<PK: PublicKey, D: Data, A: Data> {
@@ -63,10 +64,10 @@ impl<PK: PublicKey, D: Data, A: Data> Network<PK, A, D> for ServiceInterface<PK,
 
 	/// Send a message to a single peer.
 	/// This function should be implemented in a non-blocking manner.
-	fn send(&self, data: D, recipient: PK) {
+	fn send(&self, data: D, recipient: PK, priority: Priority) {
 		if self
 			.commands_for_service
-			.unbounded_send(ServiceCommand::SendData(data, recipient))
+			.unbounded_send(ServiceCommand::SendData(data, recipient, priority))
 			.is_err()
 		{
 			info!(target: LOG_TARGET, "Service is dead.");
@@ -148,6 +149,7 @@ where
 	spawn_handle: SH,
 	secret_key: SK,
 	metrics: Metrics,
+	status_subscribers: Vec<UnboundedSender<ManagerStatus<SK::PublicKey>>>,
 }
 
 impl<SK: SecretKey, D: Data, A: Data + Debug, ND: Dialer<A>, NL: Listener, SH: SpawnHandleT>
@@ -184,11 +186,26 @@ where
 				spawn_handle,
 				secret_key,
 				metrics,
+				status_subscribers: Vec::new(),
 			},
 			ServiceInterface { commands_for_service, next_from_service },
 		)
 	}
 
+	/// Returns a stream of the periodic status reports of the network.
+	pub fn status_updates(&mut self) -> mpsc::UnboundedReceiver<ManagerStatus<SK::PublicKey>> {
+		let (status_for_subscriber, status_from_service) = mpsc::unbounded();
+		self.status_subscribers.push(status_for_subscriber);
+		status_from_service
+	}
+
+	fn report_status(&mut self) {
+		let status = self.manager.status_report();
+		info!(target: LOG_TARGET, "Clique Network status: {}", status);
+		self.status_subscribers
+			.retain(|subscriber| subscriber.unbounded_send(status.clone()).is_ok());
+	}
+
 	fn spawn_new_outgoing(
 		&mut self,
 		public_key: SK::PublicKey,
@@ -245,7 +262,7 @@ where
 	fn add_connection(
 		&mut self,
 		public_key: SK::PublicKey,
-		data_for_network: mpsc::UnboundedSender<D>,
+		data_for_network: LaneSender<D>,
 	) -> AddResult {
 		self.manager.add_connection(public_key, data_for_network)
 	}
@@ -253,10 +270,7 @@ where
 	fn handle_command(
 		&mut self,
 		command: ServiceCommand<<SK as SecretKey>::PublicKey, D, A>,
-		result_for_parent: &UnboundedSender<(
-			<SK as SecretKey>::PublicKey,
-			Option<UnboundedSender<D>>,
-		)>,
+		result_for_parent: &UnboundedSender<ResultForService<SK::PublicKey, D>>,
 	) {
 		use ServiceCommand::*;
 		match command {
@@ -272,14 +286,22 @@ where
 				self.manager.remove_peer(&public_key);
 			},
 			// pass the data to the manager
-			SendData(data, public_key) => match self.manager.send_to(&public_key, data) {
-				Ok(_) => trace!(target: LOG_TARGET, "Sending data to {}.", public_key),
-				Err(e) => trace!(
-					target: LOG_TARGET,
-					"Failed sending to {}: {}",
-					public_key,
-					e
-				),
+			SendData(data, public_key, priority) => {
+				match self.manager.send_to(&public_key, data, priority) {
+					Ok(_) => trace!(target: LOG_TARGET, "Sending data to {}.", public_key),
+					Err(e @ SendError::QueueFull(_)) => warn!(
+						target: LOG_TARGET,
+						"Dropping data for {}: {}.",
+						public_key,
+						e
+					),
+					Err(e) => trace!(
+						target: LOG_TARGET,
+						"Failed sending to {}: {}",
+						public_key,
+						e
+					),
+				}
 			},
 		}
 	}
@@ -301,11 +323,8 @@ where
 	fn handle_data_for_network(
 		&mut self,
 		public_key: <SK as SecretKey>::PublicKey,
-		maybe_data_for_network: Option<UnboundedSender<D>>,
-		result_for_parent: &UnboundedSender<(
-			<SK as SecretKey>::PublicKey,
-			Option<UnboundedSender<D>>,
-		)>,
+		maybe_data_for_network: Option<LaneSender<D>>,
+		result_for_parent: &UnboundedSender<ResultForService<SK::PublicKey, D>>,
 	) {
 		use AddResult::*;
 		match maybe_data_for_network {
@@ -361,9 +380,7 @@ where
 					self.handle_data_for_network(public_key, maybe_data_for_network, &result_for_parent);
 				},
 				// periodically reporting what we are trying to do
-				_ = status_ticker.tick() => {
-					info!(target: LOG_TARGET, "Clique Network status: {}", self.manager.status_report());
-				}
+				_ = status_ticker.tick() => self.report_status(),
 				// received exit signal, stop the network
 				// all workers will be killed automatically after the manager gets dropped
 				_ = &mut exit => break,
@@ -19,7 +19,7 @@ use crate::{
 		UnreliableConnectionMaker,
 	},
 	service::SpawnHandleT,
-	Network, SecretKey, Service,
+	Network, Priority, SecretKey, Service,
 };
 
 impl SpawnHandleT for Spawner {
@@ -83,7 +83,7 @@ fn spawn_peer(
 					// choose a peer
 					let peer: MockPublicKey = peer_ids[thread_rng().gen_range(0..peer_ids.len())].clone();
 					// send
-					interface.send(data, peer);
+					interface.send(data, peer, Priority::Normal);
 				},
 				data = interface.next() => {
 					// receive the message
@@ -0,0 +1,402 @@
+use std::{collections::VecDeque, time::Duration};
+
+use current_aleph_bft::{Network, Recipient};
+use parking_lot::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+use crate::{
+	block::UnverifiedHeader,
+	data_io::AlephData,
+	testing::byzantine::{Behaviour, ByzantineDataProvider},
+};
+
+enum Sending {
+	Honest,
+	Withhold,
+	Delay(Duration),
+}
+
+/// Wraps the network of an AlephBFT member, making it withhold or delay everything it sends
+/// according to the behaviour.
+///
+/// Delayed messages are sent out while the member waits for incoming ones, which AlephBFT does
+/// all the time.
+pub struct ByzantineNetwork<D, N> {
+	inner: N,
+	sending: Sending,
+	delayed: Mutex<VecDeque<(Instant, D, Recipient)>>,
+}
+
+impl<D: current_aleph_bft::Data, N: Network<D>> ByzantineNetwork<D, N> {
+	pub fn new<UH: UnverifiedHeader>(inner: N, behaviour: &Behaviour<UH>) -> Self {
+		let sending = match behaviour {
+			Behaviour::WithholdUnits => Sending::Withhold,
+			Behaviour::DelaySignatures(delay) => Sending::Delay(*delay),
+			_ => Sending::Honest,
+		};
+		ByzantineNetwork { inner, sending, delayed: Mutex::new(VecDeque::new()) }
+	}
+
+	/// Sends the delayed messages that are due, returning when the next one will be.
+	fn send_due(&self) -> Option<Instant> {
+		let mut delayed = self.delayed.lock();
+		let now = Instant::now();
+		while let Some((due, _, _)) = delayed.front() {
+			if *due > now {
+				return Some(*due);
+			}
+			let (_, data, recipient) = delayed.pop_front().expect("there is a front");
+			self.inner.send(data, recipient);
+		}
+		None
+	}
+}
+
+#[async_trait::async_trait]
+impl<D: current_aleph_bft::Data, N: Network<D>> Network<D> for ByzantineNetwork<D, N> {
+	fn send(&self, data: D, recipient: Recipient) {
+		match self.sending {
+			Sending::Honest => self.inner.send(data, recipient),
+			Sending::Withhold => {},
+			Sending::Delay(delay) => {
+				self.delayed.lock().push_back((Instant::now() + delay, data, recipient))
+			},
+		}
+	}
+
+	async fn next_event(&mut self) -> Option<D> {
+		loop {
+			let next_due = self.send_due();
+			tokio::select! {
+				event = self.inner.next_event() => return event,
+				_ = sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {},
+			}
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<UH: UnverifiedHeader> current_aleph_bft::DataProvider<AlephData<UH>>
+	for ByzantineDataProvider<UH>
+{
+	async fn get_data(&mut self) -> Option<AlephData<UH>> {
+		ByzantineDataProvider::get_data(self).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{sync::Arc, time::Duration};
+
+	use current_aleph_bft::{LocalIO, Network, Recipient, Terminator};
+	use futures::{
+		channel::{
+			mpsc::{self, UnboundedReceiver, UnboundedSender},
+			oneshot,
+		},
+		io, StreamExt,
+	};
+	use sc_service::TaskManager;
+	use tokio::{
+		task::JoinHandle,
+		time::{sleep, timeout},
+	};
+
+	use super::ByzantineNetwork;
+	use crate::{
+		abft::{current_create_aleph_config, CurrentNetworkData, SpawnHandle},
+		block::{mock::MockHeader, Header},
+		data_io::{
+			ChainTracker, OrderedDataInterpreter, SubstrateChainInfoProvider, MAX_DATA_BRANCH_LEN,
+		},
+		metrics::{BlockTracer, TimingBlockMetrics},
+		network::mock::crypto_basics,
+		party::manager::Runnable,
+		testing::{
+			byzantine::{Behaviour, ByzantineDataProvider},
+			client_chain_builder::ClientChainBuilder,
+			mocks::{
+				aleph_data_from_blocks, TBlock, THeader, TestClientBuilder, TestClientBuilderExt,
+				TestVerifier,
+			},
+		},
+		BlockId, Keychain, SessionBoundaryInfo, SessionId, SessionPeriod, UnitCreationDelay,
+	};
+
+	struct MockNetwork {
+		sent: UnboundedSender<u64>,
+		events: UnboundedReceiver<u64>,
+	}
+
+	#[async_trait::async_trait]
+	impl Network<u64> for MockNetwork {
+		fn send(&self, data: u64, _recipient: Recipient) {
+			self.sent.unbounded_send(data).expect("receiver should be open");
+		}
+
+		async fn next_event(&mut self) -> Option<u64> {
+			futures::StreamExt::next(&mut self.events).await
+		}
+	}
+
+	fn network(
+		behaviour: Behaviour<MockHeader>,
+	) -> (ByzantineNetwork<u64, MockNetwork>, UnboundedReceiver<u64>, UnboundedSender<u64>) {
+		let (sent, sent_receiver) = mpsc::unbounded();
+		let (events_sender, events) = mpsc::unbounded();
+		(
+			ByzantineNetwork::new(MockNetwork { sent, events }, &behaviour),
+			sent_receiver,
+			events_sender,
+		)
+	}
+
+	#[tokio::test]
+	async fn honest_network_passes_everything() {
+		let (mut network, mut sent, events) = network(Behaviour::Honest);
+		network.send(1, Recipient::Everyone);
+		assert_eq!(sent.try_next().expect("should be sent"), Some(1));
+		events.unbounded_send(2).expect("network should be open");
+		assert_eq!(network.next_event().await, Some(2));
+	}
+
+	#[tokio::test]
+	async fn withholds_everything() {
+		let (mut network, mut sent, events) = network(Behaviour::WithholdUnits);
+		network.send(1, Recipient::Everyone);
+		assert!(sent.try_next().is_err(), "nothing should be sent");
+		events.unbounded_send(2).expect("network should be open");
+		assert_eq!(network.next_event().await, Some(2), "receiving should still work");
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn delays_sending() {
+		let (mut network, mut sent, _events) =
+			network(Behaviour::DelaySignatures(Duration::from_secs(2)));
+		network.send(1, Recipient::Everyone);
+		sleep(Duration::from_secs(1)).await;
+		network.send(2, Recipient::Everyone);
+		assert!(timeout(Duration::from_millis(1500), network.next_event()).await.is_err());
+		assert_eq!(sent.try_next().expect("should be sent"), Some(1));
+		assert!(sent.try_next().is_err(), "the second message should still be delayed");
+		assert!(timeout(Duration::from_secs(1), network.next_event()).await.is_err());
+		assert_eq!(sent.try_next().expect("should be sent"), Some(2));
+	}
+
+	type CommitteeData = CurrentNetworkData<THeader>;
+
+	/// Connects the members of a committee directly to one another.
+	struct CommitteeNetwork {
+		index: usize,
+		members: Vec<UnboundedSender<CommitteeData>>,
+		events: UnboundedReceiver<CommitteeData>,
+	}
+
+	fn committee_networks(n_members: usize) -> Vec<CommitteeNetwork> {
+		let (members, receivers): (Vec<_>, Vec<_>) =
+			(0..n_members).map(|_| mpsc::unbounded()).unzip();
+		receivers
+			.into_iter()
+			.enumerate()
+			.map(|(index, events)| CommitteeNetwork { index, members: members.clone(), events })
+			.collect()
+	}
+
+	#[async_trait::async_trait]
+	impl Network<CommitteeData> for CommitteeNetwork {
+		fn send(&self, data: CommitteeData, recipient: Recipient) {
+			let recipients: Vec<_> = match crate::Recipient::from(recipient) {
+				crate::Recipient::Node(node) => vec![node.0],
+				crate::Recipient::Everyone => {
+					(0..self.members.len()).filter(|member| *member != self.index).collect()
+				},
+			};
+			for member in recipients {
+				// The member might have already finished its session.
+				let _ = self.members[member].unbounded_send(data.clone());
+			}
+		}
+
+		async fn next_event(&mut self) -> Option<CommitteeData> {
+			self.events.next().await
+		}
+	}
+
+	/// Committee sizes together with the numbers of faulty members in them, up to the most the
+	/// protocol tolerates.
+	const COMMITTEES: [(usize, usize); 3] = [(4, 1), (7, 1), (7, 2)];
+	const SESSION_PERIOD: SessionPeriod = SessionPeriod(900);
+	const ORDERING_TIMEOUT: Duration = Duration::from_secs(60);
+
+	struct Member {
+		session: JoinHandle<()>,
+		exit_session: oneshot::Sender<()>,
+		exit_chain_tracker: oneshot::Sender<()>,
+		ordered: UnboundedReceiver<BlockId>,
+	}
+
+	fn ids(blocks: &[TBlock]) -> Vec<BlockId> {
+		blocks.iter().map(|block| block.header.id()).collect()
+	}
+
+	/// Runs a real AlephBFT session of a committee of `n_members` on a chain of
+	/// `MAX_DATA_BRANCH_LEN` blocks and a shorter fork of it, with the first `n_faulty` members
+	/// behaving as returned by `byzantine` when given the fork. Returns the chain, the fork and
+	/// the blocks ordered by every honest member until it reached the head of either of them.
+	async fn run_committee(
+		n_members: usize,
+		n_faulty: usize,
+		byzantine: impl Fn(Vec<TBlock>) -> Behaviour<THeader>,
+	) -> (Vec<BlockId>, Vec<BlockId>, Vec<Vec<BlockId>>) {
+		let (client, select_chain) = TestClientBuilder::new().build_with_longest_chain();
+		let client = Arc::new(client);
+		let mut chain_builder =
+			ClientChainBuilder::new(client.clone(), Arc::new(TestClientBuilder::new().build()));
+		let chain = chain_builder.initialize_single_branch_and_import(MAX_DATA_BRANCH_LEN).await;
+		// Shorter, so that honest members never consider it the best chain.
+		let genesis = chain_builder.genesis_hash();
+		let fork = chain_builder
+			.build_and_import_branch_above(&genesis, MAX_DATA_BRANCH_LEN - 1)
+			.await;
+		let (chain, fork_ids) = (ids(&chain), ids(&fork));
+
+		let session_boundaries =
+			SessionBoundaryInfo::new(SESSION_PERIOD).boundaries_for_session(SessionId(0));
+		let task_manager = TaskManager::new(tokio::runtime::Handle::current(), None)
+			.expect("task manager should start");
+		let spawn_handle = SpawnHandle::from(task_manager.spawn_handle());
+		let (pens, verifier) = crypto_basics(n_members);
+		let mut members = Vec::new();
+		for ((index, pen), network) in pens.into_iter().zip(committee_networks(n_members)) {
+			let behaviour = match index.0 < n_faulty {
+				true => byzantine(fork.clone()),
+				false => Behaviour::Honest,
+			};
+			let (chain_tracker, data_provider) = ChainTracker::new(
+				select_chain.clone(),
+				client.clone(),
+				session_boundaries.clone(),
+				Default::default(),
+				TimingBlockMetrics::noop(),
+			);
+			let (exit_chain_tracker, exit) = oneshot::channel();
+			tokio::spawn(Runnable::run(chain_tracker, exit));
+			let (blocks_tx, ordered) = mpsc::unbounded();
+			let ordered_data_interpreter = OrderedDataInterpreter::new(
+				blocks_tx,
+				SubstrateChainInfoProvider::new(client.clone()),
+				TestVerifier,
+				session_boundaries.clone(),
+				BlockTracer::noop(),
+			);
+			let local_io = LocalIO::new(
+				ByzantineDataProvider::new(data_provider, behaviour.clone()),
+				ordered_data_interpreter,
+				io::sink(),
+				io::empty(),
+			);
+			let config =
+				current_create_aleph_config(n_members, index, SessionId(0), UnitCreationDelay(200));
+			let (exit_session, exit) = oneshot::channel();
+			let session = tokio::spawn(current_aleph_bft::run_session(
+				config,
+				local_io,
+				ByzantineNetwork::new(network, &behaviour),
+				Keychain::new(index, verifier.clone(), pen),
+				spawn_handle.clone(),
+				Terminator::create_root(exit, "member"),
+			));
+			members.push(Member { session, exit_session, exit_chain_tracker, ordered });
+		}
+
+		// Neither branch can be extended, so once an honest member orders the head of one of them
+		// it will never order anything else, however long the committee keeps running.
+		let heads = [
+			chain.last().expect("the chain is not empty").clone(),
+			fork_ids.last().expect("the fork is not empty").clone(),
+		];
+		let mut ordered_by_honest = Vec::new();
+		for member in &mut members[n_faulty..] {
+			let mut ordered = Vec::new();
+			while !ordered.last().map_or(false, |block| heads.contains(block)) {
+				let block = timeout(ORDERING_TIMEOUT, member.ordered.next())
+					.await
+					.expect("honest members should order blocks despite the faulty ones")
+					.expect("the interpreter should be running");
+				ordered.push(block);
+			}
+			ordered_by_honest.push(ordered);
+		}
+		for member in members {
+			let _ = member.exit_chain_tracker.send(());
+			let _ = member.exit_session.send(());
+			member.session.await.expect("session should finish cleanly");
+		}
+		(chain, fork_ids, ordered_by_honest)
+	}
+
+	fn assert_consistent(ordered_by_honest: &[Vec<BlockId>]) {
+		for ordered in ordered_by_honest {
+			assert_eq!(ordered, &ordered_by_honest[0], "honest members should agree");
+		}
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn committee_orders_the_chain_with_all_members_honest() {
+		for (n_members, _) in COMMITTEES {
+			let (chain, _, ordered_by_honest) =
+				run_committee(n_members, 0, |_| Behaviour::Honest).await;
+			assert_consistent(&ordered_by_honest);
+			assert_eq!(ordered_by_honest[0], chain);
+		}
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn committee_orders_the_chain_despite_withholding_members() {
+		for (n_members, n_faulty) in COMMITTEES {
+			let (chain, _, ordered_by_honest) =
+				run_committee(n_members, n_faulty, |_| Behaviour::WithholdUnits).await;
+			assert_consistent(&ordered_by_honest);
+			assert_eq!(ordered_by_honest[0], chain);
+		}
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn committee_orders_the_chain_despite_delaying_members() {
+		for (n_members, n_faulty) in COMMITTEES {
+			let (chain, _, ordered_by_honest) = run_committee(n_members, n_faulty, |_| {
+				Behaviour::DelaySignatures(Duration::from_secs(2))
+			})
+			.await;
+			assert_consistent(&ordered_by_honest);
+			assert_eq!(ordered_by_honest[0], chain);
+		}
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn committee_orders_the_chain_despite_members_referencing_unknown_blocks() {
+		for (n_members, n_faulty) in COMMITTEES {
+			let (chain, _, ordered_by_honest) =
+				run_committee(n_members, n_faulty, |_| Behaviour::ReferenceUnknownBlocks).await;
+			assert_consistent(&ordered_by_honest);
+			assert_eq!(ordered_by_honest[0], chain);
+		}
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn committee_agrees_on_a_branch_despite_conflicting_members() {
+		for (n_members, n_faulty) in COMMITTEES {
+			let (chain, fork, ordered_by_honest) = run_committee(n_members, n_faulty, |fork| {
+				Behaviour::ProposeConflicting(aleph_data_from_blocks(fork))
+			})
+			.await;
+			assert_consistent(&ordered_by_honest);
+			// Whichever branch got ordered first is finalized whole, and nothing conflicting with it.
+			let ordered = &ordered_by_honest[0];
+			assert!(
+				ordered == &chain || ordered == &fork,
+				"unexpected blocks ordered: {ordered:?}"
+			);
+		}
+	}
+}
@@ -4,6 +4,8 @@ use current_aleph_bft::{create_config, default_delay_config, Config, LocalIO, Te
 use log::debug;
 use network_clique::SpawnHandleExt;
 
+#[cfg(test)]
+mod byzantine;
 mod network;
 mod traits;
 
@@ -14,8 +14,8 @@ use crate::{
 		EquivocationProof as EquivocationProofT, FinalizationStatus, Finalizer, Header,
 		HeaderVerifier, Justification as JustificationT, JustificationVerifier, VerifiedHeader,
 	},
-	nodes::VERIFIER_CACHE_SIZE,
 	session::{SessionBoundaryInfo, SessionId},
+	sync::VERIFIER_CACHE_SIZE,
 	BlockId, BlockNumber,
 };
 
@@ -74,6 +74,13 @@ impl Backend {
 		(Backend::new(notification_sender, session_boundary_info), notification_receiver)
 	}
 
+	/// Opens the same storage again with a fresh notifier, as a restarted node would.
+	pub fn reopen(&self) -> (Self, impl ChainStatusNotifier<MockHeader>) {
+		let (notification_sender, notification_receiver) = mpsc::unbounded();
+
+		(Backend { inner: self.inner.clone(), notification_sender }, notification_receiver)
+	}
+
 	fn new(
 		notification_sender: UnboundedSender<MockNotification>,
 		session_boundary_info: SessionBoundaryInfo,
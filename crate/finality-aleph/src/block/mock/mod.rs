@@ -21,7 +21,11 @@ impl BlockId {
 	}
 
 	pub fn random_child(&self) -> MockHeader {
-		let id = Self::new_random(self.number + 1);
+		self.child(BlockHash::random())
+	}
+
+	pub fn child(&self, hash: BlockHash) -> MockHeader {
+		let id = Self::new(hash, self.number + 1);
 		let parent = Some(self.clone());
 		MockHeader { id, parent, valid: true, equivocated: false, own: false }
 	}
@@ -1,6 +1,6 @@
 use crate::{
 	block::{Block as BlockT, BlockId, BlockImport, Header as HeaderT, UnverifiedHeader},
-	metrics::TimingBlockMetrics,
+	metrics::{BlockMetrics, BlockStage},
 };
 use sc_consensus::import_queue::{ImportQueueService, IncomingBlock};
 use selendra_primitives::{Block, BlockNumber, Header};
@@ -53,15 +53,15 @@ impl HeaderT for Header {
 /// Wrapper around the trait object that we get from Substrate.
 pub struct BlockImporter {
 	importer: Box<dyn ImportQueueService<Block>>,
-	metrics: TimingBlockMetrics,
+	metrics: BlockMetrics,
 }
 
 impl BlockImporter {
 	pub fn new(importer: Box<dyn ImportQueueService<Block>>) -> Self {
-		Self { importer, metrics: TimingBlockMetrics::noop() }
+		Self { importer, metrics: BlockMetrics::noop() }
 	}
 
-	pub fn attach_metrics(&mut self, metrics: TimingBlockMetrics) {
+	pub fn attach_metrics(&mut self, metrics: BlockMetrics) {
 		self.metrics = metrics;
 	}
 }
@@ -69,10 +69,10 @@ impl BlockImporter {
 impl BlockImport<Block> for BlockImporter {
 	fn import_block(&mut self, block: Block, own: bool) {
 		// We only need to distinguish between blocks produced by us and blocks incoming from the network
-		// for the purpose of running `FinalityRateMetrics`. We use `BlockOrigin` to make this distinction.
-		let origin = match own {
-			true => BlockOrigin::Own,
-			false => BlockOrigin::NetworkBroadcast,
+		// for the purpose of running `FinalityRateMetrics` and tracing. We use `BlockOrigin` to make this distinction.
+		let (origin, stage) = match own {
+			true => (BlockOrigin::Own, BlockStage::Authored),
+			false => (BlockOrigin::NetworkBroadcast, BlockStage::Received),
 		};
 		let hash = block.header.hash();
 		let incoming_block = IncomingBlock::<Block> {
@@ -87,7 +87,8 @@ impl BlockImport<Block> for BlockImporter {
 			import_existing: false,
 			state: None,
 		};
-		self.metrics.report_block(hash, Checkpoint::Importing);
+		self.metrics.timing.report_block(hash, Checkpoint::Importing);
+		self.metrics.tracer.report_block(hash, stage);
 		self.importer.import_blocks(origin, vec![incoming_block]);
 	}
 }
@@ -429,7 +429,7 @@ mod tests {
 		sync::{Arc, Mutex},
 	};
 
-	use selendra_primitives::SessionAuthorityData;
+	use selendra_primitives::{SessionAuthorityData, SessionPeriodHistory};
 	use sp_runtime::testing::UintAuthorityId;
 
 	use super::{
@@ -535,6 +535,13 @@ mod tests {
 				.cloned()
 				.map(|v| v.into_iter().map(|aura_id| (placeholder_id.clone(), aura_id)).collect())
 		}
+
+		fn session_period_history(
+			&self,
+			_block_number: BlockNumber,
+		) -> Option<SessionPeriodHistory> {
+			None
+		}
 	}
 
 	fn setup_test(max_session_n: u32, finalized_number: Arc<Mutex<u32>>) -> TestVerifierCache {
@@ -1,41 +1,19 @@
-use std::fmt::{Display, Error as FmtError, Formatter};
-
-use crate::{crypto::AuthorityVerifier, justification::AlephJustification, AuthorityId};
+use aleph_light_client::SessionVerifier as LightClientVerifier;
+pub use aleph_light_client::VerificationError as SessionVerificationError;
 use selendra_primitives::SessionAuthorityData;
-use sp_runtime::RuntimeAppPublic;
+
+use crate::justification::AlephJustification;
 
 /// A justification verifier within a single session.
+///
+/// The checks are the ones performed by `aleph-light-client`, so light clients accept exactly
+/// the justifications the node accepts.
 #[derive(Clone, PartialEq, Debug)]
-pub struct SessionVerifier {
-	authority_verifier: AuthorityVerifier,
-	emergency_signer: Option<AuthorityId>,
-}
+pub struct SessionVerifier(LightClientVerifier);
 
 impl From<SessionAuthorityData> for SessionVerifier {
 	fn from(authority_data: SessionAuthorityData) -> Self {
-		SessionVerifier {
-			authority_verifier: AuthorityVerifier::new(authority_data.authorities().to_vec()),
-			emergency_signer: authority_data.emergency_finalizer().clone(),
-		}
-	}
-}
-
-/// Ways in which a justification can be wrong.
-#[derive(Debug, PartialEq, Eq)]
-pub enum SessionVerificationError {
-	BadMultisignature,
-	BadEmergencySignature,
-	NoEmergencySigner,
-}
-
-impl Display for SessionVerificationError {
-	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-		use SessionVerificationError::*;
-		match self {
-			BadMultisignature => write!(f, "bad multisignature"),
-			BadEmergencySignature => write!(f, "bad emergency signature"),
-			NoEmergencySigner => write!(f, "no emergency signer defined"),
-		}
+		SessionVerifier(authority_data.into())
 	}
 }
 
@@ -46,24 +24,6 @@ impl SessionVerifier {
 		justification: &AlephJustification,
 		bytes: Vec<u8>,
 	) -> Result<(), SessionVerificationError> {
-		use AlephJustification::*;
-		use SessionVerificationError::*;
-		match justification {
-			CommitteeMultisignature(multisignature) => {
-				match self.authority_verifier.is_complete(&bytes, multisignature) {
-					true => Ok(()),
-					false => Err(BadMultisignature),
-				}
-			},
-			EmergencySignature(signature) => match self
-				.emergency_signer
-				.as_ref()
-				.ok_or(NoEmergencySigner)?
-				.verify(&bytes, signature)
-			{
-				true => Ok(()),
-				false => Err(BadEmergencySignature),
-			},
-		}
+		self.0.verify_bytes(&justification.clone().into(), &bytes)
 	}
 }
@@ -11,6 +11,7 @@ use crate::{
 		status_provider::get_proposal_status,
 		AlephData, ChainInfoProvider,
 	},
+	metrics::{BlockStage, BlockTracer},
 	mpsc::TrySendError,
 	BlockId, SessionBoundaries,
 };
@@ -32,6 +33,7 @@ where
 	verifier: V,
 	last_finalized_by_aleph: BlockId,
 	session_boundaries: SessionBoundaries,
+	tracer: BlockTracer,
 	_phantom: PhantomData<H>,
 }
 
@@ -65,6 +67,7 @@ where
 		mut chain_info: CIP,
 		verifier: V,
 		session_boundaries: SessionBoundaries,
+		tracer: BlockTracer,
 	) -> Self {
 		let last_finalized_by_aleph =
 			get_last_block_prev_session(session_boundaries.clone(), &mut chain_info);
@@ -79,6 +82,7 @@ where
 			last_finalized_by_aleph,
 			session_boundaries,
 			verifier,
+			tracer,
 			_phantom: PhantomData,
 		}
 	}
@@ -130,6 +134,7 @@ where
 
 	pub fn data_finalized(&mut self, data: AlephData<H::Unverified>) {
 		for block in self.blocks_to_finalize_from_data(data) {
+			self.tracer.report_block(block.hash(), BlockStage::Ordered);
 			self.set_last_finalized(block.clone());
 			self.chain_info_provider().inner().update_aux_finalized(block.clone());
 			if let Err(err) = self.send_block_to_finalize(block) {
@@ -345,6 +345,7 @@ mod tests {
 		metrics::TimingBlockMetrics,
 		party::manager::Runnable,
 		testing::{
+			byzantine::{Behaviour, ByzantineDataProvider},
 			client_chain_builder::ClientChainBuilder,
 			mocks::{aleph_data_from_blocks, THeader, TestClientBuilder, TestClientBuilderExt},
 		},
@@ -479,4 +480,66 @@ mod tests {
 		})
 		.await;
 	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn withholding_node_proposes_nothing() {
+		run_test(|mut chain_builder, data_provider| async move {
+			let mut data_provider =
+				ByzantineDataProvider::new(data_provider, Behaviour::WithholdUnits);
+			chain_builder.initialize_single_branch_and_import(MAX_DATA_BRANCH_LEN).await;
+			for _ in 0..10 {
+				sleep(SLEEP_TIME).await;
+				assert_eq!(data_provider.get_data().await, None, "Expected no proposal");
+			}
+		})
+		.await;
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn alternates_conflicting_and_honest_proposals() {
+		run_test(|mut chain_builder, data_provider| async move {
+			let genesis = chain_builder.genesis_hash();
+			let fork = chain_builder.build_branch_above(&genesis, MAX_DATA_BRANCH_LEN).await;
+			let conflicting = aleph_data_from_blocks(fork);
+			let mut data_provider = ByzantineDataProvider::new(
+				data_provider,
+				Behaviour::ProposeConflicting(conflicting.clone()),
+			);
+			let blocks =
+				chain_builder.initialize_single_branch_and_import(MAX_DATA_BRANCH_LEN).await;
+			let honest = aleph_data_from_blocks(blocks);
+
+			let mut proposals = Vec::new();
+			while proposals.len() < 4 {
+				sleep(SLEEP_TIME).await;
+				if let Some(data) = data_provider.get_data().await {
+					proposals.push(data);
+				}
+			}
+			assert!(proposals.contains(&honest), "Expected the honest proposal");
+			assert!(proposals.contains(&conflicting), "Expected the conflicting proposal");
+		})
+		.await;
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn references_unknown_blocks() {
+		run_test(|mut chain_builder, data_provider| async move {
+			let mut data_provider =
+				ByzantineDataProvider::new(data_provider, Behaviour::ReferenceUnknownBlocks);
+			let blocks =
+				chain_builder.initialize_single_branch_and_import(MAX_DATA_BRANCH_LEN).await;
+			let honest = aleph_data_from_blocks(blocks);
+
+			let data = loop {
+				sleep(SLEEP_TIME).await;
+				if let Some(data) = data_provider.get_data().await {
+					break data;
+				}
+			};
+			assert_eq!(data.head_proposal.top_block(), honest.head_proposal.top_block());
+			assert_ne!(data, honest, "Expected the tail to be replaced");
+		})
+		.await;
+	}
 }
@@ -26,7 +26,7 @@ use crate::{
 		chain_info::{CachedChainInfoProvider, ChainInfoProvider, SubstrateChainInfoProvider},
 		proposal::{AlephProposal, PendingProposalStatus, ProposalStatus},
 		status_provider::get_proposal_status,
-		AlephNetworkMessage,
+		AlephNetworkMessage, ChainInfoCacheConfig, DataStoreMetrics,
 	},
 	network::data::{
 		component::{Network as ComponentNetwork, Receiver, SimpleNetwork},
@@ -79,6 +79,7 @@ impl<UH: UnverifiedHeader, M: AlephNetworkMessage<UH>> PendingMessageInfo<UH, M>
 	}
 }
 
+#[derive(Clone, Debug)]
 pub struct DataStoreConfig {
 	pub max_triggers_pending: usize,
 	pub max_proposals_pending: usize,
@@ -88,6 +89,7 @@ pub struct DataStoreConfig {
 	// Specifies how much time must pass from receiving a given proposal for the first time, till we
 	// perform a request for either a block or a justification required to let this proposal through.
 	pub request_block_after: Duration,
+	pub chain_info_cache: ChainInfoCacheConfig,
 }
 
 impl Default for DataStoreConfig {
@@ -99,6 +101,7 @@ impl Default for DataStoreConfig {
 			available_proposals_cache_capacity: NonZeroUsize::new(8000).unwrap(),
 			periodic_maintenance_interval: Duration::from_secs(25),
 			request_block_after: Duration::from_secs(20),
+			chain_info_cache: ChainInfoCacheConfig::default(),
 		}
 	}
 }
@@ -194,6 +197,7 @@ where
 	blockchain_events: Arc<BEV>,
 	block_requester: RB,
 	config: DataStoreConfig,
+	metrics: DataStoreMetrics,
 	messages_from_network: R,
 	messages_for_aleph: UnboundedSender<Message>,
 }
@@ -222,6 +226,7 @@ where
 		verifier: V,
 		block_requester: RB,
 		config: DataStoreConfig,
+		metrics: DataStoreMetrics,
 		component_network: N,
 	) -> (Self, impl DataNetwork<Message>) {
 		let (messages_for_aleph, messages_from_data_store) = mpsc::unbounded();
@@ -229,7 +234,7 @@ where
 		let highest_finalized_num = header_backend.top_finalized_id().number();
 		let chain_info_provider = CachedChainInfoProvider::new(
 			SubstrateChainInfoProvider::new(header_backend),
-			Default::default(),
+			config.chain_info_cache.clone(),
 		);
 
 		(
@@ -247,6 +252,7 @@ where
 				blockchain_events,
 				block_requester,
 				config,
+				metrics,
 				messages_from_network,
 				messages_for_aleph,
 			},
@@ -260,6 +266,8 @@ where
 		loop {
 			self.prune_pending_messages();
 			self.prune_triggers();
+			self.metrics
+				.report_pending(self.pending_proposals.len(), self.pending_messages.len());
 			tokio::select! {
 				maybe_message = self.messages_from_network.next() => {
 					let message = maybe_message.ok_or(Error::NetworkMessagesTerminated)?;
@@ -764,5 +772,6 @@ where
 		if let Err(err) = DataStore::run(&mut self, exit).await {
 			error!(target: LOG_TARGET, "DataStore exited with error: {err}.");
 		}
+		self.metrics.remove_session();
 	}
 }
@@ -0,0 +1,83 @@
+use prometheus_endpoint::{register, GaugeVec, Opts, PrometheusError, Registry, U64};
+
+use crate::SessionId;
+
+/// Sizes of the pending queues of the data store. Every session runs its own data store, so the
+/// gauges are labelled by session.
+#[derive(Clone)]
+pub enum DataStoreMetrics {
+	Prometheus {
+		pending_proposals: GaugeVec<U64>,
+		pending_messages: GaugeVec<U64>,
+		session: String,
+	},
+	Noop,
+}
+
+impl DataStoreMetrics {
+	pub fn new(registry: Option<&Registry>) -> Result<Self, PrometheusError> {
+		let registry = match registry {
+			None => return Ok(DataStoreMetrics::Noop),
+			Some(registry) => registry,
+		};
+
+		Ok(DataStoreMetrics::Prometheus {
+			pending_proposals: register(
+				GaugeVec::new(
+					Opts::new(
+						"aleph_data_store_pending_proposals",
+						"Number of proposals the data store waits to become available",
+					),
+					&["session"],
+				)?,
+				registry,
+			)?,
+			pending_messages: register(
+				GaugeVec::new(
+					Opts::new(
+						"aleph_data_store_pending_messages",
+						"Number of messages the data store holds back until their data is available",
+					),
+					&["session"],
+				)?,
+				registry,
+			)?,
+			session: String::new(),
+		})
+	}
+
+	pub fn noop() -> Self {
+		DataStoreMetrics::Noop
+	}
+
+	/// Metrics to be reported by the data store of the given session.
+	pub fn for_session(&self, session_id: SessionId) -> Self {
+		match self {
+			DataStoreMetrics::Prometheus { pending_proposals, pending_messages, .. } => {
+				DataStoreMetrics::Prometheus {
+					pending_proposals: pending_proposals.clone(),
+					pending_messages: pending_messages.clone(),
+					session: session_id.0.to_string(),
+				}
+			},
+			DataStoreMetrics::Noop => DataStoreMetrics::Noop,
+		}
+	}
+
+	pub fn report_pending(&self, proposals: usize, messages: usize) {
+		if let DataStoreMetrics::Prometheus { pending_proposals, pending_messages, session } = self
+		{
+			pending_proposals.with_label_values(&[session]).set(proposals as u64);
+			pending_messages.with_label_values(&[session]).set(messages as u64);
+		}
+	}
+
+	/// Removes the gauges of the session, should be called once its data store terminates.
+	pub fn remove_session(&self) {
+		if let DataStoreMetrics::Prometheus { pending_proposals, pending_messages, session } = self
+		{
+			let _ = pending_proposals.remove_label_values(&[session]);
+			let _ = pending_messages.remove_label_values(&[session]);
+		}
+	}
+}
@@ -12,6 +12,7 @@ mod chain_info;
 mod data_interpreter;
 mod data_provider;
 mod data_store;
+mod metrics;
 mod proposal;
 mod status_provider;
 
@@ -19,6 +20,7 @@ pub use chain_info::{ChainInfoProvider, SubstrateChainInfoProvider};
 pub use data_interpreter::OrderedDataInterpreter;
 pub use data_provider::{ChainTracker, DataProvider};
 pub use data_store::{DataStore, DataStoreConfig};
+pub use metrics::DataStoreMetrics;
 pub use proposal::UnvalidatedAlephProposal;
 
 // Maximum number of blocks above the last finalized allowed in an AlephBFT proposal.
@@ -68,6 +68,13 @@ impl<UH: UnverifiedHeader> UnvalidatedAlephProposal<UH> {
 		self.head.id()
 	}
 
+	/// The same head, with the blocks below it replaced by ones nobody knows.
+	#[cfg(test)]
+	pub fn with_unknown_tail(&self) -> Self {
+		let tail = self.tail.iter().map(|_| BlockHash::random()).collect();
+		UnvalidatedAlephProposal { head: self.head.clone(), tail }
+	}
+
 	fn branch_len(&self) -> usize {
 		self.tail.len() + 1
 	}
@@ -10,7 +10,10 @@ use sp_runtime::{
 	Justification,
 };
 
-use crate::BlockId;
+use crate::{
+	metrics::{BlockStage, BlockTracer},
+	BlockId,
+};
 
 pub trait BlockFinalizer {
 	fn finalize_block(&self, block: BlockId, justification: Justification) -> Result<(), Error>;
@@ -23,6 +26,7 @@ where
 	C: HeaderBackend<B> + LockImportRun<B, BE> + Finalizer<B, BE>,
 {
 	client: Arc<C>,
+	tracer: BlockTracer,
 	phantom: PhantomData<(B, BE)>,
 }
 
@@ -32,8 +36,8 @@ where
 	BE: Backend<B>,
 	C: HeaderBackend<B> + LockImportRun<B, BE> + Finalizer<B, BE>,
 {
-	pub(crate) fn new(client: Arc<C>) -> Self {
-		AlephFinalizer { client, phantom: PhantomData }
+	pub(crate) fn new(client: Arc<C>, tracer: BlockTracer) -> Self {
+		AlephFinalizer { client, tracer, phantom: PhantomData }
 	}
 }
 
@@ -64,6 +68,7 @@ where
 		let status = self.client.info();
 		match &update_res {
 			Ok(_) => {
+				self.tracer.report_block(hash, BlockStage::Finalized);
 				debug!(target: "aleph-finality", "Successfully finalized block with hash {:?} and number {:?}. Current best: #{:?}.", hash, number, status.best_number);
 			},
 			Err(_) => {
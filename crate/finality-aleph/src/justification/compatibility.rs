@@ -1,134 +1,13 @@
-use std::{
-	fmt::{Display, Error as FmtError, Formatter},
-	mem::size_of,
-};
+use std::fmt::{Display, Error as FmtError, Formatter};
 
+use aleph_light_client::DecodeError as LightClientDecodeError;
 use log::warn;
-use parity_scale_codec::{Decode, DecodeAll, Encode, Error as CodecError, Input as CodecInput};
 
 use crate::{
-	abft::SignatureSet,
-	crypto::{Signature, SignatureV1},
 	justification::{AlephJustification, LOG_TARGET},
 	Version,
 };
 
-type ByteCount = u16;
-
-/// Old format of justifications, needed for backwards compatibility.
-/// Used an old format of signature which unnecessarily contained the signer ID.
-#[derive(Clone, Encode, Decode, Debug, PartialEq, Eq)]
-struct AlephJustificationV1 {
-	pub signature: SignatureSet<SignatureV1>,
-}
-
-impl From<AlephJustificationV1> for AlephJustification {
-	fn from(justification: AlephJustificationV1) -> AlephJustification {
-		let size = justification.signature.size();
-		let just_drop_id: SignatureSet<Signature> = justification
-			.signature
-			.into_iter()
-			.fold(SignatureSet::with_size(size), |sig_set, (id, sgn)| {
-				sig_set.add_signature(&sgn.into(), id)
-			});
-		AlephJustification::CommitteeMultisignature(just_drop_id)
-	}
-}
-
-/// Old format of justifications, needed for backwards compatibility.
-/// Used an old format of signature from before the compatibility changes.
-#[derive(Clone, Encode, Decode, Debug, PartialEq, Eq)]
-struct AlephJustificationV2 {
-	pub signature: SignatureSet<Signature>,
-}
-
-impl From<AlephJustificationV2> for AlephJustification {
-	fn from(justification: AlephJustificationV2) -> AlephJustification {
-		AlephJustification::CommitteeMultisignature(justification.signature)
-	}
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum VersionedAlephJustification {
-	// Most likely from the future.
-	Other(Version, Vec<u8>),
-	V1(AlephJustificationV1),
-	V2(AlephJustificationV2),
-	V3(AlephJustification),
-}
-
-fn encode_with_version(version: Version, payload: &[u8]) -> Vec<u8> {
-	// This will produce rubbish if we ever try encodings that have more than u16::MAX bytes. We
-	// expect this won't happen, since we will switch to proper multisignatures before proofs get
-	// that big.
-	// We do not have a guarantee that size_hint is implemented for AlephJustification, so we need
-	// to compute actual size to place it in the encoded data.
-	let size = payload.len().try_into().unwrap_or_else(|_| {
-		if payload.len() > ByteCount::MAX.into() {
-			warn!(
-				target: LOG_TARGET,
-				"Versioned Justification v{:?} too big during Encode. Size is {:?}. Should be {:?} at max.",
-				version,
-				payload.len(),
-				ByteCount::MAX
-			);
-		}
-		ByteCount::MAX
-	});
-
-	let mut result = Vec::with_capacity(version.size_hint() + size.size_hint() + payload.len());
-
-	version.encode_to(&mut result);
-	size.encode_to(&mut result);
-	result.extend_from_slice(payload);
-
-	result
-}
-
-impl Encode for VersionedAlephJustification {
-	fn size_hint(&self) -> usize {
-		use VersionedAlephJustification::*;
-		let version_size = size_of::<Version>();
-		let byte_count_size = size_of::<ByteCount>();
-		version_size
-			+ byte_count_size
-			+ match self {
-				Other(_, payload) => payload.len(),
-				V1(justification) => justification.size_hint(),
-				V2(justification) => justification.size_hint(),
-				V3(justification) => justification.size_hint(),
-			}
-	}
-
-	fn encode(&self) -> Vec<u8> {
-		use VersionedAlephJustification::*;
-		match self {
-			Other(version, payload) => encode_with_version(*version, payload),
-			V1(justification) => encode_with_version(Version(1), &justification.encode()),
-			V2(justification) => encode_with_version(Version(2), &justification.encode()),
-			V3(justification) => encode_with_version(Version(3), &justification.encode()),
-		}
-	}
-}
-
-impl Decode for VersionedAlephJustification {
-	fn decode<I: CodecInput>(input: &mut I) -> Result<Self, CodecError> {
-		use VersionedAlephJustification::*;
-		let version = Version::decode(input)?;
-		let num_bytes = ByteCount::decode(input)?;
-		match version {
-			Version(1) => Ok(V1(AlephJustificationV1::decode(input)?)),
-			Version(2) => Ok(V2(AlephJustificationV2::decode(input)?)),
-			Version(3) => Ok(V3(AlephJustification::decode(input)?)),
-			_ => {
-				let mut payload = vec![0; num_bytes.into()];
-				input.read(payload.as_mut_slice())?;
-				Ok(Other(version, payload))
-			},
-		}
-	}
-}
-
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Error {
 	BadFormat,
@@ -147,54 +26,43 @@ impl Display for Error {
 	}
 }
 
-fn decode_pre_compatibility_justification(
-	justification_raw: Vec<u8>,
-) -> Result<AlephJustification, Error> {
-	use Error::*;
-
-	// We still have to be able to decode the pre-compatibility justifications, since they
-	// may be lingering in the DB. Perhaps one day in the future we will be able to remove
-	// this code, but I wouldn't count on it.
-	match AlephJustificationV2::decode_all(&mut justification_raw.as_slice()) {
-		Ok(justification) => Ok(justification.into()),
-		Err(_) => match AlephJustificationV1::decode_all(&mut justification_raw.as_slice()) {
-			Ok(justification) => Ok(justification.into()),
-			Err(_) => Err(BadFormat),
-		},
+impl From<LightClientDecodeError> for Error {
+	fn from(error: LightClientDecodeError) -> Self {
+		match error {
+			LightClientDecodeError::BadFormat => Error::BadFormat,
+			LightClientDecodeError::UnknownVersion(version) => {
+				Error::UnknownVersion(Version(version))
+			},
+		}
 	}
 }
 
 /// Decodes a justification, even if it was produced by ancient code which does not conform to our
 /// backwards compatibility style.
+///
+/// The decoding itself lives in `aleph-light-client`, so that justifications are understood the
+/// same way by the node and by light clients.
 pub fn backwards_compatible_decode(
 	justification_raw: Vec<u8>,
 ) -> Result<AlephJustification, Error> {
-	use Error::*;
-	let justification_cloned = justification_raw.clone();
-	match VersionedAlephJustification::decode_all(&mut justification_cloned.as_slice()) {
-		Ok(justification) => {
-			use VersionedAlephJustification::*;
-			match justification {
-				V1(justification) => Ok(justification.into()),
-				V2(justification) => Ok(justification.into()),
-				V3(justification) => Ok(justification),
-				Other(version, _) => {
-					// it is a coincidence that sometimes pre-compatibility legacy justification second word,
-					// which is in VersionedAlephJustification byte_count_size, can be small enough
-					// so that justification is false positively recognized  as from the future
-					// therefore we should try to decode formats
-					decode_pre_compatibility_justification(justification_raw)
-						.map_err(|_| UnknownVersion(version))
-				},
-			}
-		},
-		Err(_) => decode_pre_compatibility_justification(justification_raw),
-	}
+	Ok(aleph_light_client::backwards_compatible_decode(&justification_raw)?.into())
 }
 
 /// Encodes the justification in a way that is forwards compatible with future versions.
 pub fn versioned_encode(justification: AlephJustification) -> Vec<u8> {
-	VersionedAlephJustification::V3(justification).encode()
+	let encoded = aleph_light_client::versioned_encode(&justification.into());
+	// This will produce rubbish if we ever try encodings that have more than u16::MAX bytes. We
+	// expect this won't happen, since we will switch to proper multisignatures before proofs get
+	// that big.
+	if encoded.len() > usize::from(u16::MAX) + 4 {
+		warn!(
+			target: LOG_TARGET,
+			"Versioned Justification too big during Encode. Size is {:?}. Should be {:?} at max.",
+			encoded.len() - 4,
+			u16::MAX
+		);
+	}
+	encoded
 }
 
 #[cfg(test)]
@@ -203,16 +71,44 @@ mod test {
 	use selendra_primitives::{AuthorityPair, AuthoritySignature};
 	use sp_core::Pair;
 
-	use super::{
-		backwards_compatible_decode, versioned_encode, AlephJustificationV1, AlephJustificationV2,
-		VersionedAlephJustification,
-	};
+	use super::{backwards_compatible_decode, versioned_encode, Error};
 	use crate::{
 		crypto::{Signature, SignatureV1},
 		justification::AlephJustification,
 		NodeCount, SignatureSet, Version,
 	};
 
+	/// Old format of justifications, using signatures which contained the signer ID.
+	#[derive(Clone, Encode, Decode, Debug, PartialEq, Eq)]
+	struct AlephJustificationV1 {
+		pub signature: SignatureSet<SignatureV1>,
+	}
+
+	impl From<AlephJustificationV1> for AlephJustification {
+		fn from(justification: AlephJustificationV1) -> AlephJustification {
+			let size = justification.signature.size();
+			let just_drop_id: SignatureSet<Signature> = justification
+				.signature
+				.into_iter()
+				.fold(SignatureSet::with_size(size), |sig_set, (id, sgn)| {
+					sig_set.add_signature(&sgn.into(), id)
+				});
+			AlephJustification::CommitteeMultisignature(just_drop_id)
+		}
+	}
+
+	/// Old format of justifications from before the compatibility changes.
+	#[derive(Clone, Encode, Decode, Debug, PartialEq, Eq)]
+	struct AlephJustificationV2 {
+		pub signature: SignatureSet<Signature>,
+	}
+
+	impl From<AlephJustificationV2> for AlephJustification {
+		fn from(justification: AlephJustificationV2) -> AlephJustification {
+			AlephJustification::CommitteeMultisignature(justification.signature)
+		}
+	}
+
 	#[test]
 	fn correctly_decodes_v1() {
 		let mut signature_set: SignatureSet<SignatureV1> = SignatureSet::with_size(7.into());
@@ -265,11 +161,11 @@ mod test {
 	}
 
 	#[test]
-	fn correctly_decodes_other() {
-		let other = VersionedAlephJustification::Other(Version(43), vec![21, 37]);
-		let encoded = other.encode();
-		let decoded = VersionedAlephJustification::decode(&mut encoded.as_slice());
-		assert_eq!(decoded, Ok(other));
+	fn recognizes_other_versions() {
+		let mut encoded = Version(43).encode();
+		encoded.extend(2u16.encode());
+		encoded.extend([21, 37]);
+		assert_eq!(backwards_compatible_decode(encoded), Err(Error::UnknownVersion(Version(43))));
 	}
 
 	fn assert_backwards_compatible_decodes_pre_compatibility_justification(
@@ -1,4 +1,7 @@
-use crate::{abft::SignatureSet, crypto::Signature};
+use crate::{abft::SignatureSet, crypto::Signature, NodeCount, NodeIndex};
+use aleph_light_client::{
+	AlephJustification as LightClientJustification, SignatureSet as LightClientSignatureSet,
+};
 use parity_scale_codec::{Decode, Encode};
 use selendra_primitives::{AuthoritySignature, ALEPH_ENGINE_ID};
 use sp_runtime::Justification;
@@ -22,3 +25,103 @@ impl From<AlephJustification> for Justification {
 		(ALEPH_ENGINE_ID, versioned_encode(val))
 	}
 }
+
+impl From<LightClientJustification> for AlephJustification {
+	fn from(justification: LightClientJustification) -> Self {
+		match justification {
+			LightClientJustification::CommitteeMultisignature(signatures) => {
+				let signature_set = signatures.iter().fold(
+					SignatureSet::with_size(NodeCount(signatures.size())),
+					|set, (index, signature)| {
+						set.add_signature(&signature.clone().into(), NodeIndex(index))
+					},
+				);
+				AlephJustification::CommitteeMultisignature(signature_set)
+			},
+			LightClientJustification::EmergencySignature(signature) => {
+				AlephJustification::EmergencySignature(signature)
+			},
+		}
+	}
+}
+
+impl From<AlephJustification> for LightClientJustification {
+	fn from(justification: AlephJustification) -> Self {
+		match justification {
+			AlephJustification::CommitteeMultisignature(signatures) => {
+				let signature_set = signatures.iter().fold(
+					LightClientSignatureSet::with_size(signatures.size().0),
+					|set, (index, signature)| set.add_signature(&signature.clone().into(), index.0),
+				);
+				LightClientJustification::CommitteeMultisignature(signature_set)
+			},
+			AlephJustification::EmergencySignature(signature) => {
+				LightClientJustification::EmergencySignature(signature)
+			},
+		}
+	}
+}
+
+impl AlephJustification {
+	/// Returns a bitmap of the committee members whose signatures are included in this
+	/// justification, indexed by their position in the session committee. Emergency
+	/// justifications are not signed by the committee, so `None` is returned for them.
+	pub fn signers(&self) -> Option<Vec<bool>> {
+		match self {
+			AlephJustification::CommitteeMultisignature(signature_set) => {
+				let mut signers = vec![false; signature_set.size().0];
+				for (index, _) in signature_set.iter() {
+					if let Some(signed) = signers.get_mut(index.0) {
+						*signed = true;
+					}
+				}
+				Some(signers)
+			},
+			AlephJustification::EmergencySignature(_) => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use parity_scale_codec::Encode;
+	use selendra_primitives::{AuthorityPair, AuthoritySignature};
+	use sp_core::Pair;
+
+	use super::LightClientJustification;
+	use crate::{justification::AlephJustification, SignatureSet};
+
+	#[test]
+	fn reports_committee_signers() {
+		let mut signature_set = SignatureSet::with_size(5.into());
+		for i in [0, 2, 3] {
+			let authority_signature: AuthoritySignature =
+				AuthorityPair::generate().0.sign(vec![0u8, 0u8, 0u8, 0u8].as_slice());
+			signature_set = signature_set.add_signature(&authority_signature.into(), i.into());
+		}
+		let justification = AlephJustification::CommitteeMultisignature(signature_set);
+		assert_eq!(justification.signers(), Some(vec![true, false, true, true, false]));
+	}
+
+	#[test]
+	fn emergency_justification_has_no_signers() {
+		let authority_signature: AuthoritySignature =
+			AuthorityPair::generate().0.sign(vec![0u8, 0u8, 0u8, 0u8].as_slice());
+		let justification = AlephJustification::EmergencySignature(authority_signature);
+		assert_eq!(justification.signers(), None);
+	}
+
+	#[test]
+	fn light_client_justifications_are_wire_compatible() {
+		let mut signature_set = SignatureSet::with_size(4.into());
+		for i in [1, 2, 3] {
+			let authority_signature: AuthoritySignature =
+				AuthorityPair::generate().0.sign(vec![0u8, 0u8, 0u8, 0u8].as_slice());
+			signature_set = signature_set.add_signature(&authority_signature.into(), i.into());
+		}
+		let justification = AlephJustification::CommitteeMultisignature(signature_set);
+		let light_client_justification = LightClientJustification::from(justification.clone());
+		assert_eq!(light_client_justification.encode(), justification.encode());
+		assert_eq!(AlephJustification::from(light_client_justification), justification);
+	}
+}
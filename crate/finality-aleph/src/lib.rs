@@ -13,6 +13,7 @@ use futures::{
 	},
 	Future,
 };
+use network_clique::Priority;
 use parity_scale_codec::{Decode, Encode, Output};
 use prometheus_endpoint::Registry;
 use sc_client_api::{
@@ -35,8 +36,8 @@ use crate::{
 	aggregation::{CurrentRmcNetworkData, LegacyRmcNetworkData},
 	block::UnverifiedHeader,
 	compatibility::{Version, Versioned},
-	network::data::split::Split,
-	session::{SessionBoundaries, SessionBoundaryInfo, SessionId},
+	network::{data::split::Split, Data, Prioritized},
+	session::{SessionBoundaries, SessionId},
 	VersionedTryFromError::{ExpectedNewGotOld, ExpectedOldGotNew},
 };
 
@@ -65,17 +66,27 @@ pub mod testing;
 pub use crate::{
 	block::{
 		substrate::{BlockImporter, Justification, JustificationTranslator, SubstrateChainStatus},
-		BlockId,
+		BlockId, BlockchainEvents, ChainStatusNotification, ChainStatusNotifier,
 	},
 	import::{get_aleph_block_import, AlephBlockImport, RedirectingBlockImport},
-	justification::AlephJustification,
+	justification::{backwards_compatible_decode, AlephJustification},
+	metrics::{
+		BlockTracer, CommitteeConnectivity, HealthMonitor, HealthStatus, HealthThresholds,
+		OtlpError, OtlpExporter,
+	},
 	network::{
 		address_cache::{ValidatorAddressCache, ValidatorAddressingInfo},
-		build_network, BuildNetworkOutput, ProtocolNetwork, SubstratePeerId,
+		build_network,
+		key::{Error as ValidatorNetworkKeyError, ValidatorNetworkKey},
+		BuildNetworkOutput, ProtocolNetwork, SubstratePeerId,
 	},
 	nodes::run_validator_node,
-	session::SessionPeriod,
-	sync::FavouriteSelectChainProvider,
+	party::backup::{
+		BackupStorage, DatabaseBackupStorage, FsBackupStorage, SessionBackupSummary,
+		DEFAULT_BACKUP_RETAINED_SESSIONS,
+	},
+	session::{SessionBoundaryInfo, SessionPeriod},
+	sync::{FavouriteSelectChainProvider, SyncConfig, SyncConfigError},
 	sync_oracle::SyncOracle,
 };
 
@@ -188,6 +199,26 @@ impl<UH: UnverifiedHeader> From<CurrentSplitData<UH>> for VersionedNetworkData<U
 	}
 }
 
+/// Both AlephBFT units and the signature messages of the aggregator are needed for finalization.
+/// The small signature messages go first, as finalization waits for them whenever they are late.
+/// Units go with the normal priority, so at most 4096 of them wait for a single connection and
+/// any further ones are rejected, relying on AlephBFT to request the missing units again.
+fn split_priority<AlephData: Data, RmcData: Data>(data: &Split<AlephData, RmcData>) -> Priority {
+	match data {
+		Split::Left(_) => Priority::Normal,
+		Split::Right(_) => Priority::High,
+	}
+}
+
+impl<UH: UnverifiedHeader> Prioritized for VersionedNetworkData<UH> {
+	fn priority(&self) -> Priority {
+		match self {
+			VersionedEitherMessage::Left(data) => split_priority(data),
+			VersionedEitherMessage::Right(data) => split_priority(data),
+		}
+	}
+}
+
 pub trait ClientForAleph<B, BE>:
 	LockImportRun<B, BE>
 	+ Finalizer<B, BE>
@@ -256,6 +287,10 @@ type Hasher = abft::HashWrapper<BlakeTwo256>;
 pub struct RateLimiterConfig {
 	/// Maximum bit-rate per node in bytes per second of the alephbft validator network.
 	pub alephbft_bit_rate_per_connection: usize,
+	/// Maximum combined bit-rate of all the connections of the alephbft validator network. If set,
+	/// the rate of every connection adapts to the committee size and its backlog, never dropping
+	/// below `alephbft_bit_rate_per_connection`.
+	pub alephbft_aggregate_bit_rate: Option<usize>,
 }
 
 pub struct AlephConfig<C, T> {
@@ -270,14 +305,20 @@ pub struct AlephConfig<C, T> {
 	pub justification_channel_provider: ChannelProvider<Justification>,
 	pub block_rx: mpsc::UnboundedReceiver<AlephBlock>,
 	pub registry: Option<Registry>,
-	pub session_period: SessionPeriod,
+	pub session_info: SessionBoundaryInfo,
 	pub millisecs_per_block: MillisecsPerBlock,
 	pub unit_creation_delay: UnitCreationDelay,
-	pub backup_saving_path: Option<PathBuf>,
+	pub backup_storage: Option<Arc<dyn BackupStorage>>,
+	pub backup_retained_sessions: u32,
 	pub external_addresses: Vec<String>,
 	pub validator_port: u16,
 	pub rate_limiter_config: RateLimiterConfig,
 	pub sync_oracle: SyncOracle,
 	pub validator_address_cache: Option<ValidatorAddressCache>,
 	pub transaction_pool: Arc<T>,
+	pub sync_reputation_path: Option<PathBuf>,
+	pub sync_config: SyncConfig,
+	pub validator_network_key: ValidatorNetworkKey,
+	pub health_monitor: HealthMonitor,
+	pub block_tracer: BlockTracer,
 }
@@ -0,0 +1,449 @@
+use std::{
+	fmt::{Display, Error as FmtError, Formatter},
+	num::NonZeroUsize,
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::Future;
+use hyper::{header::CONTENT_TYPE, Body, Client, Request, Uri};
+use log::{debug, trace, warn};
+use lru::LruCache;
+use parking_lot::Mutex;
+use selendra_primitives::BlockHash;
+use serde_json::{json, Value};
+use tokio::{sync::mpsc, time};
+
+use crate::metrics::LOG_TARGET;
+
+// How many blocks we trace at the same time. Blocks that are never finalized, e.g. abandoned
+// forks, are forgotten once this limit is exceeded.
+const MAX_TRACED_BLOCKS: usize = 5000;
+// How many spans are sent to the collector in a single request at most.
+const MAX_SPANS_PER_REQUEST: usize = 1024;
+// How many finalized blocks wait for their spans to be sent at most. The spans of any further
+// blocks are dropped until the collector catches up.
+const MAX_PENDING_EXPORTS: usize = 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_OTLP_PATH: &str = "/v1/traces";
+const SERVICE_NAME: &str = "selendra-node";
+
+/// Stages in the lifecycle of a block, each reported by a different component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockStage {
+	/// Authored by this node and passed to the import queue by the sync.
+	Authored,
+	/// Received from the network and passed to the import queue by the sync.
+	Received,
+	/// Chosen for finalization by the ordered data interpreter.
+	Ordered,
+	/// Signed by enough of the committee, as gathered by the aggregator.
+	Aggregated,
+	/// Finalized by the finalizer.
+	Finalized,
+}
+
+impl BlockStage {
+	fn name(&self) -> &'static str {
+		use BlockStage::*;
+		match self {
+			Authored => "authored",
+			Received => "received",
+			Ordered => "ordered",
+			Aggregated => "aggregated",
+			Finalized => "finalized",
+		}
+	}
+}
+
+/// A finished span of the trace of a single block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockSpan {
+	pub block_hash: BlockHash,
+	pub span_id: u64,
+	/// `None` for the span covering the whole lifecycle of the block.
+	pub parent_span_id: Option<u64>,
+	pub name: &'static str,
+	pub start: SystemTime,
+	pub end: SystemTime,
+}
+
+impl BlockSpan {
+	/// All the spans of a block share the trace identifier derived from its hash.
+	pub fn trace_id(&self) -> [u8; 16] {
+		let mut trace_id = [0; 16];
+		trace_id.copy_from_slice(&self.block_hash.as_bytes()[..16]);
+		trace_id
+	}
+}
+
+/// Destination of the spans of blocks that got finalized.
+pub trait SpanExporter: Send + Sync {
+	fn export(&self, spans: Vec<BlockSpan>);
+}
+
+pub struct TracedBlock {
+	root_span_id: u64,
+	started: SystemTime,
+	last_seen: SystemTime,
+	spans: Vec<BlockSpan>,
+}
+
+/// Traces the lifecycle of blocks from being authored or received until they are finalized.
+///
+/// Every stage of a block becomes a span lasting since the previous stage of the same block,
+/// and all of them are children of a span covering the whole lifecycle. The spans are exported
+/// once the block gets finalized.
+#[derive(Clone)]
+pub enum BlockTracer {
+	Enabled {
+		blocks: Arc<Mutex<LruCache<BlockHash, TracedBlock>>>,
+		exporter: Arc<dyn SpanExporter>,
+	},
+	Noop,
+}
+
+impl BlockTracer {
+	pub fn new(exporter: Arc<dyn SpanExporter>) -> Self {
+		BlockTracer::Enabled {
+			blocks: Arc::new(Mutex::new(LruCache::new(
+				NonZeroUsize::new(MAX_TRACED_BLOCKS).expect("the limit is a non-zero constant"),
+			))),
+			exporter,
+		}
+	}
+
+	pub fn noop() -> Self {
+		BlockTracer::Noop
+	}
+
+	/// Reports that the block reached the stage, if it hasn't been reported before.
+	pub fn report_block(&self, hash: BlockHash, stage: BlockStage) {
+		self.report_block_at(hash, stage, SystemTime::now())
+	}
+
+	fn report_block_at(&self, hash: BlockHash, stage: BlockStage, now: SystemTime) {
+		let (blocks, exporter) = match self {
+			BlockTracer::Enabled { blocks, exporter } => (blocks, exporter),
+			BlockTracer::Noop => return,
+		};
+		trace!(target: LOG_TARGET, "Tracing block {:?} at stage {:?}.", hash, stage);
+		let mut blocks = blocks.lock();
+		if !blocks.contains(&hash) {
+			// Blocks we have not seen before being finalized have no lifecycle worth tracing.
+			if stage != BlockStage::Finalized {
+				let root_span_id = rand::random();
+				let spans = vec![BlockSpan {
+					block_hash: hash,
+					span_id: rand::random(),
+					parent_span_id: Some(root_span_id),
+					name: stage.name(),
+					start: now,
+					end: now,
+				}];
+				blocks.put(hash, TracedBlock { root_span_id, started: now, last_seen: now, spans });
+			}
+			return;
+		}
+		let block = blocks.get_mut(&hash).expect("we just checked the block is there");
+		if block.spans.iter().any(|span| span.name == stage.name()) {
+			return;
+		}
+		block.spans.push(BlockSpan {
+			block_hash: hash,
+			span_id: rand::random(),
+			parent_span_id: Some(block.root_span_id),
+			name: stage.name(),
+			start: block.last_seen,
+			end: now,
+		});
+		block.last_seen = now;
+		if stage != BlockStage::Finalized {
+			return;
+		}
+		if let Some(mut block) = blocks.pop(&hash) {
+			block.spans.push(BlockSpan {
+				block_hash: hash,
+				span_id: block.root_span_id,
+				parent_span_id: None,
+				name: "block",
+				start: block.started,
+				end: now,
+			});
+			exporter.export(block.spans);
+		}
+	}
+}
+
+/// What can go wrong when setting up the export of spans.
+#[derive(Debug, PartialEq, Eq)]
+pub enum OtlpError {
+	UnsupportedEndpoint(String),
+}
+
+impl Display for OtlpError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use OtlpError::*;
+		match self {
+			UnsupportedEndpoint(endpoint) => {
+				write!(f, "unsupported OTLP endpoint {endpoint}, expected http://host:port[/path]")
+			},
+		}
+	}
+}
+
+/// Exports spans to an OpenTelemetry collector using OTLP over HTTP with JSON encoding.
+pub struct OtlpExporter {
+	spans_for_sender: mpsc::Sender<Vec<BlockSpan>>,
+}
+
+impl OtlpExporter {
+	/// Creates the exporter together with the task sending the spans, which has to be spawned.
+	pub fn new(endpoint: &str) -> Result<(Self, impl Future<Output = ()>), OtlpError> {
+		let endpoint = parse_endpoint(endpoint)
+			.ok_or_else(|| OtlpError::UnsupportedEndpoint(endpoint.to_string()))?;
+		let (spans_for_sender, spans_from_tracer) = mpsc::channel(MAX_PENDING_EXPORTS);
+		Ok((OtlpExporter { spans_for_sender }, send_spans(endpoint, spans_from_tracer)))
+	}
+}
+
+impl SpanExporter for OtlpExporter {
+	fn export(&self, spans: Vec<BlockSpan>) {
+		if let Err(e) = self.spans_for_sender.try_send(spans) {
+			debug!(target: LOG_TARGET, "Dropping spans, the span sender is not keeping up: {}.", e);
+		}
+	}
+}
+
+/// Only plain HTTP endpoints with an explicit port are supported, the path defaults to the
+/// standard OTLP traces path.
+fn parse_endpoint(endpoint: &str) -> Option<Uri> {
+	let uri: Uri = endpoint.parse().ok()?;
+	if uri.scheme_str() != Some("http") || uri.host()?.is_empty() || uri.port().is_none() {
+		return None;
+	}
+	match uri.path() {
+		"" | "/" => Uri::builder()
+			.scheme("http")
+			.authority(uri.authority()?.clone())
+			.path_and_query(DEFAULT_OTLP_PATH)
+			.build()
+			.ok(),
+		_ => Some(uri),
+	}
+}
+
+async fn send_spans(endpoint: Uri, mut spans_from_tracer: mpsc::Receiver<Vec<BlockSpan>>) {
+	let client = Client::new();
+	while let Some(mut spans) = spans_from_tracer.recv().await {
+		while spans.len() < MAX_SPANS_PER_REQUEST {
+			match spans_from_tracer.try_recv() {
+				Ok(more_spans) => spans.extend(more_spans),
+				Err(_) => break,
+			}
+		}
+		let request = match Request::post(endpoint.clone())
+			.header(CONTENT_TYPE, "application/json")
+			.body(Body::from(otlp_json(&spans).to_string()))
+		{
+			Ok(request) => request,
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Failed to build the span export request: {}.", e);
+				continue;
+			},
+		};
+		match time::timeout(REQUEST_TIMEOUT, client.request(request)).await {
+			Ok(Ok(response)) if response.status().is_success() => {
+				trace!(target: LOG_TARGET, "Exported {} span(s).", spans.len())
+			},
+			Ok(Ok(response)) => warn!(
+				target: LOG_TARGET,
+				"Collector at {} rejected spans with status {}.",
+				endpoint,
+				response.status()
+			),
+			Ok(Err(e)) => {
+				warn!(target: LOG_TARGET, "Failed to export spans to {}: {}.", endpoint, e)
+			},
+			Err(_) => warn!(target: LOG_TARGET, "Exporting spans to {} timed out.", endpoint),
+		}
+	}
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+	time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
+
+fn otlp_json(spans: &[BlockSpan]) -> Value {
+	let spans: Vec<_> = spans
+		.iter()
+		.map(|span| {
+			let mut otlp_span = json!({
+				"traceId": hex::encode(span.trace_id()),
+				"spanId": hex::encode(span.span_id.to_be_bytes()),
+				"name": span.name,
+				// SPAN_KIND_INTERNAL
+				"kind": 1,
+				"startTimeUnixNano": unix_nanos(span.start),
+				"endTimeUnixNano": unix_nanos(span.end),
+				"attributes": [{
+					"key": "block.hash",
+					"value": { "stringValue": format!("{:?}", span.block_hash) },
+				}],
+			});
+			if let Some(parent_span_id) = span.parent_span_id {
+				otlp_span["parentSpanId"] = hex::encode(parent_span_id.to_be_bytes()).into();
+			}
+			otlp_span
+		})
+		.collect();
+	json!({
+		"resourceSpans": [{
+			"resource": {
+				"attributes": [{
+					"key": "service.name",
+					"value": { "stringValue": SERVICE_NAME },
+				}],
+			},
+			"scopeSpans": [{
+				"scope": { "name": "finality-aleph" },
+				"spans": spans,
+			}],
+		}],
+	})
+}
+
+#[cfg(test)]
+pub mod mock {
+	use std::sync::Arc;
+
+	use parking_lot::Mutex;
+
+	use super::{BlockSpan, SpanExporter};
+
+	/// Keeps the exported spans in memory, in place of a collector.
+	#[derive(Clone, Default)]
+	pub struct InMemoryExporter {
+		spans: Arc<Mutex<Vec<BlockSpan>>>,
+	}
+
+	impl InMemoryExporter {
+		pub fn spans(&self) -> Vec<BlockSpan> {
+			self.spans.lock().clone()
+		}
+	}
+
+	impl SpanExporter for InMemoryExporter {
+		fn export(&self, spans: Vec<BlockSpan>) {
+			self.spans.lock().extend(spans);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		convert::Infallible,
+		sync::Arc,
+		time::{Duration, SystemTime},
+	};
+
+	use hyper::{
+		service::{make_service_fn, service_fn},
+		Body, Request, Response, Server,
+	};
+	use selendra_primitives::BlockHash;
+	use tokio::sync::mpsc;
+
+	use super::{
+		mock::InMemoryExporter, BlockStage::*, BlockTracer, OtlpExporter, SpanExporter,
+		MAX_PENDING_EXPORTS,
+	};
+
+	#[test]
+	fn exports_lifecycle_once_finalized() {
+		let exporter = InMemoryExporter::default();
+		let tracer = BlockTracer::new(Arc::new(exporter.clone()));
+		let hash = BlockHash::repeat_byte(1);
+		let start = SystemTime::now();
+		let at = |millis| start + Duration::from_millis(millis);
+		tracer.report_block_at(hash, Authored, at(0));
+		tracer.report_block_at(hash, Ordered, at(100));
+		tracer.report_block_at(hash, Ordered, at(150));
+		tracer.report_block_at(hash, Aggregated, at(300));
+		assert!(exporter.spans().is_empty());
+		tracer.report_block_at(hash, Finalized, at(350));
+
+		let spans = exporter.spans();
+		let names: Vec<_> = spans.iter().map(|span| span.name).collect();
+		assert_eq!(names, ["authored", "ordered", "aggregated", "finalized", "block"]);
+		let root = spans.last().expect("there are spans");
+		assert_eq!((root.start, root.end, root.parent_span_id), (at(0), at(350), None));
+		assert!(spans[..4].iter().all(|span| span.parent_span_id == Some(root.span_id)));
+		assert_eq!((spans[2].start, spans[2].end), (at(100), at(300)));
+		assert!(spans.iter().all(|span| span.trace_id() == root.trace_id()));
+	}
+
+	#[test]
+	fn ignores_blocks_first_seen_when_finalized() {
+		let exporter = InMemoryExporter::default();
+		let tracer = BlockTracer::new(Arc::new(exporter.clone()));
+		tracer.report_block(BlockHash::repeat_byte(2), Finalized);
+		assert!(exporter.spans().is_empty());
+	}
+
+	#[tokio::test]
+	async fn sends_spans_to_collector() {
+		let (requests_for_test, mut requests) = mpsc::unbounded_channel();
+		let collector =
+			Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service_fn(move |_| {
+				let requests_for_test = requests_for_test.clone();
+				async move {
+					Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+						let requests_for_test = requests_for_test.clone();
+						async move {
+							let path = request.uri().path().to_string();
+							let body = hyper::body::to_bytes(request.into_body()).await?;
+							let _ = requests_for_test
+								.send((path, String::from_utf8_lossy(&body).into_owned()));
+							Ok::<_, hyper::Error>(Response::new(Body::empty()))
+						}
+					}))
+				}
+			}));
+		let endpoint = format!("http://{}", collector.local_addr());
+		tokio::spawn(collector);
+		let (exporter, sender) = OtlpExporter::new(&endpoint).expect("the endpoint is valid");
+		tokio::spawn(sender);
+
+		let inner = InMemoryExporter::default();
+		let tracer = BlockTracer::new(Arc::new(inner.clone()));
+		let hash = BlockHash::repeat_byte(7);
+		tracer.report_block(hash, Received);
+		tracer.report_block(hash, Finalized);
+		exporter.export(inner.spans());
+
+		let (path, body) = requests.recv().await.expect("the collector should get a request");
+		assert_eq!(path, "/v1/traces");
+		assert!(body.contains(&format!("\"traceId\":\"{}\"", hex::encode([7; 16]))));
+		assert!(body.contains("\"name\":\"finalized\""));
+	}
+
+	#[test]
+	fn drops_spans_when_sender_lags() {
+		let (exporter, _sender) =
+			OtlpExporter::new("http://localhost:4318").expect("the endpoint is valid");
+		for _ in 0..=MAX_PENDING_EXPORTS {
+			exporter.export(Vec::new());
+		}
+		assert_eq!(exporter.spans_for_sender.capacity(), 0);
+	}
+
+	#[test]
+	fn rejects_unsupported_endpoints() {
+		assert!(OtlpExporter::new("https://localhost:4318").is_err());
+		assert!(OtlpExporter::new("http://localhost").is_err());
+		assert!(OtlpExporter::new("localhost:4318").is_err());
+		assert!(OtlpExporter::new("http://localhost:4318/custom").is_ok());
+	}
+}
@@ -0,0 +1,378 @@
+use std::{
+	collections::VecDeque,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use log::warn;
+use parking_lot::Mutex;
+use prometheus_endpoint::{register, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64};
+use serde::{Deserialize, Serialize};
+
+use crate::{block::HeaderBackend, metrics::LOG_TARGET, BlockNumber, SubstrateChainStatus};
+
+/// How often the health gauges are refreshed and the finality lag sampled.
+const HEALTH_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// How many samples of the finality lag make up the rolling average, five minutes worth.
+const ROLLING_WINDOW: usize = 60;
+
+/// Limits beyond which the node is reported as unhealthy.
+#[derive(Clone, Debug)]
+pub struct HealthThresholds {
+	/// Maximal rolling average of the gap between the best and the finalized block.
+	pub max_finality_lag: f64,
+	/// Maximal time without a new finalized block.
+	pub max_time_since_finalized: Duration,
+	/// Minimal percentage of the committee of any session we take part in that we should be
+	/// connected to, including ourselves.
+	pub min_committee_connected_percent: u32,
+}
+
+impl Default for HealthThresholds {
+	fn default() -> Self {
+		HealthThresholds {
+			max_finality_lag: 20.0,
+			max_time_since_finalized: Duration::from_secs(60),
+			min_committee_connected_percent: 67,
+		}
+	}
+}
+
+/// How many members of the committee of a session we are connected to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitteeConnectivity {
+	pub session: u32,
+	/// Connected committee members, including ourselves.
+	pub connected: u32,
+	pub committee_size: u32,
+}
+
+impl CommitteeConnectivity {
+	pub fn connected_percent(&self) -> u32 {
+		match self.committee_size {
+			0 => 100,
+			size => self.connected.saturating_mul(100) / size,
+		}
+	}
+}
+
+/// The health of the node, together with the reasons it is unhealthy, if it is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+	pub healthy: bool,
+	pub reasons: Vec<String>,
+	pub best_block: BlockNumber,
+	pub finalized_block: BlockNumber,
+	pub finality_lag: BlockNumber,
+	pub rolling_finality_lag: f64,
+	/// Counted from the start of the node if it did not finalize any block since then.
+	pub seconds_since_finalized: u64,
+	pub committee_connectivity: Vec<CommitteeConnectivity>,
+}
+
+#[derive(Clone)]
+enum HealthGauges {
+	Prometheus {
+		finality_lag: Gauge<U64>,
+		rolling_finality_lag: Gauge<F64>,
+		seconds_since_finalized: Gauge<U64>,
+		committee_connected_percent: GaugeVec<U64>,
+	},
+	Noop,
+}
+
+impl HealthGauges {
+	fn new(registry: Option<&Registry>) -> Result<Self, PrometheusError> {
+		let registry = match registry {
+			Some(registry) => registry,
+			None => return Ok(HealthGauges::Noop),
+		};
+		Ok(HealthGauges::Prometheus {
+			finality_lag: register(
+				Gauge::new(
+					"aleph_health_finality_lag",
+					"Number of blocks between the best and the top finalized block",
+				)?,
+				registry,
+			)?,
+			rolling_finality_lag: register(
+				Gauge::new(
+					"aleph_health_rolling_finality_lag",
+					"Average finality lag over the last five minutes",
+				)?,
+				registry,
+			)?,
+			seconds_since_finalized: register(
+				Gauge::new(
+					"aleph_health_seconds_since_finalized",
+					"Seconds since the last block was finalized",
+				)?,
+				registry,
+			)?,
+			committee_connected_percent: register(
+				GaugeVec::new(
+					Opts::new(
+						"aleph_health_committee_connected_percent",
+						"Percentage of the committee of a session we are connected to",
+					),
+					&["session"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+struct HealthState {
+	best_block: BlockNumber,
+	finalized_block: BlockNumber,
+	last_finalized: Instant,
+	lag_samples: VecDeque<BlockNumber>,
+	committee_connectivity: Vec<CommitteeConnectivity>,
+}
+
+impl HealthState {
+	fn finality_lag(&self) -> BlockNumber {
+		self.best_block.saturating_sub(self.finalized_block)
+	}
+
+	fn rolling_finality_lag(&self) -> f64 {
+		match self.lag_samples.len() {
+			0 => self.finality_lag() as f64,
+			samples => self.lag_samples.iter().map(|lag| *lag as f64).sum::<f64>() / samples as f64,
+		}
+	}
+}
+
+/// Derives the health of the node from the progress of finalization and the connectivity of the
+/// committees it is part of.
+#[derive(Clone)]
+pub struct HealthMonitor {
+	state: Arc<Mutex<HealthState>>,
+	gauges: HealthGauges,
+	thresholds: HealthThresholds,
+}
+
+impl HealthMonitor {
+	/// The top finalized block of the chain status counts as finalized at the start, so that a
+	/// restarted node is not unhealthy until it finalizes another block.
+	pub fn new(
+		registry: Option<&Registry>,
+		thresholds: HealthThresholds,
+		chain_status: &SubstrateChainStatus,
+	) -> Self {
+		let gauges = HealthGauges::new(registry).unwrap_or_else(|e| {
+			warn!(target: LOG_TARGET, "Failed to register Prometheus health metrics: {:?}.", e);
+			HealthGauges::Noop
+		});
+		let finalized_block = chain_status.top_finalized_id().number();
+		let state = HealthState {
+			best_block: finalized_block,
+			finalized_block,
+			last_finalized: Instant::now(),
+			lag_samples: VecDeque::new(),
+			committee_connectivity: Vec::new(),
+		};
+		HealthMonitor { state: Arc::new(Mutex::new(state)), gauges, thresholds }
+	}
+
+	pub fn report_best_block(&self, number: BlockNumber) {
+		self.state.lock().best_block = number;
+	}
+
+	pub fn report_finalized_block(&self, number: BlockNumber) {
+		self.report_finalized_block_at(number, Instant::now());
+	}
+
+	fn report_finalized_block_at(&self, number: BlockNumber, now: Instant) {
+		let mut state = self.state.lock();
+		state.finalized_block = number;
+		state.last_finalized = now;
+	}
+
+	/// Replaces the connectivity of all the committees we are part of.
+	pub fn report_committee_connectivity(&self, connectivity: Vec<CommitteeConnectivity>) {
+		let mut state = self.state.lock();
+		if let HealthGauges::Prometheus { committee_connected_percent, .. } = &self.gauges {
+			for old in &state.committee_connectivity {
+				if !connectivity.iter().any(|new| new.session == old.session) {
+					let _ = committee_connected_percent
+						.remove_label_values(&[&old.session.to_string()]);
+				}
+			}
+			for session in &connectivity {
+				committee_connected_percent
+					.with_label_values(&[&session.session.to_string()])
+					.set(session.connected_percent() as u64);
+			}
+		}
+		state.committee_connectivity = connectivity;
+	}
+
+	fn refresh_at(&self, now: Instant) {
+		let mut state = self.state.lock();
+		let lag = state.finality_lag();
+		if state.lag_samples.len() == ROLLING_WINDOW {
+			state.lag_samples.pop_front();
+		}
+		state.lag_samples.push_back(lag);
+		if let HealthGauges::Prometheus {
+			finality_lag,
+			rolling_finality_lag,
+			seconds_since_finalized,
+			..
+		} = &self.gauges
+		{
+			finality_lag.set(lag as u64);
+			rolling_finality_lag.set(state.rolling_finality_lag());
+			seconds_since_finalized.set(now.duration_since(state.last_finalized).as_secs());
+		}
+	}
+
+	/// The current health of the node.
+	pub fn status(&self) -> HealthStatus {
+		self.status_at(Instant::now())
+	}
+
+	fn status_at(&self, now: Instant) -> HealthStatus {
+		let state = self.state.lock();
+		let rolling_finality_lag = state.rolling_finality_lag();
+		let since_finalized = now.duration_since(state.last_finalized);
+		let mut reasons = Vec::new();
+		if rolling_finality_lag > self.thresholds.max_finality_lag {
+			reasons.push(format!(
+				"finality lags {:.1} blocks behind the best block on average, more than {:.1}",
+				rolling_finality_lag, self.thresholds.max_finality_lag
+			));
+		}
+		if since_finalized > self.thresholds.max_time_since_finalized {
+			reasons.push(format!(
+				"no block finalized for {}s, more than {}s",
+				since_finalized.as_secs(),
+				self.thresholds.max_time_since_finalized.as_secs()
+			));
+		}
+		for session in &state.committee_connectivity {
+			let percent = session.connected_percent();
+			if percent < self.thresholds.min_committee_connected_percent {
+				reasons.push(format!(
+					"connected to {}/{} ({}%) of the committee of session {}, less than {}%",
+					session.connected,
+					session.committee_size,
+					percent,
+					session.session,
+					self.thresholds.min_committee_connected_percent
+				));
+			}
+		}
+		HealthStatus {
+			healthy: reasons.is_empty(),
+			reasons,
+			best_block: state.best_block,
+			finalized_block: state.finalized_block,
+			finality_lag: state.finality_lag(),
+			rolling_finality_lag,
+			seconds_since_finalized: since_finalized.as_secs(),
+			committee_connectivity: state.committee_connectivity.clone(),
+		}
+	}
+}
+
+/// Periodically samples the finality lag and refreshes the health gauges.
+pub async fn run_health_service(health: HealthMonitor) {
+	let mut refresh_ticker = tokio::time::interval(HEALTH_REFRESH_INTERVAL);
+	loop {
+		refresh_ticker.tick().await;
+		health.refresh_at(Instant::now());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use super::{CommitteeConnectivity, HealthMonitor, HealthThresholds, ROLLING_WINDOW};
+	use crate::{
+		testing::mocks::{TestClientBuilder, TestClientBuilderExt},
+		SubstrateChainStatus,
+	};
+
+	fn monitor() -> HealthMonitor {
+		let (_, backend) = TestClientBuilder::new().build_with_backend();
+		let chain_status =
+			SubstrateChainStatus::new(backend).expect("the test backend has a genesis block");
+		HealthMonitor::new(None, HealthThresholds::default(), &chain_status)
+	}
+
+	#[test]
+	fn healthy_when_finalizing() {
+		let health = monitor();
+		let now = Instant::now();
+		health.report_best_block(12);
+		health.report_finalized_block_at(10, now);
+		health.report_committee_connectivity(vec![CommitteeConnectivity {
+			session: 3,
+			connected: 4,
+			committee_size: 4,
+		}]);
+		health.refresh_at(now);
+		let status = health.status_at(now + Duration::from_secs(1));
+		assert!(status.healthy, "unexpected reasons: {:?}", status.reasons);
+		assert_eq!(status.finality_lag, 2);
+		assert_eq!(status.seconds_since_finalized, 1);
+	}
+
+	#[test]
+	fn unhealthy_without_recent_finalization() {
+		let health = monitor();
+		let now = Instant::now();
+		let status = health.status_at(now);
+		assert!(status.healthy, "the top finalized block should count as just finalized");
+		assert_eq!(status.finalized_block, 0);
+		assert!(!health.status_at(now + Duration::from_secs(61)).healthy);
+		health.report_finalized_block_at(10, now);
+		assert!(health.status_at(now + Duration::from_secs(30)).healthy);
+		let status = health.status_at(now + Duration::from_secs(61));
+		assert!(!status.healthy);
+		assert_eq!(status.reasons.len(), 1);
+	}
+
+	#[test]
+	fn finality_lag_is_averaged() {
+		let health = monitor();
+		let now = Instant::now();
+		health.report_finalized_block_at(0, now);
+		health.report_best_block(100);
+		health.refresh_at(now);
+		for _ in 1..ROLLING_WINDOW {
+			health.report_best_block(0);
+			health.refresh_at(now);
+		}
+		let status = health.status_at(now);
+		assert!(status.healthy, "unexpected reasons: {:?}", status.reasons);
+		health.report_best_block(100);
+		for _ in 0..ROLLING_WINDOW / 2 {
+			health.refresh_at(now);
+		}
+		let status = health.status_at(now);
+		assert!(!status.healthy);
+		assert_eq!(status.rolling_finality_lag, 50.0);
+	}
+
+	#[test]
+	fn unhealthy_when_committee_disconnected() {
+		let health = monitor();
+		let now = Instant::now();
+		health.report_finalized_block_at(10, now);
+		health.report_committee_connectivity(vec![
+			CommitteeConnectivity { session: 3, connected: 4, committee_size: 4 },
+			CommitteeConnectivity { session: 4, connected: 2, committee_size: 4 },
+		]);
+		let status = health.status_at(now);
+		assert!(!status.healthy);
+		assert_eq!(status.reasons.len(), 1);
+		assert!(status.reasons[0].contains("session 4"));
+	}
+}
@@ -1,12 +1,31 @@
 mod best_block;
+mod block_tracing;
 mod finality_rate;
+mod health;
 mod slo;
 mod timing;
 pub mod transaction_pool;
 
+pub use block_tracing::{BlockStage, BlockTracer, OtlpError, OtlpExporter};
+pub use health::{
+	run_health_service, CommitteeConnectivity, HealthMonitor, HealthStatus, HealthThresholds,
+};
 pub use slo::{run_metrics_service, SloMetrics};
 pub use timing::{Checkpoint, DefaultClock};
 pub type TimingBlockMetrics = timing::TimingBlockMetrics<DefaultClock>;
+
+/// Everything reporting the progress of individual blocks, passed around together.
+#[derive(Clone)]
+pub struct BlockMetrics {
+	pub timing: TimingBlockMetrics,
+	pub tracer: BlockTracer,
+}
+
+impl BlockMetrics {
+	pub fn noop() -> Self {
+		BlockMetrics { timing: TimingBlockMetrics::noop(), tracer: BlockTracer::noop() }
+	}
+}
 use prometheus_endpoint::{exponential_buckets, prometheus};
 
 const LOG_TARGET: &str = "selendra-metrics";
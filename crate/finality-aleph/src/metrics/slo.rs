@@ -10,7 +10,7 @@ use crate::{
 	block::ChainStatus,
 	metrics::{
 		best_block::BestBlockMetrics, timing::Checkpoint, transaction_pool::TransactionPoolMetrics,
-		TimingBlockMetrics, LOG_TARGET,
+		HealthMonitor, TimingBlockMetrics, LOG_TARGET,
 	},
 	BlockId, SubstrateChainStatus,
 };
@@ -36,11 +36,16 @@ pub struct SloMetrics {
 	finality_rate_metrics: FinalityRateMetrics,
 	best_block_metrics: BestBlockMetrics,
 	transaction_metrics: TransactionPoolMetrics<TxHash, DefaultClock>,
+	health: HealthMonitor,
 	chain_status: SubstrateChainStatus,
 }
 
 impl SloMetrics {
-	pub fn new(registry: Option<&Registry>, chain_status: SubstrateChainStatus) -> Self {
+	pub fn new(
+		registry: Option<&Registry>,
+		chain_status: SubstrateChainStatus,
+		health: HealthMonitor,
+	) -> Self {
 		let warn_creation_failed = |name, e| warn!(target: LOG_TARGET, "Failed to register Prometheus {name} metrics: {e:?}.");
 		let timing_metrics = TimingBlockMetrics::new(registry, DefaultClock).unwrap_or_else(|e| {
 			warn!(
@@ -72,6 +77,7 @@ impl SloMetrics {
 			finality_rate_metrics,
 			best_block_metrics,
 			transaction_metrics,
+			health,
 			chain_status,
 		}
 	}
@@ -97,6 +103,7 @@ impl SloMetrics {
 			self.finality_rate_metrics.report_own_imported(block_id.clone());
 		}
 		if is_new_best {
+			self.health.report_best_block(block_id.number());
 			self.best_block_metrics.report_best_block_imported(block_id.clone());
 		}
 		if let Ok(Some(block)) = self.chain_status.block(block_id.clone()) {
@@ -111,6 +118,7 @@ impl SloMetrics {
 	pub fn report_block_finalized(&self, block_id: BlockId) {
 		self.timing_metrics.report_block(block_id.hash(), Checkpoint::Finalized);
 		self.finality_rate_metrics.report_finalized(block_id.clone());
+		self.health.report_finalized_block(block_id.number());
 		self.best_block_metrics.report_block_finalized(block_id.clone());
 	}
 }
@@ -0,0 +1,199 @@
+use std::{
+	fmt::{Display, Error as FmtError, Formatter},
+	fs,
+	io::{self, Write},
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use log::{info, warn};
+use parking_lot::Mutex;
+use sc_keystore::{Keystore, LocalKeystore};
+use selendra_primitives::AuthorityId;
+use sp_keystore::Error as KeystoreError;
+
+use crate::{
+	crypto::{AuthorityPen, Error as CryptoError},
+	network::tcp::KEY_TYPE,
+};
+
+const LOG_TARGET: &str = "validator-network-key";
+
+/// What can go wrong when loading or rotating the validator network key.
+#[derive(Debug)]
+pub enum Error {
+	Keystore(KeystoreError),
+	Io(io::Error),
+	Pen(CryptoError),
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use Error::*;
+		match self {
+			Keystore(e) => write!(f, "keystore error: {e}"),
+			Io(e) => write!(f, "failed to access the stored key: {e}"),
+			Pen(e) => write!(f, "the key cannot be used for signing: {e:?}"),
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(e: io::Error) -> Self {
+		Error::Io(e)
+	}
+}
+
+/// The key identifying the node in the validator network.
+///
+/// The seed of the key is kept in a file at `path`, and the key itself only in the memory of the
+/// keystore, never next to the session keys on disk. Thanks to this the identity of the node,
+/// and thus its signed addressing information, survives restarts, so peers can reconnect to it
+/// without waiting for the next discovery round, while rotated keys leave nothing behind.
+#[derive(Clone)]
+pub struct ValidatorNetworkKey {
+	keystore: Arc<LocalKeystore>,
+	path: Option<PathBuf>,
+	seed: Arc<Mutex<Option<String>>>,
+}
+
+impl ValidatorNetworkKey {
+	/// Without a `path` a fresh key is generated on every start.
+	pub fn new(keystore: Arc<LocalKeystore>, path: Option<PathBuf>) -> Self {
+		ValidatorNetworkKey { keystore, path, seed: Arc::new(Mutex::new(None)) }
+	}
+
+	fn stored(&self) -> Result<Option<String>, Error> {
+		if let Some(seed) = self.seed.lock().clone() {
+			return Ok(Some(seed));
+		}
+		let path = match &self.path {
+			Some(path) if path.exists() => path,
+			_ => return Ok(None),
+		};
+		let seed = fs::read_to_string(path)?.trim().to_string();
+		match seed.strip_prefix("0x").and_then(|seed| hex::decode(seed).ok()) {
+			Some(bytes) if bytes.len() == 32 => Ok(Some(seed)),
+			_ => {
+				warn!(target: LOG_TARGET, "Malformed validator network key stored at {:?}.", path);
+				Ok(None)
+			},
+		}
+	}
+
+	fn store(&self, seed: String) -> Result<(), Error> {
+		if let Some(path) = &self.path {
+			let tmp_path = path.with_extension("tmp");
+			write_secret(&tmp_path, &seed)?;
+			fs::rename(tmp_path, path)?;
+		}
+		*self.seed.lock() = Some(seed);
+		Ok(())
+	}
+
+	/// Puts the key with the given seed into the keystore, in memory only.
+	fn load(&self, seed: &str) -> Result<AuthorityId, Error> {
+		Ok(self
+			.keystore
+			.ed25519_generate_new(KEY_TYPE, Some(seed))
+			.map_err(Error::Keystore)?
+			.into())
+	}
+
+	/// The current key, generating one if there is none yet.
+	pub fn current(&self) -> Result<AuthorityId, Error> {
+		match self.stored()? {
+			Some(seed) => {
+				let key = self.load(&seed)?;
+				*self.seed.lock() = Some(seed);
+				Ok(key)
+			},
+			None => self.rotate(),
+		}
+	}
+
+	/// Generates a new key and makes it the current one. The validator network keeps using the
+	/// old key until the node restarts, which is why it stays in the keystore until then.
+	pub fn rotate(&self) -> Result<AuthorityId, Error> {
+		let seed = format!("0x{}", hex::encode(rand::random::<[u8; 32]>()));
+		let key = self.load(&seed)?;
+		self.store(seed)?;
+		info!(target: LOG_TARGET, "Generated new validator network key {:?}.", key);
+		Ok(key)
+	}
+
+	/// A pen signing with the current key.
+	pub fn pen(&self) -> Result<AuthorityPen, Error> {
+		AuthorityPen::new_with_key_type(self.current()?, self.keystore.clone(), KEY_TYPE)
+			.map_err(Error::Pen)
+	}
+}
+
+/// Writes the seed to a file only the owner can read.
+fn write_secret(path: &Path, seed: &str) -> Result<(), io::Error> {
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+	options.open(path)?.write_all(seed.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, path::PathBuf, sync::Arc};
+
+	use sc_keystore::LocalKeystore;
+	use tempfile::TempDir;
+
+	use super::ValidatorNetworkKey;
+
+	fn key_path(dir: &TempDir) -> Option<PathBuf> {
+		Some(dir.path().join("validator-network-key"))
+	}
+
+	#[test]
+	fn keeps_key_across_restarts() {
+		let dir = TempDir::new().expect("creating a temporary directory should work");
+		let key = ValidatorNetworkKey::new(Arc::new(LocalKeystore::in_memory()), key_path(&dir));
+		let current = key.current().expect("generating a key should work");
+		let restarted =
+			ValidatorNetworkKey::new(Arc::new(LocalKeystore::in_memory()), key_path(&dir));
+		assert_eq!(restarted.current().expect("the key should be there"), current);
+		assert_eq!(restarted.pen().expect("the pen should work").authority_id(), current);
+	}
+
+	#[test]
+	fn keeps_key_without_path() {
+		let key = ValidatorNetworkKey::new(Arc::new(LocalKeystore::in_memory()), None);
+		let current = key.current().expect("generating a key should work");
+		assert_eq!(key.current().expect("the key should be there"), current);
+	}
+
+	#[test]
+	fn rotation_replaces_current_key() {
+		let dir = TempDir::new().expect("creating a temporary directory should work");
+		let keystore = Arc::new(LocalKeystore::in_memory());
+		let key = ValidatorNetworkKey::new(keystore.clone(), key_path(&dir));
+		let old = key.current().expect("generating a key should work");
+		let new = key.rotate().expect("rotating should work");
+		assert_ne!(old, new);
+		assert_eq!(key.current().expect("the key should be there"), new);
+		let restarted = ValidatorNetworkKey::new(keystore, key_path(&dir));
+		assert_eq!(restarted.current().expect("the key should be there"), new);
+	}
+
+	#[test]
+	fn keeps_keys_out_of_keystore_directory() {
+		let dir = TempDir::new().expect("creating a temporary directory should work");
+		let keystore_path = dir.path().join("keystore");
+		let keystore =
+			Arc::new(LocalKeystore::open(&keystore_path, None).expect("keystore should open"));
+		let key = ValidatorNetworkKey::new(keystore, key_path(&dir));
+		key.current().expect("generating a key should work");
+		key.rotate().expect("rotating should work");
+		assert_eq!(
+			fs::read_dir(&keystore_path).expect("keystore directory should exist").count(),
+			0
+		);
+	}
+}
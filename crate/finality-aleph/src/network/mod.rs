@@ -5,7 +5,7 @@ use std::{
 };
 
 pub use build::{network as build_network, NetworkOutput as BuildNetworkOutput};
-use network_clique::{AddressingInformation, NetworkIdentity, PeerId};
+use network_clique::{AddressingInformation, NetworkIdentity, PeerId, Priority};
 use parity_scale_codec::Codec;
 pub use substrate::{PeerId as SubstratePeerId, ProtocolNetwork};
 
@@ -13,6 +13,7 @@ pub mod address_cache;
 mod base_protocol;
 mod build;
 pub mod data;
+pub mod key;
 #[cfg(test)]
 pub mod mock;
 pub mod session;
@@ -26,6 +27,11 @@ pub trait Data: Clone + Codec + Send + Sync + 'static {}
 
 impl<D: Clone + Codec + Send + Sync + 'static> Data for D {}
 
+/// Data that knows how urgently it should be delivered through the validator network.
+pub trait Prioritized {
+	fn priority(&self) -> Priority;
+}
+
 #[async_trait::async_trait]
 /// Interface for the gossip network. This represents a P2P network and a lot of the properties of
 /// this interface result from that. In particular we might know the ID of a given peer, but not be
@@ -53,6 +59,9 @@ pub trait GossipNetwork<D: Data>: Send + 'static {
 	/// returned, retry appropriately.
 	fn broadcast(&mut self, data: D) -> Result<(), Self::Error>;
 
+	/// The peers we are currently directly connected to.
+	fn connected_peers(&self) -> HashSet<Self::PeerId>;
+
 	/// Receive some data from the network, including information about who sent it.
 	/// This method's implementation must be cancellation safe.
 	async fn next(&mut self) -> Result<(D, Self::PeerId), Self::Error>;
@@ -140,20 +140,27 @@ mod test {
 
 	use network_clique::mock::MockAddressingInformation;
 	use parity_scale_codec::{Decode, Encode};
-	use sc_keystore::LocalKeystore;
+	use sc_keystore::{Keystore, LocalKeystore};
 
 	use super::VersionedAuthentication;
 	use crate::{
-		crypto::AuthorityVerifier,
+		crypto::{AuthorityPen, AuthorityVerifier},
 		network::{
 			session::{compatibility::MAX_AUTHENTICATION_SIZE, SessionHandler},
-			tcp::{testing::new_identity, SignedTcpAddressingInformation},
+			tcp::{testing::new_identity, SignedTcpAddressingInformation, KEY_TYPE},
 			NetworkIdentity,
 		},
-		nodes::new_pen,
 		NodeIndex, SessionId, Version,
 	};
 
+	fn new_pen(mnemonic: &str, keystore: Arc<LocalKeystore>) -> AuthorityPen {
+		let validator_peer_id = keystore
+			.ed25519_generate_new(KEY_TYPE, Some(mnemonic))
+			.expect("generating a key should work");
+		AuthorityPen::new_with_key_type(validator_peer_id.into(), keystore, KEY_TYPE)
+			.expect("we just generated this key so everything should work")
+	}
+
 	/// Session Handler used for generating versioned authentication in `raw_authentication_v1`
 	fn handler() -> SessionHandler<SignedTcpAddressingInformation> {
 		let mnemonic = "ring cool spatial rookie need wing opinion pond fork garbage more april";
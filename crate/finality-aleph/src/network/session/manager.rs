@@ -10,6 +10,7 @@ use log::{debug, info};
 use crate::{
 	abft::Recipient,
 	crypto::{AuthorityPen, AuthorityVerifier},
+	metrics::CommitteeConnectivity,
 	network::{
 		address_cache::{ValidatorAddressCacheUpdater, ValidatorAddressingInfo},
 		session::{
@@ -21,6 +22,9 @@ use crate::{
 	NodeIndex, SessionId,
 };
 
+/// How long to wait between rebroadcasts of authentications in the given session.
+pub type DiscoveryCooldown = Box<dyn Fn(SessionId) -> Duration + Send>;
+
 /// Commands for manipulating the reserved peers set.
 #[derive(Debug, PartialEq, Eq)]
 pub enum ConnectionCommand<A: AddressingInformation> {
@@ -81,7 +85,7 @@ pub struct Manager<NI: NetworkIdentity, D: Data, VCU: ValidatorAddressCacheUpdat
 	connections: Connections<NI::PeerId>,
 	sessions: HashMap<SessionId, Session<D, NI::AddressingInformation>>,
 	validator_address_cache_updater: VCU,
-	discovery_cooldown: Duration,
+	discovery_cooldown: DiscoveryCooldown,
 }
 
 /// Error when trying to forward data from the network to the user, should never be fatal.
@@ -96,7 +100,7 @@ impl<NI: NetworkIdentity, D: Data, VCU: ValidatorAddressCacheUpdater> Manager<NI
 	pub fn new(
 		network_identity: NI,
 		validator_address_cache_updater: VCU,
-		discovery_cooldown: Duration,
+		discovery_cooldown: DiscoveryCooldown,
 	) -> Self {
 		Manager {
 			network_identity,
@@ -153,7 +157,7 @@ impl<NI: NetworkIdentity, D: Data, VCU: ValidatorAddressCacheUpdater> Manager<NI
 	) -> (Option<Authentication<NI::AddressingInformation>>, mpsc::UnboundedReceiver<D>) {
 		let PreValidatorSession { session_id, verifier, node_id, pen } = pre_session;
 		let handler = SessionHandler::new(Some((node_id, pen)), verifier, session_id, address);
-		let discovery = Discovery::new(self.discovery_cooldown);
+		let discovery = Discovery::new((self.discovery_cooldown)(session_id));
 		let (data_for_user, data_from_network) = mpsc::unbounded();
 		let data_for_user = Some(data_for_user);
 		self.sessions.insert(session_id, Session { handler, discovery, data_for_user });
@@ -219,7 +223,7 @@ impl<NI: NetworkIdentity, D: Data, VCU: ValidatorAddressCacheUpdater> Manager<NI
 	) {
 		let PreNonvalidatorSession { session_id, verifier } = pre_session;
 		let handler = SessionHandler::new(None, verifier, session_id, address);
-		let discovery = Discovery::new(self.discovery_cooldown);
+		let discovery = Discovery::new((self.discovery_cooldown)(session_id));
 		self.sessions
 			.insert(session_id, Session { handler, discovery, data_for_user: None });
 	}
@@ -315,6 +319,31 @@ impl<NI: NetworkIdentity, D: Data, VCU: ValidatorAddressCacheUpdater> Manager<NI
 		}
 	}
 
+	/// How many members of the committees of the sessions we are a validator in we are connected
+	/// to, counting ourselves.
+	pub fn committee_connectivity(
+		&self,
+		is_connected: impl Fn(&NI::PeerId) -> bool,
+	) -> Vec<CommitteeConnectivity> {
+		let mut connectivity: Vec<_> = self
+			.sessions
+			.iter()
+			.filter(|(_, session)| session.handler.authentication().is_some())
+			.map(|(session_id, session)| CommitteeConnectivity {
+				session: session_id.0,
+				connected: session
+					.handler
+					.peers()
+					.values()
+					.filter(|peer_id| is_connected(peer_id))
+					.count() as u32 + 1,
+				committee_size: session.handler.node_count().0 as u32,
+			})
+			.collect();
+		connectivity.sort_by_key(|session| session.session);
+		connectivity
+	}
+
 	pub fn status_report(&self) {
 		let mut status = String::from("Connection Manager status report: ");
 
@@ -393,6 +422,7 @@ mod tests {
 		SendError,
 	};
 	use crate::{
+		metrics::CommitteeConnectivity,
 		network::{
 			address_cache::{test::noop_updater, ValidatorAddressCacheUpdater},
 			mock::crypto_basics,
@@ -405,7 +435,7 @@ mod tests {
 	const DISCOVERY_PERIOD: Duration = Duration::from_secs(60);
 
 	fn build() -> Manager<MockAddressingInformation, i32, impl ValidatorAddressCacheUpdater> {
-		Manager::new(random_address(), noop_updater(), DISCOVERY_PERIOD)
+		Manager::new(random_address(), noop_updater(), Box::new(|_| DISCOVERY_PERIOD))
 	}
 
 	#[test]
@@ -435,6 +465,35 @@ mod tests {
 		assert_eq!(manager.send_session_data(&session_id, -43), Ok(()));
 	}
 
+	#[test]
+	fn reports_connectivity_of_validator_sessions() {
+		let mut manager = build();
+		let (validator_data, verifier) = crypto_basics(NUM_NODES);
+		let (node_id, pen) = validator_data[0].clone();
+		manager
+			.update_validator_session(PreValidatorSession {
+				session_id: SessionId(43),
+				verifier: verifier.clone(),
+				node_id,
+				pen,
+			})
+			.unwrap();
+		manager
+			.update_nonvalidator_session(PreNonvalidatorSession {
+				session_id: SessionId(44),
+				verifier,
+			})
+			.unwrap();
+		assert_eq!(
+			manager.committee_connectivity(|_| true),
+			vec![CommitteeConnectivity {
+				session: 43,
+				connected: 1,
+				committee_size: NUM_NODES as u32
+			}]
+		);
+	}
+
 	#[tokio::test]
 	async fn stops_session() {
 		let mut manager = build();
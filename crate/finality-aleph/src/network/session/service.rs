@@ -9,25 +9,27 @@ use futures::{
 	StreamExt,
 };
 use log::{debug, trace, warn};
-use network_clique::{Network as CliqueNetwork, PublicKey};
+use network_clique::{ManagerStatus, Network as CliqueNetwork, PublicKey};
 use tokio::time::{self, Instant};
 
 use crate::{
 	abft::Recipient,
 	crypto::{AuthorityPen, AuthorityVerifier},
+	metrics::HealthMonitor,
 	network::{
 		address_cache::ValidatorAddressCacheUpdater,
 		session::{
 			data::DataInSession,
 			manager::{
-				AddressedData, ConnectionCommand, Manager, ManagerActions, PreNonvalidatorSession,
-				PreValidatorSession, SendError,
+				AddressedData, ConnectionCommand, DiscoveryCooldown, Manager, ManagerActions,
+				PreNonvalidatorSession, PreValidatorSession, SendError,
 			},
 			Network, SessionHandlerError, SessionManager, SessionSender, VersionedAuthentication,
 		},
-		AddressingInformation, Data, GossipNetwork, NetworkIdentity,
+		AddressingInformation, Data, GossipNetwork, NetworkIdentity, Prioritized,
 	},
-	MillisecsPerBlock, NodeIndex, SessionId, SessionPeriod, STATUS_REPORT_INTERVAL,
+	MillisecsPerBlock, NodeIndex, SessionBoundaryInfo, SessionId, SessionPeriod,
+	STATUS_REPORT_INTERVAL,
 };
 
 /// Commands for manipulating sessions, stopping them and starting both validator and non-validator
@@ -130,37 +132,54 @@ impl<D: Data> SessionManager<D> for ManagerInterface<D> {
 /// Configuration for the session manager. Controls how often the maintenance and
 /// rebroadcasts are triggerred. Also controls when maintenance starts.
 pub struct Config {
-	discovery_cooldown: Duration,
+	discovery_cooldown: DiscoveryCooldown,
 	maintenance_period: Duration,
 	initial_delay: Duration,
 }
 
+/// Discovery should happen about 5 times per session.
+fn discovery_cooldown(
+	session_period: SessionPeriod,
+	millisecs_per_block: MillisecsPerBlock,
+) -> Duration {
+	Duration::from_millis(millisecs_per_block.0 * session_period.0 as u64 / 5)
+}
+
 impl Config {
 	fn new(
-		discovery_cooldown: Duration,
+		discovery_cooldown: DiscoveryCooldown,
 		maintenance_period: Duration,
 		initial_delay: Duration,
 	) -> Self {
 		Config { discovery_cooldown, maintenance_period, initial_delay }
 	}
 
-	/// Returns a configuration that triggers maintenance about 5 times per session.
-	pub fn with_session_period(
-		session_period: &SessionPeriod,
+	/// Returns a configuration that triggers discovery about 5 times per session, following
+	/// changes of the session period. Maintenance is frequent enough for the shortest session
+	/// known at startup.
+	pub fn with_session_info(
+		session_info: &SessionBoundaryInfo,
 		millisecs_per_block: &MillisecsPerBlock,
 	) -> Self {
-		let discovery_cooldown =
-			Duration::from_millis(millisecs_per_block.0 * session_period.0 as u64 / 5);
-		let maintenance_period = discovery_cooldown / 2;
+		let millisecs_per_block = *millisecs_per_block;
+		let maintenance_period =
+			discovery_cooldown(session_info.shortest_session_period(), millisecs_per_block) / 2;
 		let initial_delay =
 			cmp::min(Duration::from_millis(millisecs_per_block.0 * 10), maintenance_period);
-		Config::new(discovery_cooldown, maintenance_period, initial_delay)
+		let session_info = session_info.clone();
+		Config::new(
+			Box::new(move |session_id| {
+				discovery_cooldown(session_info.session_period(session_id), millisecs_per_block)
+			}),
+			maintenance_period,
+			initial_delay,
+		)
 	}
 }
 
 /// The connection manager service.
 pub struct Service<
-	D: Data,
+	D: Data + Prioritized,
 	NI: NetworkIdentity,
 	CN: CliqueNetwork<NI::PeerId, NI::AddressingInformation, DataInSession<D>>,
 	GN: GossipNetwork<VersionedAuthentication<NI::AddressingInformation>>,
@@ -172,7 +191,9 @@ pub struct Service<
 	commands_from_user: mpsc::UnboundedReceiver<SessionCommand<D>>,
 	messages_from_user: mpsc::UnboundedReceiver<(D, SessionId, Recipient)>,
 	validator_network: CN,
+	validator_network_status: mpsc::UnboundedReceiver<ManagerStatus<NI::PeerId>>,
 	gossip_network: GN,
+	health: HealthMonitor,
 	maintenance_period: Duration,
 	initial_delay: Duration,
 }
@@ -199,7 +220,7 @@ impl<GE: Display> Display for Error<GE> {
 }
 
 impl<
-		D: Data,
+		D: Data + Prioritized,
 		NI: NetworkIdentity,
 		CN: CliqueNetwork<NI::PeerId, NI::AddressingInformation, DataInSession<D>>,
 		GN: GossipNetwork<VersionedAuthentication<NI::AddressingInformation>>,
@@ -211,8 +232,10 @@ where
 	pub fn new(
 		network_identity: NI,
 		validator_network: CN,
+		validator_network_status: mpsc::UnboundedReceiver<ManagerStatus<NI::PeerId>>,
 		gossip_network: GN,
 		validator_address_cache_updater: VCU,
+		health: HealthMonitor,
 		config: Config,
 	) -> (Service<D, NI, CN, GN, VCU>, impl SessionManager<D, Error = ManagerError>) {
 		let Config { discovery_cooldown, maintenance_period, initial_delay } = config;
@@ -226,7 +249,9 @@ where
 				commands_from_user,
 				messages_from_user,
 				validator_network,
+				validator_network_status,
 				gossip_network,
+				health,
 				maintenance_period,
 				initial_delay,
 			},
@@ -235,7 +260,8 @@ where
 	}
 
 	fn send_data(&self, to_send: AddressedData<DataInSession<D>, NI::PeerId>) {
-		self.validator_network.send(to_send.0, to_send.1)
+		let priority = to_send.0.data.priority();
+		self.validator_network.send(to_send.0, to_send.1, priority)
 	}
 
 	fn send_authentications(
@@ -359,6 +385,12 @@ where
 						self.send_authentications(to_send.into())?;
 					}
 				},
+				maybe_status = self.validator_network_status.next() => {
+					let status = maybe_status.ok_or(Error::ValidatorNetwork)?;
+					self.health.report_committee_connectivity(
+						self.manager.committee_connectivity(|peer_id| status.is_connected(peer_id)),
+					);
+				},
 				_ = status_ticker.tick() => {
 					self.manager.status_report();
 				}
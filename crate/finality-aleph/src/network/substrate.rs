@@ -130,6 +130,10 @@ impl<D: Data> GossipNetwork<D> for ProtocolNetwork {
 		Ok(())
 	}
 
+	fn connected_peers(&self) -> HashSet<PeerId> {
+		self.connected_peers.clone()
+	}
+
 	async fn next(&mut self) -> Result<(D, PeerId), Self::Error> {
 		let mut status_ticker = time::interval_at(
 			self.last_status_report
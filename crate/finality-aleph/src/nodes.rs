@@ -1,13 +1,11 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::marker::PhantomData;
 
-use bip39::{Language, Mnemonic, MnemonicType};
-use futures::channel::oneshot;
-use log::{debug, error};
+use futures::{channel::oneshot, StreamExt};
+use log::{debug, error, warn};
 use network_clique::{RateLimitingDialer, RateLimitingListener, Service, SpawnHandleT};
 use pallet_aleph_runtime_api::AlephSessionApi;
-use rate_limiter::SleepingRateLimiter;
+use rate_limiter::{AdaptiveConfig, AdaptiveLimits, SleepingRateLimiter};
 use sc_client_api::Backend;
-use sc_keystore::{Keystore, LocalKeystore};
 use sc_transaction_pool_api::TransactionPool;
 use selendra_primitives::{AuraId, Block, TransactionHash};
 use sp_consensus_aura::AuraApi;
@@ -17,21 +15,20 @@ use crate::{
 		substrate::{JustificationTranslator, SubstrateFinalizationInfo, VerifierCache},
 		BlockchainEvents, ChainStatus, FinalizationStatus, Justification,
 	},
-	crypto::AuthorityPen,
+	data_io::DataStoreMetrics,
 	finalization::AlephFinalizer,
 	idx_to_account::ValidatorIndexToAccountIdConverterImpl,
-	metrics::{run_metrics_service, SloMetrics},
+	metrics::{run_health_service, run_metrics_service, BlockMetrics, SloMetrics},
 	network::{
 		address_cache::validator_address_cache_updater,
 		session::{ConnectionManager, ConnectionManagerConfig},
-		tcp::{new_tcp_network, KEY_TYPE},
+		tcp::new_tcp_network,
 	},
 	party::{
 		impls::ChainStateImpl, manager::NodeSessionManagerImpl, ConsensusParty,
 		ConsensusPartyParams,
 	},
 	runtime_api::RuntimeApiImpl,
-	session::SessionBoundaryInfo,
 	session_map::{
 		AuthorityProviderImpl, FinalityNotifierImpl, FinalizedBlockProviderImpl, SessionMapUpdater,
 	},
@@ -39,21 +36,8 @@ use crate::{
 	AlephConfig,
 };
 
-// How many sessions we remember.
-// Keep in mind that Aura stores authority info in the parent block,
-// so the actual size probably needs to be increased by one.
-pub const VERIFIER_CACHE_SIZE: usize = 3;
-
 const LOG_TARGET: &str = "selendra-party";
 
-pub fn new_pen(mnemonic: &str, keystore: Arc<LocalKeystore>) -> AuthorityPen {
-	let validator_peer_id = keystore
-		.ed25519_generate_new(KEY_TYPE, Some(mnemonic))
-		.expect("generating a key should work");
-	AuthorityPen::new_with_key_type(validator_peer_id.into(), keystore, KEY_TYPE)
-		.expect("we just generated this key so everything should work")
-}
-
 pub async fn run_validator_node<C, BE, TP>(aleph_config: AlephConfig<C, TP>)
 where
 	C: crate::ClientForAleph<Block, BE> + Send + Sync + 'static,
@@ -72,24 +56,30 @@ where
 		keystore,
 		registry,
 		unit_creation_delay,
-		session_period,
+		session_info,
 		millisecs_per_block,
 		justification_channel_provider,
 		block_rx,
-		backup_saving_path,
+		backup_storage,
+		backup_retained_sessions,
 		external_addresses,
 		validator_port,
 		rate_limiter_config,
 		sync_oracle,
 		validator_address_cache,
 		transaction_pool,
+		sync_reputation_path,
+		sync_config,
+		validator_network_key,
+		health_monitor,
+		block_tracer,
 	} = aleph_config;
 
-	// We generate the phrase manually to only save the key in RAM, we don't want to have these
-	// relatively low-importance keys getting spammed around the absolutely crucial Aleph keys.
-	// The interface of `ed25519_generate_new` only allows to save in RAM by providing a mnemonic.
+	// Warn if a later change of the session period makes sessions too long for the forest.
+	session_info.set_forest_max_depth(sync_config.forest_max_depth);
+
 	let network_authority_pen =
-		new_pen(Mnemonic::new(MnemonicType::Words12, Language::English).phrase(), keystore.clone());
+		validator_network_key.pen().expect("the validator network key should be usable");
 
 	debug!(
 		target: LOG_TARGET,
@@ -102,18 +92,46 @@ where
 			.await
 			.expect("we should have working networking");
 
-	let alephbft_rate_limiter =
-		SleepingRateLimiter::new(rate_limiter_config.alephbft_bit_rate_per_connection);
+	let (alephbft_rate_limiter, adaptive_limits) = match rate_limiter_config
+		.alephbft_aggregate_bit_rate
+	{
+		Some(aggregate_rate) => {
+			debug!(
+				target: LOG_TARGET,
+				"Adapting the rate of validator-network connections to the committee, with at most {} byte(s) per second in total.",
+				aggregate_rate
+			);
+			let limits = AdaptiveLimits::new(AdaptiveConfig {
+				min_rate_per_connection: rate_limiter_config.alephbft_bit_rate_per_connection,
+				max_rate_per_connection: aggregate_rate,
+				aggregate_rate,
+			});
+			(SleepingRateLimiter::adaptive(limits.clone()), Some(limits))
+		},
+		None => {
+			(SleepingRateLimiter::new(rate_limiter_config.alephbft_bit_rate_per_connection), None)
+		},
+	};
 	let dialer = RateLimitingDialer::new(dialer, alephbft_rate_limiter.clone());
 	let listener = RateLimitingListener::new(listener, alephbft_rate_limiter);
 
-	let (validator_network_service, validator_network) = Service::new(
+	let (mut validator_network_service, validator_network) = Service::new(
 		dialer,
 		listener,
 		network_authority_pen,
 		spawn_handle.clone(),
 		registry.clone(),
 	);
+	let validator_network_status = validator_network_service.status_updates();
+	if let Some(limits) = adaptive_limits {
+		let mut status_updates = validator_network_service.status_updates();
+		spawn_handle.spawn("aleph/validator_network_rate_limits", async move {
+			while let Some(status) = status_updates.next().await {
+				// The peers we want to be connected to are the committee members except us.
+				limits.set_committee_size(status.wanted_peers() + 1);
+			}
+		});
+	}
 	let (_validator_network_exit, exit) = oneshot::channel();
 	spawn_handle.spawn("aleph/validator_network", async move {
 		debug!(target: LOG_TARGET, "Validator network has started.");
@@ -126,10 +144,12 @@ where
 		}
 	});
 
+	// Shared by all the components, so that they all follow changes of the session period
+	// tracked by the session map updater.
 	let map_updater = SessionMapUpdater::new(
 		AuthorityProviderImpl::new(client.clone(), RuntimeApiImpl::new(client.clone())),
 		FinalityNotifierImpl::new(client.clone()),
-		session_period,
+		session_info.clone(),
 	);
 	let session_authorities = map_updater.readonly_session_map();
 	spawn_handle.spawn("aleph/updater", async move {
@@ -140,8 +160,16 @@ where
 
 	let chain_events = client.chain_status_notifier();
 
-	let slo_metrics = SloMetrics::new(registry.as_ref(), chain_status.clone());
-	let timing_metrics = slo_metrics.timing_metrics().clone();
+	let slo_metrics =
+		SloMetrics::new(registry.as_ref(), chain_status.clone(), health_monitor.clone());
+	let data_store_metrics = DataStoreMetrics::new(registry.as_ref()).unwrap_or_else(|e| {
+		warn!(target: LOG_TARGET, "Failed to create data store metrics: {}.", e);
+		DataStoreMetrics::noop()
+	});
+	let block_metrics =
+		BlockMetrics { timing: slo_metrics.timing_metrics().clone(), tracer: block_tracer };
+
+	spawn_handle.spawn("aleph/health", run_health_service(health_monitor.clone()));
 
 	spawn_handle.spawn("aleph/slo-metrics", {
 		let slo_metrics = slo_metrics.clone();
@@ -151,7 +179,6 @@ where
 		}
 	});
 
-	let session_info = SessionBoundaryInfo::new(session_period);
 	let genesis_header = match chain_status.finalized_at(0) {
 		Ok(FinalizationStatus::FinalizedWithJustification(justification)) => {
 			justification.header().clone()
@@ -170,11 +197,11 @@ where
 		SubstrateFinalizationInfo::new(client.clone()),
 		session_authority_provider,
 		session_block_availability_provider,
-		VERIFIER_CACHE_SIZE,
+		sync_config.verifier_cache_size,
 		genesis_header,
 	);
-	let finalizer = AlephFinalizer::new(client.clone());
-	import_queue_handle.attach_metrics(timing_metrics.clone());
+	let finalizer = AlephFinalizer::new(client.clone(), block_metrics.tracer.clone());
+	import_queue_handle.attach_metrics(block_metrics.clone());
 	let justifications_for_sync = justification_channel_provider.get_sender();
 	let sync_io = SyncIO::new(
 		SyncDatabaseIO::new(chain_status.clone(), finalizer, import_queue_handle),
@@ -193,6 +220,8 @@ where
 		registry.clone(),
 		slo_metrics,
 		favourite_block_user_requests,
+		sync_reputation_path,
+		sync_config.forest_max_depth,
 	) {
 		Ok(x) => x,
 		Err(e) => panic!("Failed to initialize Sync service: {e}"),
@@ -218,9 +247,11 @@ where
 	let (connection_manager_service, connection_manager) = ConnectionManager::new(
 		network_identity,
 		validator_network,
+		validator_network_status,
 		authentication_network,
 		validator_address_cache_updater,
-		ConnectionManagerConfig::with_session_period(&session_period, &millisecs_per_block),
+		health_monitor,
+		ConnectionManagerConfig::with_session_info(&session_info, &millisecs_per_block),
 	);
 
 	let connection_manager_task = async move {
@@ -238,22 +269,25 @@ where
 	let party = ConsensusParty::new(ConsensusPartyParams {
 		session_authorities,
 		sync_oracle,
-		backup_saving_path,
+		backup_storage,
+		backup_retained_sessions,
 		chain_state: ChainStateImpl { client: client.clone(), _phantom: PhantomData },
 		session_manager: NodeSessionManagerImpl::new(
 			client,
 			chain_status.clone(),
 			select_chain,
 			verifier,
-			session_period,
+			session_info.clone(),
 			unit_creation_delay,
 			justifications_for_sync,
 			JustificationTranslator::new(chain_status.clone()),
 			request_block,
-			timing_metrics,
+			block_metrics,
 			spawn_handle,
 			connection_manager,
 			keystore,
+			sync_config.data_store,
+			data_store_metrics,
 		),
 		session_info,
 	});
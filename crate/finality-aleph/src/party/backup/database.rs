@@ -0,0 +1,225 @@
+use std::{io, sync::Arc};
+
+use log::debug;
+use parity_scale_codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sc_client_api::AuxStore;
+
+use crate::party::backup::{BackupLoadError, BackupStorage, RecordAppender, SessionBackupSummary};
+
+/// Prefix of all the keys used for backups in the auxiliary storage.
+const BACKUP_KEY_PREFIX: &[u8] = b"aleph-bft-backup";
+
+fn database_error(e: sp_blockchain::Error) -> io::Error {
+	io::Error::other(e.to_string())
+}
+
+fn sessions_key() -> Vec<u8> {
+	(BACKUP_KEY_PREFIX, "sessions").encode()
+}
+
+fn length_key(session_id: u32) -> Vec<u8> {
+	(BACKUP_KEY_PREFIX, session_id).encode()
+}
+
+fn record_key(session_id: u32, index: u32) -> Vec<u8> {
+	(BACKUP_KEY_PREFIX, session_id, index).encode()
+}
+
+fn get<AS: AuxStore, T: Decode>(store: &AS, key: &[u8]) -> io::Result<Option<T>> {
+	match store.get_aux(key).map_err(database_error)? {
+		Some(value) => T::decode(&mut &value[..])
+			.map(Some)
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+		None => Ok(None),
+	}
+}
+
+/// Appends records to the backup of a session, each together with the new length in a single
+/// database transaction.
+struct DatabaseAppender<AS> {
+	store: Arc<AS>,
+	session_id: u32,
+	length: u32,
+}
+
+impl<AS: AuxStore + Send + Sync> RecordAppender for DatabaseAppender<AS> {
+	fn append(&mut self, record: &[u8]) -> io::Result<()> {
+		let length = self.length + 1;
+		self.store
+			.insert_aux(
+				&[
+					(&record_key(self.session_id, self.length)[..], record),
+					(&length_key(self.session_id)[..], &length.encode()[..]),
+				],
+				&[],
+			)
+			.map_err(database_error)?;
+		self.length = length;
+		Ok(())
+	}
+}
+
+/// Keeps backups in the auxiliary storage of the node's database, so they are kept wherever the
+/// database is. Writes to the auxiliary storage are not synced to disk, so on power loss the
+/// most recent records might be lost, unlike with the filesystem storage.
+///
+/// Every record is stored under a separate key and the number of records of a session under
+/// another one, both updated atomically. The list of sessions with backups is kept to be able to
+/// remove them, as the storage cannot be iterated.
+pub struct DatabaseBackupStorage<AS> {
+	store: Arc<AS>,
+	sessions_lock: Arc<Mutex<()>>,
+}
+
+impl<AS> Clone for DatabaseBackupStorage<AS> {
+	fn clone(&self) -> Self {
+		DatabaseBackupStorage {
+			store: self.store.clone(),
+			sessions_lock: self.sessions_lock.clone(),
+		}
+	}
+}
+
+impl<AS: AuxStore> DatabaseBackupStorage<AS> {
+	pub fn new(store: Arc<AS>) -> Self {
+		DatabaseBackupStorage { store, sessions_lock: Arc::new(Mutex::new(())) }
+	}
+
+	fn sessions(&self) -> io::Result<Vec<u32>> {
+		Ok(get(self.store.as_ref(), &sessions_key())?.unwrap_or_default())
+	}
+
+	fn length(&self, session_id: u32) -> io::Result<u32> {
+		Ok(get(self.store.as_ref(), &length_key(session_id))?.unwrap_or(0))
+	}
+}
+
+impl<AS: AuxStore + Send + Sync + 'static> BackupStorage for DatabaseBackupStorage<AS> {
+	fn open_session(
+		&self,
+		session_id: u32,
+	) -> Result<(Vec<Vec<u8>>, Box<dyn RecordAppender>), BackupLoadError> {
+		debug!(target: "aleph-party", "Loading backup for session {:?} from the database", session_id);
+		{
+			let _guard = self.sessions_lock.lock();
+			let mut sessions = self.sessions()?;
+			if let Err(position) = sessions.binary_search(&session_id) {
+				sessions.insert(position, session_id);
+				self.store
+					.insert_aux(&[(&sessions_key()[..], &sessions.encode()[..])], &[])
+					.map_err(database_error)?;
+			}
+		}
+		let length = self.length(session_id)?;
+		let mut records = Vec::new();
+		for index in 0..length {
+			match self.store.get_aux(&record_key(session_id, index)).map_err(database_error)? {
+				Some(record) => records.push(record),
+				None => return Err(BackupLoadError::RecordMissing { session_id, index }),
+			}
+		}
+		let appender = DatabaseAppender { store: self.store.clone(), session_id, length };
+		Ok((records, Box::new(appender)))
+	}
+
+	fn remove_sessions_before(&self, session_id: u32) -> io::Result<()> {
+		let _guard = self.sessions_lock.lock();
+		let (old, retained): (Vec<_>, Vec<_>) =
+			self.sessions()?.into_iter().partition(|session| *session < session_id);
+		if old.is_empty() {
+			return Ok(());
+		}
+		let mut keys = Vec::new();
+		for session in old {
+			keys.extend((0..self.length(session)?).map(|index| record_key(session, index)));
+			keys.push(length_key(session));
+		}
+		let keys: Vec<_> = keys.iter().map(|key| &key[..]).collect();
+		self.store
+			.insert_aux(&[(&sessions_key()[..], &retained.encode()[..])], &keys)
+			.map_err(database_error)
+	}
+
+	fn inspect(&self) -> io::Result<Vec<SessionBackupSummary>> {
+		self.sessions()?
+			.into_iter()
+			.map(|session| {
+				Ok(SessionBackupSummary {
+					session,
+					units: self.length(session)? as usize,
+					legacy_bytes: 0,
+					damaged_bytes: 0,
+				})
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+pub mod mock {
+	use std::collections::HashMap;
+
+	use parking_lot::Mutex;
+	use sc_client_api::AuxStore;
+
+	/// Auxiliary storage kept in memory.
+	#[derive(Default)]
+	pub struct InMemoryAuxStore {
+		data: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+	}
+
+	impl AuxStore for InMemoryAuxStore {
+		fn insert_aux<
+			'a,
+			'b: 'a,
+			'c: 'a,
+			I: IntoIterator<Item = &'a (&'c [u8], &'c [u8])>,
+			D: IntoIterator<Item = &'a &'b [u8]>,
+		>(
+			&self,
+			insert: I,
+			delete: D,
+		) -> sp_blockchain::Result<()> {
+			let mut data = self.data.lock();
+			for (key, value) in insert {
+				data.insert(key.to_vec(), value.to_vec());
+			}
+			for key in delete {
+				data.remove(*key);
+			}
+			Ok(())
+		}
+
+		fn get_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>> {
+			Ok(self.data.lock().get(key).cloned())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use sc_client_api::AuxStore;
+
+	use super::{mock::InMemoryAuxStore, record_key, DatabaseBackupStorage};
+	use crate::party::backup::{BackupLoadError, BackupStorage};
+
+	#[test]
+	fn names_missing_record() {
+		let store = Arc::new(InMemoryAuxStore::default());
+		let storage = DatabaseBackupStorage::new(store.clone());
+		let (_, mut appender) = storage.open_session(7).expect("opening should work");
+		for record in [b"first", b"secnd", b"third"] {
+			appender.append(record).expect("appending should work");
+		}
+		store
+			.insert_aux(&[] as &[(&[u8], &[u8])], &[&record_key(7, 1)[..]])
+			.expect("removing should work");
+		assert!(matches!(
+			storage.open_session(7),
+			Err(BackupLoadError::RecordMissing { session_id: 7, index: 1 })
+		));
+	}
+}
@@ -0,0 +1,410 @@
+use std::{
+	fs,
+	fs::{File, OpenOptions},
+	io,
+	io::{Read, Write},
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+use log::{debug, warn};
+use sp_core::hashing::twox_64;
+
+use crate::party::backup::{BackupLoadError, BackupStorage, RecordAppender, SessionBackupSummary};
+
+const BACKUP_FILE_EXTENSION: &str = ".abfts";
+/// Suffix of the directory a session backup is compacted into before replacing the original.
+const COMPACTION_SUFFIX: &str = ".compact";
+/// Suffix the original session backup directory is moved to while being replaced.
+const STALE_SUFFIX: &str = ".old";
+/// Marks backup files consisting of checksummed records, older files contain raw unit data.
+const RECORD_FILE_MAGIC: &[u8; 8] = b"ABFTREC1";
+/// A record is the length of its payload, the checksum of the payload and the payload itself.
+const RECORD_HEADER_SIZE: usize = 4 + 8;
+
+fn encode_record(payload: &[u8]) -> Vec<u8> {
+	let mut record = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+	record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+	record.extend_from_slice(&twox_64(payload));
+	record.extend_from_slice(payload);
+	record
+}
+
+/// Appends records to a backup file, syncing each of them to disk.
+struct FileAppender {
+	file: File,
+	valid_len: u64,
+}
+
+impl FileAppender {
+	/// Opens the record file at `path` for appending, dropping everything after `valid_len`.
+	fn open(path: &Path, valid_len: u64) -> io::Result<Self> {
+		let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+		file.set_len(valid_len)?;
+		let mut valid_len = valid_len;
+		if valid_len == 0 {
+			file.write_all(RECORD_FILE_MAGIC)?;
+			valid_len = RECORD_FILE_MAGIC.len() as u64;
+		}
+		file.sync_all()?;
+		Ok(FileAppender { file, valid_len })
+	}
+}
+
+impl RecordAppender for FileAppender {
+	fn append(&mut self, record: &[u8]) -> io::Result<()> {
+		let record = encode_record(record);
+		if let Err(e) = self.file.write_all(&record).and_then(|_| self.file.sync_data()) {
+			// Try not to leave a partial record in the middle of the file.
+			let _ = self.file.set_len(self.valid_len);
+			return Err(e);
+		}
+		self.valid_len += record.len() as u64;
+		Ok(())
+	}
+}
+
+/// Contents of a single backup file.
+struct BackupFile {
+	/// Payloads of the complete records, or the whole content of a file from before records.
+	records: Vec<Vec<u8>>,
+	/// Whether the file was written before records were introduced.
+	legacy: bool,
+	/// Length of the prefix of the file containing valid data.
+	valid_len: u64,
+	/// Length of the whole file.
+	len: u64,
+}
+
+impl BackupFile {
+	fn parse(data: &[u8]) -> Self {
+		let len = data.len() as u64;
+		let mut rest = match data.strip_prefix(RECORD_FILE_MAGIC) {
+			Some(rest) => rest,
+			// Empty, or the crash happened while the file was being created.
+			None if RECORD_FILE_MAGIC.starts_with(data) => {
+				return BackupFile { records: Vec::new(), legacy: false, valid_len: 0, len }
+			},
+			None => {
+				return BackupFile {
+					records: vec![data.to_vec()],
+					legacy: true,
+					valid_len: len,
+					len,
+				}
+			},
+		};
+		let mut records = Vec::new();
+		let mut valid_len = RECORD_FILE_MAGIC.len() as u64;
+		while rest.len() >= RECORD_HEADER_SIZE {
+			let (header, data) = rest.split_at(RECORD_HEADER_SIZE);
+			let (payload_len, checksum) = header.split_at(4);
+			let payload_len =
+				u32::from_le_bytes(payload_len.try_into().expect("length has 4 bytes")) as usize;
+			let payload = match data.get(..payload_len) {
+				Some(payload) if twox_64(payload) == checksum => payload,
+				_ => break,
+			};
+			records.push(payload.to_vec());
+			valid_len += (RECORD_HEADER_SIZE + payload_len) as u64;
+			rest = &data[payload_len..];
+		}
+		BackupFile { records, legacy: false, valid_len, len }
+	}
+
+	fn is_damaged(&self) -> bool {
+		self.valid_len < self.len
+	}
+}
+
+fn backup_file_path(session_path: &Path, index: usize) -> PathBuf {
+	session_path.join(format!("{index}{BACKUP_FILE_EXTENSION}"))
+}
+
+/// Find all `*.abfts` files at `session_path` and return their indexes sorted.
+fn backup_idxs(session_path: &Path) -> io::Result<Vec<usize>> {
+	let mut session_backups: Vec<_> = fs::read_dir(session_path)?
+		.filter_map(|r| r.ok())
+		.filter_map(|x| x.file_name().into_string().ok())
+		.filter_map(|s| usize::from_str(s.strip_suffix(BACKUP_FILE_EXTENSION)?).ok())
+		.collect();
+	session_backups.sort_unstable();
+	Ok(session_backups)
+}
+
+/// Find all `*.abfts` files at `session_path` and return their indexes sorted, if all are present.
+fn get_session_backup_idxs(session_path: &Path) -> Result<Vec<usize>, BackupLoadError> {
+	fs::create_dir_all(session_path)?;
+	let session_backups = backup_idxs(session_path)?;
+	if !session_backups.iter().cloned().eq(0..session_backups.len()) {
+		return Err(BackupLoadError::BackupIncomplete(session_backups));
+	}
+	Ok(session_backups)
+}
+
+/// Read the session backup at path `session_path` from all `session_idxs`.
+///
+/// Nothing after the first damaged record is read, as later units might depend on the lost ones.
+fn read_backup_files(session_path: &Path, session_idxs: &[usize]) -> io::Result<Vec<BackupFile>> {
+	let mut files = Vec::new();
+	for index in session_idxs.iter() {
+		let mut data = Vec::new();
+		File::open(backup_file_path(session_path, *index))?.read_to_end(&mut data)?;
+		let file = BackupFile::parse(&data);
+		let damaged = file.is_damaged();
+		files.push(file);
+		if damaged {
+			warn!(target: "aleph-party", "Backup file {} at {:?} ends with a partial record, dropping {} later file(s).", index, session_path, session_idxs.len() - files.len());
+			break;
+		}
+	}
+	Ok(files)
+}
+
+fn sync_dir(path: &Path) -> io::Result<()> {
+	File::open(path)?.sync_all()
+}
+
+/// Session of a directory in the backup directory, including ones left by interrupted compactions.
+fn session_of_dir(name: &str) -> Option<u32> {
+	let name = name
+		.strip_suffix(COMPACTION_SUFFIX)
+		.or_else(|| name.strip_suffix(STALE_SUFFIX))
+		.unwrap_or(name);
+	name.parse().ok()
+}
+
+/// Keeps backups as files in a directory on the local filesystem.
+///
+/// Current directory structure (this is an implementation detail, not part of the public API):
+///   backup-stash/      - the main directory, backup_path/--backup-saving-path
+///   `-- 18723/         - subdirectory for the current session
+///       `-- 0.abfts    - file containing checksummed records, one per unit
+///
+/// Backups written by older versions could consist of many files, counting up sequentially with
+/// each restart, and without records. These get compacted into a single file when loaded.
+#[derive(Clone, Debug)]
+pub struct FsBackupStorage {
+	path: PathBuf,
+}
+
+impl FsBackupStorage {
+	/// Storage at `path`, i.e. the argument to `--backup-saving-path`.
+	pub fn new(path: PathBuf) -> Self {
+		FsBackupStorage { path }
+	}
+
+	fn session_dir(&self, session_id: u32, suffix: &str) -> PathBuf {
+		self.path.join(format!("{session_id}{suffix}"))
+	}
+
+	/// Finish or roll back a compaction interrupted by a crash.
+	///
+	/// The compacted backup is complete once the original gets moved out of the way, so it can be
+	/// used whenever the original is missing.
+	fn recover_compaction(&self, session_id: u32) -> io::Result<()> {
+		let session_path = self.session_dir(session_id, "");
+		let compact_path = self.session_dir(session_id, COMPACTION_SUFFIX);
+		let stale_path = self.session_dir(session_id, STALE_SUFFIX);
+		if !session_path.exists() {
+			if compact_path.exists() {
+				debug!(target: "aleph-party", "Finishing interrupted compaction of backup for session {:?}", session_id);
+				fs::rename(&compact_path, &session_path)?;
+			} else if stale_path.exists() {
+				fs::rename(&stale_path, &session_path)?;
+			}
+		}
+		for path in [compact_path, stale_path] {
+			if path.exists() {
+				fs::remove_dir_all(path)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Replace all the files of the session backup with a single one containing `records`.
+	fn compact(&self, session_id: u32, records: &[Vec<u8>]) -> io::Result<()> {
+		let session_path = self.session_dir(session_id, "");
+		let compact_path = self.session_dir(session_id, COMPACTION_SUFFIX);
+		let stale_path = self.session_dir(session_id, STALE_SUFFIX);
+		fs::create_dir_all(&compact_path)?;
+		let mut file = File::create(backup_file_path(&compact_path, 0))?;
+		file.write_all(RECORD_FILE_MAGIC)?;
+		for record in records {
+			file.write_all(&encode_record(record))?;
+		}
+		file.sync_all()?;
+		sync_dir(&compact_path)?;
+		fs::rename(&session_path, &stale_path)?;
+		fs::rename(&compact_path, &session_path)?;
+		sync_dir(&self.path)?;
+		fs::remove_dir_all(stale_path)
+	}
+}
+
+impl BackupStorage for FsBackupStorage {
+	fn open_session(
+		&self,
+		session_id: u32,
+	) -> Result<(Vec<Vec<u8>>, Box<dyn RecordAppender>), BackupLoadError> {
+		let session_path = self.session_dir(session_id, "");
+		debug!(target: "aleph-party", "Loading backup for session {:?} at path {:?}", session_id, session_path);
+
+		self.recover_compaction(session_id)?;
+		let session_backup_idxs = get_session_backup_idxs(&session_path)?;
+		let files = read_backup_files(&session_path, &session_backup_idxs)?;
+		let records: Vec<_> = files.iter().flat_map(|file| file.records.iter().cloned()).collect();
+
+		let valid_len = match files.as_slice() {
+			[file] if !file.legacy && session_backup_idxs.len() == 1 => {
+				if file.is_damaged() {
+					warn!(target: "aleph-party", "Dropping {} byte(s) of partial record from backup of session {:?}", file.len - file.valid_len, session_id);
+				}
+				file.valid_len
+			},
+			[] if session_backup_idxs.is_empty() => 0,
+			_ => {
+				debug!(target: "aleph-party", "Compacting {} backup file(s) of session {:?}", session_backup_idxs.len(), session_id);
+				self.compact(session_id, &records)?;
+				fs::metadata(backup_file_path(&session_path, 0))?.len()
+			},
+		};
+		let file_path = backup_file_path(&session_path, 0);
+		debug!(target: "aleph-party", "Appending to backup file at {:?}", file_path);
+		Ok((records, Box::new(FileAppender::open(&file_path, valid_len)?)))
+	}
+
+	fn remove_sessions_before(&self, session_id: u32) -> io::Result<()> {
+		if !self.path.exists() {
+			return Ok(());
+		}
+		for read_dir in fs::read_dir(&self.path)? {
+			let item = read_dir?;
+			match item.file_name().to_str().and_then(session_of_dir) {
+				Some(session) => {
+					if session < session_id {
+						fs::remove_dir_all(item.path())?;
+					}
+				},
+				None => debug!(target: "aleph-party", "backup directory contains unexpected data."),
+			};
+		}
+		Ok(())
+	}
+
+	fn inspect(&self) -> io::Result<Vec<SessionBackupSummary>> {
+		let mut summaries = Vec::new();
+		for item in fs::read_dir(&self.path)? {
+			let item = item?;
+			let session = match item.file_name().to_str().map(u32::from_str) {
+				Some(Ok(session)) => session,
+				_ => continue,
+			};
+			let session_path = item.path();
+			let mut summary =
+				SessionBackupSummary { session, units: 0, legacy_bytes: 0, damaged_bytes: 0 };
+			let mut damaged = false;
+			for index in backup_idxs(&session_path)? {
+				let data = fs::read(backup_file_path(&session_path, index))?;
+				if damaged {
+					summary.damaged_bytes += data.len() as u64;
+					continue;
+				}
+				let file = BackupFile::parse(&data);
+				if file.legacy {
+					summary.legacy_bytes += file.len;
+				} else {
+					summary.units += file.records.len();
+				}
+				summary.damaged_bytes += file.len - file.valid_len;
+				damaged = file.is_damaged();
+			}
+			summaries.push(summary);
+		}
+		summaries.sort_by_key(|summary| summary.session);
+		Ok(summaries)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, io::Write};
+
+	use tempfile::TempDir;
+
+	use super::{
+		backup_file_path, encode_record, FsBackupStorage, COMPACTION_SUFFIX, RECORD_FILE_MAGIC,
+	};
+	use crate::party::backup::BackupStorage;
+
+	/// The directory is removed once dropped, so it has to be kept for the whole test.
+	fn storage() -> (FsBackupStorage, TempDir) {
+		let dir = TempDir::new().expect("creating a temporary directory should work");
+		(FsBackupStorage::new(dir.path().to_path_buf()), dir)
+	}
+
+	fn load(storage: &FsBackupStorage, session: u32) -> Vec<u8> {
+		let (records, _) = storage.open_session(session).expect("opening should work");
+		records.concat()
+	}
+
+	#[test]
+	fn drops_partial_trailing_record() {
+		let (storage, _dir) = storage();
+		let (_, mut appender) = storage.open_session(3).expect("opening should work");
+		appender.append(b"complete").expect("saving should work");
+		let file_path = backup_file_path(&storage.session_dir(3, ""), 0);
+		let complete_len = fs::metadata(&file_path).unwrap().len();
+		let mut partial = encode_record(b"partial");
+		partial.truncate(partial.len() - 2);
+		fs::OpenOptions::new()
+			.append(true)
+			.open(&file_path)
+			.unwrap()
+			.write_all(&partial)
+			.unwrap();
+
+		assert_eq!(storage.inspect().unwrap()[0].damaged_bytes, partial.len() as u64);
+		let (records, mut appender) = storage.open_session(3).expect("opening should work");
+		assert_eq!(records, vec![b"complete".to_vec()]);
+		assert_eq!(fs::metadata(&file_path).unwrap().len(), complete_len);
+		appender.append(b"more").expect("saving should work");
+		assert_eq!(load(&storage, 3), b"completemore");
+	}
+
+	#[test]
+	fn compacts_old_backup_files() {
+		let (storage, _dir) = storage();
+		let session_path = storage.session_dir(5, "");
+		fs::create_dir_all(&session_path).unwrap();
+		fs::write(backup_file_path(&session_path, 0), b"old").unwrap();
+		fs::write(backup_file_path(&session_path, 1), b"older").unwrap();
+
+		assert_eq!(load(&storage, 5), b"oldolder");
+		assert_eq!(fs::read_dir(&session_path).unwrap().count(), 1);
+		let compacted = fs::read(backup_file_path(&session_path, 0)).unwrap();
+		assert!(compacted.starts_with(RECORD_FILE_MAGIC));
+	}
+
+	#[test]
+	fn finishes_interrupted_compaction() {
+		let (storage, _dir) = storage();
+		let compact_path = storage.session_dir(2, COMPACTION_SUFFIX);
+		fs::create_dir_all(&compact_path).unwrap();
+		let mut data = RECORD_FILE_MAGIC.to_vec();
+		data.extend(encode_record(b"unit"));
+		fs::write(backup_file_path(&compact_path, 0), data).unwrap();
+
+		assert_eq!(load(&storage, 2), b"unit");
+		assert!(!compact_path.exists());
+	}
+
+	#[test]
+	fn removes_leftovers_of_old_compactions() {
+		let (storage, dir) = storage();
+		fs::create_dir_all(storage.session_dir(1, COMPACTION_SUFFIX)).unwrap();
+		fs::create_dir_all(storage.session_dir(2, "")).unwrap();
+		storage.remove_sessions_before(2).expect("removal should work");
+		assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
+}
@@ -0,0 +1,276 @@
+use std::{
+	fmt, io,
+	io::{Cursor, Read, Write},
+	pin::Pin,
+};
+
+use futures::io::{AllowStdIo, AsyncRead, AsyncWrite};
+use log::debug;
+
+mod database;
+mod filesystem;
+
+pub use database::DatabaseBackupStorage;
+pub use filesystem::FsBackupStorage;
+
+/// How many of the most recent sessions keep their backups by default.
+pub const DEFAULT_BACKUP_RETAINED_SESSIONS: u32 = 3;
+
+#[derive(Debug)]
+pub enum BackupLoadError {
+	BackupIncomplete(Vec<usize>),
+	RecordMissing { session_id: u32, index: u32 },
+	IOError(io::Error),
+}
+
+impl fmt::Display for BackupLoadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			BackupLoadError::BackupIncomplete(backups) => {
+				write!(f, "Backup is not complete. Got backup for runs numbered: {backups:?}")
+			},
+			BackupLoadError::RecordMissing { session_id, index } => write!(
+				f,
+				"Backup of session {session_id} is missing record number {index} out of the ones it should have"
+			),
+			BackupLoadError::IOError(err) => {
+				write!(f, "Backup could not be loaded because of IO error: {err}")
+			},
+		}
+	}
+}
+
+impl From<io::Error> for BackupLoadError {
+	fn from(err: io::Error) -> Self {
+		Self::IOError(err)
+	}
+}
+
+impl std::error::Error for BackupLoadError {}
+
+// Both the `Both` traits are only necessary for backwards compatibility with old ABFT.
+// TODO(A0-4271): Remove these when removing support for ABFT 0.33 and
+// check whether the implementations should also be replaced with purely async ones.
+pub trait BothRead: Read + AsyncRead {}
+impl<T: Read + AsyncRead> BothRead for T {}
+
+pub trait BothWrite: Write + AsyncWrite {}
+impl<T: Write + AsyncWrite> BothWrite for T {}
+
+pub type Saver = Pin<Box<dyn BothWrite + Send + Sync + Unpin>>;
+pub type Loader = Pin<Box<dyn BothRead + Send + Sync + Unpin>>;
+pub type ABFTBackup = (Saver, Loader);
+
+/// Appends records to the backup of a single session.
+pub trait RecordAppender: Send + Sync {
+	/// Saves the record, returning only once the storage has persisted it. How much that
+	/// survives depends on the storage: the filesystem storage syncs every record to disk, so it
+	/// survives power loss, while the database storage commits it to the database, which
+	/// survives a crash of the node, but not necessarily of the whole machine.
+	fn append(&mut self, record: &[u8]) -> io::Result<()>;
+}
+
+/// Place where the backups of AlephBFT sessions are kept.
+pub trait BackupStorage: Send + Sync {
+	/// Loads the records saved for the session, and opens its backup for appending. Damage left
+	/// by a crash in the middle of saving is repaired.
+	fn open_session(
+		&self,
+		session_id: u32,
+	) -> Result<(Vec<Vec<u8>>, Box<dyn RecordAppender>), BackupLoadError>;
+
+	/// Removes the backups of all sessions older than `session_id`.
+	fn remove_sessions_before(&self, session_id: u32) -> io::Result<()>;
+
+	/// Summarizes the backups of all sessions without modifying them.
+	fn inspect(&self) -> io::Result<Vec<SessionBackupSummary>>;
+}
+
+/// Summary of the backup of a single session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionBackupSummary {
+	pub session: u32,
+	/// Number of units saved in records.
+	pub units: usize,
+	/// Bytes of unit data written before records were introduced.
+	pub legacy_bytes: u64,
+	/// Bytes that will be dropped when the backup is loaded.
+	pub damaged_bytes: u64,
+}
+
+impl fmt::Display for SessionBackupSummary {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "session {}: {} unit(s)", self.session, self.units)?;
+		if self.legacy_bytes > 0 {
+			write!(f, ", {} byte(s) of units in the old format", self.legacy_bytes)?;
+		}
+		if self.damaged_bytes > 0 {
+			write!(f, ", {} damaged byte(s)", self.damaged_bytes)?;
+		}
+		Ok(())
+	}
+}
+
+/// Saves everything written between flushes as a single record.
+///
+/// AlephBFT flushes the saver after every unit and only uses the unit afterwards, so any unit
+/// that might have been sent to other nodes is guaranteed to survive a crash.
+struct RecordWriter {
+	appender: Box<dyn RecordAppender>,
+	pending: Vec<u8>,
+}
+
+impl Write for RecordWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.pending.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		if self.pending.is_empty() {
+			return Ok(());
+		}
+		self.appender.append(&self.pending)?;
+		self.pending.clear();
+		Ok(())
+	}
+}
+
+/// Loads the existing backup, and opens it for saving further units.
+///
+/// `storage` is where backups are kept, if they are enabled (i.e. not `--no-backup`).
+///
+/// Returns the saver appending records to the backup, and the concatenation of the contents of
+/// all valid records.
+pub fn rotate(
+	storage: Option<&dyn BackupStorage>,
+	session_id: u32,
+) -> Result<ABFTBackup, BackupLoadError> {
+	debug!(target: "aleph-party", "Loading AlephBFT backup for session {:?}", session_id);
+	let storage = if let Some(storage) = storage {
+		storage
+	} else {
+		debug!(target: "aleph-party", "Passing empty backup for session {:?} as no backup argument was provided", session_id);
+		return Ok((Box::pin(AllowStdIo::new(io::sink())), Box::pin(AllowStdIo::new(io::empty()))));
+	};
+
+	let (records, appender) = storage.open_session(session_id)?;
+	let loaded = records.concat();
+	debug!(target: "aleph-party", "Loaded {} unit(s), {} byte(s) of backup for session {:?}", records.len(), loaded.len(), session_id);
+	let backup_loader = Box::pin(AllowStdIo::new(Cursor::new(loaded)));
+	let backup_saver = Box::pin(AllowStdIo::new(RecordWriter { appender, pending: Vec::new() }));
+
+	debug!(target: "aleph-party", "Backup rotation done for session {:?}", session_id);
+	Ok((backup_saver, backup_loader))
+}
+
+/// Removes the backups of all old sessions except the `retained_sessions` most recent ones,
+/// including the current session.
+///
+/// If `storage` is `None`, nothing is done. The backup of the current session is always retained.
+///
+/// Any storage errors are returned.
+///
+/// This should be done at the beginning of the new session.
+pub fn remove_old_backups(
+	storage: Option<&dyn BackupStorage>,
+	current_session: u32,
+	retained_sessions: u32,
+) -> io::Result<()> {
+	match storage {
+		Some(storage) => storage.remove_sessions_before(
+			current_session.saturating_add(1).saturating_sub(retained_sessions.max(1)),
+		),
+		None => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		io::{Read, Write},
+		pin::Pin,
+		sync::Arc,
+	};
+
+	use tempfile::TempDir;
+
+	use super::{
+		database::mock::InMemoryAuxStore, remove_old_backups, rotate, BackupStorage,
+		DatabaseBackupStorage, FsBackupStorage, SessionBackupSummary,
+	};
+
+	/// Runs the test against every kind of storage.
+	fn for_each_storage(test: impl Fn(&dyn BackupStorage)) {
+		let dir = TempDir::new().expect("creating a temporary directory should work");
+		test(&FsBackupStorage::new(dir.path().to_path_buf()));
+		test(&DatabaseBackupStorage::new(Arc::new(InMemoryAuxStore::default())));
+	}
+
+	fn load(storage: &dyn BackupStorage, session: u32) -> Vec<u8> {
+		let (_, loader) = rotate(Some(storage), session).expect("rotation should work");
+		let mut loaded = Vec::new();
+		Pin::into_inner(loader).read_to_end(&mut loaded).expect("loading should work");
+		loaded
+	}
+
+	fn save(storage: &dyn BackupStorage, session: u32, units: &[&[u8]]) {
+		let (saver, _) = rotate(Some(storage), session).expect("rotation should work");
+		let mut saver = Pin::into_inner(saver);
+		for unit in units {
+			saver.write_all(unit).expect("saving should work");
+			saver.flush().expect("flushing should work");
+		}
+	}
+
+	#[test]
+	fn loads_saved_units_across_restarts() {
+		for_each_storage(|storage| {
+			save(storage, 7, &[b"first", b"second"]);
+			save(storage, 7, &[b"third"]);
+			assert_eq!(load(storage, 7), b"firstsecondthird");
+			assert_eq!(load(storage, 8), b"");
+		});
+	}
+
+	#[test]
+	fn drops_unflushed_data() {
+		for_each_storage(|storage| {
+			save(storage, 1, &[b"saved"]);
+			let (saver, _) = rotate(Some(storage), 1).expect("rotation should work");
+			Pin::into_inner(saver).write_all(b"lost").expect("writing should work");
+			assert_eq!(load(storage, 1), b"saved");
+		});
+	}
+
+	#[test]
+	fn retains_most_recent_sessions() {
+		for_each_storage(|storage| {
+			for session in 1..=5 {
+				save(storage, session, &[b"unit"]);
+			}
+			remove_old_backups(Some(storage), 5, 2).expect("removal should work");
+			assert_eq!(
+				storage.inspect().expect("inspection should work"),
+				[4, 5]
+					.into_iter()
+					.map(|session| SessionBackupSummary {
+						session,
+						units: 1,
+						legacy_bytes: 0,
+						damaged_bytes: 0
+					})
+					.collect::<Vec<_>>()
+			);
+		});
+	}
+
+	#[test]
+	fn always_retains_current_session() {
+		for_each_storage(|storage| {
+			save(storage, 3, &[b"unit"]);
+			remove_old_backups(Some(storage), 3, 0).expect("removal should work");
+			assert_eq!(load(storage, 3), b"unit");
+		});
+	}
+}
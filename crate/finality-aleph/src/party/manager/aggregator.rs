@@ -19,7 +19,7 @@ use crate::{
 	},
 	crypto::Signature,
 	justification::AlephJustification,
-	metrics::{Checkpoint, TimingBlockMetrics},
+	metrics::{BlockMetrics, BlockStage, Checkpoint, TimingBlockMetrics},
 	network::data::Network,
 	party::{
 		manager::aggregator::AggregatorVersion::{Current, Legacy},
@@ -107,7 +107,7 @@ async fn run_aggregator<H, C, CN, LN, JS>(
 	io: IO<JS>,
 	client: C,
 	session_boundaries: &SessionBoundaries,
-	mut metrics: TimingBlockMetrics,
+	metrics: BlockMetrics,
 	mut exit_rx: oneshot::Receiver<()>,
 ) -> Result<(), Error>
 where
@@ -118,6 +118,7 @@ where
 	CN: Network<CurrentRmcNetworkData>,
 {
 	let IO { blocks_from_interpreter, mut justifications_for_chain, justification_translator } = io;
+	let BlockMetrics { timing: mut timing_metrics, tracer } = metrics;
 
 	let blocks_from_interpreter = blocks_from_interpreter.take_while(|block| {
 		let block_num = block.number();
@@ -143,7 +144,7 @@ where
 					process_new_block_data::<CN, LN>(
 						&mut aggregator,
 						block,
-						&mut metrics
+						&mut timing_metrics
 					).await;
 				},
 				None => {
@@ -153,6 +154,7 @@ where
 			},
 			multisigned_hash = aggregator.next_multisigned_hash() => {
 				let (hash, multisignature) = multisigned_hash.ok_or(Error::MultisignaturesStreamTerminated)?;
+				tracer.report_block(hash, BlockStage::Aggregated);
 				process_hash(hash, multisignature, &mut justifications_for_chain, &justification_translator, &client).map_err(|_| Error::UnableToProcessHash)?;
 				if Some(hash) == hash_of_last_block {
 					hash_of_last_block = None;
@@ -186,7 +188,7 @@ pub fn task<H, C, CN, LN, JS>(
 	client: C,
 	io: IO<JS>,
 	session_boundaries: SessionBoundaries,
-	metrics: TimingBlockMetrics,
+	metrics: BlockMetrics,
 	multikeychain: Keychain,
 	version: AggregatorVersion<CN, LN>,
 ) -> Task
@@ -20,8 +20,11 @@ use crate::{
 		BestBlockSelector, Block, Header, HeaderVerifier, UnverifiedHeader,
 	},
 	crypto::{AuthorityPen, AuthorityVerifier},
-	data_io::{ChainTracker, DataStore, OrderedDataInterpreter, SubstrateChainInfoProvider},
-	metrics::TimingBlockMetrics,
+	data_io::{
+		ChainTracker, DataStore, DataStoreConfig, DataStoreMetrics, OrderedDataInterpreter,
+		SubstrateChainInfoProvider,
+	},
+	metrics::BlockMetrics,
 	mpsc,
 	network::{
 		data::{
@@ -35,8 +38,8 @@ use crate::{
 	},
 	sync::JustificationSubmissions,
 	AuthorityId, BlockId, CurrentRmcNetworkData, Keychain, LegacyRmcNetworkData, NodeIndex,
-	ProvideRuntimeApi, SessionBoundaries, SessionBoundaryInfo, SessionId, SessionPeriod,
-	UnitCreationDelay, VersionedNetworkData,
+	ProvideRuntimeApi, SessionBoundaries, SessionBoundaryInfo, SessionId, UnitCreationDelay,
+	VersionedNetworkData,
 };
 
 mod aggregator;
@@ -110,10 +113,12 @@ where
 	justifications_for_sync: JS,
 	justification_translator: JustificationTranslator,
 	block_requester: RB,
-	metrics: TimingBlockMetrics,
+	metrics: BlockMetrics,
 	spawn_handle: SpawnHandle,
 	session_manager: SM,
 	keystore: Arc<LocalKeystore>,
+	data_store_config: DataStoreConfig,
+	data_store_metrics: DataStoreMetrics,
 	_phantom: PhantomData<(B, H)>,
 }
 
@@ -137,22 +142,24 @@ where
 		header_backend: HB,
 		best_block_selection_strategy: BBS,
 		verifier: V,
-		session_period: SessionPeriod,
+		session_info: SessionBoundaryInfo,
 		unit_creation_delay: UnitCreationDelay,
 		justifications_for_sync: JS,
 		justification_translator: JustificationTranslator,
 		block_requester: RB,
-		metrics: TimingBlockMetrics,
+		metrics: BlockMetrics,
 		spawn_handle: SpawnHandle,
 		session_manager: SM,
 		keystore: Arc<LocalKeystore>,
+		data_store_config: DataStoreConfig,
+		data_store_metrics: DataStoreMetrics,
 	) -> Self {
 		Self {
 			client,
 			header_backend,
 			best_block_selection_strategy,
 			verifier,
-			session_info: SessionBoundaryInfo::new(session_period),
+			session_info,
 			unit_creation_delay,
 			justifications_for_sync,
 			justification_translator,
@@ -161,6 +168,8 @@ where
 			spawn_handle,
 			session_manager,
 			keystore,
+			data_store_config,
+			data_store_metrics,
 			_phantom: PhantomData,
 		}
 	}
@@ -189,13 +198,14 @@ where
 			self.header_backend.clone(),
 			session_boundaries.clone(),
 			Default::default(),
-			self.metrics.clone(),
+			self.metrics.timing.clone(),
 		);
 		let ordered_data_interpreter = OrderedDataInterpreter::new(
 			blocks_for_aggregator,
 			chain_info,
 			self.verifier.clone(),
 			session_boundaries.clone(),
+			self.metrics.tracer.clone(),
 		);
 		let consensus_config =
 			legacy_create_aleph_config(n_members, node_id, session_id, self.unit_creation_delay);
@@ -209,7 +219,8 @@ where
 			self.client.clone(),
 			self.verifier.clone(),
 			self.block_requester.clone(),
-			Default::default(),
+			self.data_store_config.clone(),
+			self.data_store_metrics.for_session(session_id),
 			unfiltered_aleph_network,
 		);
 		Subtasks::new(
@@ -261,13 +272,14 @@ where
 			self.header_backend.clone(),
 			session_boundaries.clone(),
 			Default::default(),
-			self.metrics.clone(),
+			self.metrics.timing.clone(),
 		);
 		let ordered_data_interpreter = OrderedDataInterpreter::new(
 			blocks_for_aggregator,
 			chain_info,
 			self.verifier.clone(),
 			session_boundaries.clone(),
+			self.metrics.tracer.clone(),
 		);
 		let consensus_config =
 			current_create_aleph_config(n_members, node_id, session_id, self.unit_creation_delay);
@@ -281,7 +293,8 @@ where
 			self.client.clone(),
 			self.verifier.clone(),
 			self.block_requester.clone(),
-			Default::default(),
+			self.data_store_config.clone(),
+			self.data_store_metrics.for_session(session_id),
 			unfiltered_aleph_network,
 		);
 		Subtasks::new(
@@ -1,4 +1,4 @@
-use std::{default::Default, path::PathBuf, time::Duration};
+use std::{default::Default, sync::Arc, time::Duration};
 
 use futures::FutureExt;
 use futures_timer::Delay;
@@ -8,6 +8,7 @@ use tokio::{task::spawn_blocking, time::sleep};
 
 use crate::{
 	party::{
+		backup::BackupStorage,
 		manager::{Handle, Task, TaskCommon as AuthoritySubtaskCommon},
 		traits::{ChainState, NodeSessionManager},
 	},
@@ -28,7 +29,8 @@ pub(crate) struct ConsensusPartyParams<CS, NSM> {
 	pub session_authorities: ReadOnlySessionMap,
 	pub chain_state: CS,
 	pub sync_oracle: SyncOracle,
-	pub backup_saving_path: Option<PathBuf>,
+	pub backup_storage: Option<Arc<dyn BackupStorage>>,
+	pub backup_retained_sessions: u32,
 	pub session_manager: NSM,
 	pub session_info: SessionBoundaryInfo,
 }
@@ -41,7 +43,8 @@ where
 	session_authorities: ReadOnlySessionMap,
 	chain_state: CS,
 	sync_oracle: SyncOracle,
-	backup_saving_path: Option<PathBuf>,
+	backup_storage: Option<Arc<dyn BackupStorage>>,
+	backup_retained_sessions: u32,
 	session_manager: NSM,
 	session_info: SessionBoundaryInfo,
 }
@@ -57,7 +60,8 @@ where
 		let ConsensusPartyParams {
 			session_authorities,
 			sync_oracle,
-			backup_saving_path,
+			backup_storage,
+			backup_retained_sessions,
 			chain_state,
 			session_manager,
 			session_info,
@@ -66,7 +70,8 @@ where
 		Self {
 			sync_oracle,
 			session_authorities,
-			backup_saving_path,
+			backup_storage,
+			backup_retained_sessions,
 			chain_state,
 			session_manager,
 			session_info,
@@ -102,9 +107,14 @@ where
 	async fn run_session(&mut self, session_id: SessionId) {
 		let last_block = self.session_info.last_block_of_session(session_id);
 		if session_id.0.checked_sub(1).is_some() {
-			let backup_saving_path = self.backup_saving_path.clone();
+			let backup_storage = self.backup_storage.clone();
+			let retained_sessions = self.backup_retained_sessions;
 			spawn_blocking(move || {
-				if let Err(e) = backup::remove_old_backups(backup_saving_path, session_id.0) {
+				if let Err(e) = backup::remove_old_backups(
+					backup_storage.as_deref(),
+					session_id.0,
+					retained_sessions,
+				) {
 					warn!(target: "aleph-party", "Error when clearing old backups: {}", e);
 				}
 			});
@@ -144,7 +154,7 @@ where
 		let mut maybe_authority_task = if let Some(node_id) =
 			self.session_manager.node_idx(authorities)
 		{
-			match backup::rotate(self.backup_saving_path.clone(), session_id.0) {
+			match backup::rotate(self.backup_storage.as_deref(), session_id.0) {
 				Ok(backup) => {
 					debug!(target: "aleph-party", "Running session {:?} as authority id {:?}", session_id, node_id);
 					Some(
@@ -475,7 +485,8 @@ mod tests {
 			session_authorities: readonly_session_authorities,
 			chain_state,
 			sync_oracle,
-			backup_saving_path: None,
+			backup_storage: None,
+			backup_retained_sessions: 1,
 			session_manager,
 			session_info,
 		};
@@ -1,7 +1,16 @@
+use std::{
+	fmt::{Display, Error as FmtError, Formatter},
+	sync::Arc,
+};
+
+use log::warn;
 use parity_scale_codec::{Decode, Encode};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
-use selendra_primitives::BlockNumber;
+use selendra_primitives::{BlockNumber, SessionPeriodHistory, DEFAULT_SESSION_PERIOD};
+
+const LOG_TARGET: &str = "selendra-session";
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct SessionBoundaries {
@@ -19,15 +28,61 @@ impl SessionBoundaries {
 	}
 }
 
+/// Struct for getting the session boundaries. The session period might change over time, so the
+/// boundaries are computed from the history of its changes, which is shared between all the
+/// clones and can be updated as the chain progresses.
 #[derive(Clone, Debug)]
 pub struct SessionBoundaryInfo {
-	session_period: SessionPeriod,
+	history: Arc<RwLock<SessionPeriodHistory>>,
+	forest_max_depth: Arc<RwLock<Option<u32>>>,
 }
 
-/// Struct for getting the session boundaries.
 impl SessionBoundaryInfo {
-	pub const fn new(session_period: SessionPeriod) -> Self {
-		Self { session_period }
+	/// Session boundaries with the given session period, until the history gets updated.
+	pub fn new(session_period: SessionPeriod) -> Self {
+		Self::with_history(SessionPeriodHistory::new(session_period.0))
+	}
+
+	pub fn with_history(history: SessionPeriodHistory) -> Self {
+		Self {
+			history: Arc::new(RwLock::new(history)),
+			forest_max_depth: Arc::new(RwLock::new(None)),
+		}
+	}
+
+	/// Makes history updates, for all the clones of this struct, check that two of the longest
+	/// sessions still fit into a forest of blocks of the given depth.
+	pub fn set_forest_max_depth(&self, max_depth: u32) {
+		*self.forest_max_depth.write() = Some(max_depth);
+	}
+
+	/// Returns the longest session period known so far, if two such sessions do not fit into
+	/// the forest of blocks.
+	fn too_long_for_forest(&self) -> Option<(SessionPeriod, u32)> {
+		let max_depth = (*self.forest_max_depth.read())?;
+		let session_period = self.longest_session_period();
+		match session_period.0.saturating_mul(2) > max_depth {
+			true => Some((session_period, max_depth)),
+			false => None,
+		}
+	}
+
+	/// Replaces the history of session period changes, for all the clones of this struct.
+	/// Invalid histories are ignored.
+	pub fn update_history(&self, history: SessionPeriodHistory) -> Result<(), InvalidHistory> {
+		if !history.is_valid() {
+			return Err(InvalidHistory);
+		}
+		*self.history.write() = history;
+		if let Some((session_period, max_depth)) = self.too_long_for_forest() {
+			warn!(
+				target: LOG_TARGET,
+				"Sessions of {} blocks no longer fit twice into the forest of max depth {}, block sync might stall at session boundaries. Restart the node with a larger forest.",
+				session_period.0,
+				max_depth
+			);
+		}
+		Ok(())
 	}
 
 	pub fn boundaries_for_session(&self, session_id: SessionId) -> SessionBoundaries {
@@ -37,19 +92,48 @@ impl SessionBoundaryInfo {
 		}
 	}
 
+	/// Returns the number of blocks in the session.
+	pub fn session_period(&self, session_id: SessionId) -> SessionPeriod {
+		SessionPeriod(self.history.read().period_of_session(session_id.0))
+	}
+
 	/// Returns session id of the session that block belongs to.
 	pub fn session_id_from_block_num(&self, n: BlockNumber) -> SessionId {
-		SessionId(n / self.session_period.0)
+		SessionId(self.history.read().session_of_block(n))
 	}
 
 	/// Returns block number which is the last block of the session.
 	pub fn last_block_of_session(&self, session_id: SessionId) -> BlockNumber {
-		(session_id.0 + 1) * self.session_period.0 - 1
+		self.history.read().last_block_of_session(session_id.0)
 	}
 
 	/// Returns block number which is the first block of the session.
 	pub fn first_block_of_session(&self, session_id: SessionId) -> BlockNumber {
-		session_id.0 * self.session_period.0
+		self.history.read().first_block_of_session(session_id.0)
+	}
+
+	fn session_periods(&self) -> Vec<u32> {
+		self.history.read().changes().iter().map(|change| change.period).collect()
+	}
+
+	/// Returns the smallest number of blocks in any session known so far.
+	pub fn shortest_session_period(&self) -> SessionPeriod {
+		SessionPeriod(self.session_periods().into_iter().min().unwrap_or(DEFAULT_SESSION_PERIOD))
+	}
+
+	/// Returns the largest number of blocks in any session known so far.
+	pub fn longest_session_period(&self) -> SessionPeriod {
+		SessionPeriod(self.session_periods().into_iter().max().unwrap_or(DEFAULT_SESSION_PERIOD))
+	}
+}
+
+/// The session period history was malformed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidHistory;
+
+impl Display for InvalidHistory {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		write!(f, "session period history is malformed")
 	}
 }
 
@@ -93,3 +177,60 @@ impl SessionId {
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Encode, Decode)]
 pub struct SessionPeriod(pub u32);
+
+#[cfg(test)]
+mod tests {
+	use selendra_primitives::{SessionPeriodChange, SessionPeriodHistory};
+
+	use super::{SessionBoundaryInfo, SessionId, SessionPeriod};
+
+	#[test]
+	fn fixed_period_boundaries() {
+		let session_info = SessionBoundaryInfo::new(SessionPeriod(10));
+		assert_eq!(session_info.first_block_of_session(SessionId(3)), 30);
+		assert_eq!(session_info.last_block_of_session(SessionId(3)), 39);
+		assert_eq!(session_info.session_id_from_block_num(39), SessionId(3));
+		assert_eq!(session_info.session_id_from_block_num(40), SessionId(4));
+	}
+
+	#[test]
+	fn clones_follow_history_updates() {
+		let session_info = SessionBoundaryInfo::new(SessionPeriod(10));
+		let clone = session_info.clone();
+		let mut history = SessionPeriodHistory::new(10);
+		history
+			.push(SessionPeriodChange { period: 4, session: 2 })
+			.expect("valid change");
+		session_info.update_history(history).expect("valid history");
+
+		assert_eq!(clone.last_block_of_session(SessionId(1)), 19);
+		assert_eq!(clone.boundaries_for_session(SessionId(2)).first_block(), 20);
+		assert_eq!(clone.boundaries_for_session(SessionId(2)).last_block(), 23);
+		assert_eq!(clone.session_id_from_block_num(25), SessionId(3));
+		assert_eq!(clone.session_period(SessionId(1)), SessionPeriod(10));
+		assert_eq!(clone.session_period(SessionId(5)), SessionPeriod(4));
+	}
+
+	#[test]
+	fn checks_history_updates_against_forest_depth() {
+		let session_info = SessionBoundaryInfo::new(SessionPeriod(10));
+		let clone = session_info.clone();
+		assert_eq!(clone.too_long_for_forest(), None);
+		session_info.set_forest_max_depth(20);
+		assert_eq!(clone.too_long_for_forest(), None);
+		let mut history = SessionPeriodHistory::new(10);
+		history
+			.push(SessionPeriodChange { period: 11, session: 2 })
+			.expect("valid change");
+		clone.update_history(history).expect("valid history");
+		assert_eq!(session_info.too_long_for_forest(), Some((SessionPeriod(11), 20)));
+	}
+
+	#[test]
+	fn rejects_malformed_history() {
+		let session_info = SessionBoundaryInfo::new(SessionPeriod(10));
+		let history = SessionPeriodHistory::new(0);
+		assert!(session_info.update_history(history).is_err());
+		assert_eq!(session_info.last_block_of_session(SessionId(0)), 9);
+	}
+}
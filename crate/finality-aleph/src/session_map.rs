@@ -1,11 +1,13 @@
 use std::{cmp::min, collections::HashMap, marker::PhantomData, ops::Deref, sync::Arc};
 
 use futures::StreamExt;
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
 use pallet_aleph_runtime_api::AlephSessionApi;
 use sc_client_api::{Backend, FinalityNotification};
 use sc_utils::mpsc::TracingUnboundedReceiver;
-use selendra_primitives::{AccountId, AuraId, BlockHash, BlockNumber, SessionAuthorityData};
+use selendra_primitives::{
+	AccountId, AuraId, BlockHash, BlockNumber, SessionAuthorityData, SessionPeriodHistory,
+};
 use sp_consensus_aura::AuraApi;
 use sp_runtime::traits::{Block, Header};
 use tokio::sync::{
@@ -15,7 +17,7 @@ use tokio::sync::{
 
 use crate::{
 	block::substrate::FinalizationInfo, runtime_api::RuntimeApi, session::SessionBoundaryInfo,
-	ClientForAleph, SessionId, SessionPeriod,
+	ClientForAleph, SessionId,
 };
 const PRUNING_THRESHOLD: u32 = 10;
 const LOG_TARGET: &str = "selendra-session-updater";
@@ -31,6 +33,8 @@ pub trait AuthorityProvider: Clone + Send + Sync + 'static {
 	fn aura_authorities(&self, block_number: BlockNumber) -> Option<Vec<AuraId>>;
 	/// returns list of next session Aura authorities for a given block number
 	fn next_aura_authorities(&self, block_number: BlockNumber) -> Option<Vec<(AccountId, AuraId)>>;
+	/// returns the history of session period changes known at a given block number
+	fn session_period_history(&self, block_number: BlockNumber) -> Option<SessionPeriodHistory>;
 }
 
 /// Returns number of some available (i.e. we are should be able to read its state) finalized block withing a given session.
@@ -112,6 +116,11 @@ where
 		self.api.next_aura_authorities(self.block_hash(block_number)?).ok()
 	}
 
+	fn session_period_history(&self, block_number: BlockNumber) -> Option<SessionPeriodHistory> {
+		let block_hash = self.block_hash(block_number)?;
+		self.client.runtime_api().session_period_history(block_hash).ok()
+	}
+
 	fn authority_data(&self, block_number: BlockNumber) -> Option<SessionAuthorityData> {
 		let block_hash = self.block_hash(block_number)?;
 		match self.client.runtime_api().authority_data(block_hash) {
@@ -320,12 +329,35 @@ where
 	AP: AuthorityProvider,
 	FN: FinalityNotifier,
 {
-	pub fn new(authority_provider: AP, finality_notifier: FN, period: SessionPeriod) -> Self {
-		Self {
+	/// Creates the updater, immediately bringing the session boundaries up to date with the
+	/// session period history at the last finalized block.
+	pub fn new(
+		authority_provider: AP,
+		finality_notifier: FN,
+		session_info: SessionBoundaryInfo,
+	) -> Self {
+		let updater = Self {
 			session_map: SharedSessionMap::new(),
 			authority_provider,
 			finality_notifier,
-			session_info: SessionBoundaryInfo::new(period),
+			session_info,
+		};
+		updater.update_session_periods(updater.finality_notifier.last_finalized());
+		updater
+	}
+
+	/// Updates the session boundaries with the session period history at the given block.
+	/// Runtimes that do not support session period changes leave the boundaries intact.
+	fn update_session_periods(&self, block_number: BlockNumber) {
+		let history = match self.authority_provider.session_period_history(block_number) {
+			Some(history) => history,
+			None => return,
+		};
+		if let Err(e) = self.session_info.update_history(history) {
+			warn!(
+				target: LOG_TARGET,
+				"Ignoring session period history at block #{}: {}.", block_number, e
+			);
 		}
 	}
 
@@ -341,6 +373,7 @@ where
 			target: LOG_TARGET,
 			"Handling first block #{:?} of session {:?}", first_block, session_id.0
 		);
+		self.update_session_periods(first_block);
 
 		if let Some(authority_data) = self.authority_provider.next_authority_data(first_block) {
 			self.session_map.update(SessionId(session_id.0 + 1), authority_data).await;
@@ -437,7 +470,7 @@ mod tests {
 
 	use futures_timer::Delay;
 	use sc_utils::mpsc::tracing_unbounded;
-	use selendra_primitives::BlockNumber;
+	use selendra_primitives::{BlockNumber, SessionPeriodChange};
 	use tokio::sync::oneshot::error::TryRecvError;
 
 	use super::*;
@@ -456,17 +489,26 @@ mod tests {
 	struct MockProvider {
 		pub session_map: HashMap<BlockNumber, SessionAuthorityData>,
 		pub next_session_map: HashMap<BlockNumber, SessionAuthorityData>,
+		pub session_period_history: Option<SessionPeriodHistory>,
 	}
 
 	impl MockProvider {
 		fn new() -> Self {
-			Self { session_map: HashMap::new(), next_session_map: HashMap::new() }
+			Self {
+				session_map: HashMap::new(),
+				next_session_map: HashMap::new(),
+				session_period_history: None,
+			}
 		}
 
 		fn add_session(&mut self, session_id: BlockNumber) {
-			self.session_map.insert(session_id, authority_data_for_session(session_id));
+			self.add_session_starting_at(session_id, session_id);
+		}
+
+		fn add_session_starting_at(&mut self, session_id: u32, first_block: BlockNumber) {
+			self.session_map.insert(first_block, authority_data_for_session(session_id));
 			self.next_session_map
-				.insert(session_id, authority_data_for_session(session_id + 1));
+				.insert(first_block, authority_data_for_session(session_id + 1));
 		}
 	}
 	impl AuthorityProvider for MockProvider {
@@ -488,6 +530,13 @@ mod tests {
 		) -> Option<Vec<(AccountId, AuraId)>> {
 			None
 		}
+
+		fn session_period_history(
+			&self,
+			_block_number: BlockNumber,
+		) -> Option<SessionPeriodHistory> {
+			self.session_period_history.clone()
+		}
 	}
 
 	struct MockNotifier {
@@ -512,6 +561,10 @@ mod tests {
 		}
 	}
 
+	fn session_info() -> SessionBoundaryInfo {
+		SessionBoundaryInfo::new(SessionPeriod(1))
+	}
+
 	fn authority_data_for_session(session_id: u32) -> SessionAuthorityData {
 		authority_data(session_id * 4, (session_id + 1) * 4)
 	}
@@ -524,7 +577,7 @@ mod tests {
 
 		mock_provider.add_session(0);
 
-		let updater = SessionMapUpdater::new(mock_provider, mock_notifier, SessionPeriod(1));
+		let updater = SessionMapUpdater::new(mock_provider, mock_notifier, session_info());
 		let session_map = updater.readonly_session_map();
 
 		let _handle = tokio::spawn(updater.run());
@@ -546,7 +599,7 @@ mod tests {
 		mock_provider.add_session(1);
 		mock_provider.add_session(2);
 
-		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, SessionPeriod(1));
+		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, session_info());
 		let session_map = updater.readonly_session_map();
 
 		for n in 1..3 {
@@ -564,6 +617,46 @@ mod tests {
 		assert_eq!(session_map.get(SessionId(3)).await, Some(authority_data(12, 16)));
 	}
 
+	#[tokio::test(flavor = "multi_thread")]
+	async fn follows_session_period_change() {
+		let (sender, receiver) = tracing_unbounded("test", 1_000);
+		let mut mock_provider = MockProvider::new();
+		let mock_notificator = MockNotifier::new(receiver);
+
+		// Sessions have a single block until session 2, from which they have 3 blocks.
+		let mut history = SessionPeriodHistory::new(1);
+		history
+			.push(SessionPeriodChange { period: 3, session: 2 })
+			.expect("change is valid");
+		mock_provider.session_period_history = Some(history);
+		for (session_id, first_block) in [(0, 0), (1, 1), (2, 2), (3, 5), (4, 8)] {
+			mock_provider.add_session_starting_at(session_id, first_block);
+		}
+
+		let session_info = session_info();
+		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, session_info.clone());
+		let session_map = updater.readonly_session_map();
+
+		for n in 1..=8 {
+			sender.unbounded_send(n).unwrap();
+		}
+
+		let _handle = tokio::spawn(updater.run());
+
+		// wait a bit
+		Delay::new(Duration::from_millis(50)).await;
+
+		assert_eq!(session_info.session_id_from_block_num(7), SessionId(3));
+		assert_eq!(session_info.first_block_of_session(SessionId(4)), 8);
+		for session_id in 0..=5 {
+			assert_eq!(
+				session_map.get(SessionId(session_id)).await,
+				Some(authority_data_for_session(session_id))
+			);
+		}
+		assert_eq!(session_map.get(SessionId(6)).await, None);
+	}
+
 	#[tokio::test(flavor = "multi_thread")]
 	async fn catch_up() {
 		let (_sender, receiver) = tracing_unbounded("test", 1_000);
@@ -576,7 +669,7 @@ mod tests {
 
 		mock_notificator.last_finalized = 2;
 
-		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, SessionPeriod(1));
+		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, session_info());
 		let session_map = updater.readonly_session_map();
 
 		let _handle = tokio::spawn(updater.run());
@@ -602,7 +695,7 @@ mod tests {
 
 		mock_notificator.last_finalized = 20;
 
-		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, SessionPeriod(1));
+		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, session_info());
 		let session_map = updater.readonly_session_map();
 
 		let _handle = tokio::spawn(updater.run());
@@ -631,7 +724,7 @@ mod tests {
 		mock_provider.add_session(5);
 		mock_notificator.last_finalized = 5;
 
-		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, SessionPeriod(1));
+		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, session_info());
 		let session_map = updater.readonly_session_map();
 
 		let _handle = tokio::spawn(updater.run());
@@ -661,7 +754,7 @@ mod tests {
 			mock_provider.add_session(i);
 		}
 
-		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, SessionPeriod(1));
+		let updater = SessionMapUpdater::new(mock_provider, mock_notificator, session_info());
 		let session_map = updater.readonly_session_map();
 
 		let _handle = tokio::spawn(updater.run());
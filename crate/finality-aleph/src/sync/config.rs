@@ -0,0 +1,145 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use crate::{data_io::DataStoreConfig, sync::forest::DEFAULT_MAX_DEPTH, SessionBoundaryInfo};
+
+/// How many sessions the justification verifier remembers by default.
+/// Keep in mind that Aura stores authority info in the parent block,
+/// so the actual size probably needs to be increased by one.
+pub const VERIFIER_CACHE_SIZE: usize = 3;
+
+/// Limits bounding the memory used by block sync and the data availability checks of ABFT.
+#[derive(Clone, Debug)]
+pub struct SyncConfig {
+	/// How many blocks above the top finalized one the forest of blocks is allowed to track.
+	pub forest_max_depth: u32,
+	/// How many sessions the justification verifier keeps authorities for.
+	pub verifier_cache_size: usize,
+	/// Limits on the proposals, messages and blocks kept by the data store of every session.
+	pub data_store: DataStoreConfig,
+}
+
+impl Default for SyncConfig {
+	fn default() -> Self {
+		SyncConfig {
+			forest_max_depth: DEFAULT_MAX_DEPTH,
+			verifier_cache_size: VERIFIER_CACHE_SIZE,
+			data_store: DataStoreConfig::default(),
+		}
+	}
+}
+
+/// Reasons why a sync config cannot work with a given session period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+	ForestTooShallow { max_depth: u32, session_period: u32 },
+	VerifierCacheTooSmall(usize),
+	ZeroLimit(&'static str),
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use Error::*;
+		match self {
+			ForestTooShallow { max_depth, session_period } => write!(
+				f,
+				"forest max depth {max_depth} does not fit a whole session of {session_period} blocks"
+			),
+			VerifierCacheTooSmall(size) => write!(
+				f,
+				"verifier cache of size {size} cannot hold both the current and the next session"
+			),
+			ZeroLimit(name) => write!(f, "{name} must be positive"),
+		}
+	}
+}
+
+impl SyncConfig {
+	/// The default config, with the forest deepened if needed so that two of the longest known
+	/// sessions fit into it.
+	pub fn for_session_info(session_info: &SessionBoundaryInfo) -> Self {
+		let session_period = session_info.longest_session_period();
+		SyncConfig {
+			forest_max_depth: DEFAULT_MAX_DEPTH.max(session_period.0.saturating_mul(2)),
+			..Default::default()
+		}
+	}
+
+	/// Check whether the limits make sense for all the sessions known so far, including the
+	/// ones after a scheduled change of the session period.
+	pub fn validate(&self, session_info: &SessionBoundaryInfo) -> Result<(), Error> {
+		let session_period = session_info.longest_session_period();
+		if self.forest_max_depth < session_period.0 {
+			return Err(Error::ForestTooShallow {
+				max_depth: self.forest_max_depth,
+				session_period: session_period.0,
+			});
+		}
+		if self.verifier_cache_size < 2 {
+			return Err(Error::VerifierCacheTooSmall(self.verifier_cache_size));
+		}
+		let data_store = &self.data_store;
+		for (name, limit) in [
+			("max_triggers_pending", data_store.max_triggers_pending),
+			("max_proposals_pending", data_store.max_proposals_pending),
+			("max_messages_pending", data_store.max_messages_pending),
+		] {
+			if limit == 0 {
+				return Err(Error::ZeroLimit(name));
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use selendra_primitives::{SessionPeriodChange, SessionPeriodHistory};
+
+	use super::{Error, SyncConfig};
+	use crate::{SessionBoundaryInfo, SessionPeriod};
+
+	#[test]
+	fn default_fits_default_session() {
+		let session_info =
+			SessionBoundaryInfo::new(SessionPeriod(selendra_primitives::DEFAULT_SESSION_PERIOD));
+		assert_eq!(SyncConfig::default().validate(&session_info), Ok(()));
+	}
+
+	#[test]
+	fn grows_forest_for_long_sessions() {
+		let session_info = SessionBoundaryInfo::new(SessionPeriod(5000));
+		assert!(matches!(
+			SyncConfig::default().validate(&session_info),
+			Err(Error::ForestTooShallow { .. })
+		));
+		let config = SyncConfig::for_session_info(&session_info);
+		assert_eq!(config.forest_max_depth, 10000);
+		assert_eq!(config.validate(&session_info), Ok(()));
+	}
+
+	#[test]
+	fn fits_sessions_after_period_change() {
+		let mut history = SessionPeriodHistory::new(900);
+		history
+			.push(SessionPeriodChange { period: 5000, session: 10 })
+			.expect("change is valid");
+		let session_info = SessionBoundaryInfo::with_history(history);
+		assert!(matches!(
+			SyncConfig::default().validate(&session_info),
+			Err(Error::ForestTooShallow { session_period: 5000, .. })
+		));
+		let config = SyncConfig::for_session_info(&session_info);
+		assert_eq!(config.forest_max_depth, 10000);
+		assert_eq!(config.validate(&session_info), Ok(()));
+	}
+
+	#[test]
+	fn rejects_zero_limits() {
+		let mut config = SyncConfig::default();
+		config.data_store.max_messages_pending = 0;
+		assert_eq!(
+			config.validate(&SessionBoundaryInfo::new(SessionPeriod(900))),
+			Err(Error::ZeroLimit("max_messages_pending"))
+		);
+	}
+}
@@ -399,6 +399,10 @@ where
 		self.inner.broadcast(VersionedNetworkData::V4(data))
 	}
 
+	fn connected_peers(&self) -> HashSet<Self::PeerId> {
+		self.inner.connected_peers()
+	}
+
 	/// Retrieves next message from the network.
 	///
 	/// # Cancel safety
@@ -1,7 +1,7 @@
 use std::{
 	collections::{
 		hash_map::{Entry, OccupiedEntry, VacantEntry},
-		HashMap, HashSet, VecDeque,
+		BTreeMap, HashMap, HashSet, VecDeque,
 	},
 	fmt::{Display, Error as FmtError, Formatter},
 };
@@ -61,6 +61,15 @@ pub enum ExtensionRequest<H: Header, I: PeerId> {
 	HighestJustified { header: H, know_most: HashSet<I>, branch_knowledge: BranchKnowledge },
 }
 
+/// A block we know to be on the finalized branch, but have not imported yet, together with
+/// everything required to download the blocks leading up to it.
+#[derive(Clone, Debug)]
+pub struct RangeAnchor<I: PeerId, J: Justification> {
+	pub header: J::Header,
+	pub justification: Option<J>,
+	pub know_most: HashSet<I>,
+}
+
 /// What can go wrong when inserting data into the forest.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Error {
@@ -140,24 +149,33 @@ pub struct Status {
 	imported: BlockId,
 	/// Number of the favourite block.
 	favourite: BlockId,
+	/// Number of vertices kept in the forest.
+	vertices: usize,
+}
+
+impl Status {
+	/// Number of vertices kept in the forest.
+	pub fn vertices(&self) -> usize {
+		self.vertices
+	}
 }
 
 impl Display for Status {
 	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
 		write!(
 			f,
-			"Imported: {}, finalized: {}, favourite: {}.",
-			self.imported, self.finalized, self.favourite
+			"Imported: {}, finalized: {}, favourite: {}, tracked blocks: {}.",
+			self.imported, self.finalized, self.favourite, self.vertices
 		)
 	}
 }
 
-// How deep can the forest be, vaguely based on two sessions ahead, which is the most we expect to
-// ever need worst case scenario.
+// How deep can the forest be by default, vaguely based on two sessions ahead, which is the most we
+// expect to ever need worst case scenario.
 //
 // At least one session must fit into the Forest.
-const MAX_DEPTH: u32 = 1800;
-const_assert!(DEFAULT_SESSION_PERIOD <= MAX_DEPTH);
+pub const DEFAULT_MAX_DEPTH: u32 = 1800;
+const_assert!(DEFAULT_SESSION_PERIOD <= DEFAULT_MAX_DEPTH);
 
 pub struct Forest<I, J>
 where
@@ -167,11 +185,14 @@ where
 	vertices: HashMap<BlockId, VertexWithChildren<I, J>>,
 	highest_justified: J::Header,
 	justified_blocks: HashMap<BlockNumber, BlockId>,
+	/// Blocks we have justifications for but did not import yet, the anchors of range downloads.
+	unimported_justified: BTreeMap<BlockNumber, BlockId>,
 	imported_leaves: HashMap<BlockId, J::Header>,
 	favourite: J::Header,
 	root: J::Header,
 	root_children: HashSet<BlockId>,
 	compost_bin: HashSet<BlockId>,
+	max_depth: u32,
 }
 
 type Edge = (BlockId, BlockId);
@@ -181,8 +202,11 @@ where
 	I: PeerId,
 	J: Justification,
 {
-	/// Creates a new forest.
-	pub fn new<B, CS>(chain_status: &CS) -> Result<Self, InitializationError<B, J, CS>>
+	/// Creates a new forest, tracking blocks at most `max_depth` above the top finalized one.
+	pub fn new<B, CS>(
+		chain_status: &CS,
+		max_depth: u32,
+	) -> Result<Self, InitializationError<B, J, CS>>
 	where
 		B: Block<UnverifiedHeader = UnverifiedHeaderFor<J>>,
 		CS: ChainStatus<B, J>,
@@ -196,11 +220,13 @@ where
 			vertices: HashMap::new(),
 			highest_justified: top_finalized.clone(),
 			justified_blocks: HashMap::new(),
+			unimported_justified: BTreeMap::new(),
 			imported_leaves: HashMap::new(),
 			favourite: top_finalized.clone(),
 			root: top_finalized.clone(),
 			root_children: HashSet::new(),
 			compost_bin: HashSet::new(),
+			max_depth,
 		};
 
 		// Populate the forest
@@ -222,7 +248,7 @@ where
 			Some(HighestFinalized)
 		} else if id.number() <= self.root.id().number() {
 			Some(BelowMinimal)
-		} else if id.number() > self.root.id().number() + MAX_DEPTH {
+		} else if id.number() > self.root.id().number() + self.max_depth {
 			Some(TooNew)
 		} else if self.compost_bin.contains(id) {
 			Some(HopelessFork)
@@ -380,6 +406,7 @@ where
 				let vertex = &mut entry.get_mut().vertex;
 				let update_favourite = vertex.insert_body(header.clone());
 				if vertex.justified_block() {
+					self.unimported_justified.remove(&id.number());
 					self.justified_blocks.insert(id.number(), id.clone());
 				}
 				if update_favourite {
@@ -427,6 +454,8 @@ where
 				vertex.insert_justification(parent_id, justification, holder);
 				if vertex.justified_block() {
 					self.justified_blocks.insert(id.number(), id.clone());
+				} else {
+					self.unimported_justified.insert(id.number(), id.clone());
 				}
 				self.try_update_highest_justified(&header)
 			},
@@ -468,6 +497,7 @@ where
 		}
 		self.compost_bin.retain(|k| k.number() > level);
 		self.justified_blocks.retain(|k, _| k > &level);
+		self.unimported_justified.retain(|k, _| k > &level);
 		self.pick_favourite();
 	}
 
@@ -623,6 +653,24 @@ where
 		Noop
 	}
 
+	/// Blocks on the finalized branch that we did not import yet, sorted by their numbers. Only
+	/// the justified blocks are anchors, the blocks between them are downloaded in segments
+	/// spanning from one anchor to another.
+	pub fn range_anchors(&self) -> Vec<RangeAnchor<I, J>> {
+		use VertexHandle::Candidate;
+		self.unimported_justified
+			.values()
+			.filter_map(|id| match self.get(id) {
+				Candidate(vertex) if !vertex.vertex.imported() => Some(RangeAnchor {
+					header: vertex.vertex.header()?,
+					justification: vertex.vertex.justification(),
+					know_most: vertex.vertex.know_most(),
+				}),
+				_ => None,
+			})
+			.collect()
+	}
+
 	/// Whether this block should be skipped during importing.
 	/// It either needs to be already imported, or too old to be checked.
 	pub fn skippable(&self, id: &BlockId) -> bool {
@@ -651,6 +699,7 @@ where
 				.unwrap_or(&self.root.id())
 				.clone(),
 			favourite: self.favourite_block().id(),
+			vertices: self.vertices.len(),
 		}
 	}
 }
@@ -659,7 +708,7 @@ where
 mod tests {
 	use std::collections::HashSet;
 
-	use super::{Error, ExtensionRequest::*, Forest, Interest::*, MAX_DEPTH};
+	use super::{Error, ExtensionRequest::*, Forest, Interest::*, DEFAULT_MAX_DEPTH};
 	use crate::{
 		block::{
 			mock::{Backend, MockHeader, MockJustification},
@@ -672,12 +721,14 @@ mod tests {
 
 	type MockForest = Forest<MockPeerId, MockJustification>;
 
-	const SESSION_BOUNDARY_INFO: SessionBoundaryInfo = SessionBoundaryInfo::new(SessionPeriod(20));
+	fn session_boundary_info() -> SessionBoundaryInfo {
+		SessionBoundaryInfo::new(SessionPeriod(20))
+	}
 
 	fn setup() -> (MockHeader, MockForest) {
-		let (backend, _) = Backend::setup(SESSION_BOUNDARY_INFO);
+		let (backend, _) = Backend::setup(session_boundary_info());
 		let header = backend.top_finalized().expect("should return genesis").header().clone();
-		let forest = Forest::new(&backend).expect("should initialize");
+		let forest = Forest::new(&backend, DEFAULT_MAX_DEPTH).expect("should initialize");
 		(header, forest)
 	}
 
@@ -1226,7 +1277,7 @@ mod tests {
 		assert!(forest.importable(&branch[3].id()));
 	}
 
-	const HUGE_BRANCH_LENGTH: usize = MAX_DEPTH as usize;
+	const HUGE_BRANCH_LENGTH: usize = DEFAULT_MAX_DEPTH as usize;
 
 	#[test]
 	fn finalizes_huge_branch() {
@@ -1347,4 +1398,28 @@ mod tests {
 			assert!(forest.importable(&header.id()));
 		}
 	}
+
+	#[test]
+	fn range_anchors_are_unimported_justified_blocks() {
+		let (initial_header, mut forest) = setup();
+		let branch: Vec<_> = initial_header.random_branch().take(10).collect();
+		let peer_id = rand::random();
+		for header in &branch[4..] {
+			forest.update_header(header, Some(peer_id), false).expect("header was correct");
+		}
+		for number in [0, 5, 9] {
+			let justification = MockJustification::for_header(branch[number].clone());
+			forest
+				.update_justification(justification, Some(peer_id))
+				.expect("justification was correct");
+		}
+		forest.update_body(&branch[0]).expect("parent was imported");
+		let anchors = forest.range_anchors();
+		assert_eq!(
+			anchors.iter().map(|anchor| anchor.header.id()).collect::<Vec<_>>(),
+			vec![branch[5].id(), branch[9].id()]
+		);
+		assert!(anchors.iter().all(|anchor| anchor.justification.is_some()));
+		assert!(anchors[0].know_most.contains(&peer_id));
+	}
 }
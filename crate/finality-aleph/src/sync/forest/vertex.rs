@@ -121,6 +121,14 @@ impl<I: PeerId, J: Justification> Vertex<I, J> {
 		}
 	}
 
+	/// The justification of the vertex, if known.
+	pub fn justification(&self) -> Option<J> {
+		match &self.inner {
+			InnerVertex::Justification { justification, .. } => Some(justification.clone()),
+			_ => None,
+		}
+	}
+
 	/// The list of peers which know most about the data this vertex refers to.
 	pub fn know_most(&self) -> HashSet<I> {
 		self.know_most.iter().map(|(peer, ())| peer).cloned().collect()
@@ -15,7 +15,8 @@ use crate::{
 		data::{BranchKnowledge, NetworkData, Request, State},
 		forest::{
 			Error as ForestError, ExtensionRequest, Forest,
-			InitializationError as ForestInitializationError, Interest, Status as ForestStatus,
+			InitializationError as ForestInitializationError, Interest, RangeAnchor,
+			Status as ForestStatus,
 		},
 		handler::request_handler::RequestHandler,
 		PeerId,
@@ -125,6 +126,11 @@ type HandleRequestOutput<B, J, V> =
 	(Action<B, J>, Option<<V as HeaderVerifier<<J as Justification>::Header>>::EquivocationProof>);
 type HandleInternalRequestOutput<J, V> =
 	(bool, Option<<V as HeaderVerifier<<J as Justification>::Header>>::EquivocationProof>);
+type HandleResponseOutput<B, J, CS, V, F> = (
+	bool,
+	Vec<<V as HeaderVerifier<<J as Justification>::Header>>::EquivocationProof>,
+	Option<Error<B, J, CS, V, F>>,
+);
 
 impl<B, J> HandleStateAction<B, J>
 where
@@ -255,9 +261,11 @@ where
 		verifier: V,
 		sync_oracle: SyncOracle,
 		session_info: SessionBoundaryInfo,
+		forest_max_depth: u32,
 	) -> Result<Self, <Self as HandlerTypes>::Error> {
 		let DatabaseIO { chain_status, finalizer, block_importer, .. } = database_io;
-		let forest = Forest::new(&chain_status).map_err(Error::ForestInitialization)?;
+		let forest =
+			Forest::new(&chain_status, forest_max_depth).map_err(Error::ForestInitialization)?;
 		Ok(Handler {
 			chain_status,
 			verifier,
@@ -486,11 +494,31 @@ where
 		&mut self,
 		response_items: ResponseItems<B, J>,
 		peer: I,
-	) -> (bool, Vec<V::EquivocationProof>, Option<<Self as HandlerTypes>::Error>) {
+	) -> HandleResponseOutput<B, J, CS, V, F> {
+		self.handle_response_items(response_items, peer, None)
+	}
+
+	/// Handle a downloaded segment of a range, returning the same as `handle_request_response`.
+	/// The `previous` block has to be the last block of the preceding segment, which was already
+	/// passed to the importer, so that the first block of this segment can be imported after it.
+	pub fn handle_range_response(
+		&mut self,
+		response_items: ResponseItems<B, J>,
+		peer: I,
+		previous: BlockId,
+	) -> HandleResponseOutput<B, J, CS, V, F> {
+		self.handle_response_items(response_items, peer, Some(previous))
+	}
+
+	fn handle_response_items(
+		&mut self,
+		response_items: ResponseItems<B, J>,
+		peer: I,
+		// Lets us import descendands of importable blocks, useful for favourite blocks.
+		mut last_imported: Option<BlockId>,
+	) -> HandleResponseOutput<B, J, CS, V, F> {
 		let mut equivocation_proofs = vec![];
 		let mut new_highest = false;
-		// Lets us import descendands of importable blocks, useful for favourite blocks.
-		let mut last_imported: Option<BlockId> = None;
 		for item in response_items {
 			match item {
 				ResponseItem::Justification(j) => {
@@ -633,6 +661,16 @@ where
 		Ok((should_request, maybe_equivocation_proof))
 	}
 
+	/// Blocks on the finalized branch we could download in ranges.
+	pub fn range_anchors(&self) -> Vec<RangeAnchor<I, J>> {
+		self.forest.range_anchors()
+	}
+
+	/// How far behind in finalization are we.
+	pub fn behind_finalization(&self) -> u32 {
+		self.forest.behind_finalization()
+	}
+
 	/// Returns the extension request we could be making right now.
 	pub fn extension_request(&self) -> ExtensionRequest<J::Header, I> {
 		self.forest.extension_request()
@@ -681,7 +719,7 @@ mod tests {
 		session::{SessionBoundaryInfo, SessionId},
 		sync::{
 			data::{BranchKnowledge::*, NetworkData, Request, ResponseItem, ResponseItems, State},
-			forest::{ExtensionRequest, Interest},
+			forest::{ExtensionRequest, Interest, DEFAULT_MAX_DEPTH},
 			handler::Action,
 			Justification, MockPeerId,
 		},
@@ -692,15 +730,22 @@ mod tests {
 		Handler<MockBlock, MockPeerId, MockJustification, Backend, Backend, Backend, Backend>;
 	type MockResponseItems = ResponseItems<MockBlock, MockJustification>;
 
-	const SESSION_BOUNDARY_INFO: SessionBoundaryInfo = SessionBoundaryInfo::new(SessionPeriod(20));
+	fn session_boundary_info() -> SessionBoundaryInfo {
+		SessionBoundaryInfo::new(SessionPeriod(20))
+	}
 
 	fn setup() -> (TestHandler, Backend, impl ChainStatusNotifier<MockHeader>, BlockId) {
-		let (backend, notifier) = Backend::setup(SESSION_BOUNDARY_INFO);
+		let (backend, notifier) = Backend::setup(session_boundary_info());
 		let verifier = backend.clone();
 		let database_io = DatabaseIO::new(backend.clone(), backend.clone(), backend.clone());
-		let handler =
-			Handler::new(database_io, verifier, SyncOracle::new().0, SESSION_BOUNDARY_INFO)
-				.expect("mock backend works");
+		let handler = Handler::new(
+			database_io,
+			verifier,
+			SyncOracle::new().0,
+			session_boundary_info(),
+			DEFAULT_MAX_DEPTH,
+		)
+		.expect("mock backend works");
 		let genesis = backend.top_finalized().expect("genesis").header().id();
 		(handler, backend, notifier, genesis)
 	}
@@ -1070,7 +1115,7 @@ mod tests {
 		let mut bottom = genesis;
 		let peer_id = 0;
 		for session in 0.. {
-			let top = SESSION_BOUNDARY_INFO.last_block_of_session(SessionId(session));
+			let top = session_boundary_info().last_block_of_session(SessionId(session));
 			let branch = grow_light_branch_till(&mut handler, &bottom, &top, peer_id);
 			bottom = branch.last().expect("should not be empty").id();
 			// import blocks
@@ -1109,9 +1154,10 @@ mod tests {
 	#[tokio::test]
 	async fn skips_justification_gap_with_last_of_current_session_only() {
 		let (mut handler, _backend, mut notifier, genesis) = setup();
-		let last_block_of_first_session = SESSION_BOUNDARY_INFO.last_block_of_session(SessionId(0));
+		let last_block_of_first_session =
+			session_boundary_info().last_block_of_session(SessionId(0));
 		let last_block_of_second_session =
-			SESSION_BOUNDARY_INFO.last_block_of_session(SessionId(1));
+			session_boundary_info().last_block_of_session(SessionId(1));
 		let peer_id = 0;
 		let branch_low =
 			grow_light_branch_till(&mut handler, &genesis, &last_block_of_first_session, peer_id);
@@ -1484,7 +1530,7 @@ mod tests {
 
 	#[test]
 	fn initializes_forest_properly() {
-		let (mut backend, _keep) = Backend::setup(SESSION_BOUNDARY_INFO);
+		let (mut backend, _keep) = Backend::setup(session_boundary_info());
 		let header = import_branch(&mut backend, 1)[0].clone();
 		// header already imported, Handler should initialize Forest properly
 		let verifier = backend.clone();
@@ -1494,6 +1540,7 @@ mod tests {
 			verifier,
 			SyncOracle::new().0,
 			SessionBoundaryInfo::new(SessionPeriod(20)),
+			DEFAULT_MAX_DEPTH,
 		)
 		.expect("mock backend works");
 		let justification = MockJustification::for_header(header);
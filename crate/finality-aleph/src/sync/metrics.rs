@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
-use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use prometheus_endpoint::{
+	register, Counter, Gauge, GaugeVec, Opts, PrometheusError, Registry, I64, U64,
+};
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum Event {
@@ -18,6 +20,7 @@ pub enum Event {
 	HandleStateResponse,
 	HandleJustificationFromUser,
 	HandleInternalRequest,
+	SendRangeRequest,
 }
 
 use Event::*;
@@ -39,11 +42,12 @@ impl Event {
 			HandleStateResponse => "handle_state_response",
 			HandleJustificationFromUser => "handle_justification_from_user",
 			HandleInternalRequest => "handle_internal_request",
+			SendRangeRequest => "send_range_request",
 		}
 	}
 }
 
-const ALL_EVENTS: [Event; 14] = [
+const ALL_EVENTS: [Event; 15] = [
 	Broadcast,
 	SendRequest,
 	SendTo,
@@ -58,9 +62,10 @@ const ALL_EVENTS: [Event; 14] = [
 	HandleStateResponse,
 	HandleJustificationFromUser,
 	HandleInternalRequest,
+	SendRangeRequest,
 ];
 
-const ERRORING_EVENTS: [Event; 11] = [
+const ERRORING_EVENTS: [Event; 12] = [
 	Broadcast,
 	SendRequest,
 	SendTo,
@@ -72,12 +77,17 @@ const ERRORING_EVENTS: [Event; 11] = [
 	HandleBlockImported,
 	HandleJustificationFromUser,
 	HandleInternalRequest,
+	SendRangeRequest,
 ];
 
 pub enum Metrics {
 	Prometheus {
 		event_calls: HashMap<Event, Counter<U64>>,
 		event_errors: HashMap<Event, Counter<U64>>,
+		peer_scores: GaugeVec<I64>,
+		banned_peers: Gauge<U64>,
+		bans: Counter<U64>,
+		forest_vertices: Gauge<U64>,
 	},
 	Noop,
 }
@@ -114,7 +124,33 @@ impl Metrics {
 				)?,
 			);
 		}
-		Ok(Metrics::Prometheus { event_calls, event_errors })
+		let peer_scores = register(
+			GaugeVec::new(
+				Opts::new("aleph_sync_peer_score", "reputation score of a block sync peer"),
+				&["peer"],
+			)?,
+			&registry,
+		)?;
+		let banned_peers = register(
+			Gauge::new("aleph_sync_banned_peers", "number of currently banned block sync peers")?,
+			&registry,
+		)?;
+		let bans = register(
+			Counter::new("aleph_sync_peer_bans", "number of times a block sync peer got banned")?,
+			&registry,
+		)?;
+		let forest_vertices = register(
+			Gauge::new("aleph_sync_forest_vertices", "number of blocks tracked by block sync")?,
+			&registry,
+		)?;
+		Ok(Metrics::Prometheus {
+			event_calls,
+			event_errors,
+			peer_scores,
+			banned_peers,
+			bans,
+			forest_vertices,
+		})
 	}
 
 	pub fn noop() -> Self {
@@ -136,4 +172,32 @@ impl Metrics {
 			}
 		}
 	}
+
+	pub fn report_bans(&self, count: usize) {
+		if let Metrics::Prometheus { bans, .. } = self {
+			bans.inc_by(count as u64);
+		}
+	}
+
+	/// Sets the scores of the given peers, removing the labels of all the other peers, so that
+	/// the gauge does not grow with every peer we ever saw.
+	pub fn report_reputation<'a, PI: Display + 'a, I: IntoIterator<Item = (&'a PI, i32)>>(
+		&self,
+		scores: I,
+		banned_count: usize,
+	) {
+		if let Metrics::Prometheus { peer_scores, banned_peers, .. } = self {
+			peer_scores.reset();
+			for (peer, score) in scores {
+				peer_scores.with_label_values(&[&peer.to_string()]).set(score as i64);
+			}
+			banned_peers.set(banned_count as u64);
+		}
+	}
+
+	pub fn report_forest_vertices(&self, count: usize) {
+		if let Metrics::Prometheus { forest_vertices, .. } = self {
+			forest_vertices.set(count as u64);
+		}
+	}
 }
@@ -9,17 +9,23 @@ use crate::{
 	BlockId,
 };
 
+mod config;
 mod data;
 mod forest;
 mod handler;
 mod message_limiter;
 mod metrics;
+mod range;
+mod reputation;
 mod select_chain;
 mod service;
 mod task_queue;
 mod tasks;
 mod ticker;
 
+pub use config::{Error as SyncConfigError, SyncConfig, VERIFIER_CACHE_SIZE};
+#[cfg(test)]
+pub use data::VersionedNetworkData;
 pub use data::MAX_MESSAGE_SIZE;
 pub use handler::DatabaseIO;
 pub use select_chain::FavouriteSelectChainProvider;
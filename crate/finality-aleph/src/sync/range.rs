@@ -0,0 +1,563 @@
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	time::{Duration, Instant},
+};
+
+use log::debug;
+
+use crate::{
+	block::{
+		Block, Header, Justification, UnverifiedHeader, UnverifiedHeaderFor,
+		UnverifiedJustification,
+	},
+	session::{SessionBoundaryInfo, SessionId},
+	sync::{
+		data::{ResponseItem, ResponseItems},
+		forest::RangeAnchor,
+		PeerId, LOG_TARGET,
+	},
+	BlockId, BlockNumber,
+};
+
+/// How many segments can be downloaded at the same time.
+const MAX_SEGMENTS_IN_FLIGHT: usize = 8;
+/// How many segments can be kept in memory, including the downloaded ones waiting for the lower
+/// ones to arrive.
+const MAX_BUFFERED_SEGMENTS: usize = 32;
+const INITIAL_SEGMENT_SIZE: u32 = 128;
+const MIN_SEGMENT_SIZE: u32 = 16;
+const MAX_SEGMENT_SIZE: u32 = 2048;
+/// We aim for every peer to take about this long to send us a segment.
+const TARGET_SEGMENT_DURATION: Duration = Duration::from_secs(10);
+const SEGMENT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The weight of the newest measurement in the moving average of a peer's throughput.
+const THROUGHPUT_SMOOTHING: f64 = 0.3;
+
+/// A request for the blocks in a segment of the finalized branch that should be sent to a peer.
+pub struct SegmentRequest<I: PeerId, J: Justification> {
+	pub peer: I,
+	/// The justification to use as the top justification in the state attached to the request,
+	/// `None` means our actual state should be used.
+	pub base: Option<J>,
+	/// The block right below the segment.
+	pub lower: BlockId,
+	/// The highest block of the segment.
+	pub target: J::Header,
+}
+
+/// A completely downloaded segment, ready to be handled.
+pub struct ReadySegment<B, I, J>
+where
+	I: PeerId,
+	J: Justification,
+	B: Block<UnverifiedHeader = UnverifiedHeaderFor<J>>,
+{
+	pub peer: I,
+	/// The last block of the previous segment, the parent of the first block in this one.
+	pub previous: BlockId,
+	pub items: ResponseItems<B, J>,
+}
+
+enum SegmentStatus<I: PeerId> {
+	Pending,
+	InFlight { peer: I, sent: Instant },
+	Complete { peer: I },
+}
+
+struct Segment<B, I, J>
+where
+	I: PeerId,
+	J: Justification,
+	B: Block<UnverifiedHeader = UnverifiedHeaderFor<J>>,
+{
+	base: Option<J>,
+	lower: BlockId,
+	target: J::Header,
+	candidates: HashSet<I>,
+	status: SegmentStatus<I>,
+	justifications: Vec<J::Unverified>,
+	blocks: BTreeMap<BlockNumber, B>,
+}
+
+impl<B, I, J> Segment<B, I, J>
+where
+	I: PeerId,
+	J: Justification,
+	B: Block<UnverifiedHeader = UnverifiedHeaderFor<J>>,
+{
+	fn size(&self) -> u32 {
+		self.target.id().number().saturating_sub(self.lower.number())
+	}
+
+	fn contains(&self, number: BlockNumber) -> bool {
+		self.lower.number() < number && number <= self.target.id().number()
+	}
+
+	fn downloaded(&self) -> bool {
+		let target = self.target.id();
+		self.blocks.len() as u32 == self.size()
+			&& self
+				.blocks
+				.get(&target.number())
+				.map(|block| block.header().id() == target)
+				.unwrap_or(false)
+	}
+
+	fn in_flight_peer(&self) -> Option<&I> {
+		match &self.status {
+			SegmentStatus::InFlight { peer, .. } => Some(peer),
+			_ => None,
+		}
+	}
+
+	fn reset(&mut self) {
+		self.status = SegmentStatus::Pending;
+		self.justifications.clear();
+		self.blocks.clear();
+	}
+
+	fn request(&self, peer: I) -> SegmentRequest<I, J> {
+		SegmentRequest {
+			peer,
+			base: self.base.clone(),
+			lower: self.lower.clone(),
+			target: self.target.clone(),
+		}
+	}
+}
+
+/// Downloads long stretches of the finalized branch by splitting them into segments, which are
+/// requested from multiple peers in parallel. The segments are handed out in order once they
+/// are complete, and their sizes adapt to the throughput of the peer they are requested from.
+///
+/// Segments can only end at justified blocks, so the granularity depends on the justifications
+/// the `Forest` knows about. Every segment is requested with a state whose top justification is
+/// the highest one below it, so that peers agree to send it even if it is far above our actual
+/// top finalized block. If the lowest justification we know is further than peers would send,
+/// the plan is dropped and the blocks are requested one by one instead.
+pub struct RangeDownloader<B, I, J>
+where
+	I: PeerId,
+	J: Justification,
+	B: Block<UnverifiedHeader = UnverifiedHeaderFor<J>>,
+{
+	session_info: SessionBoundaryInfo,
+	/// Segments that were not handed out yet, keyed by the number of their lower block.
+	segments: BTreeMap<BlockNumber, Segment<B, I, J>>,
+	/// Anchors above all the segments, to be split into segments.
+	anchors: VecDeque<RangeAnchor<I, J>>,
+	next_lower: BlockId,
+	next_base: (BlockNumber, Option<J>),
+	throughput: HashMap<I, f64>,
+}
+
+impl<B, I, J> RangeDownloader<B, I, J>
+where
+	I: PeerId,
+	J: Justification,
+	B: Block<UnverifiedHeader = UnverifiedHeaderFor<J>>,
+{
+	pub fn new(session_info: SessionBoundaryInfo) -> Self {
+		RangeDownloader {
+			session_info,
+			segments: BTreeMap::new(),
+			anchors: VecDeque::new(),
+			next_lower: BlockId::new(Default::default(), 0),
+			next_base: (0, None),
+			throughput: HashMap::new(),
+		}
+	}
+
+	/// Whether there is anything left to download.
+	pub fn is_active(&self) -> bool {
+		!self.segments.is_empty() || !self.anchors.is_empty()
+	}
+
+	/// Whether any segments are being downloaded right now.
+	pub fn downloading(&self) -> bool {
+		self.segments.values().any(|segment| segment.in_flight_peer().is_some())
+	}
+
+	/// Plan downloading the blocks up to the provided anchors, or extend the current plan with
+	/// the ones above it. Segments that got finalized in the meantime are dropped.
+	pub fn start(&mut self, top_finalized: BlockId, anchors: Vec<RangeAnchor<I, J>>) {
+		let finalized_number = top_finalized.number();
+		self.segments
+			.retain(|_, segment| segment.target.id().number() > finalized_number);
+		if !self.is_active() || self.next_lower.number() < finalized_number {
+			self.anchors.clear();
+			self.next_lower = top_finalized;
+			self.next_base = (finalized_number, None);
+		}
+		let covered = self
+			.anchors
+			.back()
+			.map(|anchor| anchor.header.id().number())
+			.unwrap_or(self.next_lower.number());
+		self.anchors
+			.extend(anchors.into_iter().filter(|anchor| anchor.header.id().number() > covered));
+	}
+
+	fn segment_size(&self, peer: &I) -> u32 {
+		match self.throughput.get(peer) {
+			Some(throughput) => ((throughput * TARGET_SEGMENT_DURATION.as_secs_f64()) as u32)
+				.clamp(MIN_SEGMENT_SIZE, MAX_SEGMENT_SIZE),
+			None => INITIAL_SEGMENT_SIZE,
+		}
+	}
+
+	fn best_peer<'a, P: IntoIterator<Item = &'a I>>(&self, peers: P) -> Option<I>
+	where
+		I: 'a,
+	{
+		peers
+			.into_iter()
+			.max_by(|a, b| self.segment_size(a).cmp(&self.segment_size(b)))
+			.cloned()
+	}
+
+	/// Peers will only send blocks up to the end of the session after the one of the top
+	/// justification in our request.
+	fn upper_limit(&self, base: BlockNumber) -> BlockNumber {
+		let session = self.session_info.session_id_from_block_num(base);
+		self.session_info.last_block_of_session(SessionId(session.0 + 1))
+	}
+
+	fn cut_segment(&mut self, peer: &I) -> Option<Segment<B, I, J>> {
+		let limit = self.upper_limit(self.next_base.0);
+		let wanted = self.next_lower.number().saturating_add(self.segment_size(peer)).min(limit);
+		let mut candidates = HashSet::new();
+		let mut target = None;
+		let mut base = None;
+		while let Some(anchor) = self.anchors.front() {
+			let number = anchor.header.id().number();
+			if number > limit || (target.is_some() && number > wanted) {
+				break;
+			}
+			let anchor = self.anchors.pop_front().expect("we just checked it is there");
+			candidates.extend(anchor.know_most);
+			if let Some(justification) = anchor.justification {
+				base = Some((number, Some(justification)));
+			}
+			target = Some(anchor.header);
+		}
+		let target = match target {
+			Some(target) => target,
+			None => {
+				// Nothing we could base the request on would get us past the limit, so the plan
+				// cannot make progress. Drop it and let the blocks be requested one by one.
+				if let Some(anchor) = self.anchors.front() {
+					debug!(
+						target: LOG_TARGET,
+						"Range anchor {:?} is beyond the limit of {}, dropping the range plan.",
+						anchor.header.id(),
+						limit
+					);
+					self.anchors.clear();
+				}
+				return None;
+			},
+		};
+		let lower = std::mem::replace(&mut self.next_lower, target.id());
+		let segment_base = self.next_base.1.clone();
+		if let Some(base) = base {
+			self.next_base = base;
+		}
+		candidates.insert(peer.clone());
+		Some(Segment {
+			base: segment_base,
+			lower,
+			target,
+			candidates,
+			status: SegmentStatus::Pending,
+			justifications: Vec::new(),
+			blocks: BTreeMap::new(),
+		})
+	}
+
+	/// Requests that should be sent now, only to peers for which `available` returns true.
+	pub fn next_requests<F: Fn(&I) -> bool>(
+		&mut self,
+		now: Instant,
+		available: F,
+	) -> Vec<SegmentRequest<I, J>> {
+		let mut busy: HashSet<I> = self
+			.segments
+			.values()
+			.filter_map(|segment| segment.in_flight_peer())
+			.cloned()
+			.collect();
+		let mut requests = Vec::new();
+		// First retry the segments that failed before.
+		for segment in self.segments.values_mut() {
+			if busy.len() >= MAX_SEGMENTS_IN_FLIGHT {
+				return requests;
+			}
+			if !matches!(segment.status, SegmentStatus::Pending) {
+				continue;
+			}
+			let peer = segment
+				.candidates
+				.iter()
+				.filter(|peer| available(peer) && !busy.contains(peer))
+				.max_by_key(|peer| {
+					self.throughput.get(peer).map(|throughput| *throughput as u64).unwrap_or(0)
+				})
+				.cloned();
+			if let Some(peer) = peer {
+				segment.status = SegmentStatus::InFlight { peer: peer.clone(), sent: now };
+				busy.insert(peer.clone());
+				requests.push(segment.request(peer));
+			}
+		}
+		// Then cut new segments for the remaining peers.
+		while busy.len() < MAX_SEGMENTS_IN_FLIGHT && self.segments.len() < MAX_BUFFERED_SEGMENTS {
+			let peers: HashSet<_> = self
+				.anchors
+				.iter()
+				.flat_map(|anchor| anchor.know_most.iter())
+				.filter(|peer| available(peer) && !busy.contains(peer))
+				.cloned()
+				.collect();
+			let peer = match self.best_peer(&peers) {
+				Some(peer) => peer,
+				None => break,
+			};
+			let mut segment = match self.cut_segment(&peer) {
+				Some(segment) => segment,
+				None => break,
+			};
+			segment.status = SegmentStatus::InFlight { peer: peer.clone(), sent: now };
+			busy.insert(peer.clone());
+			requests.push(segment.request(peer));
+			self.segments.insert(segment.lower.number(), segment);
+		}
+		requests
+	}
+
+	/// Keep the items belonging to the segment requested from this peer, returning the rest.
+	pub fn handle_response(
+		&mut self,
+		peer: &I,
+		items: ResponseItems<B, J>,
+		now: Instant,
+	) -> ResponseItems<B, J> {
+		let segment = match self
+			.segments
+			.values_mut()
+			.find(|segment| segment.in_flight_peer() == Some(peer))
+		{
+			Some(segment) => segment,
+			None => return items,
+		};
+		let mut rest = Vec::new();
+		for item in items {
+			match item {
+				ResponseItem::Justification(justification)
+					if segment.contains(justification.header().id().number()) =>
+				{
+					segment.justifications.push(justification)
+				},
+				ResponseItem::Block(block) if segment.contains(block.header().id().number()) => {
+					segment.blocks.insert(block.header().id().number(), block);
+				},
+				item => rest.push(item),
+			}
+		}
+		if let SegmentStatus::InFlight { sent, .. } = segment.status {
+			if segment.downloaded() {
+				let elapsed = now.saturating_duration_since(sent).as_secs_f64().max(0.001);
+				let measured = segment.size() as f64 / elapsed;
+				self.throughput
+					.entry(peer.clone())
+					.and_modify(|throughput| {
+						*throughput = THROUGHPUT_SMOOTHING * measured
+							+ (1.0 - THROUGHPUT_SMOOTHING) * *throughput
+					})
+					.or_insert(measured);
+				segment.status = SegmentStatus::Complete { peer: peer.clone() };
+			}
+		}
+		rest
+	}
+
+	/// Returns the downloaded segments that can be handled, in order.
+	pub fn ready(&mut self) -> Vec<ReadySegment<B, I, J>> {
+		let mut ready = Vec::new();
+		while let Some(entry) = self.segments.first_entry() {
+			if !matches!(entry.get().status, SegmentStatus::Complete { .. }) {
+				break;
+			}
+			let segment = entry.remove();
+			let peer = match segment.status {
+				SegmentStatus::Complete { peer } => peer,
+				_ => unreachable!("we just checked the status"),
+			};
+			let mut items: ResponseItems<B, J> =
+				segment.justifications.into_iter().map(ResponseItem::Justification).collect();
+			items.extend(segment.blocks.into_values().map(ResponseItem::Block));
+			ready.push(ReadySegment { peer, previous: segment.lower, items });
+		}
+		ready
+	}
+
+	/// Put the segments that were not downloaded in time back in the queue, and slow down the
+	/// peers responsible, including the ones we have no measurements for yet.
+	pub fn maintain(&mut self, now: Instant) {
+		for segment in self.segments.values_mut() {
+			let peer = match &segment.status {
+				SegmentStatus::InFlight { peer, sent }
+					if now.saturating_duration_since(*sent) >= SEGMENT_TIMEOUT =>
+				{
+					peer.clone()
+				},
+				_ => continue,
+			};
+			debug!(
+				target: LOG_TARGET,
+				"Peer {:?} did not send segment up to {:?} in time.",
+				peer,
+				segment.target.id()
+			);
+			// A peer we know nothing about gets the segments of the initial size.
+			let initial = INITIAL_SEGMENT_SIZE as f64 / TARGET_SEGMENT_DURATION.as_secs_f64();
+			*self.throughput.entry(peer).or_insert(initial) /= 2.0;
+			segment.reset();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		collections::HashSet,
+		time::{Duration, Instant},
+	};
+
+	use super::{RangeDownloader, ReadySegment, INITIAL_SEGMENT_SIZE, SEGMENT_TIMEOUT};
+	use crate::{
+		block::{
+			mock::{MockBlock, MockHeader, MockJustification},
+			Header, Justification,
+		},
+		session::SessionBoundaryInfo,
+		sync::{data::ResponseItem, forest::RangeAnchor, MockPeerId},
+		SessionPeriod,
+	};
+
+	type MockDownloader = RangeDownloader<MockBlock, MockPeerId, MockJustification>;
+
+	const LENGTH: usize = 1000;
+
+	fn setup() -> (MockDownloader, Vec<MockHeader>) {
+		let genesis = MockHeader::genesis();
+		let mut branch = vec![genesis.clone()];
+		branch.extend(genesis.random_branch().take(LENGTH));
+		let mut downloader = RangeDownloader::new(SessionBoundaryInfo::new(SessionPeriod(400)));
+		let anchors = [399, 799, LENGTH]
+			.into_iter()
+			.map(|number| RangeAnchor {
+				header: branch[number].clone(),
+				justification: Some(MockJustification::for_header(branch[number].clone())),
+				know_most: HashSet::from([1, 2, 3]),
+			})
+			.collect();
+		downloader.start(genesis.id(), anchors);
+		(downloader, branch)
+	}
+
+	fn blocks(branch: &[MockHeader]) -> Vec<ResponseItem<MockBlock, MockJustification>> {
+		branch
+			.iter()
+			.map(|header| ResponseItem::Block(MockBlock::new(header.clone(), true)))
+			.collect()
+	}
+
+	#[test]
+	fn requests_segments_from_different_peers() {
+		let (mut downloader, _) = setup();
+		let requests = downloader.next_requests(Instant::now(), |_| true);
+		assert_eq!(requests.len(), 3);
+		let peers: HashSet<_> = requests.iter().map(|request| request.peer).collect();
+		assert_eq!(peers.len(), 3);
+		assert_eq!(requests[0].target.id().number(), 399);
+		assert!(requests[0].base.is_none());
+		assert_eq!(requests[1].lower.number(), 399);
+		assert_eq!(requests[1].base.as_ref().map(|base| base.header().id().number()), Some(399));
+		assert!(downloader.next_requests(Instant::now(), |_| true).is_empty());
+	}
+
+	#[test]
+	fn releases_segments_in_order() {
+		let (mut downloader, branch) = setup();
+		let now = Instant::now();
+		let requests = downloader.next_requests(now, |_| true);
+		let first = &requests[0];
+		let second = &requests[1];
+		let rest = downloader.handle_response(&second.peer, blocks(&branch[400..=799]), now);
+		assert!(rest.is_empty());
+		assert!(downloader.ready().is_empty());
+		let rest = downloader.handle_response(&first.peer, blocks(&branch[1..=400]), now);
+		assert_eq!(rest.len(), 1);
+		let ready = downloader.ready();
+		assert_eq!(ready.len(), 2);
+		let ReadySegment { peer, previous, items } = &ready[1];
+		assert_eq!(*peer, second.peer);
+		assert_eq!(*previous, branch[399].id());
+		assert_eq!(items.len(), 400);
+		assert!(downloader.is_active());
+	}
+
+	#[test]
+	fn retries_timed_out_segments() {
+		let (mut downloader, _) = setup();
+		let now = Instant::now();
+		let requests = downloader.next_requests(now, |peer| *peer == 1);
+		assert_eq!(requests.len(), 1);
+		downloader.maintain(now + SEGMENT_TIMEOUT);
+		let retried = downloader.next_requests(now + SEGMENT_TIMEOUT, |peer| *peer == 2);
+		assert_eq!(retried.len(), 1);
+		assert_eq!(retried[0].peer, 2);
+		assert_eq!(retried[0].target.id(), requests[0].target.id());
+	}
+
+	#[test]
+	fn adapts_segment_size_to_throughput() {
+		let (mut downloader, branch) = setup();
+		let now = Instant::now();
+		assert_eq!(downloader.segment_size(&1), INITIAL_SEGMENT_SIZE);
+		let requests = downloader.next_requests(now, |peer| *peer == 1);
+		downloader.handle_response(&1, blocks(&branch[1..=399]), now + Duration::from_secs(1));
+		assert_eq!(requests[0].peer, 1);
+		assert!(downloader.segment_size(&1) > INITIAL_SEGMENT_SIZE);
+	}
+
+	#[test]
+	fn shrinks_segments_of_unmeasured_peers_that_time_out() {
+		let (mut downloader, _) = setup();
+		let now = Instant::now();
+		downloader.next_requests(now, |peer| *peer == 1);
+		downloader.maintain(now + SEGMENT_TIMEOUT);
+		assert!(downloader.segment_size(&1) < INITIAL_SEGMENT_SIZE);
+		assert_eq!(downloader.segment_size(&2), INITIAL_SEGMENT_SIZE);
+	}
+
+	#[test]
+	fn drops_plan_beyond_what_peers_would_send() {
+		let genesis = MockHeader::genesis();
+		let branch: Vec<_> = genesis.random_branch().take(LENGTH).collect();
+		let top = branch[LENGTH - 1].clone();
+		let mut downloader: MockDownloader =
+			RangeDownloader::new(SessionBoundaryInfo::new(SessionPeriod(400)));
+		downloader.start(
+			genesis.id(),
+			vec![RangeAnchor {
+				header: top.clone(),
+				justification: Some(MockJustification::for_header(top)),
+				know_most: HashSet::from([1]),
+			}],
+		);
+		assert!(downloader.next_requests(Instant::now(), |_| true).is_empty());
+		assert!(!downloader.is_active());
+	}
+}
@@ -0,0 +1,377 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fmt::Display,
+	fs,
+	io::{Error as IoError, Write},
+	path::PathBuf,
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+use log::{debug, warn};
+use rand::seq::SliceRandom;
+use static_assertions::const_assert;
+
+use crate::sync::{PeerId, LOG_TARGET};
+
+/// The score every unknown peer starts with.
+const INITIAL_SCORE: i32 = 0;
+const MAX_SCORE: i32 = 1000;
+const MIN_SCORE: i32 = -1000;
+/// Peers whose score drops to or below this value get banned.
+const BAN_THRESHOLD: i32 = -300;
+/// The score a peer gets after their ban expires, so that they have to behave for a while
+/// before they are trusted again, but a single mistake does not get them banned immediately.
+const SCORE_AFTER_BAN: i32 = BAN_THRESHOLD / 2;
+const BAN_DURATION: Duration = Duration::from_secs(10 * 60);
+/// How long we wait for any response to a request before considering it unanswered.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Every time the reputation is maintained, scores move this much towards the initial score.
+const SCORE_DECAY: i32 = 2;
+/// Peers might legitimately not answer, e.g. when they lost the data in the meantime. At most
+/// one unanswered request per peer is penalized every time the reputation is maintained, so
+/// with the decay at least as big as the penalty silence alone never gets a peer banned, it
+/// only makes us prefer other peers.
+const UNANSWERED_REQUEST_PENALTY: i32 = 2;
+const_assert!(SCORE_DECAY >= UNANSWERED_REQUEST_PENALTY);
+
+/// Something a peer did that affects their reputation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReputationChange {
+	/// The peer sent us data that failed verification.
+	InvalidData,
+	/// The peer did not respond in time to a request for data they claimed to have.
+	UnansweredRequest,
+	/// The peer sent us data that was new to us.
+	UsefulResponse,
+}
+
+impl ReputationChange {
+	fn value(&self) -> i32 {
+		use ReputationChange::*;
+		match self {
+			InvalidData => -100,
+			UnansweredRequest => -UNANSWERED_REQUEST_PENALTY,
+			UsefulResponse => 5,
+		}
+	}
+}
+
+/// Keeps track of how useful peers are for block sync, bans misbehaving ones and prefers the
+/// well behaved ones when choosing whom to send requests to.
+/// The scores can be persisted, so that they survive node restarts.
+pub struct Reputation<PI: PeerId> {
+	scores: HashMap<PI, i32>,
+	bans: HashMap<PI, Instant>,
+	pending_requests: HashMap<PI, Instant>,
+	path: Option<PathBuf>,
+}
+
+impl<PI: PeerId> Reputation<PI> {
+	/// A reputation that is not persisted anywhere.
+	pub fn new() -> Self {
+		Reputation {
+			scores: HashMap::new(),
+			bans: HashMap::new(),
+			pending_requests: HashMap::new(),
+			path: None,
+		}
+	}
+
+	/// The current score of the peer.
+	pub fn score(&self, peer: &PI) -> i32 {
+		self.scores.get(peer).copied().unwrap_or(INITIAL_SCORE)
+	}
+
+	/// All the peers we have a score for, with their scores.
+	pub fn scores(&self) -> impl Iterator<Item = (&PI, i32)> {
+		self.scores.iter().map(|(peer, score)| (peer, *score))
+	}
+
+	/// The number of currently banned peers.
+	pub fn banned_count(&self) -> usize {
+		self.bans.len()
+	}
+
+	/// Whether the peer is banned at the given time.
+	pub fn is_banned(&self, peer: &PI, now: Instant) -> bool {
+		self.bans.get(peer).map(|until| *until > now).unwrap_or(false)
+	}
+
+	/// Adjust the score of the peer, returns whether this resulted in the peer getting banned.
+	pub fn report(&mut self, peer: &PI, change: ReputationChange, now: Instant) -> bool {
+		let score = self.scores.entry(peer.clone()).or_insert(INITIAL_SCORE);
+		*score = score.saturating_add(change.value()).clamp(MIN_SCORE, MAX_SCORE);
+		if *score > BAN_THRESHOLD || self.bans.contains_key(peer) {
+			return false;
+		}
+		debug!(
+			target: LOG_TARGET,
+			"Banning peer {:?} with score {} for {:?}.", peer, score, BAN_DURATION
+		);
+		self.bans.insert(peer.clone(), now + BAN_DURATION);
+		self.pending_requests.remove(peer);
+		true
+	}
+
+	/// Notes that we sent a request to the peer and expect some response, because they claimed
+	/// to have the data we asked for.
+	pub fn request_sent(&mut self, peer: PI, now: Instant) {
+		self.pending_requests.entry(peer).or_insert(now + REQUEST_TIMEOUT);
+	}
+
+	/// Notes that the peer responded to our requests.
+	pub fn response_received(&mut self, peer: &PI) {
+		self.pending_requests.remove(peer);
+	}
+
+	/// Choose a peer to send a request to from among the candidates. Banned peers are never
+	/// chosen, and the choice is made randomly from among the better half of the rest, so that
+	/// well behaved peers are favoured without completely starving the others.
+	/// Returns `None` if there are no acceptable candidates.
+	pub fn choose_peer(&self, candidates: HashSet<PI>, now: Instant) -> Option<PI> {
+		let mut candidates: Vec<_> = candidates
+			.into_iter()
+			.filter(|peer| !self.is_banned(peer, now))
+			.map(|peer| (self.score(&peer), peer))
+			.collect();
+		candidates.sort_by_key(|(score, _)| -score);
+		let best = (candidates.len() + 1) / 2;
+		candidates
+			.get(..best)
+			.and_then(|best| best.choose(&mut rand::thread_rng()))
+			.map(|(_, peer)| peer.clone())
+	}
+
+	/// Slowly let scores return to neutral, penalize connected peers that did not answer
+	/// requests in time and lift expired bans. Disconnected peers are not expected to answer, and
+	/// once their score is back to neutral they are forgotten. Should be called periodically.
+	/// Returns the number of peers that got banned.
+	pub fn maintain(&mut self, now: Instant, connected: &HashSet<PI>) -> usize {
+		// Decay first, so that the penalties for unanswered requests are still visible when
+		// choosing peers until the next maintenance.
+		for score in self.scores.values_mut() {
+			let distance = *score - INITIAL_SCORE;
+			*score -= distance.signum() * distance.abs().min(SCORE_DECAY);
+		}
+		self.pending_requests.retain(|peer, _| connected.contains(peer));
+		let unanswered: Vec<_> = self
+			.pending_requests
+			.iter()
+			.filter(|(_, deadline)| **deadline <= now)
+			.map(|(peer, _)| peer.clone())
+			.collect();
+		let mut new_bans = 0;
+		for peer in unanswered {
+			self.pending_requests.remove(&peer);
+			if self.report(&peer, ReputationChange::UnansweredRequest, now) {
+				new_bans += 1;
+			}
+		}
+		let expired: Vec<_> = self
+			.bans
+			.iter()
+			.filter(|(_, until)| **until <= now)
+			.map(|(peer, _)| peer.clone())
+			.collect();
+		for peer in expired {
+			debug!(target: LOG_TARGET, "Ban of peer {:?} expired.", peer);
+			self.bans.remove(&peer);
+			self.scores.insert(peer, SCORE_AFTER_BAN);
+		}
+		let bans = &self.bans;
+		self.scores.retain(|peer, score| {
+			*score != INITIAL_SCORE || bans.contains_key(peer) || connected.contains(peer)
+		});
+		new_bans
+	}
+}
+
+impl<PI: PeerId> Default for Reputation<PI> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<PI: PeerId + Display + FromStr> Reputation<PI> {
+	/// A reputation persisted in the given file. If the file already contains scores, they
+	/// are loaded and peers with scores low enough get banned again.
+	pub fn with_persistence(path: PathBuf) -> Self {
+		let mut reputation = Self::new();
+		match fs::read_to_string(&path) {
+			Ok(contents) => {
+				let now = Instant::now();
+				for (peer, score) in contents.lines().filter_map(parse_line::<PI>) {
+					reputation.scores.insert(peer.clone(), score.clamp(MIN_SCORE, MAX_SCORE));
+					if score <= BAN_THRESHOLD {
+						reputation.bans.insert(peer, now + BAN_DURATION);
+					}
+				}
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+			Err(e) => warn!(
+				target: LOG_TARGET,
+				"Failed to load peer reputation from {:?}: {}.", path, e
+			),
+		}
+		reputation.path = Some(path);
+		reputation
+	}
+
+	/// Write the scores to the persistence file, if any, skipping the neutral ones. The file is
+	/// replaced atomically, so a crash in the middle of saving does not lose the previous scores.
+	pub fn save(&self) -> Result<(), IoError> {
+		let path = match &self.path {
+			Some(path) => path,
+			None => return Ok(()),
+		};
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let tmp_path = path.with_extension("tmp");
+		let mut file = fs::File::create(&tmp_path)?;
+		for (peer, score) in self.scores().filter(|(_, score)| *score != INITIAL_SCORE) {
+			writeln!(file, "{peer} {score}")?;
+		}
+		file.sync_all()?;
+		fs::rename(tmp_path, path)
+	}
+}
+
+fn parse_line<PI: FromStr>(line: &str) -> Option<(PI, i32)> {
+	let (peer, score) = line.trim().split_once(' ')?;
+	Some((peer.parse().ok()?, score.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		collections::HashSet,
+		time::{Duration, Instant},
+	};
+
+	use tempfile::TempDir;
+
+	use super::{
+		Reputation, ReputationChange, BAN_DURATION, INITIAL_SCORE, REQUEST_TIMEOUT, SCORE_AFTER_BAN,
+	};
+	use crate::sync::MockPeerId;
+
+	fn ban(reputation: &mut Reputation<MockPeerId>, peer: MockPeerId, now: Instant) {
+		while !reputation.report(&peer, ReputationChange::InvalidData, now) {}
+	}
+
+	#[test]
+	fn useful_responses_increase_score() {
+		let mut reputation = Reputation::new();
+		let now = Instant::now();
+		reputation.report(&1, ReputationChange::UsefulResponse, now);
+		assert!(reputation.score(&1) > INITIAL_SCORE);
+		assert_eq!(reputation.score(&2), INITIAL_SCORE);
+	}
+
+	#[test]
+	fn bans_misbehaving_peers_temporarily() {
+		let mut reputation = Reputation::new();
+		let now = Instant::now();
+		ban(&mut reputation, 1, now);
+		assert!(reputation.is_banned(&1, now));
+		assert!(!reputation.is_banned(&2, now));
+		assert_eq!(reputation.banned_count(), 1);
+		let later = now + BAN_DURATION;
+		reputation.maintain(later, &HashSet::from([1]));
+		assert!(!reputation.is_banned(&1, later));
+		assert_eq!(reputation.banned_count(), 0);
+		assert!(reputation.score(&1) >= SCORE_AFTER_BAN);
+	}
+
+	#[test]
+	fn never_chooses_banned_peers() {
+		let mut reputation = Reputation::new();
+		let now = Instant::now();
+		ban(&mut reputation, 1, now);
+		assert_eq!(reputation.choose_peer(HashSet::from([1]), now), None);
+		for _ in 0..20 {
+			assert_eq!(reputation.choose_peer(HashSet::from([1, 2]), now), Some(2));
+		}
+	}
+
+	#[test]
+	fn prefers_better_peers() {
+		let mut reputation = Reputation::new();
+		let now = Instant::now();
+		reputation.report(&1, ReputationChange::UsefulResponse, now);
+		reputation.report(&2, ReputationChange::UnansweredRequest, now);
+		for _ in 0..20 {
+			assert_eq!(reputation.choose_peer(HashSet::from([1, 2]), now), Some(1));
+		}
+	}
+
+	#[test]
+	fn penalizes_unanswered_requests() {
+		let mut reputation = Reputation::new();
+		let now = Instant::now();
+		reputation.request_sent(1, now);
+		reputation.request_sent(2, now);
+		reputation.response_received(&2);
+		reputation.maintain(now + REQUEST_TIMEOUT, &HashSet::from([1, 2]));
+		assert!(reputation.score(&1) < INITIAL_SCORE);
+		assert_eq!(reputation.score(&2), INITIAL_SCORE);
+	}
+
+	#[test]
+	fn silent_peers_are_not_banned() {
+		let mut reputation = Reputation::new();
+		let mut now = Instant::now();
+		for _ in 0..1000 {
+			reputation.request_sent(1, now);
+			now += REQUEST_TIMEOUT;
+			assert_eq!(reputation.maintain(now, &HashSet::from([1])), 0);
+		}
+		assert!(!reputation.is_banned(&1, now));
+		assert!(reputation.score(&1) < INITIAL_SCORE);
+		assert!(reputation.score(&1) > SCORE_AFTER_BAN);
+	}
+
+	#[test]
+	fn scores_decay_towards_neutral() {
+		let mut reputation = Reputation::new();
+		let now = Instant::now();
+		reputation.report(&1, ReputationChange::UsefulResponse, now);
+		let score = reputation.score(&1);
+		reputation.maintain(now + Duration::from_secs(1), &HashSet::from([1]));
+		assert!(reputation.score(&1) < score);
+	}
+
+	#[test]
+	fn forgets_disconnected_peers() {
+		let mut reputation = Reputation::new();
+		let mut now = Instant::now();
+		reputation.report(&1, ReputationChange::UsefulResponse, now);
+		reputation.report(&2, ReputationChange::UsefulResponse, now);
+		reputation.request_sent(3, now);
+		let connected = HashSet::from([2]);
+		for _ in 0..10 {
+			now += REQUEST_TIMEOUT;
+			reputation.maintain(now, &connected);
+		}
+		assert_eq!(reputation.score(&3), INITIAL_SCORE);
+		let peers: HashSet<_> = reputation.scores().map(|(peer, _)| *peer).collect();
+		assert_eq!(peers, connected);
+	}
+
+	#[test]
+	fn persists_scores_and_bans() {
+		let dir = TempDir::new().expect("creating a temporary directory should work");
+		let path = dir.path().join("sync-reputation");
+		let now = Instant::now();
+		let mut reputation = Reputation::<MockPeerId>::with_persistence(path.clone());
+		ban(&mut reputation, 1, now);
+		reputation.report(&2, ReputationChange::UsefulResponse, now);
+		reputation.save().expect("saving should work");
+
+		let reputation = Reputation::<MockPeerId>::with_persistence(path.clone());
+		assert_eq!(reputation.score(&2), ReputationChange::UsefulResponse.value());
+		assert!(reputation.is_banned(&1, Instant::now()));
+	}
+}
@@ -1,4 +1,10 @@
-use std::{collections::HashSet, fmt::Display, time::Duration};
+use std::{
+	collections::HashSet,
+	fmt::Display,
+	path::PathBuf,
+	str::FromStr,
+	time::{Duration, Instant},
+};
 
 use futures::{
 	channel::{mpsc, oneshot},
@@ -13,20 +19,22 @@ use crate::{
 	block::{
 		Block, BlockImport, ChainStatus, ChainStatusNotification, ChainStatusNotifier,
 		EquivocationProof, Finalizer, Header, HeaderVerifier, Justification, JustificationVerifier,
-		UnverifiedHeader, UnverifiedHeaderFor,
+		UnverifiedHeader, UnverifiedHeaderFor, UnverifiedJustification,
 	},
 	metrics::SloMetrics,
 	network::GossipNetwork,
 	session::SessionBoundaryInfo,
 	sync::{
 		data::{
-			NetworkData, PreRequest, Request, ResponseItem, ResponseItems, State, VersionWrapper,
-			VersionedNetworkData,
+			BranchKnowledge, NetworkData, PreRequest, Request, ResponseItem, ResponseItems, State,
+			VersionWrapper, VersionedNetworkData,
 		},
 		forest::ExtensionRequest,
 		handler::{Action, DatabaseIO, Error as HandlerError, HandleStateAction, Handler},
 		message_limiter::{Error as MsgLimiterError, MsgLimiter},
 		metrics::{Event, Metrics},
+		range::{RangeDownloader, ReadySegment, SegmentRequest},
+		reputation::{Reputation, ReputationChange},
 		task_queue::TaskQueue,
 		tasks::{Action as TaskAction, RequestTask},
 		ticker::Ticker,
@@ -38,6 +46,9 @@ use crate::{
 const BROADCAST_COOLDOWN: Duration = Duration::from_millis(600);
 const CHAIN_EXTENSION_COOLDOWN: Duration = Duration::from_millis(300);
 const TICK_PERIOD: Duration = Duration::from_secs(5);
+/// How far behind in finalization we have to be to start downloading ranges of blocks from
+/// multiple peers in parallel.
+const RANGE_DOWNLOAD_THRESHOLD: u32 = 512;
 
 pub struct IO<B, J, N, CE, CS, F, BI>
 where
@@ -146,6 +157,8 @@ where
 	metrics: Metrics,
 	slo_metrics: SloMetrics,
 	favourite_block_request: mpsc::UnboundedReceiver<oneshot::Sender<J::Header>>,
+	reputation: Reputation<N::PeerId>,
+	range_downloader: RangeDownloader<B, N::PeerId, J>,
 }
 
 impl<J: Justification> JustificationSubmissions<J> for mpsc::UnboundedSender<J::Unverified> {
@@ -174,9 +187,12 @@ where
 	V: JustificationVerifier<J> + HeaderVerifier<J::Header>,
 	F: Finalizer<J>,
 	BI: BlockImport<B>,
+	N::PeerId: Display + FromStr,
 {
 	/// Create a new service using the provided network for communication.
 	/// Also returns an interface for requesting blocks.
+	/// If `reputation_path` is provided, peer reputation is persisted there.
+	#[allow(clippy::too_many_arguments)]
 	pub fn new(
 		verifier: V,
 		session_info: SessionBoundaryInfo,
@@ -184,6 +200,8 @@ where
 		metrics_registry: Option<Registry>,
 		slo_metrics: SloMetrics,
 		favourite_block_request: mpsc::UnboundedReceiver<oneshot::Sender<J::Header>>,
+		reputation_path: Option<PathBuf>,
+		forest_max_depth: u32,
 	) -> Result<(Self, impl RequestBlocks<B::UnverifiedHeader>), HandlerError<B, J, CS, V, F>> {
 		let IO {
 			network,
@@ -194,7 +212,9 @@ where
 			database_io,
 		} = io;
 		let network = VersionWrapper::new(network);
-		let handler = Handler::new(database_io, verifier, sync_oracle, session_info)?;
+		let range_downloader = RangeDownloader::new(session_info.clone());
+		let handler =
+			Handler::new(database_io, verifier, sync_oracle, session_info, forest_max_depth)?;
 		let tasks = TaskQueue::new();
 		let broadcast_ticker = Ticker::new(TICK_PERIOD, BROADCAST_COOLDOWN);
 		let chain_extension_ticker = Ticker::new(TICK_PERIOD, CHAIN_EXTENSION_COOLDOWN);
@@ -203,6 +223,10 @@ where
 			warn!(target: LOG_TARGET, "Failed to create metrics: {}.", e);
 			Metrics::noop()
 		});
+		let reputation = match reputation_path {
+			Some(path) => Reputation::with_persistence(path),
+			None => Reputation::new(),
+		};
 
 		Ok((
 			Service {
@@ -219,6 +243,8 @@ where
 				metrics,
 				slo_metrics,
 				favourite_block_request,
+				reputation,
+				range_downloader,
 			},
 			block_requests_for_sync,
 		))
@@ -268,7 +294,7 @@ where
 				return;
 			},
 		};
-		match self.network.send_to_random(data, know_most) {
+		match self.send_to_reputable(data, know_most) {
 			Ok(()) => self.chain_extension_ticker.reset(),
 			Err(e) => {
 				self.metrics.report_event_error(Event::SendExtensionRequest);
@@ -285,11 +311,14 @@ where
 		match self.handler.extension_request() {
 			FavouriteBlock { know_most } => self.request_favourite_extension(know_most),
 			HighestJustified { header, know_most, branch_knowledge } => {
-				self.send_request(PreRequest::new(
-					header.into_unverified(),
-					branch_knowledge,
-					know_most,
-				));
+				let far_behind = self.handler.behind_finalization() >= RANGE_DOWNLOAD_THRESHOLD;
+				if !(far_behind && self.request_ranges()) {
+					self.send_request(PreRequest::new(
+						header.into_unverified(),
+						branch_knowledge,
+						know_most,
+					));
+				}
 				self.chain_extension_ticker.reset();
 			},
 			Noop => {
@@ -300,6 +329,62 @@ where
 		}
 	}
 
+	/// Request segments of the finalized branch from multiple peers, returns whether any
+	/// segments are being downloaded.
+	fn request_ranges(&mut self) -> bool {
+		self.metrics.report_event(Event::SendRangeRequest);
+		let state = match self.handler.state() {
+			Ok(state) => state,
+			Err(e) => {
+				self.metrics.report_event_error(Event::SendRangeRequest);
+				warn!(
+					target: LOG_TARGET,
+					"Failed to construct own knowledge state: {}.", e
+				);
+				return false;
+			},
+		};
+		let now = Instant::now();
+		self.range_downloader.maintain(now);
+		self.range_downloader
+			.start(state.top_justification().header().id(), self.handler.range_anchors());
+		let reputation = &self.reputation;
+		let requests = self
+			.range_downloader
+			.next_requests(now, |peer| !reputation.is_banned(peer, now));
+		for SegmentRequest { peer, base, lower, target } in requests {
+			let state = match base {
+				Some(base) => {
+					let favourite_block = base.header().clone().into_unverified();
+					State::new(base.into_unverified(), favourite_block)
+				},
+				None => state.clone(),
+			};
+			let request =
+				Request::new(target.into_unverified(), BranchKnowledge::TopImported(lower), state);
+			trace!(
+				target: LOG_TARGET,
+				"Sending a range request to {:?}: {:?}", peer, request
+			);
+			self.reputation.request_sent(peer.clone(), now);
+			self.send_to(NetworkData::Request(request), peer);
+		}
+		self.range_downloader.downloading()
+	}
+
+	fn handle_ready_segments(&mut self) {
+		let ready = self.range_downloader.ready();
+		if ready.is_empty() {
+			return;
+		}
+		for ReadySegment { peer, previous, items } in ready {
+			self.process_response(items, peer, Some(previous));
+		}
+		if self.range_downloader.is_active() {
+			self.request_ranges();
+		}
+	}
+
 	fn try_request_chain_extension(&mut self) {
 		if self.chain_extension_ticker.try_tick() {
 			self.request_chain_extension(false);
@@ -323,12 +408,80 @@ where
 		trace!(target: LOG_TARGET, "Sending a request: {:?}", request);
 		let data = NetworkData::Request(request);
 
-		if let Err(e) = self.network.send_to_random(data, peers) {
+		if let Err(e) = self.send_to_reputable(data, peers) {
 			self.metrics.report_event_error(Event::SendRequest);
 			warn!(target: LOG_TARGET, "Error sending request: {}.", e);
 		}
 	}
 
+	/// Sends the request like `send_to_random` does, to one of the given peers we are connected
+	/// to or, if there are none, to any connected peer, except that banned peers are never
+	/// chosen and the more reputable ones are favoured. The chosen peer is expected to answer.
+	fn send_to_reputable(
+		&mut self,
+		data: NetworkData<B, J>,
+		peers: HashSet<N::PeerId>,
+	) -> Result<(), N::Error> {
+		let now = Instant::now();
+		let connected = self.network.connected_peers();
+		let candidates = peers.intersection(&connected).cloned().collect();
+		let peer = self
+			.reputation
+			.choose_peer(candidates, now)
+			.or_else(|| self.reputation.choose_peer(connected, now));
+		match peer {
+			Some(peer) => {
+				self.reputation.request_sent(peer.clone(), now);
+				self.network.send_to(data, peer)
+			},
+			None => {
+				debug!(
+					target: LOG_TARGET,
+					"No acceptable connected peer to send a request to."
+				);
+				Ok(())
+			},
+		}
+	}
+
+	fn report_peer(&mut self, peer: &N::PeerId, change: ReputationChange) {
+		if self.reputation.report(peer, change, Instant::now()) {
+			info!(
+				target: LOG_TARGET,
+				"Banned misbehaving sync peer {}.", peer
+			);
+			self.metrics.report_bans(1);
+		}
+	}
+
+	fn report_handler_error(&mut self, error: &HandlerError<B, J, CS, V, F>, peer: &N::PeerId) {
+		if matches!(error, HandlerError::JustificationVerifier(_) | HandlerError::HeaderVerifier(_))
+		{
+			self.report_peer(peer, ReputationChange::InvalidData);
+		}
+	}
+
+	fn report_response(&mut self, new_info: bool, peer: &N::PeerId) {
+		self.reputation.response_received(peer);
+		if new_info {
+			self.report_peer(peer, ReputationChange::UsefulResponse);
+		}
+	}
+
+	fn maintain_reputation(&mut self) {
+		let connected = self.network.connected_peers();
+		let new_bans = self.reputation.maintain(Instant::now(), &connected);
+		self.metrics.report_bans(new_bans);
+		// Only connected peers are reported, so that disconnected peers disappear from metrics.
+		self.metrics.report_reputation(
+			self.reputation.scores().filter(|(peer, _)| connected.contains(*peer)),
+			self.reputation.banned_count(),
+		);
+		if let Err(e) = self.reputation.save() {
+			warn!(target: LOG_TARGET, "Failed to save peer reputation: {}.", e);
+		}
+	}
+
 	fn send_to(&mut self, data: NetworkData<B, J>, peer: N::PeerId) {
 		self.metrics.report_event(Event::SendTo);
 		trace!(
@@ -372,6 +525,7 @@ where
 			},
 			Err(e) => {
 				self.metrics.report_event_error(Event::HandleState);
+				self.report_handler_error(&e, &peer);
 				match e {
 					HandlerError::JustificationVerifier(e) => debug!(
 						target: LOG_TARGET,
@@ -407,6 +561,10 @@ where
 		let (new_info, maybe_error) =
 			self.handler
 				.handle_state_response(justification, maybe_justification, peer.clone());
+		if let Some(e) = &maybe_error {
+			self.report_handler_error(e, &peer);
+		}
+		self.report_response(new_info, &peer);
 		match maybe_error {
 			Some(HandlerError::JustificationVerifier(e)) => debug!(
 				target: LOG_TARGET,
@@ -462,6 +620,21 @@ where
 	}
 
 	fn handle_request_response(&mut self, response_items: ResponseItems<B, J>, peer: N::PeerId) {
+		let response_items =
+			self.range_downloader.handle_response(&peer, response_items, Instant::now());
+		self.handle_ready_segments();
+		match response_items.is_empty() {
+			true => self.reputation.response_received(&peer),
+			false => self.process_response(response_items, peer, None),
+		}
+	}
+
+	fn process_response(
+		&mut self,
+		response_items: ResponseItems<B, J>,
+		peer: N::PeerId,
+		previous: Option<BlockId>,
+	) {
 		trace!(
 			target: LOG_TARGET,
 			"Handling request response from peer {:?}. Items: {:?}.",
@@ -469,8 +642,16 @@ where
 			response_items,
 		);
 		self.metrics.report_event(Event::HandleRequestResponse);
-		let (new_info, equivocation_proofs, maybe_error) =
-			self.handler.handle_request_response(response_items, peer.clone());
+		let (new_info, equivocation_proofs, maybe_error) = match previous {
+			Some(previous) => {
+				self.handler.handle_range_response(response_items, peer.clone(), previous)
+			},
+			None => self.handler.handle_request_response(response_items, peer.clone()),
+		};
+		if let Some(e) = &maybe_error {
+			self.report_handler_error(e, &peer);
+		}
+		self.report_response(new_info, &peer);
 		match maybe_error {
 			Some(HandlerError::JustificationVerifier(e)) => {
 				debug!(
@@ -544,6 +725,7 @@ where
 			},
 			Err(e) => {
 				self.metrics.report_event_error(Event::HandleRequest);
+				self.report_handler_error(&e, &peer);
 				match e {
 					HandlerError::JustificationVerifier(e) => debug!(
 						target: LOG_TARGET,
@@ -666,6 +848,7 @@ where
 			Ok(Action::Noop) => {},
 			Err(e) => {
 				self.metrics.report_event_error(Event::HandleExtensionRequest);
+				self.report_handler_error(&e, &peer);
 				match e {
 					HandlerError::JustificationVerifier(e) => debug!(
 						target: LOG_TARGET,
@@ -686,6 +869,14 @@ where
 
 	fn handle_network_data(&mut self, data: NetworkData<B, J>, peer: N::PeerId) {
 		use NetworkData::*;
+		if self.reputation.is_banned(&peer, Instant::now()) {
+			trace!(
+				target: LOG_TARGET,
+				"Ignoring data from banned peer {:?}.",
+				peer
+			);
+			return;
+		}
 		match data {
 			StateBroadcast(state) => self.handle_state(state, peer),
 			StateBroadcastResponse(justification, maybe_justification) => {
@@ -788,7 +979,10 @@ where
 				}
 
 				_ = status_ticker.tick() => {
-					info!(target: LOG_TARGET, "{}", self.handler.status());
+					let status = self.handler.status();
+					self.metrics.report_forest_vertices(status.vertices());
+					info!(target: LOG_TARGET, "{}", status);
+					self.maintain_reputation();
 				},
 			}
 		}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use crate::{
+	block::UnverifiedHeader,
+	data_io::{AlephData, DataProvider},
+};
+
+/// How a node deviates from the protocol, for testing that the rest of the committee stays live
+/// and safe with up to a third of the nodes being faulty.
+///
+/// The data provider and the AlephBFT network of a node can be made to misbehave by wrapping
+/// them in `ByzantineDataProvider` and `ByzantineNetwork` respectively.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Behaviour<UH: UnverifiedHeader> {
+	/// Follows the protocol.
+	Honest,
+	/// Never proposes any data, and never lets its units reach other nodes.
+	WithholdUnits,
+	/// Proposes the given data every other time, typically a branch conflicting with the one
+	/// proposed by honest nodes.
+	ProposeConflicting(AlephData<UH>),
+	/// Holds back every message it sends, and thus every signature in it, for the given time.
+	DelaySignatures(Duration),
+	/// Proposes the honest head, but claims the blocks below it have hashes nobody knows.
+	ReferenceUnknownBlocks,
+}
+
+impl<UH: UnverifiedHeader> Default for Behaviour<UH> {
+	fn default() -> Self {
+		Behaviour::Honest
+	}
+}
+
+/// Wraps the data provider of an AlephBFT member, making it propose data according to the
+/// behaviour.
+pub struct ByzantineDataProvider<UH: UnverifiedHeader> {
+	inner: DataProvider<UH>,
+	behaviour: Behaviour<UH>,
+	held_back: Option<AlephData<UH>>,
+	conflicting_next: bool,
+}
+
+impl<UH: UnverifiedHeader> ByzantineDataProvider<UH> {
+	pub fn new(inner: DataProvider<UH>, behaviour: Behaviour<UH>) -> Self {
+		ByzantineDataProvider { inner, behaviour, held_back: None, conflicting_next: true }
+	}
+
+	pub async fn get_data(&mut self) -> Option<AlephData<UH>> {
+		// Prefer fresh data, and only fall back to the proposal held back for a conflicting one.
+		let honest = self.inner.get_data().await.or(self.held_back.take());
+		use Behaviour::*;
+		match &self.behaviour {
+			Honest | DelaySignatures(_) => honest,
+			WithholdUnits => None,
+			ProposeConflicting(data) => {
+				let conflicting = self.conflicting_next;
+				self.conflicting_next = !conflicting;
+				match conflicting {
+					true => {
+						self.held_back = honest;
+						Some(data.clone())
+					},
+					false => honest,
+				}
+			},
+			ReferenceUnknownBlocks => honest
+				.map(|data| AlephData { head_proposal: data.head_proposal.with_unknown_tail() }),
+		}
+	}
+}
@@ -14,7 +14,10 @@ use selendra_primitives::BlockNumber;
 
 use crate::{
 	block::{Block, Header},
-	data_io::{AlephData, AlephNetworkMessage, DataStore, DataStoreConfig, MAX_DATA_BRANCH_LEN},
+	data_io::{
+		AlephData, AlephNetworkMessage, DataStore, DataStoreConfig, DataStoreMetrics,
+		MAX_DATA_BRANCH_LEN,
+	},
 	network::{
 		data::{component::Network as ComponentNetwork, Network as DataNetwork},
 		Data,
@@ -156,6 +159,7 @@ fn prepare_data_store(
 		available_proposals_cache_capacity: NonZeroUsize::new(8000).unwrap(),
 		periodic_maintenance_interval: Duration::from_millis(20),
 		request_block_after: Duration::from_millis(30),
+		chain_info_cache: Default::default(),
 	};
 
 	let session_boundaries = if let Some(session_boundaries) = session_boundaries {
@@ -170,6 +174,7 @@ fn prepare_data_store(
 		TestVerifier,
 		block_requester,
 		data_store_config,
+		DataStoreMetrics::noop(),
 		test_network,
 	);
 
@@ -417,6 +422,27 @@ async fn sends_block_request_on_missing_block() {
 	.await;
 }
 
+#[tokio::test]
+async fn proposal_referencing_unknown_blocks_is_held_until_hopeless() {
+	run_test(|mut test_handler| async move {
+		let blocks =
+			test_handler.initialize_single_branch_and_import(MAX_DATA_BRANCH_LEN * 2).await;
+		let honest = aleph_data_from_blocks(blocks[0..MAX_DATA_BRANCH_LEN].to_vec());
+		let byzantine = AlephData { head_proposal: honest.head_proposal.with_unknown_tail() };
+		test_handler.send_data(vec![byzantine.clone()]);
+
+		test_handler
+			.assert_no_message_out("Data Store let through a proposal referencing unknown blocks")
+			.await;
+
+		test_handler.finalize_block(&blocks[MAX_DATA_BRANCH_LEN - 1].header.id().hash());
+
+		let message = test_handler.assert_message_out("Data Store held a hopeless proposal").await;
+		assert_eq!(message, vec![byzantine]);
+	})
+	.await;
+}
+
 #[tokio::test]
 async fn does_not_send_requests_when_no_block_missing() {
 	run_test(|mut test_handler| async move {
@@ -1,3 +1,5 @@
+pub mod byzantine;
 pub mod client_chain_builder;
 mod data_store;
 pub mod mocks;
+pub mod simulation;
@@ -0,0 +1,220 @@
+//! A deterministic, in-process simulation of a whole committee of validators.
+//!
+//! Every simulated node runs the real block sync on top of a mock backend, connected through a
+//! [SimulatedNetwork] with configurable latency, loss and partitions. What is under test is how
+//! the sync spreads blocks and justifications under such conditions, so block authoring and
+//! finality are stood in by the harness: blocks are authored round robin on the favourite block
+//! of the author, and a block gets a justification once a quorum of mutually reachable nodes has
+//! it on their favourite branch. AlephBFT and the aggregator are not simulated, the behaviour of
+//! the committee under faulty nodes is tested with the real AlephBFT elsewhere.
+//!
+//! This is not a `run_validator_node` equivalent: the consensus party, the data store and the
+//! aggregator, and with them finality version switches and restarts from AlephBFT backups, need
+//! a substrate client running the Aleph runtime APIs and are not covered here yet. A crash only
+//! kills the sync, the chain in the backend survives.
+//!
+//! All the randomness of the network and the authored blocks comes from a single seed, and
+//! scenarios run with paused tokio time, so hours of simulated time take milliseconds and do not
+//! depend on the load of the machine running them.
+
+use std::time::Duration;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tokio::time::sleep;
+
+use crate::{
+	block::{
+		mock::{MockHeader, MockJustification},
+		Block, Header, Justification,
+	},
+	metrics::{HealthMonitor, HealthThresholds, SloMetrics},
+	sync::MockPeerId,
+	testing::mocks::{TestClientBuilder, TestClientBuilderExt},
+	BlockHash, BlockId, SessionPeriod, SubstrateChainStatus,
+};
+
+mod network;
+mod node;
+mod scenarios;
+
+pub use network::{LinkConfig, SimulatedGossip, SimulatedNetwork};
+pub use node::{SimulatedNode, SyncData};
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+	pub nodes: u32,
+	pub session_period: SessionPeriod,
+	/// Time between consecutive blocks.
+	pub block_time: Duration,
+	/// Properties of all the links, unless overridden through the network.
+	pub link: LinkConfig,
+	/// Seed of all the randomness of the simulation.
+	pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+	fn default() -> Self {
+		SimulationConfig {
+			nodes: 4,
+			session_period: SessionPeriod(20),
+			block_time: Duration::from_secs(1),
+			link: LinkConfig::default(),
+			seed: 0,
+		}
+	}
+}
+
+pub struct Simulation {
+	config: SimulationConfig,
+	network: SimulatedNetwork<SyncData>,
+	nodes: Vec<SimulatedNode>,
+	rng: StdRng,
+	next_author: usize,
+	finalized: MockHeader,
+}
+
+impl Simulation {
+	/// Starts all the nodes, has to be called within a tokio runtime.
+	pub fn new(config: SimulationConfig) -> Self {
+		let network = SimulatedNetwork::new(config.seed, config.link);
+		// The metrics only need some chain status, the nodes never report substrate blocks.
+		let (_, backend) = TestClientBuilder::new().build_with_backend();
+		let chain_status =
+			SubstrateChainStatus::new(backend).expect("the test backend has a genesis block");
+		let health_monitor = HealthMonitor::new(None, HealthThresholds::default(), &chain_status);
+		let slo_metrics = SloMetrics::new(None, chain_status, health_monitor);
+		let nodes = (0..config.nodes)
+			.map(|id| {
+				let mut node = SimulatedNode::new(id, config.session_period, slo_metrics.clone());
+				node.start(&network);
+				node
+			})
+			.collect();
+		let rng = StdRng::seed_from_u64(config.seed);
+		Simulation { config, network, nodes, rng, next_author: 0, finalized: MockHeader::genesis() }
+	}
+
+	pub fn network(&self) -> &SimulatedNetwork<SyncData> {
+		&self.network
+	}
+
+	pub fn node(&self, id: MockPeerId) -> &SimulatedNode {
+		&self.nodes[id as usize]
+	}
+
+	/// The minimal number of nodes that have to agree on a block to finalize it.
+	pub fn quorum(&self) -> usize {
+		self.nodes.len() * 2 / 3 + 1
+	}
+
+	/// The highest block finalized by the committee so far.
+	pub fn finalized(&self) -> BlockId {
+		self.finalized.id()
+	}
+
+	/// Kills the sync of the node, keeping only the chain it has persisted.
+	pub fn crash(&mut self, id: MockPeerId) {
+		self.nodes[id as usize].crash(&self.network);
+	}
+
+	/// Starts a crashed node again.
+	pub fn restart(&mut self, id: MockPeerId) {
+		self.nodes[id as usize].start(&self.network);
+	}
+
+	/// Authors the given number of blocks, one every block time, finalizing whatever a quorum
+	/// agrees on along the way.
+	pub async fn run_for(&mut self, blocks: u32) {
+		for _ in 0..blocks {
+			self.author_block().await;
+			sleep(self.config.block_time).await;
+			self.finalize().await;
+		}
+	}
+
+	/// Lets time pass without authoring any blocks, finalizing whatever a quorum agrees on.
+	pub async fn idle_for(&mut self, duration: Duration) {
+		let steps = duration.as_millis() / self.config.block_time.as_millis().max(1);
+		for _ in 0..steps {
+			sleep(self.config.block_time).await;
+			self.finalize().await;
+		}
+	}
+
+	/// Waits until all the running nodes finalize at least the block finalized by the committee.
+	/// Returns whether that happened within the limit.
+	pub async fn wait_for_finality(&self, limit: Duration) -> bool {
+		let step = self.config.block_time;
+		let mut waited = Duration::ZERO;
+		while waited <= limit {
+			let target = self.finalized();
+			if self
+				.nodes
+				.iter()
+				.filter(|node| node.is_running())
+				.all(|node| node.top_finalized().number() >= target.number())
+			{
+				return true;
+			}
+			sleep(step).await;
+			waited += step;
+		}
+		false
+	}
+
+	async fn author_block(&mut self) {
+		for _ in 0..self.nodes.len() {
+			let author = &self.nodes[self.next_author];
+			self.next_author = (self.next_author + 1) % self.nodes.len();
+			if author.is_running() {
+				author.author_block(BlockHash::from(self.rng.gen::<[u8; 32]>())).await;
+				return;
+			}
+		}
+	}
+
+	/// Finalizes as many consecutive blocks above the currently finalized one as possible.
+	async fn finalize(&mut self) {
+		while let Some(justification) = self.next_justification().await {
+			self.finalized = justification.header().clone();
+		}
+	}
+
+	/// A justification for the child of the finalized block which a quorum of mutually reachable
+	/// nodes has on their favourite branches, if there is one. It is passed to all these nodes,
+	/// as their aggregators would do.
+	async fn next_justification(&self) -> Option<MockJustification> {
+		let parent = self.finalized.id();
+		let mut candidates = Vec::new();
+		for (id, node) in self.nodes.iter().enumerate() {
+			if let Some(block) = node.favourite_child(&parent).await {
+				candidates.push((id as MockPeerId, block));
+			}
+		}
+		let quorum = self.quorum();
+		for (pivot, block) in &candidates {
+			let supporters: Vec<_> = candidates
+				.iter()
+				.filter(|(supporter, candidate)| {
+					candidate == block
+						&& (supporter == pivot || self.network.connected(*pivot, *supporter))
+				})
+				.map(|(supporter, _)| *supporter)
+				.collect();
+			if supporters.len() < quorum {
+				continue;
+			}
+			let header = self.nodes[*pivot as usize]
+				.block(block.clone())
+				.expect("the node has its favourite block")
+				.header()
+				.clone();
+			let justification = MockJustification::for_header(header);
+			for supporter in supporters {
+				self.nodes[supporter as usize].submit(justification.clone());
+			}
+			return Some(justification);
+		}
+		None
+	}
+}
@@ -0,0 +1,330 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fmt::{Display, Error as FmtError, Formatter},
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::{
+	channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+	StreamExt,
+};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use tokio::time::sleep;
+
+use crate::{
+	network::{Data, GossipNetwork},
+	sync::MockPeerId,
+};
+
+/// Properties of a directed link between two simulated nodes.
+#[derive(Clone, Copy, Debug)]
+pub struct LinkConfig {
+	/// The minimal time it takes to deliver a message.
+	pub latency: Duration,
+	/// Upper bound of the random delay added on top of the latency, so messages can get reordered.
+	pub jitter: Duration,
+	/// Probability of a message getting lost, between 0 and 1.
+	pub loss: f64,
+}
+
+impl Default for LinkConfig {
+	fn default() -> Self {
+		LinkConfig {
+			latency: Duration::from_millis(50),
+			jitter: Duration::from_millis(50),
+			loss: 0.0,
+		}
+	}
+}
+
+struct State<D> {
+	rng: StdRng,
+	default_link: LinkConfig,
+	links: HashMap<(MockPeerId, MockPeerId), LinkConfig>,
+	/// Every connection gets a new generation, so that a restarted node does not receive messages
+	/// sent to its previous incarnation.
+	inboxes: HashMap<MockPeerId, (u64, UnboundedSender<(D, MockPeerId)>)>,
+	next_generation: u64,
+	partition: Option<HashMap<MockPeerId, usize>>,
+}
+
+impl<D> State<D> {
+	fn connected(&self, from: MockPeerId, to: MockPeerId) -> bool {
+		if from == to || !self.inboxes.contains_key(&from) || !self.inboxes.contains_key(&to) {
+			return false;
+		}
+		match &self.partition {
+			Some(groups) => {
+				matches!((groups.get(&from), groups.get(&to)), (Some(from), Some(to)) if from == to)
+			},
+			None => true,
+		}
+	}
+
+	/// The peers the node can currently reach, in a deterministic order.
+	fn peers(&self, node: MockPeerId) -> Vec<MockPeerId> {
+		let mut peers: Vec<_> = self
+			.inboxes
+			.keys()
+			.copied()
+			.filter(|peer| self.connected(node, *peer))
+			.collect();
+		peers.sort_unstable();
+		peers
+	}
+
+	/// The delay after which a message should be delivered together with the generation of the
+	/// recipient, or `None` if the message gets lost.
+	fn delay(&mut self, from: MockPeerId, to: MockPeerId) -> Option<(Duration, u64)> {
+		if !self.connected(from, to) {
+			return None;
+		}
+		let link = self.links.get(&(from, to)).copied().unwrap_or(self.default_link);
+		if self.rng.gen_bool(link.loss) {
+			return None;
+		}
+		let (generation, _) = self.inboxes.get(&to)?;
+		Some((link.latency + link.jitter.mul_f64(self.rng.gen()), *generation))
+	}
+}
+
+/// An in-process network connecting simulated nodes, with configurable latency, loss and
+/// partitions. All the randomness comes from a seeded generator, so with paused tokio time runs
+/// are reproducible.
+pub struct SimulatedNetwork<D> {
+	state: Arc<Mutex<State<D>>>,
+}
+
+impl<D> Clone for SimulatedNetwork<D> {
+	fn clone(&self) -> Self {
+		SimulatedNetwork { state: self.state.clone() }
+	}
+}
+
+impl<D: Data> SimulatedNetwork<D> {
+	pub fn new(seed: u64, default_link: LinkConfig) -> Self {
+		SimulatedNetwork {
+			state: Arc::new(Mutex::new(State {
+				rng: StdRng::seed_from_u64(seed),
+				default_link,
+				links: HashMap::new(),
+				inboxes: HashMap::new(),
+				next_generation: 0,
+				partition: None,
+			})),
+		}
+	}
+
+	/// Overrides the properties of the link from one node to another.
+	pub fn set_link(&self, from: MockPeerId, to: MockPeerId, link: LinkConfig) {
+		self.state.lock().links.insert((from, to), link);
+	}
+
+	/// Splits the network, so that only nodes within the same group can communicate. Nodes not
+	/// present in any group are cut off completely.
+	pub fn partition(&self, groups: Vec<Vec<MockPeerId>>) {
+		let groups = groups
+			.into_iter()
+			.enumerate()
+			.flat_map(|(group, nodes)| nodes.into_iter().map(move |node| (node, group)))
+			.collect();
+		self.state.lock().partition = Some(groups);
+	}
+
+	/// Removes any partition.
+	pub fn heal(&self) {
+		self.state.lock().partition = None;
+	}
+
+	/// Whether messages can currently get from one node to the other.
+	pub fn connected(&self, from: MockPeerId, to: MockPeerId) -> bool {
+		self.state.lock().connected(from, to)
+	}
+
+	/// Connects the node to the network, replacing its previous connection if there was one.
+	pub fn join(&self, node: MockPeerId) -> SimulatedGossip<D> {
+		let (sender, inbox) = mpsc::unbounded();
+		let mut state = self.state.lock();
+		let generation = state.next_generation;
+		state.next_generation += 1;
+		state.inboxes.insert(node, (generation, sender));
+		drop(state);
+		SimulatedGossip { node, network: self.clone(), inbox }
+	}
+
+	/// Disconnects the node, dropping all the messages in flight to it.
+	pub fn leave(&self, node: MockPeerId) {
+		self.state.lock().inboxes.remove(&node);
+	}
+
+	fn send(&self, data: D, from: MockPeerId, to: MockPeerId) {
+		let (delay, generation) = match self.state.lock().delay(from, to) {
+			Some(delay) => delay,
+			None => return,
+		};
+		let network = self.clone();
+		tokio::spawn(async move {
+			sleep(delay).await;
+			network.deliver(data, from, to, generation);
+		});
+	}
+
+	fn deliver(&self, data: D, from: MockPeerId, to: MockPeerId, generation: u64) {
+		let state = self.state.lock();
+		// The network might have changed while the message was in flight.
+		if !state.connected(from, to) {
+			return;
+		}
+		match state.inboxes.get(&to) {
+			Some((current, inbox)) if *current == generation => {
+				let _ = inbox.unbounded_send((data, from));
+			},
+			_ => {},
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum Error {
+	Disconnected,
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		match self {
+			Error::Disconnected => write!(f, "node disconnected from the simulated network"),
+		}
+	}
+}
+
+/// The connection of a single node to the [SimulatedNetwork].
+pub struct SimulatedGossip<D> {
+	node: MockPeerId,
+	network: SimulatedNetwork<D>,
+	inbox: UnboundedReceiver<(D, MockPeerId)>,
+}
+
+#[async_trait::async_trait]
+impl<D: Data> GossipNetwork<D> for SimulatedGossip<D> {
+	type Error = Error;
+	type PeerId = MockPeerId;
+
+	fn send_to(&mut self, data: D, peer_id: MockPeerId) -> Result<(), Self::Error> {
+		self.network.send(data, self.node, peer_id);
+		Ok(())
+	}
+
+	fn send_to_random(
+		&mut self,
+		data: D,
+		peer_ids: HashSet<MockPeerId>,
+	) -> Result<(), Self::Error> {
+		let peer = {
+			let mut state = self.network.state.lock();
+			let peers = state.peers(self.node);
+			let preferred: Vec<_> =
+				peers.iter().copied().filter(|peer| peer_ids.contains(peer)).collect();
+			let candidates = match preferred.is_empty() {
+				true => peers,
+				false => preferred,
+			};
+			candidates.choose(&mut state.rng).copied()
+		};
+		if let Some(peer) = peer {
+			self.network.send(data, self.node, peer);
+		}
+		Ok(())
+	}
+
+	fn broadcast(&mut self, data: D) -> Result<(), Self::Error> {
+		let peers = self.network.state.lock().peers(self.node);
+		for peer in peers {
+			self.network.send(data.clone(), self.node, peer);
+		}
+		Ok(())
+	}
+
+	fn connected_peers(&self) -> HashSet<MockPeerId> {
+		self.network.state.lock().peers(self.node).into_iter().collect()
+	}
+
+	async fn next(&mut self) -> Result<(D, MockPeerId), Self::Error> {
+		self.inbox.next().await.ok_or(Error::Disconnected)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{collections::HashSet, time::Duration};
+
+	use futures::FutureExt;
+	use tokio::time::sleep;
+
+	use super::{LinkConfig, SimulatedNetwork};
+	use crate::network::GossipNetwork;
+
+	fn network() -> SimulatedNetwork<u64> {
+		SimulatedNetwork::new(
+			0,
+			LinkConfig { latency: Duration::from_millis(100), jitter: Duration::ZERO, loss: 0.0 },
+		)
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn delivers_after_latency() {
+		let network = network();
+		let mut first = network.join(0);
+		let mut second = network.join(1);
+		first.send_to(7, 1).expect("sending should work");
+		sleep(Duration::from_millis(99)).await;
+		assert!(second.next().now_or_never().is_none(), "the message should still be in flight");
+		sleep(Duration::from_millis(1)).await;
+		assert_eq!(second.next().await.expect("should receive"), (7, 0));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn respects_partitions() {
+		let network = network();
+		let mut nodes: Vec<_> = (0..3).map(|node| network.join(node)).collect();
+		network.partition(vec![vec![0, 1], vec![2]]);
+		nodes[0].broadcast(1).expect("broadcasting should work");
+		sleep(Duration::from_secs(1)).await;
+		assert_eq!(nodes[1].next().await.expect("should receive"), (1, 0));
+		assert!(
+			nodes[2].next().now_or_never().is_none(),
+			"the message should not cross the partition"
+		);
+
+		nodes[2].send_to(2, 0).expect("sending should work");
+		network.heal();
+		sleep(Duration::from_secs(1)).await;
+		assert_eq!(nodes[0].next().await.expect("should receive"), (2, 2));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn loses_messages_on_lossy_links() {
+		let network = network();
+		let mut first = network.join(0);
+		let mut second = network.join(1);
+		network.set_link(0, 1, LinkConfig { loss: 1.0, ..LinkConfig::default() });
+		first.send_to_random(3, HashSet::from([1])).expect("sending should work");
+		second.send_to(4, 0).expect("sending should work");
+		sleep(Duration::from_secs(1)).await;
+		assert!(second.next().now_or_never().is_none(), "the message should be lost");
+		assert_eq!(first.next().await.expect("should receive"), (4, 1));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn drops_messages_to_nodes_that_left() {
+		let network = network();
+		let mut first = network.join(0);
+		let _second = network.join(1);
+		first.send_to(5, 1).expect("sending should work");
+		network.leave(1);
+		let mut second = network.join(1);
+		sleep(Duration::from_secs(1)).await;
+		assert!(second.next().now_or_never().is_none(), "in flight messages should be lost");
+	}
+}
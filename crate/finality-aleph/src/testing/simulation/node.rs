@@ -0,0 +1,160 @@
+use futures::channel::{mpsc, oneshot};
+use log::debug;
+use tokio::task::JoinHandle;
+
+use crate::{
+	block::{
+		mock::{Backend, MockBlock, MockHeader, MockJustification},
+		Block, ChainStatus, Header,
+	},
+	metrics::SloMetrics,
+	session::SessionBoundaryInfo,
+	sync::{DatabaseIO, MockPeerId, Service, SyncConfig, VersionedNetworkData, IO},
+	testing::simulation::network::SimulatedNetwork,
+	BlockHash, BlockId, SessionPeriod, SyncOracle,
+};
+
+const LOG_TARGET: &str = "aleph-simulation";
+
+pub type SyncData = VersionedNetworkData<MockBlock, MockJustification>;
+
+/// Handles to the services of a node that is currently running.
+struct Running {
+	sync: JoinHandle<()>,
+	justifications: mpsc::UnboundedSender<MockJustification>,
+	blocks: mpsc::UnboundedSender<MockBlock>,
+	favourite_requests: mpsc::UnboundedSender<oneshot::Sender<MockHeader>>,
+}
+
+/// A single simulated validator, running the real block sync on top of a mock backend.
+pub struct SimulatedNode {
+	id: MockPeerId,
+	session_info: SessionBoundaryInfo,
+	forest_max_depth: u32,
+	backend: Backend,
+	slo_metrics: SloMetrics,
+	running: Option<Running>,
+}
+
+impl SimulatedNode {
+	pub fn new(id: MockPeerId, session_period: SessionPeriod, slo_metrics: SloMetrics) -> Self {
+		let session_info = SessionBoundaryInfo::new(session_period);
+		let (backend, _) = Backend::setup(session_info.clone());
+		SimulatedNode {
+			id,
+			session_info: session_info.clone(),
+			forest_max_depth: SyncConfig::for_session_info(&session_info).forest_max_depth,
+			backend,
+			slo_metrics,
+			running: None,
+		}
+	}
+
+	pub fn is_running(&self) -> bool {
+		self.running.is_some()
+	}
+
+	/// Starts the services of the node with the chain it has persisted.
+	pub fn start(&mut self, network: &SimulatedNetwork<SyncData>) {
+		if self.is_running() {
+			return;
+		}
+		let (backend, chain_events) = self.backend.reopen();
+		self.backend = backend.clone();
+		let (justifications, justifications_from_user) = mpsc::unbounded();
+		let (blocks, blocks_from_creator) = mpsc::unbounded();
+		let (favourite_requests, favourite_block_request) = mpsc::unbounded();
+		let io = IO::new(
+			DatabaseIO::new(backend.clone(), backend.clone(), backend.clone()),
+			network.join(self.id),
+			chain_events,
+			SyncOracle::new().0,
+			justifications_from_user,
+			blocks_from_creator,
+		);
+		let (service, _) = Service::new(
+			backend,
+			self.session_info.clone(),
+			io,
+			None,
+			self.slo_metrics.clone(),
+			favourite_block_request,
+			None,
+			self.forest_max_depth,
+		)
+		.expect("sync should start");
+		let id = self.id;
+		let sync = tokio::spawn(async move {
+			if let Err(e) = service.run().await {
+				debug!(target: LOG_TARGET, "Sync of node {} stopped: {}", id, e);
+			}
+		});
+		self.running = Some(Running { sync, justifications, blocks, favourite_requests });
+	}
+
+	/// Kills the services of the node, keeping only the chain in its backend.
+	pub fn crash(&mut self, network: &SimulatedNetwork<SyncData>) {
+		network.leave(self.id);
+		if let Some(running) = self.running.take() {
+			running.sync.abort();
+		}
+	}
+
+	/// The block the node would build on, if it is running.
+	pub async fn favourite_block(&self) -> Option<MockHeader> {
+		let running = self.running.as_ref()?;
+		let (sender, receiver) = oneshot::channel();
+		running.favourite_requests.unbounded_send(sender).ok()?;
+		receiver.await.ok()
+	}
+
+	/// Builds a block on top of the favourite block of the node and passes it to the sync.
+	pub async fn author_block(&self, hash: BlockHash) -> Option<BlockId> {
+		let parent = self.favourite_block().await?.id();
+		let mut header = parent.child(hash);
+		header.make_own();
+		self.running
+			.as_ref()?
+			.blocks
+			.unbounded_send(MockBlock::new(header.clone(), true))
+			.ok()?;
+		Some(header.id())
+	}
+
+	/// The child of `parent` on the favourite branch of the node, if it has one.
+	pub async fn favourite_child(&self, parent: &BlockId) -> Option<BlockId> {
+		let number = parent.number() + 1;
+		let mut header = self.favourite_block().await?;
+		if header.id().number() < number {
+			return None;
+		}
+		while header.id().number() > number {
+			let parent = header.parent_id()?;
+			header = self.backend.block(parent).ok()??.header().clone();
+		}
+		(header.parent_id().as_ref() == Some(parent)).then(|| header.id())
+	}
+
+	/// Passes a justification, as if created by the aggregator of the node, to the sync.
+	pub fn submit(&self, justification: MockJustification) {
+		if let Some(running) = &self.running {
+			let _ = running.justifications.unbounded_send(justification);
+		}
+	}
+
+	pub fn block(&self, id: BlockId) -> Option<MockBlock> {
+		self.backend.block(id).expect("mock backend should work")
+	}
+
+	pub fn top_finalized(&self) -> BlockId {
+		self.backend.top_finalized().expect("mock backend should work").header().id()
+	}
+}
+
+impl Drop for SimulatedNode {
+	fn drop(&mut self) {
+		if let Some(running) = self.running.take() {
+			running.sync.abort();
+		}
+	}
+}
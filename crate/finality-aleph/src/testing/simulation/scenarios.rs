@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use crate::testing::simulation::{LinkConfig, Simulation, SimulationConfig};
+
+const CATCH_UP_LIMIT: Duration = Duration::from_secs(120);
+
+fn assert_all_finalized(simulation: &Simulation, nodes: u32) {
+	let finalized = simulation.finalized();
+	for id in 0..nodes {
+		assert_eq!(
+			simulation.node(id).top_finalized(),
+			finalized,
+			"node {id} should agree on the finalized head"
+		);
+	}
+}
+
+#[tokio::test(start_paused = true)]
+async fn finalizes_across_session_boundaries() {
+	let config = SimulationConfig::default();
+	let session_period = config.session_period.0;
+	let mut simulation = Simulation::new(config);
+	simulation.run_for(3 * session_period).await;
+	assert!(
+		simulation.finalized().number() > 2 * session_period,
+		"finalization should get through two session changes, got {:?}",
+		simulation.finalized()
+	);
+	assert!(simulation.wait_for_finality(CATCH_UP_LIMIT).await);
+	assert_all_finalized(&simulation, 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn converges_over_lossy_network() {
+	let config = SimulationConfig {
+		nodes: 7,
+		link: LinkConfig {
+			latency: Duration::from_millis(300),
+			jitter: Duration::from_millis(400),
+			loss: 0.2,
+		},
+		seed: 3,
+		..SimulationConfig::default()
+	};
+	let mut simulation = Simulation::new(config);
+	simulation.run_for(40).await;
+	simulation.idle_for(Duration::from_secs(30)).await;
+	assert!(
+		simulation.finalized().number() >= 20,
+		"finalization should progress despite the losses, got {:?}",
+		simulation.finalized()
+	);
+	assert!(simulation.wait_for_finality(CATCH_UP_LIMIT).await);
+	assert_all_finalized(&simulation, 7);
+}
+
+#[tokio::test(start_paused = true)]
+async fn minority_partition_catches_up_after_healing() {
+	let mut simulation = Simulation::new(SimulationConfig::default());
+	simulation.run_for(5).await;
+	simulation.network().partition(vec![vec![0, 1, 2], vec![3]]);
+	simulation.run_for(20).await;
+	let during_partition = simulation.finalized();
+	assert!(during_partition.number() >= 15, "the majority should keep finalizing");
+	assert!(
+		simulation.node(3).top_finalized().number() < during_partition.number(),
+		"the minority should fall behind"
+	);
+
+	simulation.network().heal();
+	simulation.run_for(5).await;
+	assert!(simulation.wait_for_finality(CATCH_UP_LIMIT).await);
+	assert_all_finalized(&simulation, 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn restarted_sync_catches_up() {
+	let mut simulation = Simulation::new(SimulationConfig::default());
+	simulation.run_for(10).await;
+	simulation.crash(3);
+	let crashed_at = simulation.node(3).top_finalized();
+	simulation.run_for(30).await;
+	assert!(simulation.finalized().number() >= 30, "the rest should keep finalizing");
+	assert_eq!(
+		simulation.node(3).top_finalized(),
+		crashed_at,
+		"the crashed node should stay behind"
+	);
+
+	simulation.restart(3);
+	simulation.run_for(5).await;
+	assert!(simulation.wait_for_finality(CATCH_UP_LIMIT).await);
+	assert_all_finalized(&simulation, 4);
+}
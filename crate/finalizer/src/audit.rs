@@ -0,0 +1,64 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use selendra_client::{sp_core::H256, BlockNumber};
+
+/// Append-only record of every justification signed, and of what happened to it.
+#[derive(Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        AuditLog { path }
+    }
+
+    pub fn signed(
+        &self,
+        num: BlockNumber,
+        hash: &H256,
+        public: &[u8; 32],
+        signature: &[u8; 64],
+        signer: &str,
+    ) -> Result<()> {
+        self.record(format!(
+            "signed number={} hash=0x{} public=0x{} signature=0x{} signer={}",
+            num,
+            hex::encode(hash),
+            hex::encode(public),
+            hex::encode(signature),
+            signer
+        ))
+    }
+
+    pub fn sent(&self, num: BlockNumber, hash: &H256) -> Result<()> {
+        self.record(format!("sent number={} hash=0x{}", num, hex::encode(hash)))
+    }
+
+    pub fn failed(&self, num: BlockNumber, hash: &H256, error: &anyhow::Error) -> Result<()> {
+        self.record(format!(
+            "failed number={} hash=0x{} error={:?}",
+            num,
+            hex::encode(hash),
+            error.to_string()
+        ))
+    }
+
+    fn record(&self, entry: String) -> Result<()> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open the audit log {:?}", self.path))?;
+        writeln!(file, "{timestamp} {entry}")?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
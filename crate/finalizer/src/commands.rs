@@ -5,15 +5,17 @@ use std::{
     path::PathBuf,
 };
 
-use selendra_client::{
-    aleph_keypair_from_string, api, pallets::aleph::AlephRpc, primitives::app::Public,
-    sp_core::H256, AlephKeyPair, BlockNumber, Connection, ConnectionApi, Pair,
-};
 use anyhow::Result;
 use dialoguer::Confirm;
 use futures::{stream::FuturesUnordered, StreamExt};
+use selendra_client::{
+    aleph_keypair_from_string, api, pallets::aleph::AlephRpc, selendra_primitives::app::Public,
+    sp_core::H256, AlephKeyPair, BlockNumber, Connection, Pair,
+};
 use subxt::config::Header;
 
+use crate::{audit::AuditLog, signer::Signer};
+
 fn pretty_print_h256(h: &H256) -> String {
     let prefix = h.0.iter().take(4).fold(String::new(), |mut output, byte| {
         let _ = write!(output, "{byte:02x}");
@@ -23,9 +25,9 @@ fn pretty_print_h256(h: &H256) -> String {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct HashNum {
-    num: BlockNumber,
-    hash: H256,
+pub(crate) struct HashNum {
+    pub(crate) num: BlockNumber,
+    pub(crate) hash: H256,
 }
 
 impl Display for HashNum {
@@ -35,8 +37,8 @@ impl Display for HashNum {
 }
 
 pub struct Connections {
-    primary: Connection,
-    secondaries: BTreeMap<String, Connection>,
+    pub(crate) primary: Connection,
+    pub(crate) secondaries: BTreeMap<String, Connection>,
 }
 
 impl Connections {
@@ -54,9 +56,9 @@ impl Connections {
     }
 }
 #[derive(Debug, PartialEq, Eq)]
-struct ChainStatus {
-    best: HashNum,
-    finalized: HashNum,
+pub(crate) struct ChainStatus {
+    pub(crate) best: HashNum,
+    pub(crate) finalized: HashNum,
 }
 
 struct AllChainStatuses {
@@ -69,9 +71,9 @@ struct AllBlocksAtNum {
     secondaries: BTreeMap<String, HashNum>,
 }
 
-struct FinalizationPlan {
-    finalized_base: HashNum,
-    target: HashNum,
+pub(crate) struct FinalizationPlan {
+    pub(crate) finalized_base: HashNum,
+    pub(crate) target: HashNum,
 }
 
 impl Display for ChainStatus {
@@ -80,7 +82,7 @@ impl Display for ChainStatus {
     }
 }
 
-async fn get_block_at_num(connection: &Connection, num: BlockNumber) -> Result<HashNum> {
+pub(crate) async fn get_block_at_num(connection: &Connection, num: BlockNumber) -> Result<HashNum> {
     let hash = connection
         .as_client()
         .rpc()
@@ -112,7 +114,7 @@ async fn get_all_blocks_at_num(
     })
 }
 
-async fn get_chain_status(connection: &Connection) -> Result<ChainStatus> {
+pub(crate) async fn get_chain_status(connection: &Connection) -> Result<ChainStatus> {
     let finalized_hash = connection.as_client().rpc().finalized_head().await?;
 
     let finalized_block = connection
@@ -200,7 +202,7 @@ fn assert_blocks_match(blocks: &AllBlocksAtNum, target_num: BlockNumber) -> Resu
     Ok(())
 }
 
-async fn pre_sequence_finalization_check(
+pub(crate) async fn pre_sequence_finalization_check(
     connections: &Connections,
     how_many: BlockNumber,
 ) -> Result<FinalizationPlan> {
@@ -242,7 +244,8 @@ async fn pre_single_finalization_check(
 
 async fn try_finalize_single_block(
     connections: &Connections,
-    key: &AlephKeyPair,
+    signer: &Signer,
+    audit_log: &AuditLog,
     num: BlockNumber,
 ) -> Result<()> {
     println!("Trying to finalize block number {num}");
@@ -254,10 +257,25 @@ async fn try_finalize_single_block(
                     num,
                     hex::encode(hash)
                 );
-                connections
+                let signature = signer.sign(num, &hash).await?;
+                audit_log.signed(
+                    num,
+                    &hash,
+                    &signer.public().await?,
+                    &signature,
+                    &signer.describe(),
+                )?;
+                match connections
                     .primary
-                    .emergency_finalize(num, hash, *key)
-                    .await?;
+                    .emergency_finalize_with_signature(num, hash, signature)
+                    .await
+                {
+                    Ok(()) => audit_log.sent(num, &hash)?,
+                    Err(e) => {
+                        audit_log.failed(num, &hash, &e)?;
+                        return Err(e);
+                    }
+                }
                 println!("Finalization call for {num} sent.",);
                 break;
             }
@@ -274,21 +292,27 @@ async fn try_finalize_single_block(
 
 pub async fn try_finalize(
     connections: Connections,
-    seed_path: PathBuf,
+    signer: Signer,
+    audit_log: AuditLog,
     how_many: BlockNumber,
+    dry_run: bool,
 ) -> Result<()> {
-    let key = read_key_from_file(seed_path)?;
+    let public = signer.public().await?;
     let on_chain_pubkey = get_finalizer_pubkey(&connections.primary)
-        .await
-        .ok_or_else(|| anyhow::anyhow!("Failed to get the finalizer PK from chain."))?;
-    if key.public().0 != on_chain_pubkey.0 .0 {
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No emergency finalizer is set on chain."))?;
+    if public != on_chain_pubkey.0 .0 {
         return Err(anyhow::anyhow!(
-            "On chain key does not match the key from file {} != {}",
+            "On chain key does not match the key of the {} signer {} != {}",
+            signer.describe(),
             hex::encode(on_chain_pubkey.0 .0),
-            hex::encode(key.public().0),
+            hex::encode(public),
         ));
     }
     let plan = pre_sequence_finalization_check(&connections, how_many).await?;
+    if dry_run {
+        return print_plan(&connections, plan).await;
+    }
     println!(
         "Sanity check passed. Will proceed to finalizing blocks from {} to {} (last hash {})",
         plan.finalized_base.num + 1,
@@ -304,20 +328,40 @@ pub async fn try_finalize(
     }
 
     for num in (plan.finalized_base.num + 1)..=plan.target.num {
-        try_finalize_single_block(&connections, &key, num).await?;
+        try_finalize_single_block(&connections, &signer, &audit_log, num).await?;
     }
     Ok(())
 }
 
-async fn get_finalizer_pubkey(connection: &Connection) -> Option<Public> {
+/// Prints every block a plan would finalize, checking that all endpoints agree on each of them.
+async fn print_plan(connections: &Connections, plan: FinalizationPlan) -> Result<()> {
+    println!("Dry run, nothing will be signed or sent. Blocks that would be finalized:");
+    for num in (plan.finalized_base.num + 1)..=plan.target.num {
+        let blocks = get_all_blocks_at_num(connections, num).await?;
+        assert_blocks_match(&blocks, num)?;
+        println!("{} {}", num, hex::encode(blocks.primary.hash));
+    }
+    Ok(())
+}
+
+/// Reads the emergency finalizer at the latest block, failing rather than panicking when the
+/// endpoint does not respond.
+pub(crate) async fn get_finalizer_pubkey(connection: &Connection) -> Result<Option<Public>> {
     let addrs = api::storage().aleph().emergency_finalizer();
-    connection.get_storage_entry_maybe(&addrs, None).await
+    let finalizer = connection
+        .as_client()
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&addrs)
+        .await?;
+    Ok(finalizer)
 }
 
-fn read_key_from_file(seed_path: PathBuf) -> Result<AlephKeyPair> {
+pub fn read_key_from_file(seed_path: PathBuf) -> Result<AlephKeyPair> {
     println!("Reading the finalizer key from file {:?}", &seed_path);
     let suri = fs::read_to_string(seed_path)?;
     let key = aleph_keypair_from_string(suri.trim());
     println!("Read a pubkey {}\n", hex::encode(key.public().0));
     Ok(key)
-}
\ No newline at end of file
+}
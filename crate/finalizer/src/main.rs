@@ -1,11 +1,19 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
 use selendra_client::BlockNumber;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
-use crate::commands::{status, try_finalize, Connections};
+use crate::{
+    audit::AuditLog,
+    commands::{read_key_from_file, status, try_finalize, Connections},
+    monitor::{monitor, MonitorConfig},
+    signer::{serve, Signer},
+};
+mod audit;
 mod commands;
+mod monitor;
+mod signer;
 
 #[derive(Debug, Parser, Clone)]
 #[clap(version = "1.0")]
@@ -33,9 +41,61 @@ pub enum Command {
         #[clap(long, default_value = "seed.txt")]
         seed_path: PathBuf,
 
+        /// Unix socket of a signer started with the `signer` command. If set, the seed is not read
+        /// and signatures are requested from the signer instead.
+        #[clap(long)]
+        signer_socket: Option<PathBuf>,
+
+        /// Path to the log every signed justification is appended to.
+        #[clap(long, default_value = "finalizer-audit.log")]
+        audit_log: PathBuf,
+
         /// The number of blocks to finalize. Should be no more than 20.
         #[clap(long)]
         how_many: BlockNumber,
+
+        /// Only print the blocks that would be finalized, after all the checks passed.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Sign emergency finalizations requested over a Unix socket, after the operator confirms each
+    /// of them, so that the seed can stay with a separate process
+    Signer {
+        /// Path to the seed phrase to emergency finalizer.
+        #[clap(long, default_value = "seed.txt")]
+        seed_path: PathBuf,
+
+        /// Unix socket to listen at.
+        #[clap(long, default_value = "finalizer-signer.sock")]
+        socket: PathBuf,
+
+        /// Path to the log every signed justification is appended to.
+        #[clap(long, default_value = "signer-audit.log")]
+        audit_log: PathBuf,
+    },
+    /// Keep polling the endpoints and report finalized-hash divergence, finality stalls and
+    /// emergency finalizer changes as Prometheus metrics (read-only method)
+    Monitor {
+        /// How often to poll the endpoints, in seconds.
+        #[clap(long, default_value = "6")]
+        poll_interval: u64,
+
+        /// Address to serve Prometheus metrics at.
+        #[clap(long, default_value = "127.0.0.1:9616")]
+        prometheus_address: SocketAddr,
+
+        /// Seconds without a newly finalized block after which finality is considered stalled.
+        #[clap(long, default_value = "120")]
+        stall_threshold: u64,
+
+        /// Seconds of stalled finality after which to offer preparing a finalization plan. The plan
+        /// is only printed, never sent. Disabled if not set.
+        #[clap(long)]
+        plan_after: Option<u64>,
+
+        /// The number of blocks a prepared plan finalizes. Should be no more than 20.
+        #[clap(long, default_value = "20")]
+        plan_blocks: BlockNumber,
     },
 }
 
@@ -46,6 +106,16 @@ async fn main() -> Result<()> {
         secondary_endpoints,
         command,
     } = Config::parse();
+    // The signer does not talk to the chain, so it can run where no endpoint is reachable.
+    if let Command::Signer {
+        seed_path,
+        socket,
+        audit_log,
+    } = command
+    {
+        let key = read_key_from_file(seed_path)?;
+        return serve(key, socket, AuditLog::new(audit_log)).await;
+    }
     println!(
         "Running with primary {} and {} secondaries {:?} ...\n",
         primary_endpoint,
@@ -59,8 +129,41 @@ async fn main() -> Result<()> {
         }
         Command::TryFinalize {
             seed_path,
+            signer_socket,
+            audit_log,
             how_many,
-        } => try_finalize(connections, seed_path, how_many).await?,
+            dry_run,
+        } => {
+            let signer = match signer_socket {
+                Some(socket) => Signer::Remote(socket),
+                None => Signer::Local(read_key_from_file(seed_path)?),
+            };
+            try_finalize(
+                connections,
+                signer,
+                AuditLog::new(audit_log),
+                how_many,
+                dry_run,
+            )
+            .await?
+        }
+        Command::Monitor {
+            poll_interval,
+            prometheus_address,
+            stall_threshold,
+            plan_after,
+            plan_blocks,
+        } => {
+            let config = MonitorConfig {
+                poll_interval: Duration::from_secs(poll_interval),
+                prometheus_address,
+                stall_threshold: Duration::from_secs(stall_threshold),
+                plan_after: plan_after.map(Duration::from_secs),
+                plan_blocks,
+            };
+            monitor(connections, config).await?
+        }
+        Command::Signer { .. } => unreachable!("the signer is started before connecting"),
     }
     Ok(())
 }
\ No newline at end of file
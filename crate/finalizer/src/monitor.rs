@@ -0,0 +1,344 @@
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use dialoguer::Confirm;
+use prometheus_endpoint::{
+    register, Counter, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64,
+};
+use selendra_client::{BlockNumber, Connection};
+use tokio::task::JoinHandle;
+
+use crate::commands::{
+    get_block_at_num, get_chain_status, get_finalizer_pubkey, pre_sequence_finalization_check,
+    ChainStatus, Connections, HashNum,
+};
+
+const PRIMARY: &str = "primary";
+
+/// Settings of the `monitor` command.
+pub struct MonitorConfig {
+    pub poll_interval: Duration,
+    pub prometheus_address: SocketAddr,
+    pub stall_threshold: Duration,
+    pub plan_after: Option<Duration>,
+    pub plan_blocks: BlockNumber,
+}
+
+struct Metrics {
+    best_block: GaugeVec<U64>,
+    finalized_block: GaugeVec<U64>,
+    endpoint_up: GaugeVec<U64>,
+    finalized_diverged: GaugeVec<U64>,
+    seconds_since_finalized: Gauge<F64>,
+    finality_stalled: Gauge<U64>,
+    emergency_finalizer_changes: Counter<U64>,
+}
+
+impl Metrics {
+    fn new(registry: &Registry) -> Result<Self, PrometheusError> {
+        let per_endpoint = |name: &str, help: &str| -> Result<GaugeVec<U64>, PrometheusError> {
+            register(
+                GaugeVec::new(Opts::new(name, help), &["endpoint"])?,
+                registry,
+            )
+        };
+
+        Ok(Self {
+            best_block: per_endpoint("finalizer_monitor_best_block", "Best block of an endpoint")?,
+            finalized_block: per_endpoint(
+                "finalizer_monitor_finalized_block",
+                "Finalized block of an endpoint",
+            )?,
+            endpoint_up: per_endpoint(
+                "finalizer_monitor_endpoint_up",
+                "Whether the last poll of an endpoint succeeded",
+            )?,
+            finalized_diverged: per_endpoint(
+                "finalizer_monitor_finalized_diverged",
+                "Whether an endpoint finalized a different block than the primary",
+            )?,
+            seconds_since_finalized: register(
+                Gauge::new(
+                    "finalizer_monitor_seconds_since_finalized",
+                    "Seconds since the primary finalized a new block",
+                )?,
+                registry,
+            )?,
+            finality_stalled: register(
+                Gauge::new(
+                    "finalizer_monitor_finality_stalled",
+                    "Whether finality has not progressed for longer than the stall threshold",
+                )?,
+                registry,
+            )?,
+            emergency_finalizer_changes: register(
+                Counter::new(
+                    "finalizer_monitor_emergency_finalizer_changes",
+                    "Number of times the emergency finalizer changed while monitoring",
+                )?,
+                registry,
+            )?,
+        })
+    }
+}
+
+struct Monitor {
+    connections: Connections,
+    config: MonitorConfig,
+    metrics: Metrics,
+    finalized: Option<BlockNumber>,
+    last_progress: Instant,
+    plan_prepared: bool,
+    emergency_finalizer: Option<Option<[u8; 32]>>,
+}
+
+impl Monitor {
+    /// Polls the endpoints once. Returns whether to offer preparing a finalization plan.
+    async fn poll(&mut self) -> bool {
+        let statuses = self.poll_statuses().await;
+        self.check_divergence(&statuses).await;
+        let offer_plan = match statuses.get(PRIMARY) {
+            Some(primary) => self.check_stall(primary.finalized.num),
+            None => false,
+        };
+        self.check_emergency_finalizer().await;
+        offer_plan
+    }
+
+    /// Reads the status of every endpoint, leaving out the ones that did not respond.
+    async fn poll_statuses(&self) -> BTreeMap<String, ChainStatus> {
+        let mut statuses = BTreeMap::new();
+        for (name, connection) in self.endpoints() {
+            match get_chain_status(connection).await {
+                Ok(status) => {
+                    self.metrics
+                        .best_block
+                        .with_label_values(&[name])
+                        .set(status.best.num.into());
+                    self.metrics
+                        .finalized_block
+                        .with_label_values(&[name])
+                        .set(status.finalized.num.into());
+                    self.metrics.endpoint_up.with_label_values(&[name]).set(1);
+                    statuses.insert(name.to_string(), status);
+                }
+                Err(e) => {
+                    println!("Failed to get the status of {name}: {e:?}");
+                    self.metrics.endpoint_up.with_label_values(&[name]).set(0);
+                }
+            }
+        }
+        statuses
+    }
+
+    /// Compares the hash of every endpoint with the primary at the lower of their finalized
+    /// numbers, so that endpoints lagging behind are not reported.
+    async fn check_divergence(&self, statuses: &BTreeMap<String, ChainStatus>) {
+        let primary = match statuses.get(PRIMARY) {
+            Some(primary) => &primary.finalized,
+            None => return,
+        };
+        for (name, connection) in self.connections.secondaries.iter() {
+            let secondary = match statuses.get(name) {
+                Some(secondary) => &secondary.finalized,
+                None => continue,
+            };
+            let num = primary.num.min(secondary.num);
+            let blocks = match (
+                self.finalized_at(&self.connections.primary, primary, num)
+                    .await,
+                self.finalized_at(connection, secondary, num).await,
+            ) {
+                (Ok(expected), Ok(actual)) => (expected, actual),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("Failed to compare finalized blocks of primary and {name}: {e:?}");
+                    continue;
+                }
+            };
+            let diverged = blocks.0 != blocks.1;
+            if diverged {
+                println!(
+                    "Finalized blocks of primary and {} diverged: {} vs {}",
+                    name, blocks.0, blocks.1
+                );
+            }
+            self.metrics
+                .finalized_diverged
+                .with_label_values(&[name])
+                .set(diverged.into());
+        }
+    }
+
+    async fn finalized_at(
+        &self,
+        connection: &Connection,
+        finalized: &HashNum,
+        num: BlockNumber,
+    ) -> Result<HashNum> {
+        if finalized.num == num {
+            return Ok(HashNum {
+                num,
+                hash: finalized.hash,
+            });
+        }
+        get_block_at_num(connection, num).await
+    }
+
+    fn check_stall(&mut self, finalized: BlockNumber) -> bool {
+        if self.finalized.map_or(true, |previous| finalized > previous) {
+            if self.plan_prepared {
+                println!("Finality progressed to {finalized}.");
+            }
+            self.finalized = Some(finalized);
+            self.last_progress = Instant::now();
+            self.plan_prepared = false;
+        }
+
+        let stalled_for = self.last_progress.elapsed();
+        self.metrics
+            .seconds_since_finalized
+            .set(stalled_for.as_secs_f64());
+        let stalled = stalled_for >= self.config.stall_threshold;
+        self.metrics.finality_stalled.set(stalled.into());
+        if !stalled {
+            return false;
+        }
+        println!(
+            "Finality stalled at {} for {}s.",
+            finalized,
+            stalled_for.as_secs()
+        );
+
+        match self.config.plan_after {
+            Some(plan_after) if stalled_for >= plan_after && !self.plan_prepared => {
+                self.plan_prepared = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Asks the operator whether to prepare a plan, without blocking the polling meanwhile.
+    fn offer_plan(&self) -> JoinHandle<std::io::Result<bool>> {
+        let how_many = self.config.plan_blocks;
+        tokio::task::spawn_blocking(move || {
+            Confirm::new()
+                .with_prompt(format!(
+                    "Prepare a plan for finalizing the next {how_many} blocks?"
+                ))
+                .default(false)
+                .interact()
+        })
+    }
+
+    /// Runs the checks of `try-finalize` and prints what it would do. Nothing is sent.
+    async fn prepare_plan(&self) -> Result<()> {
+        let how_many = self.config.plan_blocks;
+        let plan = pre_sequence_finalization_check(&self.connections, how_many).await?;
+        println!(
+            "Finalizing blocks from {} to {} (last hash {}) would be possible. \
+             To do so, run `try-finalize --how-many {}` against the same endpoints.",
+            plan.finalized_base.num + 1,
+            plan.target.num,
+            hex::encode(plan.target.hash),
+            plan.target.num - plan.finalized_base.num,
+        );
+        Ok(())
+    }
+
+    async fn check_emergency_finalizer(&mut self) {
+        let finalizer = match get_finalizer_pubkey(&self.connections.primary).await {
+            Ok(finalizer) => finalizer.map(|key| key.0 .0),
+            Err(e) => {
+                println!("Failed to read the emergency finalizer: {e:?}");
+                return;
+            }
+        };
+        match self.emergency_finalizer {
+            Some(previous) if previous != finalizer => {
+                println!(
+                    "Emergency finalizer changed from {} to {}",
+                    pretty_key(previous),
+                    pretty_key(finalizer)
+                );
+                self.metrics.emergency_finalizer_changes.inc();
+            }
+            None => println!("Emergency finalizer is {}", pretty_key(finalizer)),
+            _ => {}
+        }
+        self.emergency_finalizer = Some(finalizer);
+    }
+
+    fn endpoints(&self) -> impl Iterator<Item = (&str, &Connection)> {
+        std::iter::once((PRIMARY, &self.connections.primary)).chain(
+            self.connections
+                .secondaries
+                .iter()
+                .map(|(name, connection)| (name.as_str(), connection)),
+        )
+    }
+}
+
+fn pretty_key(key: Option<[u8; 32]>) -> String {
+    key.map(hex::encode)
+        .unwrap_or_else(|| "not set".to_string())
+}
+
+pub async fn monitor(connections: Connections, config: MonitorConfig) -> Result<()> {
+    if config.poll_interval.is_zero() {
+        return Err(anyhow::anyhow!(
+            "The poll interval has to be at least a second."
+        ));
+    }
+    let registry = Registry::new_custom(None, None)?;
+    let metrics = Metrics::new(&registry)?;
+    let prometheus_address = config.prometheus_address;
+    println!("Serving metrics at http://{prometheus_address}/metrics");
+    tokio::spawn(async move {
+        if let Err(e) = prometheus_endpoint::init_prometheus(prometheus_address, registry).await {
+            println!("Prometheus endpoint failed: {e:?}");
+        }
+    });
+
+    let mut monitor = Monitor {
+        connections,
+        config,
+        metrics,
+        finalized: None,
+        last_progress: Instant::now(),
+        plan_prepared: false,
+        emergency_finalizer: None,
+    };
+    let mut interval = tokio::time::interval(monitor.config.poll_interval);
+    let mut offered_plan: Option<JoinHandle<std::io::Result<bool>>> = None;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if monitor.poll().await && offered_plan.is_none() {
+                    offered_plan = Some(monitor.offer_plan());
+                }
+            }
+            answer = async { offered_plan.as_mut().expect("checked by the precondition").await },
+                if offered_plan.is_some() =>
+            {
+                offered_plan = None;
+                match answer {
+                    Ok(Ok(true)) => {
+                        if let Err(e) = monitor.prepare_plan().await {
+                            println!("Failed to prepare a finalization plan: {e:?}");
+                        }
+                    }
+                    Ok(Ok(false)) => {
+                        println!("Not preparing a plan until finality progresses again.")
+                    }
+                    Ok(Err(e)) => println!("Failed to ask about a finalization plan: {e:?}"),
+                    Err(e) => println!("Failed to ask about a finalization plan: {e:?}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, Result};
+use dialoguer::Confirm;
+use selendra_client::{
+    codec::Encode,
+    sp_core::{
+        ed25519::{Public, Signature},
+        H256,
+    },
+    AlephKeyPair, BlockNumber, Pair,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    time::timeout,
+};
+
+use crate::audit::AuditLog;
+
+/// Source of emergency finalizer signatures.
+///
+/// A remote signer is a separate process, see [`serve`], answering line-based requests over a
+/// Unix socket:
+/// - `public` is answered with `ok <hex public key>`,
+/// - `sign <number> <hex hash>` is answered with `ok <hex signature of the encoded hash>`,
+///
+/// and any failure with `error <message>`.
+///
+/// The remote signer only signs after its operator confirms the block number and hash, so they
+/// should be checked against the chain before confirming.
+pub enum Signer {
+    Local(AlephKeyPair),
+    Remote(PathBuf),
+}
+
+impl Signer {
+    pub async fn public(&self) -> Result<[u8; 32]> {
+        match self {
+            Signer::Local(key) => Ok(key.public().0),
+            Signer::Remote(socket_path) => {
+                let public = request(socket_path, "public".to_string()).await?;
+                decode_array(&public)
+            }
+        }
+    }
+
+    /// Signs the hash of a block, checking signatures of a remote signer against its public key.
+    pub async fn sign(&self, num: BlockNumber, hash: &H256) -> Result<[u8; 64]> {
+        match self {
+            Signer::Local(key) => Ok(key.sign(&hash.encode()).0),
+            Signer::Remote(socket_path) => {
+                let request_line = format!("sign {} {}", num, hex::encode(hash));
+                let signature = decode_array(&request(socket_path, request_line).await?)?;
+                let public = self.public().await?;
+                if !AlephKeyPair::verify(
+                    &Signature::from_raw(signature),
+                    hash.encode(),
+                    &Public::from_raw(public),
+                ) {
+                    return Err(anyhow!(
+                        "The remote signer returned an invalid signature for {}",
+                        hex::encode(hash)
+                    ));
+                }
+                Ok(signature)
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Signer::Local(_) => "local".to_string(),
+            Signer::Remote(socket_path) => format!("remote:{}", socket_path.display()),
+        }
+    }
+}
+
+async fn request(socket_path: &PathBuf, request_line: String) -> Result<String> {
+    let stream = UnixStream::connect(socket_path).await.map_err(|e| {
+        anyhow!(
+            "Failed to connect to the signer at {}: {}",
+            socket_path.display(),
+            e
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{request_line}\n").as_bytes())
+        .await?;
+
+    let mut response = String::new();
+    BufReader::new(reader).read_line(&mut response).await?;
+    match response.trim().split_once(' ') {
+        Some(("ok", value)) => Ok(value.to_string()),
+        Some(("error", message)) => Err(anyhow!("The signer refused: {}", message)),
+        _ => Err(anyhow!("Unexpected signer response {:?}", response)),
+    }
+}
+
+fn decode_array<const N: usize>(value: &str) -> Result<[u8; N]> {
+    hex::decode(value)?
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("Expected {} bytes, got {}", N, bytes.len()))
+}
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs a signer answering requests of [`Signer::Remote`] on a Unix socket, only accessible to
+/// the user running it. Every signature has to be confirmed by the operator, and is recorded in
+/// the audit log before it is returned. Connections are handled one at a time.
+pub async fn serve(key: AlephKeyPair, socket_path: PathBuf, audit_log: AuditLog) -> Result<()> {
+    if socket_path.exists() {
+        return Err(anyhow!(
+            "{} already exists, remove it if no signer is running",
+            socket_path.display()
+        ));
+    }
+    let listener = UnixListener::bind(&socket_path)?;
+    fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o600))?;
+    println!(
+        "Signing as {} at {}",
+        hex::encode(key.public().0),
+        socket_path.display()
+    );
+
+    let describe = format!("served:{}", socket_path.display());
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                println!("Failed to accept a connection: {e:?}");
+                continue;
+            }
+        };
+        if let Err(e) = handle(stream, &key, &audit_log, &describe).await {
+            println!("Failed to handle a request: {e:?}");
+        }
+    }
+}
+
+async fn handle(
+    stream: UnixStream,
+    key: &AlephKeyPair,
+    audit_log: &AuditLog,
+    describe: &str,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut request_line = String::new();
+    timeout(
+        READ_TIMEOUT,
+        BufReader::new(reader).read_line(&mut request_line),
+    )
+    .await
+    .map_err(|_| anyhow!("No request within {:?}", READ_TIMEOUT))??;
+
+    let (key, audit_log, describe) = (key.clone(), audit_log.clone(), describe.to_string());
+    let request_line = request_line.trim().to_string();
+    let response = tokio::task::spawn_blocking(move || {
+        match answer(&key, &audit_log, &describe, &request_line, confirm) {
+            Ok(value) => format!("ok {value}\n"),
+            Err(e) => {
+                println!("Refused {request_line:?}: {e}");
+                format!("error {e}\n")
+            }
+        }
+    })
+    .await?;
+    writer.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Asks the operator whether to sign the given block.
+fn confirm(num: BlockNumber, hash: &H256) -> Result<bool> {
+    Ok(Confirm::new()
+        .with_prompt(format!(
+            "Sign the emergency finalization of block {} {}?",
+            num,
+            hex::encode(hash)
+        ))
+        .default(false)
+        .interact()?)
+}
+
+fn answer(
+    key: &AlephKeyPair,
+    audit_log: &AuditLog,
+    describe: &str,
+    request_line: &str,
+    confirm: impl FnOnce(BlockNumber, &H256) -> Result<bool>,
+) -> Result<String> {
+    let mut words = request_line.split(' ');
+    match (words.next(), words.next(), words.next(), words.next()) {
+        (Some("public"), None, None, None) => Ok(hex::encode(key.public().0)),
+        (Some("sign"), Some(num), Some(hash), None) => {
+            let num: BlockNumber = num.parse()?;
+            let hash = H256(decode_array(hash)?);
+            if !confirm(num, &hash)? {
+                return Err(anyhow!("Not confirmed by the operator"));
+            }
+            let signature = key.sign(&hash.encode()).0;
+            audit_log.signed(num, &hash, &key.public().0, &signature, describe)?;
+            println!("Signed block {} {}", num, hex::encode(hash));
+            Ok(hex::encode(signature))
+        }
+        _ => Err(anyhow!("Unknown request")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use selendra_client::{aleph_keypair_from_string, sp_core::H256, AlephKeyPair, Pair};
+    use tempfile::TempDir;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::UnixListener,
+    };
+
+    use super::{answer, decode_array, request};
+    use crate::audit::AuditLog;
+
+    const HASH: H256 = H256([7; 32]);
+
+    fn setup() -> (TempDir, AlephKeyPair, AuditLog) {
+        let dir = TempDir::new().unwrap();
+        let audit_log = AuditLog::new(dir.path().join("audit.log"));
+        (dir, aleph_keypair_from_string("//Alice"), audit_log)
+    }
+
+    fn audit_entries(dir: &TempDir) -> String {
+        fs::read_to_string(dir.path().join("audit.log")).unwrap_or_default()
+    }
+
+    #[test]
+    fn answers_public_key() {
+        let (_dir, key, audit_log) = setup();
+
+        let public = answer(&key, &audit_log, "served:test", "public", |_, _| {
+            panic!("nothing to confirm")
+        })
+        .unwrap();
+
+        assert_eq!(public, hex::encode(key.public().0));
+    }
+
+    #[test]
+    fn signs_confirmed_block_and_records_it() {
+        let (dir, key, audit_log) = setup();
+        let request_line = format!("sign 12 {}", hex::encode(HASH));
+
+        let signature = answer(
+            &key,
+            &audit_log,
+            "served:test",
+            &request_line,
+            |num, hash| {
+                assert_eq!((num, *hash), (12, HASH));
+                Ok(true)
+            },
+        )
+        .unwrap();
+
+        let signature = decode_array(&signature).unwrap();
+        assert_eq!(signature, key.sign(&HASH.0).0);
+        let entries = audit_entries(&dir);
+        assert!(entries.contains("signed number=12"));
+        assert!(entries.contains("signer=served:test"));
+    }
+
+    #[test]
+    fn refuses_unconfirmed_block() {
+        let (dir, key, audit_log) = setup();
+        let request_line = format!("sign 12 {}", hex::encode(HASH));
+
+        assert!(
+            answer(&key, &audit_log, "served:test", &request_line, |_, _| Ok(
+                false
+            ))
+            .is_err()
+        );
+        assert!(audit_entries(&dir).is_empty());
+    }
+
+    #[test]
+    fn refuses_malformed_requests() {
+        let (dir, key, audit_log) = setup();
+
+        for request_line in [
+            "",
+            "public key",
+            "sign 12",
+            "sign twelve 0707",
+            "sign 12 0707",
+            &format!("sign 12 {} more", hex::encode(HASH)),
+        ] {
+            let answered = answer(&key, &audit_log, "served:test", request_line, |_, _| {
+                Ok(true)
+            });
+            assert!(answered.is_err(), "{request_line:?} was answered");
+        }
+        assert!(audit_entries(&dir).is_empty());
+    }
+
+    #[test]
+    fn decodes_arrays_of_expected_length_only() {
+        assert_eq!(decode_array::<2>("0aff").unwrap(), [10, 255]);
+        assert!(decode_array::<3>("0aff").is_err());
+        assert!(decode_array::<2>("0aff00").is_err());
+        assert!(decode_array::<1>("zz").is_err());
+    }
+
+    /// Answers a single request on a socket with the given response.
+    fn respond_once(dir: &TempDir, response: &'static str) -> PathBuf {
+        let socket_path = dir.path().join("signer.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut request_line = String::new();
+            BufReader::new(reader)
+                .read_line(&mut request_line)
+                .await
+                .unwrap();
+            assert_eq!(request_line, "public\n");
+            writer.write_all(response.as_bytes()).await.unwrap();
+        });
+        socket_path
+    }
+
+    #[tokio::test]
+    async fn request_returns_value_of_ok_response() {
+        let dir = TempDir::new().unwrap();
+        let socket_path = respond_once(&dir, "ok 0aff\n");
+
+        assert_eq!(
+            request(&socket_path, "public".to_string()).await.unwrap(),
+            "0aff"
+        );
+    }
+
+    #[tokio::test]
+    async fn request_fails_on_error_response() {
+        let dir = TempDir::new().unwrap();
+        let socket_path = respond_once(&dir, "error Not confirmed by the operator\n");
+
+        let error = request(&socket_path, "public".to_string())
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Not confirmed by the operator"));
+    }
+
+    #[tokio::test]
+    async fn request_fails_on_unexpected_response() {
+        let dir = TempDir::new().unwrap();
+        let socket_path = respond_once(&dir, "maybe\n");
+
+        assert!(request(&socket_path, "public".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn request_fails_without_signer() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(
+            request(&dir.path().join("signer.sock"), "public".to_string())
+                .await
+                .is_err()
+        );
+    }
+}
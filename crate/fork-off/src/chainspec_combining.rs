@@ -27,12 +27,52 @@ impl Index<&StoragePath> for PathCounter {
     }
 }
 
-/// Combines states - ommiting child state as we assume that it is empty in initial chainspec
+fn is_child_storage_key(key: &StorageKey) -> bool {
+    well_known_keys::is_child_storage_key(&Into::<Vec<u8>>::into(key))
+}
+
+/// Puts the state fetched under `fetched_prefixes` on top of the initial state, replacing
+/// whatever the initial chainspec has under these prefixes.
+fn overlay_states(
+    state: Storage,
+    mut initial_state: Storage,
+    fetched_prefixes: Vec<StorageKey>,
+) -> Storage {
+    let mut removed_count = 0;
+    initial_state.top.retain(|k, _v| {
+        let fetched = fetched_prefixes.iter().any(|prefix| prefix.is_prefix_of(k));
+        if fetched {
+            removed_count += 1;
+        }
+        !fetched
+    });
+    let added = state
+        .top
+        .into_iter()
+        .filter(|(k, _v)| !is_child_storage_key(k))
+        .collect::<Vec<_>>();
+    info!(
+        "Replaced {} entries of initial_spec by {} fetched entries",
+        removed_count,
+        added.len()
+    );
+    initial_state.top.extend(added);
+    initial_state.child_storage.extend(state.child_storage);
+    initial_state
+}
+
+/// Combines states - ommiting child state as we assume that it is empty in initial chainspec.
+/// A snapshot restricted to `fetched_prefixes` only holds these prefixes, so it is put on top of
+/// the initial state instead, and `storage_to_keep` does not apply.
 pub fn combine_states(
     mut state: Storage,
     initial_state: Storage,
     storage_to_keep: Vec<StoragePath>,
+    fetched_prefixes: Option<Vec<StorageKey>>,
 ) -> Storage {
+    if let Some(fetched_prefixes) = fetched_prefixes {
+        return overlay_states(state, initial_state, fetched_prefixes);
+    }
     let storage_prefixes = storage_to_keep
         .into_iter()
         .map(|path| (path.clone(), path.into()))
@@ -50,7 +90,7 @@ pub fn combine_states(
                 removed_per_path_count.bump(path);
                 false
             }
-            None => !well_known_keys::is_child_storage_key(&Into::<Vec<u8>>::into(k)), // remove child keys, they are not accepted as part of storage, see https://github.com/Cardinal-Cryptography/substrate/blob/98c2eeea74413044ae8ccfca1b6d56d01b57a76b/client/db/src/lib.rs#L778
+            None => !is_child_storage_key(k), // remove child keys, they are not accepted as part of storage, see https://github.com/Cardinal-Cryptography/substrate/blob/98c2eeea74413044ae8ccfca1b6d56d01b57a76b/client/db/src/lib.rs#L778
         }
     });
 
@@ -15,8 +15,9 @@ pub struct Config {
     #[clap(long, default_value = "./initial_chainspec.json")]
     pub initial_spec_path: String,
 
-    /// Where to write the snapshot of the current chain state.
-    #[clap(long, default_value = "./snapshot.json")]
+    /// Where to write the snapshot of the current chain state. An interrupted snapshot is resumed
+    /// when fetching again.
+    #[clap(long, default_value = "./snapshot.bin")]
     pub snapshot_path: String,
 
     /// Where to write the forked genesis chainspec.
@@ -27,6 +28,12 @@ pub struct Config {
     #[clap(long)]
     pub use_snapshot_file: bool,
 
+    /// Whether to fetch only the pallets in `storage_keep_state`, instead of the whole state. Such
+    /// a snapshot is put on top of the initial chainspec, so that these pallets keep the state of
+    /// the forked chain and everything else comes from the initial chainspec.
+    #[clap(long, conflicts_with = "use_snapshot_file")]
+    pub fetch_only_keep_state: bool,
+
     /// Maximum concurrent requests to use while downloading storage values.
     #[clap(long, default_value_t = 1000)]
     pub max_requests: u32,
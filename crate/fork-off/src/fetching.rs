@@ -1,20 +1,24 @@
-use std::{iter::repeat_with, sync::Arc};
-
-use async_channel::Receiver;
-use futures::{future::join_all, join};
+use anyhow::{anyhow, bail, Result};
+use futures::{stream, StreamExt, TryStreamExt};
+use jsonrpc_core_client::RpcError;
 use log::info;
-use parking_lot::Mutex;
 
 use crate::{
-    jsonrpc_client::Client,
-    types::{BlockHash, StorageKey},
-    Storage,
+    jsonrpc_client::{Client, RpcResult, CHUNK_SIZE},
+    snapshot::SnapshotWriter,
+    types::{BlockHash, ChildStorageMap, Get, StorageKey, StorageValue},
 };
 
 pub struct StateFetcher {
     client: Client,
 }
 
+type FetchedValue = (StorageKey, StorageValue, Option<ChildStorageMap>);
+
+fn rpc_error(e: RpcError) -> anyhow::Error {
+    anyhow!("RPC request failed: {:?}", e)
+}
+
 impl StateFetcher {
     pub async fn new(ws_rpc_endpoint: String) -> Self {
         StateFetcher {
@@ -22,66 +26,130 @@ impl StateFetcher {
         }
     }
 
-    async fn value_fetching_worker(
-        &self,
-        block: BlockHash,
-        input: Receiver<StorageKey>,
-        output: Arc<Mutex<Storage>>,
-    ) {
-        const LOG_PROGRESS_FREQUENCY: usize = 500;
+    async fn fetch_value(&self, key: StorageKey, block: &BlockHash) -> RpcResult<FetchedValue> {
+        let value = self.client.get_storage(key.clone(), block.clone()).await?;
+        let child_storage_map = self
+            .client
+            .get_child_storage_for_key(key.clone(), block)
+            .await?;
+        if let Some(child_storage_map) = &child_storage_map {
+            info!("Fetched child trie with {} keys", child_storage_map.len());
+        }
 
-        while let Ok(key) = input.recv().await {
-            let value = self
-                .client
-                .get_storage(key.clone(), block.clone())
-                .await
-                .unwrap();
+        Ok((key, value, child_storage_map))
+    }
 
-            let child_storage_map_res = self
+    /// Fetches all keys under `prefix`, continuing after `start_key`, checkpointing every page.
+    async fn fetch_prefix(
+        &self,
+        snapshot: &mut SnapshotWriter,
+        index: u32,
+        prefix: StorageKey,
+        mut start_key: Option<StorageKey>,
+        num_workers: u32,
+    ) -> Result<()> {
+        const LOG_PROGRESS_FREQUENCY: usize = 10;
+
+        let block = snapshot.block().clone();
+        info!("Fetching keys under {:?}", prefix);
+        let mut pages = 0;
+        loop {
+            let keys = self
                 .client
-                .get_child_storage_for_key(key.clone(), &block)
+                .get_keys_page(prefix.clone(), start_key.clone(), &block)
                 .await
-                .unwrap();
+                .map_err(rpc_error)?;
+            let fetched = keys.len();
 
-            let mut output_guard = output.lock();
-            output_guard.top.insert(key.clone(), value);
-            if let Some(child_storage_map) = child_storage_map_res {
-                info!("Fetched child trie with {} keys", child_storage_map.len());
-                output_guard
-                    .child_storage
-                    .insert(key.without_child_storage_prefix(), child_storage_map);
+            let values: Vec<FetchedValue> = stream::iter(keys)
+                .map(|key| self.fetch_value(key, &block))
+                .buffered(num_workers as usize)
+                .try_collect()
+                .await
+                .map_err(rpc_error)?;
+            if let Some((last_key, _, _)) = values.last() {
+                start_key = Some(last_key.clone());
+                let mut top = Vec::with_capacity(values.len());
+                let mut children = Vec::new();
+                for (key, value, child_storage_map) in values {
+                    if let Some(child_storage_map) = child_storage_map {
+                        children.push((
+                            key.clone().without_child_storage_prefix(),
+                            child_storage_map,
+                        ));
+                    }
+                    top.push((key, value));
+                }
+                snapshot.append_batch(index, start_key.clone().unwrap(), top, children)?;
             }
 
-            if output_guard.top.len() % LOG_PROGRESS_FREQUENCY == 0 {
-                info!("Fetched {} values", output_guard.top.len());
+            pages += 1;
+            if pages % LOG_PROGRESS_FREQUENCY == 0 {
+                info!("Fetched {} pages of values", pages);
+            }
+            if fetched < CHUNK_SIZE {
+                break;
             }
         }
-    }
-
-    async fn get_full_state_at_block(&self, block_hash: BlockHash, num_workers: u32) -> Storage {
-        info!("Fetching state at block {:?}", block_hash);
-
-        let (input, key_fetcher) = self.client.stream_all_keys(&block_hash);
-        let output = Arc::new(Mutex::new(Storage::default()));
-
-        let workers = repeat_with(|| {
-            self.value_fetching_worker(block_hash.clone(), input.clone(), output.clone())
-        })
-        .take(num_workers as usize);
-
-        info!("Started {} workers to download values.", num_workers);
-        let (res, _) = join!(key_fetcher, join_all(workers));
-        res.unwrap();
 
-        Arc::try_unwrap(output).unwrap().into_inner()
+        snapshot.finish_prefix(index)
     }
 
-    pub async fn get_full_state(&self, at_block: Option<BlockHash>, num_workers: u32) -> Storage {
-        let block = match at_block {
-            None => self.client.best_block().await.unwrap(),
-            Some(block) => block,
+    /// Fetches the state under `prefixes` into a binary snapshot at `path`, resuming it if it was
+    /// interrupted.
+    pub async fn fetch_snapshot(
+        &self,
+        path: &str,
+        at_block: Option<BlockHash>,
+        prefixes: Vec<StorageKey>,
+        num_workers: u32,
+    ) -> Result<()> {
+        let mut snapshot = match SnapshotWriter::resume(path)? {
+            Some(snapshot) => {
+                let snapshot_block = snapshot.block().clone().get();
+                if at_block.map_or(false, |block| block.get() != snapshot_block) {
+                    bail!(
+                        "The snapshot at {} is of block {:?}, remove it to fetch another block",
+                        path,
+                        snapshot.block()
+                    );
+                }
+                if snapshot.prefixes() != prefixes.as_slice() {
+                    bail!(
+                        "The snapshot at {} is of prefixes {:?}, remove it to fetch other ones",
+                        path,
+                        snapshot.prefixes()
+                    );
+                }
+                if snapshot.is_complete() {
+                    info!(
+                        "The snapshot at {} is complete, remove it to fetch the state again",
+                        path
+                    );
+                    return Ok(());
+                }
+                info!("Resuming the snapshot of block {:?}", snapshot.block());
+                snapshot
+            }
+            None => {
+                let block = match at_block {
+                    None => self.client.best_block().await.map_err(rpc_error)?,
+                    Some(block) => block,
+                };
+                info!("Fetching state at block {:?}", block);
+                SnapshotWriter::create(path, block, prefixes)?
+            }
         };
 
-        self.get_full_state_at_block(block, num_workers).await
+        info!(
+            "Using up to {} concurrent requests to download values.",
+            num_workers
+        );
+        for (index, prefix, start_key) in snapshot.unfinished() {
+            self.fetch_prefix(&mut snapshot, index, prefix, start_key, num_workers)
+                .await?;
+        }
+
+        snapshot.finish()
     }
 }
\ No newline at end of file
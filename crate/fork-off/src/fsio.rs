@@ -6,7 +6,7 @@ use std::{
 use log::info;
 use serde_json::Value;
 
-use crate::Storage;
+use crate::{snapshot, Storage, StorageKey};
 
 pub fn write_to_file(write_to_path: String, data: &[u8]) {
     let mut file = match fs::OpenOptions::new()
@@ -36,20 +36,18 @@ pub fn file_content(path: String) -> String {
     fs::read_to_string(&path).unwrap_or_else(|_| panic!("Could not read file: `{path}`"))
 }
 
-pub fn save_snapshot_to_file(snapshot: Storage, path: String) {
-    let data = serde_json::to_vec_pretty(&snapshot).unwrap();
-    info!(
-        "Writing snapshot of {} key-val pairs and {} total bytes",
-        snapshot.top.len(),
-        data.len()
-    );
-    write_to_file(path, &data);
-}
-
-pub fn read_snapshot_from_file(path: String) -> Storage {
-    let snapshot: Storage =
-        serde_json::from_str(&fs::read_to_string(path).expect("Could not read snapshot file"))
-            .expect("could not parse from snapshot");
+/// Reads a snapshot, either a binary one (see [`crate::snapshot`]) or a JSON one, depending on its
+/// content. Returns the key prefixes the snapshot is restricted to, `None` if it holds the whole
+/// state.
+pub fn read_snapshot_from_file(path: String) -> (Storage, Option<Vec<StorageKey>>) {
+    let data = fs::read(&path).expect("Could not read snapshot file");
+    let (snapshot, prefixes) = if snapshot::is_binary(&data) {
+        snapshot::read(&data).expect("could not parse from snapshot")
+    } else {
+        let snapshot: Storage =
+            serde_json::from_slice(&data).expect("could not parse from snapshot");
+        (snapshot, None)
+    };
     info!("Read snapshot of {} key-val pairs", snapshot.top.len());
-    snapshot
+    (snapshot, prefixes)
 }
\ No newline at end of file
@@ -1,6 +1,3 @@
-use std::future::Future;
-
-use async_channel::{bounded, Receiver, Sender};
 use backoff::{future::retry, ExponentialBackoff};
 use jsonrpc_core::Error;
 use jsonrpc_core_client::{transports::ws, RpcError};
@@ -48,7 +45,7 @@ pub trait Rpc {
     ) -> Result<Vec<StorageValue>, Error>;
 }
 
-type RpcResult<T> = Result<T, RpcError>;
+pub type RpcResult<T> = Result<T, RpcError>;
 
 /// A JSONRPC aleph client that uses a websocket transport.
 ///
@@ -58,8 +55,7 @@ pub struct Client {
     client: gen_client::Client,
 }
 
-const CHUNK_SIZE: usize = 1000usize;
-const STORAGE_CAP: usize = 10 * CHUNK_SIZE;
+pub const CHUNK_SIZE: usize = 1000usize;
 
 impl Client {
     /// Connect to the given websocket endpoint (eg. `"wss://ws.test.azero.dev"`).
@@ -74,19 +70,26 @@ impl Client {
         self.client.get_block_hash(None).await
     }
 
-    /// Fetchers all keys in the `at` block.
-    ///
-    /// Returns a `(Receiver<Storage>, fetcher)` pair. The `fetcher` must be `await`ed on to begin
-    /// fetching. Then, `StorageKeys` can be taken out of the `Receiver`.
-    pub fn stream_all_keys(
+    /// Fetch up to `CHUNK_SIZE` keys starting with `prefix` in the `at` block, following
+    /// `start_key` if given.
+    pub async fn get_keys_page(
         &self,
+        prefix: StorageKey,
+        start_key: Option<StorageKey>,
         at: &BlockHash,
-    ) -> (
-        Receiver<StorageKey>,
-        impl Future<Output = RpcResult<()>> + '_,
-    ) {
-        let (sender, receiver) = bounded(STORAGE_CAP);
-        (receiver, self.do_stream_all_keys(sender, at.clone()))
+    ) -> RpcResult<Vec<StorageKey>> {
+        retry(ExponentialBackoff::default(), || async {
+            self.client
+                .get_keys_paged(
+                    prefix.clone(),
+                    CHUNK_SIZE,
+                    start_key.clone(),
+                    Some(at.clone()),
+                )
+                .await
+                .map_err(backoff::Error::transient)
+        })
+        .await
     }
 
     /// Returns a map representing a single child trie
@@ -151,36 +154,6 @@ impl Client {
         Ok(child_storage_map)
     }
 
-    async fn do_stream_all_keys(&self, sender: Sender<StorageKey>, at: BlockHash) -> RpcResult<()> {
-        let empty_prefix = StorageKey::new("0x");
-        let mut start_key = None;
-
-        loop {
-            let keys = self
-                .client
-                .get_keys_paged(
-                    empty_prefix.clone(),
-                    CHUNK_SIZE,
-                    start_key,
-                    Some(at.clone()),
-                )
-                .await?;
-
-            let fetched = keys.len();
-            start_key = keys.last().cloned();
-
-            for key in keys {
-                sender.send(key).await.unwrap();
-            }
-
-            if fetched < CHUNK_SIZE {
-                break;
-            }
-        }
-
-        Ok(())
-    }
-
     /// Fetch the value under `key` in the `at` block.
     pub async fn get_storage(&self, key: StorageKey, at: BlockHash) -> RpcResult<StorageValue> {
         retry(ExponentialBackoff::default(), || async {
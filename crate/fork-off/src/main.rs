@@ -8,11 +8,8 @@ use crate::{
     chainspec_combining::combine_states,
     config::Config,
     fetching::StateFetcher,
-    fsio::{
-        file_content, read_json_from_file, read_snapshot_from_file, save_snapshot_to_file,
-        write_to_file,
-    },
-    types::Storage,
+    fsio::{file_content, read_json_from_file, read_snapshot_from_file, write_to_file},
+    types::{Storage, StorageKey},
 };
 
 mod account_setting;
@@ -21,6 +18,7 @@ mod config;
 mod fetching;
 mod fsio;
 mod jsonrpc_client;
+mod snapshot;
 mod types;
 
 #[tokio::main]
@@ -35,6 +33,7 @@ async fn main() -> anyhow::Result<()> {
         snapshot_path,
         combined_spec_path,
         use_snapshot_file,
+        fetch_only_keep_state,
         storage_keep_state,
         max_requests,
         accounts_path,
@@ -50,16 +49,22 @@ async fn main() -> anyhow::Result<()> {
     );
 
     if !use_snapshot_file {
+        let prefixes = if fetch_only_keep_state {
+            storage_keep_state.iter().cloned().map(Into::into).collect()
+        } else {
+            vec![StorageKey::new("0x")]
+        };
         let fetcher = StateFetcher::new(ws_rpc_endpoint).await;
-        let state = fetcher.get_full_state(at_block, max_requests).await;
-        save_snapshot_to_file(state, snapshot_path.clone());
+        fetcher
+            .fetch_snapshot(&snapshot_path, at_block, prefixes, max_requests)
+            .await?;
     }
-    let state = read_snapshot_from_file(snapshot_path);
+    let (state, fetched_prefixes) = read_snapshot_from_file(snapshot_path);
 
     // Initialize with state from chainspec + empty child storage
     let initial_state = Storage::new(&initial_spec);
 
-    let state = combine_states(state, initial_state, storage_keep_state);
+    let state = combine_states(state, initial_state, storage_keep_state, fetched_prefixes);
 
     let account_setting: AccountSetting = match accounts_path {
         Some(accounts_path) => serde_json::from_str(&file_content(accounts_path))
@@ -0,0 +1,471 @@
+//! Binary snapshot format, written incrementally so that an interrupted fetch can be resumed.
+//!
+//! A snapshot starts with `MAGIC`, followed by records, each of them SCALE encoded and prefixed
+//! with its length as a little endian `u32`:
+//!  - a `Header` with the block the state is fetched at and the fetched key prefixes,
+//!  - `Batch`es of values of consecutive keys of a prefix, each of them a checkpoint: fetching
+//!    resumes after its `last_key`,
+//!  - `PrefixDone` once all keys of a prefix are fetched,
+//!  - an `Index` with offsets of all batches, written once the snapshot is complete and followed
+//!    by its own offset and `INDEX_MAGIC`.
+//!
+//! A record cut short by an interruption, as well as an index without its footer, is discarded
+//! when resuming.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+};
+
+use anyhow::{anyhow, bail, Result};
+use codec::{Decode, Encode};
+use log::{info, warn};
+
+use crate::{
+    types::{BlockHash, ChildStorageMap, Get, StorageKey, StorageValue},
+    Storage,
+};
+
+const MAGIC: &[u8; 8] = b"FORKOFF1";
+const INDEX_MAGIC: &[u8; 8] = b"FOINDEX1";
+const FOOTER_LEN: usize = 8 + INDEX_MAGIC.len();
+
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+
+#[derive(Encode, Decode)]
+enum Record {
+    Header {
+        block: String,
+        prefixes: Vec<Vec<u8>>,
+    },
+    Batch {
+        prefix: u32,
+        last_key: Vec<u8>,
+        top: Entries,
+        children: Vec<(Vec<u8>, Entries)>,
+    },
+    PrefixDone {
+        prefix: u32,
+    },
+    Index {
+        batches: Vec<Vec<u64>>,
+    },
+}
+
+/// Whether `data` is a snapshot in the binary format, complete or not.
+pub fn is_binary(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Reads the record at `offset`, returning it with the offset of the next one.
+fn read_record(data: &[u8], offset: usize) -> Option<(Record, usize)> {
+    let len_end = offset.checked_add(4)?;
+    let len = u32::from_le_bytes(data.get(offset..len_end)?.try_into().ok()?) as usize;
+    let end = len_end.checked_add(len)?;
+    let record = Record::decode(&mut data.get(len_end..end)?).ok()?;
+    Some((record, end))
+}
+
+fn to_entries<'a>(map: impl IntoIterator<Item = (&'a StorageKey, &'a StorageValue)>) -> Entries {
+    map.into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
+fn from_entries(entries: Entries) -> impl Iterator<Item = (StorageKey, StorageValue)> {
+    entries.into_iter().map(|(key, value)| {
+        (
+            StorageKey::new(&hex::encode(key)),
+            StorageValue::new(&hex::encode(value)),
+        )
+    })
+}
+
+/// Reads a complete binary snapshot, visiting batches through the index. Returns the state
+/// together with the key prefixes the snapshot is restricted to, `None` if it holds the whole
+/// state.
+pub fn read(data: &[u8]) -> Result<(Storage, Option<Vec<StorageKey>>)> {
+    if !data.ends_with(INDEX_MAGIC) || data.len() < MAGIC.len() + FOOTER_LEN {
+        bail!("The snapshot is incomplete, fetch the state again to resume it");
+    }
+    let prefixes = match read_record(data, MAGIC.len()) {
+        Some((Record::Header { prefixes, .. }, _)) if prefixes == vec![Vec::new()] => None,
+        Some((Record::Header { prefixes, .. }, _)) => Some(
+            prefixes
+                .into_iter()
+                .map(|prefix| StorageKey::new(&hex::encode(prefix)))
+                .collect(),
+        ),
+        _ => bail!("The header of the snapshot is corrupted"),
+    };
+    let footer = &data[data.len() - FOOTER_LEN..];
+    let index_offset = u64::from_le_bytes(footer[..8].try_into()?) as usize;
+    let batches = match read_record(data, index_offset) {
+        Some((Record::Index { batches }, _)) => batches,
+        _ => bail!("The index of the snapshot is corrupted"),
+    };
+
+    let mut storage = Storage::default();
+    for offset in batches.into_iter().flatten() {
+        match read_record(data, offset as usize) {
+            Some((Record::Batch { top, children, .. }, _)) => {
+                storage.top.extend(from_entries(top));
+                for (key, entries) in children {
+                    storage.child_storage.insert(
+                        StorageKey::new(&hex::encode(key)),
+                        from_entries(entries).collect(),
+                    );
+                }
+            }
+            _ => bail!("The snapshot has no batch at offset {}", offset),
+        }
+    }
+    Ok((storage, prefixes))
+}
+
+#[derive(Default)]
+struct Progress {
+    last_key: Option<StorageKey>,
+    done: bool,
+    batches: Vec<u64>,
+}
+
+/// Appends fetched values to a binary snapshot, keeping track of what is left to fetch.
+pub struct SnapshotWriter {
+    file: File,
+    offset: u64,
+    block: BlockHash,
+    prefixes: Vec<StorageKey>,
+    progress: Vec<Progress>,
+    complete: bool,
+}
+
+impl SnapshotWriter {
+    /// Opens the binary snapshot at `path` to continue fetching it. Returns `None` if there is no
+    /// file at `path`, and fails if there is one that is not a binary snapshot.
+    pub fn resume(path: &str) -> Result<Option<Self>> {
+        let data = match fs::read(path) {
+            Ok(data) if is_binary(&data) => data,
+            Ok(_) => bail!(
+                "{} is not a binary snapshot, remove it or pass --use-snapshot-file to use it",
+                path
+            ),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let (block, prefixes, mut offset) = match read_record(&data, MAGIC.len()) {
+            Some((Record::Header { block, prefixes }, offset)) => (block, prefixes, offset),
+            _ => {
+                warn!("{} has no valid header, it will be overwritten", path);
+                return Ok(None);
+            }
+        };
+        let prefixes: Vec<_> = prefixes
+            .into_iter()
+            .map(|prefix| StorageKey::new(&hex::encode(prefix)))
+            .collect();
+        let mut progress: Vec<_> = prefixes.iter().map(|_| Progress::default()).collect();
+        let complete = data.ends_with(INDEX_MAGIC);
+
+        while !complete {
+            let (record, next) = match read_record(&data, offset) {
+                Some(record) => record,
+                None => break,
+            };
+            match record {
+                Record::Batch {
+                    prefix, last_key, ..
+                } => {
+                    let progress = progress
+                        .get_mut(prefix as usize)
+                        .ok_or_else(|| anyhow!("Unknown prefix {} in the snapshot", prefix))?;
+                    progress.last_key = Some(StorageKey::new(&hex::encode(last_key)));
+                    progress.batches.push(offset as u64);
+                }
+                Record::PrefixDone { prefix } => {
+                    progress
+                        .get_mut(prefix as usize)
+                        .ok_or_else(|| anyhow!("Unknown prefix {} in the snapshot", prefix))?
+                        .done = true;
+                }
+                // Only the footer is missing, the index is written again when finishing.
+                Record::Index { .. } => break,
+                _ => bail!("Unexpected record at offset {} of the snapshot", offset),
+            }
+            offset = next;
+        }
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        if !complete && offset < data.len() {
+            warn!(
+                "Discarding {} bytes of an interrupted write",
+                data.len() - offset
+            );
+            file.set_len(offset as u64)?;
+        }
+        let offset = if complete { data.len() } else { offset } as u64;
+
+        Ok(Some(SnapshotWriter {
+            file,
+            offset,
+            block: block
+                .parse()
+                .map_err(|e| anyhow!("Invalid block in the snapshot: {}", e))?,
+            prefixes,
+            progress,
+            complete,
+        }))
+    }
+
+    /// Starts a new snapshot at `path` of keys under `prefixes` at `block`.
+    pub fn create(path: &str, block: BlockHash, prefixes: Vec<StorageKey>) -> Result<Self> {
+        let mut file = File::create(path)?;
+        file.write_all(MAGIC)?;
+        let mut writer = SnapshotWriter {
+            file,
+            offset: MAGIC.len() as u64,
+            block: block.clone(),
+            progress: prefixes.iter().map(|_| Progress::default()).collect(),
+            prefixes: prefixes.clone(),
+            complete: false,
+        };
+        writer.append(&Record::Header {
+            block: block.get(),
+            prefixes: prefixes.iter().map(Into::into).collect(),
+        })?;
+        Ok(writer)
+    }
+
+    pub fn block(&self) -> &BlockHash {
+        &self.block
+    }
+
+    pub fn prefixes(&self) -> &[StorageKey] {
+        &self.prefixes
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Prefixes not fetched yet, with the key to continue after.
+    pub fn unfinished(&self) -> Vec<(u32, StorageKey, Option<StorageKey>)> {
+        self.prefixes
+            .iter()
+            .zip(self.progress.iter())
+            .enumerate()
+            .filter(|(_, (_, progress))| !progress.done)
+            .map(|(index, (prefix, progress))| {
+                (index as u32, prefix.clone(), progress.last_key.clone())
+            })
+            .collect()
+    }
+
+    fn append(&mut self, record: &Record) -> Result<u64> {
+        let encoded = record.encode();
+        let mut data = (encoded.len() as u32).to_le_bytes().to_vec();
+        data.extend(encoded);
+        self.file.write_all(&data)?;
+        self.file.sync_data()?;
+
+        let offset = self.offset;
+        self.offset += data.len() as u64;
+        Ok(offset)
+    }
+
+    /// Checkpoints values of consecutive keys of a prefix, up to and including `last_key`.
+    pub fn append_batch(
+        &mut self,
+        prefix: u32,
+        last_key: StorageKey,
+        top: Vec<(StorageKey, StorageValue)>,
+        children: Vec<(StorageKey, ChildStorageMap)>,
+    ) -> Result<()> {
+        let offset = self.append(&Record::Batch {
+            prefix,
+            last_key: (&last_key).into(),
+            top: to_entries(top.iter().map(|(key, value)| (key, value))),
+            children: children
+                .iter()
+                .map(|(key, map)| (key.into(), to_entries(map)))
+                .collect(),
+        })?;
+        let progress = &mut self.progress[prefix as usize];
+        progress.last_key = Some(last_key);
+        progress.batches.push(offset);
+        Ok(())
+    }
+
+    pub fn finish_prefix(&mut self, prefix: u32) -> Result<()> {
+        self.append(&Record::PrefixDone { prefix })?;
+        self.progress[prefix as usize].done = true;
+        Ok(())
+    }
+
+    /// Writes the index, once all prefixes are fetched.
+    pub fn finish(mut self) -> Result<()> {
+        if self.progress.iter().any(|progress| !progress.done) {
+            bail!("Not all prefixes of the snapshot are fetched");
+        }
+        let batches = self
+            .progress
+            .iter()
+            .map(|progress| progress.batches.clone())
+            .collect();
+        let index_offset = self.append(&Record::Index { batches })?;
+        self.file.write_all(&index_offset.to_le_bytes())?;
+        self.file.write_all(INDEX_MAGIC)?;
+        self.file.sync_data()?;
+        info!(
+            "Snapshot complete, {} bytes",
+            self.offset + FOOTER_LEN as u64
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::{read, SnapshotWriter, FOOTER_LEN};
+    use crate::types::{BlockHash, ChildStorageMap, StorageKey, StorageValue, TopStorage};
+
+    fn key(byte: u8) -> StorageKey {
+        StorageKey::new(&hex::encode([byte; 4]))
+    }
+
+    fn value(byte: u8) -> StorageValue {
+        StorageValue::new(&hex::encode([byte; 2]))
+    }
+
+    fn block() -> BlockHash {
+        "0x0101".parse().unwrap()
+    }
+
+    /// Writes a snapshot of the whole state in two batches, without finishing it.
+    fn write_batches(path: &str) -> SnapshotWriter {
+        let mut snapshot = SnapshotWriter::create(path, block(), vec![StorageKey::new("0x")])
+            .expect("snapshot should be created");
+        snapshot
+            .append_batch(
+                0,
+                key(2),
+                vec![(key(1), value(1)), (key(2), value(2))],
+                vec![],
+            )
+            .unwrap();
+        let child = ChildStorageMap::from([(key(9), value(9))]);
+        snapshot
+            .append_batch(0, key(3), vec![(key(3), value(3))], vec![(key(3), child)])
+            .unwrap();
+        snapshot
+    }
+
+    fn finish(mut snapshot: SnapshotWriter) {
+        snapshot.finish_prefix(0).unwrap();
+        snapshot.finish().unwrap();
+    }
+
+    fn path(dir: &TempDir) -> String {
+        dir.path()
+            .join("snapshot.bin")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn assert_holds_all_batches(path: &str) {
+        let (storage, prefixes) =
+            read(&fs::read(path).unwrap()).expect("snapshot should be complete");
+        assert!(prefixes.is_none());
+        assert_eq!(
+            storage.top,
+            [1, 2, 3]
+                .into_iter()
+                .map(|byte| (key(byte), value(byte)))
+                .collect::<TopStorage>()
+        );
+        assert_eq!(storage.child_storage.len(), 1);
+        assert_eq!(storage.child_storage[&key(3)][&key(9)], value(9));
+    }
+
+    #[test]
+    fn written_snapshot_reads_back() {
+        let dir = TempDir::new().unwrap();
+        finish(write_batches(&path(&dir)));
+
+        assert_holds_all_batches(&path(&dir));
+        let snapshot = SnapshotWriter::resume(&path(&dir)).unwrap().unwrap();
+        assert!(snapshot.is_complete());
+        assert!(snapshot.unfinished().is_empty());
+    }
+
+    #[test]
+    fn cut_off_record_is_discarded_on_resume() {
+        let dir = TempDir::new().unwrap();
+        drop(write_batches(&path(&dir)));
+        let data = fs::read(path(&dir)).unwrap();
+        fs::write(path(&dir), &data[..data.len() - 3]).unwrap();
+
+        let mut snapshot = SnapshotWriter::resume(&path(&dir)).unwrap().unwrap();
+        assert!(!snapshot.is_complete());
+        assert_eq!(
+            snapshot.unfinished(),
+            vec![(0, StorageKey::new("0x"), Some(key(2)))]
+        );
+        snapshot
+            .append_batch(
+                0,
+                key(3),
+                vec![(key(3), value(3))],
+                vec![(key(3), ChildStorageMap::from([(key(9), value(9))]))],
+            )
+            .unwrap();
+        finish(snapshot);
+
+        assert_holds_all_batches(&path(&dir));
+    }
+
+    #[test]
+    fn cut_off_footer_is_written_again_on_resume() {
+        let dir = TempDir::new().unwrap();
+        finish(write_batches(&path(&dir)));
+        let data = fs::read(path(&dir)).unwrap();
+        fs::write(path(&dir), &data[..data.len() - FOOTER_LEN + 2]).unwrap();
+        assert!(read(&fs::read(path(&dir)).unwrap()).is_err());
+
+        let snapshot = SnapshotWriter::resume(&path(&dir)).unwrap().unwrap();
+        assert!(!snapshot.is_complete());
+        assert!(snapshot.unfinished().is_empty());
+        snapshot.finish().unwrap();
+
+        assert_holds_all_batches(&path(&dir));
+    }
+
+    #[test]
+    fn partial_snapshot_reads_back_with_its_prefixes() {
+        let dir = TempDir::new().unwrap();
+        let mut snapshot = SnapshotWriter::create(&path(&dir), block(), vec![key(1)]).unwrap();
+        snapshot
+            .append_batch(0, key(1), vec![(key(1), value(1))], vec![])
+            .unwrap();
+        finish(snapshot);
+
+        let (storage, prefixes) = read(&fs::read(path(&dir)).unwrap()).unwrap();
+        assert_eq!(prefixes, Some(vec![key(1)]));
+        assert_eq!(storage.top, TopStorage::from([(key(1), value(1))]));
+    }
+
+    #[test]
+    fn json_snapshot_is_not_overwritten() {
+        let dir = TempDir::new().unwrap();
+        fs::write(path(&dir), "{}").unwrap();
+
+        assert!(SnapshotWriter::resume(&path(&dir)).is_err());
+        assert_eq!(fs::read_to_string(path(&dir)).unwrap(), "{}");
+    }
+}
\ No newline at end of file
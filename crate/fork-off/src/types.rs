@@ -162,6 +162,12 @@ impl StorageValue {
     }
 }
 
+impl From<&StorageValue> for Vec<u8> {
+    fn from(v: &StorageValue) -> Self {
+        hex::decode(strip_hex(&v.0)).expect("Could not decode hex value")
+    }
+}
+
 impl Get for StorageValue {
     fn get(self) -> String {
         as_hex(&self.0)
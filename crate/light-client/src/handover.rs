@@ -0,0 +1,219 @@
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use parity_scale_codec::Decode;
+use selendra_primitives::{AuthorityId, BlockHash, SessionAuthorityData, SessionPeriodChange};
+use sp_core::hashing::twox_128;
+use sp_runtime::traits::BlakeTwo256;
+use sp_std::vec::Vec;
+use sp_trie::{read_trie_value, LayoutV1, StorageProof};
+
+/// Name under which the aleph pallet is included in the tracked runtime.
+pub const ALEPH_PALLET_NAME: &[u8] = b"Aleph";
+/// Storage item of the aleph pallet holding the authorities of the next session.
+pub const NEXT_AUTHORITIES_ITEM: &[u8] = b"NextAuthorities";
+/// Storage item of the aleph pallet holding the emergency finalizer of the next session.
+pub const QUEUED_EMERGENCY_FINALIZER_ITEM: &[u8] = b"QueuedEmergencyFinalizer";
+/// Storage item of the aleph pallet holding the scheduled change of the session period.
+pub const SESSION_PERIOD_SCHEDULED_CHANGE_ITEM: &[u8] = b"SessionPeriodScheduledChange";
+
+/// What the last block of a session commits to about the next session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionHandover {
+	/// Authority data of the next session.
+	pub authority_data: SessionAuthorityData,
+	/// The scheduled change of the session period, if any. Changes are applied at the start of
+	/// the session they are scheduled for, so a change for the next session is still pending in
+	/// the state of the last block of the current one.
+	pub scheduled_period_change: Option<SessionPeriodChange>,
+}
+
+impl SessionHandover {
+	/// The number of blocks in the session following the one with the given index and period.
+	pub fn next_session_period(&self, session: u32, session_period: u32) -> u32 {
+		match self.scheduled_period_change {
+			Some(change) if change.session == session.saturating_add(1) => change.period,
+			_ => session_period,
+		}
+	}
+}
+
+/// Ways in which obtaining the authority data of the next session can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HandoverError {
+	/// The proof does not contain the required storage items for the given state root.
+	InvalidProof,
+	/// The proven state does not contain the authorities of the next session.
+	MissingNextAuthorities,
+	/// One of the proven storage items could not be decoded.
+	MalformedStorageItem,
+}
+
+impl Display for HandoverError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use HandoverError::*;
+		match self {
+			InvalidProof => write!(f, "invalid storage proof"),
+			MissingNextAuthorities => write!(f, "next session authorities missing in state"),
+			MalformedStorageItem => write!(f, "malformed storage item"),
+		}
+	}
+}
+
+fn storage_key(item: &[u8]) -> Vec<u8> {
+	[twox_128(ALEPH_PALLET_NAME), twox_128(item)].concat()
+}
+
+fn read_item<T: Decode>(
+	db: &sp_trie::MemoryDB<BlakeTwo256>,
+	state_root: &BlockHash,
+	item: &[u8],
+) -> Result<Option<T>, HandoverError> {
+	match read_trie_value::<LayoutV1<BlakeTwo256>, _>(
+		db,
+		state_root,
+		&storage_key(item),
+		None,
+		None,
+	) {
+		Ok(Some(encoded)) => T::decode(&mut encoded.as_slice())
+			.map(Some)
+			.map_err(|_| HandoverError::MalformedStorageItem),
+		Ok(None) => Ok(None),
+		Err(_) => Err(HandoverError::InvalidProof),
+	}
+}
+
+/// Reads the authority data and the session period of the next session from a storage proof
+/// against the state root of the last block of the current session. The authority data mirrors
+/// what the `next_session_authority_data` runtime API returns for that block.
+///
+/// The proof has to cover the scheduled session period change even if there is none, so that
+/// its absence is proven as well.
+pub fn session_handover(
+	state_root: BlockHash,
+	proof: Vec<Vec<u8>>,
+) -> Result<SessionHandover, HandoverError> {
+	let db = StorageProof::new(proof).into_memory_db::<BlakeTwo256>();
+	let authorities: Vec<AuthorityId> = read_item(&db, &state_root, NEXT_AUTHORITIES_ITEM)?
+		.ok_or(HandoverError::MissingNextAuthorities)?;
+	let emergency_finalizer: Option<AuthorityId> =
+		read_item(&db, &state_root, QUEUED_EMERGENCY_FINALIZER_ITEM)?;
+	let scheduled_period_change: Option<SessionPeriodChange> =
+		read_item(&db, &state_root, SESSION_PERIOD_SCHEDULED_CHANGE_ITEM)?;
+	Ok(SessionHandover {
+		authority_data: SessionAuthorityData::new(authorities, emergency_finalizer),
+		scheduled_period_change,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use parity_scale_codec::Encode;
+	use selendra_primitives::{
+		AuthorityId, AuthorityPair, SessionAuthorityData, SessionPeriodChange,
+	};
+	use sp_core::Pair;
+	use sp_runtime::{traits::BlakeTwo256, StateVersion};
+	use sp_state_machine::{prove_read, InMemoryBackend};
+
+	use super::{
+		session_handover, storage_key, HandoverError, SessionHandover, NEXT_AUTHORITIES_ITEM,
+		QUEUED_EMERGENCY_FINALIZER_ITEM, SESSION_PERIOD_SCHEDULED_CHANGE_ITEM,
+	};
+
+	fn prove(items: Vec<(&[u8], Vec<u8>)>) -> (sp_core::H256, Vec<Vec<u8>>) {
+		let keys: Vec<_> = [
+			NEXT_AUTHORITIES_ITEM,
+			QUEUED_EMERGENCY_FINALIZER_ITEM,
+			SESSION_PERIOD_SCHEDULED_CHANGE_ITEM,
+		]
+		.iter()
+		.map(|item| storage_key(item))
+		.collect();
+		let storage = items
+			.into_iter()
+			.map(|(item, value)| (storage_key(item), Some(value)))
+			.collect();
+		let backend =
+			InMemoryBackend::<BlakeTwo256>::from((vec![(None, storage)], StateVersion::V1));
+		let root = *backend.root();
+		let proof = prove_read(backend, &keys).expect("proving should work");
+		(root, proof.into_iter_nodes().collect())
+	}
+
+	fn authorities(count: usize) -> Vec<AuthorityId> {
+		(0..count).map(|_| AuthorityPair::generate().0.public()).collect()
+	}
+
+	#[test]
+	fn reads_next_authority_data() {
+		let next_authorities = authorities(4);
+		let emergency_finalizer = authorities(1).remove(0);
+		let (root, proof) = prove(vec![
+			(NEXT_AUTHORITIES_ITEM, next_authorities.encode()),
+			(QUEUED_EMERGENCY_FINALIZER_ITEM, emergency_finalizer.encode()),
+		]);
+		assert_eq!(
+			session_handover(root, proof),
+			Ok(SessionHandover {
+				authority_data: SessionAuthorityData::new(
+					next_authorities,
+					Some(emergency_finalizer)
+				),
+				scheduled_period_change: None,
+			})
+		);
+	}
+
+	#[test]
+	fn emergency_finalizer_is_optional() {
+		let next_authorities = authorities(4);
+		let (root, proof) = prove(vec![(NEXT_AUTHORITIES_ITEM, next_authorities.encode())]);
+		assert_eq!(
+			session_handover(root, proof).map(|handover| handover.authority_data),
+			Ok(SessionAuthorityData::new(next_authorities, None))
+		);
+	}
+
+	#[test]
+	fn reads_scheduled_period_change() {
+		let change = SessionPeriodChange { period: 20, session: 4 };
+		let (root, proof) = prove(vec![
+			(NEXT_AUTHORITIES_ITEM, authorities(4).encode()),
+			(SESSION_PERIOD_SCHEDULED_CHANGE_ITEM, change.encode()),
+		]);
+		let handover = session_handover(root, proof).expect("proof is valid");
+		assert_eq!(handover.scheduled_period_change, Some(change));
+		assert_eq!(handover.next_session_period(3, 10), 20);
+		assert_eq!(handover.next_session_period(2, 10), 10);
+	}
+
+	#[test]
+	fn rejects_proof_without_period_change() {
+		let keys = vec![storage_key(NEXT_AUTHORITIES_ITEM)];
+		let storage = vec![
+			(storage_key(NEXT_AUTHORITIES_ITEM), Some(authorities(4).encode())),
+			(
+				storage_key(SESSION_PERIOD_SCHEDULED_CHANGE_ITEM),
+				Some(SessionPeriodChange { period: 20, session: 4 }.encode()),
+			),
+		];
+		let backend =
+			InMemoryBackend::<BlakeTwo256>::from((vec![(None, storage)], StateVersion::V1));
+		let root = *backend.root();
+		let proof = prove_read(backend, &keys).expect("keys are present");
+		assert_eq!(
+			session_handover(root, proof.into_iter_nodes().collect()),
+			Err(HandoverError::InvalidProof)
+		);
+	}
+
+	#[test]
+	fn rejects_proof_for_other_root() {
+		let (_, proof) = prove(vec![(NEXT_AUTHORITIES_ITEM, authorities(4).encode())]);
+		assert_eq!(
+			session_handover(sp_core::H256::repeat_byte(1), proof),
+			Err(HandoverError::InvalidProof)
+		);
+	}
+}
@@ -0,0 +1,237 @@
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use parity_scale_codec::{Decode, DecodeAll, Encode, Error as CodecError, Input as CodecInput};
+use selendra_primitives::AuthoritySignature;
+use sp_std::{vec, vec::Vec};
+
+type ByteCount = u16;
+type Version = u16;
+
+/// A set of signatures indexed by the position of the signer in the committee.
+///
+/// Encodes exactly like the `SignatureSet` from `aleph-bft-crypto` used by `finality-aleph`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SignatureSet<S>(Vec<Option<S>>);
+
+impl<S: Clone> SignatureSet<S> {
+	/// An empty set for a committee of the given size.
+	pub fn with_size(len: usize) -> Self {
+		SignatureSet(vec![None; len])
+	}
+
+	/// The size of the committee this set was created for.
+	pub fn size(&self) -> usize {
+		self.0.len()
+	}
+
+	/// Adds a signature of the committee member with the given index. Signatures for indices
+	/// outside of the committee are ignored.
+	pub fn add_signature(mut self, signature: &S, index: usize) -> Self {
+		if let Some(slot) = self.0.get_mut(index) {
+			*slot = Some(signature.clone());
+		}
+		self
+	}
+
+	/// Iterates over the present signatures together with the indices of their signers.
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &S)> {
+		self.0.iter().enumerate().filter_map(|(index, maybe_signature)| {
+			maybe_signature.as_ref().map(|signature| (index, signature))
+		})
+	}
+}
+
+/// A proof of block finality, either a sufficiently long list of committee signatures or an
+/// emergency signature.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum AlephJustification {
+	/// Signatures of more than 2/3 of the session committee.
+	CommitteeMultisignature(SignatureSet<AuthoritySignature>),
+	/// Signature of the emergency finalizer of the session.
+	EmergencySignature(AuthoritySignature),
+}
+
+/// Old format of signatures, which unnecessarily contained the signer index.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+struct SignatureV1 {
+	_id: u64,
+	sgn: AuthoritySignature,
+}
+
+/// Old format of justifications using `SignatureV1`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+struct AlephJustificationV1 {
+	signature: SignatureSet<SignatureV1>,
+}
+
+impl From<AlephJustificationV1> for AlephJustification {
+	fn from(justification: AlephJustificationV1) -> AlephJustification {
+		let signatures = justification
+			.signature
+			.0
+			.into_iter()
+			.map(|maybe_signature| maybe_signature.map(|signature| signature.sgn))
+			.collect();
+		AlephJustification::CommitteeMultisignature(SignatureSet(signatures))
+	}
+}
+
+/// Old format of justifications from before the compatibility changes.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+struct AlephJustificationV2 {
+	signature: SignatureSet<AuthoritySignature>,
+}
+
+impl From<AlephJustificationV2> for AlephJustification {
+	fn from(justification: AlephJustificationV2) -> AlephJustification {
+		AlephJustification::CommitteeMultisignature(justification.signature)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum VersionedAlephJustification {
+	// Most likely from the future.
+	Other(Version),
+	V1(AlephJustificationV1),
+	V2(AlephJustificationV2),
+	V3(AlephJustification),
+}
+
+impl Decode for VersionedAlephJustification {
+	fn decode<I: CodecInput>(input: &mut I) -> Result<Self, CodecError> {
+		use VersionedAlephJustification::*;
+		let version = Version::decode(input)?;
+		let num_bytes = ByteCount::decode(input)?;
+		match version {
+			1 => Ok(V1(AlephJustificationV1::decode(input)?)),
+			2 => Ok(V2(AlephJustificationV2::decode(input)?)),
+			3 => Ok(V3(AlephJustification::decode(input)?)),
+			_ => {
+				let mut payload = vec![0; num_bytes.into()];
+				input.read(payload.as_mut_slice())?;
+				Ok(Other(version))
+			},
+		}
+	}
+}
+
+/// Ways in which decoding a justification can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+	/// The bytes are not a justification in any known format.
+	BadFormat,
+	/// The justification was encoded with a version we do not know.
+	UnknownVersion(u16),
+}
+
+impl Display for DecodeError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use DecodeError::*;
+		match self {
+			BadFormat => write!(f, "malformed encoding"),
+			UnknownVersion(version) => {
+				write!(f, "justification encoded with unknown version {version}")
+			},
+		}
+	}
+}
+
+fn decode_pre_compatibility_justification(
+	justification_raw: &[u8],
+) -> Result<AlephJustification, DecodeError> {
+	match AlephJustificationV2::decode_all(&mut &justification_raw[..]) {
+		Ok(justification) => Ok(justification.into()),
+		Err(_) => match AlephJustificationV1::decode_all(&mut &justification_raw[..]) {
+			Ok(justification) => Ok(justification.into()),
+			Err(_) => Err(DecodeError::BadFormat),
+		},
+	}
+}
+
+/// Decodes a justification in any of the formats ever produced by `finality-aleph`.
+pub fn backwards_compatible_decode(
+	justification_raw: &[u8],
+) -> Result<AlephJustification, DecodeError> {
+	use VersionedAlephJustification::*;
+	match VersionedAlephJustification::decode_all(&mut &justification_raw[..]) {
+		Ok(V1(justification)) => Ok(justification.into()),
+		Ok(V2(justification)) => Ok(justification.into()),
+		Ok(V3(justification)) => Ok(justification),
+		// Pre-compatibility justifications can be falsely recognized as coming from the future,
+		// so we have to try decoding them before giving up.
+		Ok(Other(version)) => decode_pre_compatibility_justification(justification_raw)
+			.map_err(|_| DecodeError::UnknownVersion(version)),
+		Err(_) => decode_pre_compatibility_justification(justification_raw),
+	}
+}
+
+/// Encodes the justification in the current versioned format.
+pub fn versioned_encode(justification: &AlephJustification) -> Vec<u8> {
+	let payload = justification.encode();
+	let size = ByteCount::try_from(payload.len()).unwrap_or(ByteCount::MAX);
+	let mut result = Vec::with_capacity(4 + payload.len());
+	3u16.encode_to(&mut result);
+	size.encode_to(&mut result);
+	result.extend_from_slice(&payload);
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use parity_scale_codec::Encode;
+	use selendra_primitives::{AuthorityPair, AuthoritySignature};
+	use sp_core::Pair;
+
+	use super::{
+		backwards_compatible_decode, versioned_encode, AlephJustification, AlephJustificationV1,
+		AlephJustificationV2, DecodeError, SignatureSet, SignatureV1,
+	};
+
+	fn signature() -> AuthoritySignature {
+		AuthorityPair::generate().0.sign(&[0u8, 0u8, 0u8, 0u8])
+	}
+
+	#[test]
+	fn decodes_current_version() {
+		let signatures = (0..7)
+			.fold(SignatureSet::with_size(7), |set, index| set.add_signature(&signature(), index));
+		let justification = AlephJustification::CommitteeMultisignature(signatures);
+		assert_eq!(
+			backwards_compatible_decode(&versioned_encode(&justification)),
+			Ok(justification)
+		);
+	}
+
+	#[test]
+	fn decodes_emergency_signature() {
+		let justification = AlephJustification::EmergencySignature(signature());
+		assert_eq!(
+			backwards_compatible_decode(&versioned_encode(&justification)),
+			Ok(justification)
+		);
+	}
+
+	#[test]
+	fn decodes_pre_compatibility_v1() {
+		let signatures = (0..7).fold(SignatureSet::with_size(7), |set, index| {
+			set.add_signature(&SignatureV1 { _id: index as u64, sgn: signature() }, index)
+		});
+		let justification = AlephJustificationV1 { signature: signatures };
+		let decoded = backwards_compatible_decode(&justification.encode());
+		assert_eq!(decoded, Ok(justification.into()));
+	}
+
+	#[test]
+	fn decodes_pre_compatibility_v2() {
+		let signatures = (0..7)
+			.fold(SignatureSet::with_size(7), |set, index| set.add_signature(&signature(), index));
+		let justification = AlephJustificationV2 { signature: signatures };
+		let decoded = backwards_compatible_decode(&justification.encode());
+		assert_eq!(decoded, Ok(justification.into()));
+	}
+
+	#[test]
+	fn rejects_garbage() {
+		assert_eq!(backwards_compatible_decode(&[1, 2, 3]), Err(DecodeError::BadFormat));
+	}
+}
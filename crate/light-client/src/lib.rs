@@ -0,0 +1,24 @@
+//! Verification of Aleph justifications outside of the node, e.g. in a runtime tracking the
+//! finality of another Selendra-family chain.
+//!
+//! The types here are wire compatible with the ones used by `finality-aleph`, so justifications
+//! stored by a node can be decoded and checked using only the authority data of their session.
+//! Authority data of consecutive sessions is obtained through a handover: the last block of every
+//! session is justified by the committee of that session and its state commits to the authority
+//! data of the next one, as well as to a scheduled change of the session period.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+mod handover;
+mod justification;
+mod verifier;
+
+pub use handover::{
+	session_handover, HandoverError, SessionHandover, ALEPH_PALLET_NAME, NEXT_AUTHORITIES_ITEM,
+	QUEUED_EMERGENCY_FINALIZER_ITEM, SESSION_PERIOD_SCHEDULED_CHANGE_ITEM,
+};
+pub use justification::{
+	backwards_compatible_decode, versioned_encode, AlephJustification, DecodeError, SignatureSet,
+};
+pub use verifier::{SessionVerifier, VerificationError};
@@ -0,0 +1,186 @@
+use core::fmt::{Display, Error as FmtError, Formatter};
+
+use parity_scale_codec::Encode;
+use selendra_primitives::{AuthorityId, AuthoritySignature, BlockHash, SessionAuthorityData};
+use sp_runtime::RuntimeAppPublic;
+use sp_std::vec::Vec;
+
+use crate::justification::{AlephJustification, SignatureSet};
+
+/// A justification verifier within a single session.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SessionVerifier {
+	authorities: Vec<AuthorityId>,
+	emergency_signer: Option<AuthorityId>,
+}
+
+impl From<SessionAuthorityData> for SessionVerifier {
+	fn from(authority_data: SessionAuthorityData) -> Self {
+		SessionVerifier {
+			authorities: authority_data.authorities().to_vec(),
+			emergency_signer: authority_data.emergency_finalizer().clone(),
+		}
+	}
+}
+
+/// Ways in which a justification can be wrong.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+	/// The committee multisignature is incomplete or contains invalid signatures.
+	BadMultisignature,
+	/// The emergency signature is invalid.
+	BadEmergencySignature,
+	/// The justification is an emergency signature, but the session has no emergency signer.
+	NoEmergencySigner,
+}
+
+impl Display for VerificationError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+		use VerificationError::*;
+		match self {
+			BadMultisignature => write!(f, "bad multisignature"),
+			BadEmergencySignature => write!(f, "bad emergency signature"),
+			NoEmergencySigner => write!(f, "no emergency signer defined"),
+		}
+	}
+}
+
+impl SessionVerifier {
+	fn threshold(&self) -> usize {
+		2 * self.authorities.len() / 3 + 1
+	}
+
+	fn is_complete(&self, msg: &[u8], multisignature: &SignatureSet<AuthoritySignature>) -> bool {
+		if multisignature.iter().count() < self.threshold() {
+			return false;
+		}
+		multisignature
+			.iter()
+			.all(|(index, signature)| match self.authorities.get(index) {
+				Some(authority) => authority.verify(&msg, signature),
+				None => false,
+			})
+	}
+
+	/// Verifies the correctness of a justification for supplied bytes.
+	pub fn verify_bytes(
+		&self,
+		justification: &AlephJustification,
+		bytes: &[u8],
+	) -> Result<(), VerificationError> {
+		use AlephJustification::*;
+		use VerificationError::*;
+		match justification {
+			CommitteeMultisignature(multisignature) => {
+				match self.is_complete(bytes, multisignature) {
+					true => Ok(()),
+					false => Err(BadMultisignature),
+				}
+			},
+			EmergencySignature(signature) => {
+				match self
+					.emergency_signer
+					.as_ref()
+					.ok_or(NoEmergencySigner)?
+					.verify(&bytes, signature)
+				{
+					true => Ok(()),
+					false => Err(BadEmergencySignature),
+				}
+			},
+		}
+	}
+
+	/// Verifies that the justification finalizes the block with the given hash.
+	pub fn verify_justification(
+		&self,
+		justification: &AlephJustification,
+		hash: BlockHash,
+	) -> Result<(), VerificationError> {
+		self.verify_bytes(justification, &hash.encode())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use selendra_primitives::{AuthorityPair, BlockHash, SessionAuthorityData};
+	use sp_core::Pair;
+
+	use super::{SessionVerifier, VerificationError};
+	use crate::justification::{AlephJustification, SignatureSet};
+
+	fn setup(committee_size: usize) -> (Vec<AuthorityPair>, AuthorityPair, SessionVerifier) {
+		let pairs: Vec<_> = (0..committee_size).map(|_| AuthorityPair::generate().0).collect();
+		let emergency = AuthorityPair::generate().0;
+		let authority_data = SessionAuthorityData::new(
+			pairs.iter().map(|pair| pair.public()).collect(),
+			Some(emergency.public()),
+		);
+		(pairs, emergency, authority_data.into())
+	}
+
+	fn multisignature(
+		pairs: &[AuthorityPair],
+		signers: &[usize],
+		hash: BlockHash,
+	) -> AlephJustification {
+		let message = parity_scale_codec::Encode::encode(&hash);
+		let signatures = signers.iter().fold(SignatureSet::with_size(pairs.len()), |set, index| {
+			set.add_signature(&pairs[*index].sign(&message), *index)
+		});
+		AlephJustification::CommitteeMultisignature(signatures)
+	}
+
+	#[test]
+	fn accepts_complete_multisignature() {
+		let (pairs, _, verifier) = setup(4);
+		let hash = BlockHash::repeat_byte(7);
+		let justification = multisignature(&pairs, &[0, 1, 3], hash);
+		assert_eq!(verifier.verify_justification(&justification, hash), Ok(()));
+	}
+
+	#[test]
+	fn rejects_incomplete_multisignature() {
+		let (pairs, _, verifier) = setup(4);
+		let hash = BlockHash::repeat_byte(7);
+		let justification = multisignature(&pairs, &[0, 1], hash);
+		assert_eq!(
+			verifier.verify_justification(&justification, hash),
+			Err(VerificationError::BadMultisignature)
+		);
+	}
+
+	#[test]
+	fn rejects_multisignature_of_other_block() {
+		let (pairs, _, verifier) = setup(4);
+		let justification = multisignature(&pairs, &[0, 1, 2, 3], BlockHash::repeat_byte(7));
+		assert_eq!(
+			verifier.verify_justification(&justification, BlockHash::repeat_byte(8)),
+			Err(VerificationError::BadMultisignature)
+		);
+	}
+
+	#[test]
+	fn accepts_emergency_signature() {
+		let (_, emergency, verifier) = setup(4);
+		let hash = BlockHash::repeat_byte(7);
+		let signature = emergency.sign(&parity_scale_codec::Encode::encode(&hash));
+		let justification = AlephJustification::EmergencySignature(signature);
+		assert_eq!(verifier.verify_justification(&justification, hash), Ok(()));
+	}
+
+	#[test]
+	fn rejects_emergency_signature_without_signer() {
+		let (pairs, _, _) = setup(4);
+		let verifier: SessionVerifier =
+			SessionAuthorityData::new(pairs.iter().map(|pair| pair.public()).collect(), None)
+				.into();
+		let hash = BlockHash::repeat_byte(7);
+		let signature = pairs[0].sign(&parity_scale_codec::Encode::encode(&hash));
+		let justification = AlephJustification::EmergencySignature(signature);
+		assert_eq!(
+			verifier.verify_justification(&justification, hash),
+			Err(VerificationError::NoEmergencySigner)
+		);
+	}
+}
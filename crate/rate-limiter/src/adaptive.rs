@@ -0,0 +1,241 @@
+use std::{
+	cmp::max,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use log::debug;
+use parking_lot::Mutex;
+
+use crate::{token_bucket::TokenBucket, LOG_TARGET};
+
+/// How often the rate of a single connection is adjusted.
+const ADAPTATION_PERIOD: Duration = Duration::from_secs(1);
+
+/// Bounds of the adaptive rate limiting, in units per second.
+#[derive(Clone, Debug)]
+pub struct AdaptiveConfig {
+	/// The lowest rate a single connection can be limited to.
+	pub min_rate_per_connection: usize,
+	/// The highest rate a single connection can be raised to.
+	pub max_rate_per_connection: usize,
+	/// Cap on the combined rate of all the connections.
+	pub aggregate_rate: usize,
+}
+
+impl AdaptiveConfig {
+	/// The aggregate rate split evenly between all the other members of the committee.
+	fn base_rate(&self, committee_size: usize) -> usize {
+		let peers = max(committee_size.saturating_sub(1), 1);
+		(self.aggregate_rate / peers)
+			.clamp(self.min_rate_per_connection, self.max_rate_per_connection)
+	}
+}
+
+struct SharedState {
+	aggregate: TokenBucket,
+	committee_size: usize,
+}
+
+/// State shared by all the connections limited adaptively: the aggregate cap and the size of the
+/// committee the connections are made within.
+#[derive(Clone)]
+pub struct AdaptiveLimits {
+	config: AdaptiveConfig,
+	shared: Arc<Mutex<SharedState>>,
+}
+
+impl AdaptiveLimits {
+	pub fn new(config: AdaptiveConfig) -> Self {
+		Self::new_with_now(config, Instant::now())
+	}
+
+	fn new_with_now(mut config: AdaptiveConfig, now: Instant) -> Self {
+		config.min_rate_per_connection = max(config.min_rate_per_connection, 1);
+		config.max_rate_per_connection =
+			max(config.max_rate_per_connection, config.min_rate_per_connection);
+		let aggregate = TokenBucket::new_with_now(config.aggregate_rate, now);
+		Self { config, shared: Arc::new(Mutex::new(SharedState { aggregate, committee_size: 1 })) }
+	}
+
+	/// Updates the size of the committee, which determines the rate every connection starts at.
+	pub fn set_committee_size(&self, committee_size: usize) {
+		let mut shared = self.shared.lock();
+		if shared.committee_size != committee_size {
+			debug!(
+				target: LOG_TARGET,
+				"Committee size changed from {} to {}, base rate per connection is now {}.",
+				shared.committee_size,
+				committee_size,
+				self.config.base_rate(committee_size)
+			);
+			shared.committee_size = committee_size;
+		}
+	}
+
+	fn base_rate(&self) -> usize {
+		self.config.base_rate(self.shared.lock().committee_size)
+	}
+
+	fn rate_limit_aggregate(&self, requested: usize, now: Instant) -> Option<Duration> {
+		let mut shared = self.shared.lock();
+		// Connections report concurrently, so `now` might lag behind the last update.
+		let now = max(now, shared.aggregate.last_update());
+		shared.aggregate.rate_limit(requested, now)
+	}
+}
+
+/// Rate limiting of a single connection in the adaptive mode.
+///
+/// A connection starts at the aggregate rate split evenly between the other members of the
+/// committee. If it had to wait during the last [ADAPTATION_PERIOD], i.e. it has a backlog, its
+/// rate is raised by a quarter, otherwise it decays by a tenth, never dropping below the even
+/// split. On top of that every read is counted against the aggregate cap shared by all the
+/// connections.
+#[derive(Clone)]
+pub struct AdaptiveTokenBucket {
+	limits: AdaptiveLimits,
+	bucket: TokenBucket,
+	window_start: Instant,
+	backlogged: bool,
+}
+
+impl AdaptiveTokenBucket {
+	pub fn new(limits: AdaptiveLimits) -> Self {
+		Self::new_with_now(limits, Instant::now())
+	}
+
+	fn new_with_now(limits: AdaptiveLimits, now: Instant) -> Self {
+		let bucket = TokenBucket::new_with_now(limits.base_rate(), now);
+		Self { limits, bucket, window_start: now, backlogged: false }
+	}
+
+	fn adapt(&mut self, now: Instant) {
+		let base_rate = self.limits.base_rate();
+		let rate = self.bucket.rate();
+		let new_rate = if now.saturating_duration_since(self.window_start) < ADAPTATION_PERIOD {
+			max(rate, base_rate)
+		} else {
+			self.window_start = now;
+			let change = max(rate / 10, 1);
+			let new_rate = match self.backlogged {
+				true => rate.saturating_add(max(rate / 4, 1)),
+				false => rate.saturating_sub(change),
+			};
+			self.backlogged = false;
+			new_rate.clamp(base_rate, self.limits.config.max_rate_per_connection)
+		};
+		if new_rate != rate {
+			debug!(target: LOG_TARGET, "Changing connection rate from {} to {}.", rate, new_rate);
+			self.bucket.set_rate(new_rate, now);
+		}
+	}
+
+	/// Calculates [Duration](time::Duration) by which we should delay next call to some governed resource in order to satisfy
+	/// both the rate of this connection and the aggregate cap.
+	pub fn rate_limit(&mut self, requested: usize, now: Instant) -> Option<Duration> {
+		self.adapt(now);
+		let own_delay = self.bucket.rate_limit(requested, now);
+		if own_delay.is_some() {
+			self.backlogged = true;
+		}
+		let aggregate_delay = self.limits.rate_limit_aggregate(requested, now);
+		max(own_delay, aggregate_delay)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use super::{AdaptiveConfig, AdaptiveLimits, AdaptiveTokenBucket};
+
+	fn limits(aggregate_rate: usize, now: Instant) -> AdaptiveLimits {
+		AdaptiveLimits::new_with_now(
+			AdaptiveConfig {
+				min_rate_per_connection: 10,
+				max_rate_per_connection: 400,
+				aggregate_rate,
+			},
+			now,
+		)
+	}
+
+	#[test]
+	fn splits_aggregate_rate_across_committee() {
+		let now = Instant::now();
+		let limits = limits(1000, now);
+		assert_eq!(limits.base_rate(), 400, "a lone node is capped at the maximum");
+		limits.set_committee_size(11);
+		assert_eq!(limits.base_rate(), 100);
+		limits.set_committee_size(1000);
+		assert_eq!(limits.base_rate(), 10, "large committees are bounded by the minimum");
+	}
+
+	#[test]
+	fn raises_rate_of_backlogged_connection() {
+		let now = Instant::now();
+		let limits = limits(1000, now);
+		limits.set_committee_size(11);
+		let mut connection = AdaptiveTokenBucket::new_with_now(limits.clone(), now);
+		assert_eq!(connection.bucket.rate(), 100);
+
+		assert!(connection.rate_limit(200, now).is_some());
+		assert!(connection.rate_limit(0, now + Duration::from_secs(1)).is_none());
+		assert_eq!(connection.bucket.rate(), 125);
+		assert!(connection.rate_limit(500, now + Duration::from_secs(1)).is_some());
+		connection.rate_limit(0, now + Duration::from_secs(2));
+		assert_eq!(connection.bucket.rate(), 156);
+
+		for second in 3..60 {
+			connection.rate_limit(0, now + Duration::from_secs(second));
+		}
+		assert_eq!(connection.bucket.rate(), 100, "an idle connection decays to the base rate");
+	}
+
+	#[test]
+	fn never_exceeds_maximum_rate() {
+		let now = Instant::now();
+		let limits = limits(1000, now);
+		limits.set_committee_size(11);
+		let mut connection = AdaptiveTokenBucket::new_with_now(limits, now);
+		for second in 0..30 {
+			connection.rate_limit(10_000, now + Duration::from_secs(second));
+		}
+		assert_eq!(connection.bucket.rate(), 400);
+	}
+
+	#[test]
+	fn follows_committee_size() {
+		let now = Instant::now();
+		let limits = limits(1000, now);
+		limits.set_committee_size(11);
+		let mut connection = AdaptiveTokenBucket::new_with_now(limits.clone(), now);
+		assert_eq!(connection.bucket.rate(), 100);
+		limits.set_committee_size(5);
+		connection.rate_limit(0, now);
+		assert_eq!(
+			connection.bucket.rate(),
+			250,
+			"shrinking committees raise the rate immediately"
+		);
+		limits.set_committee_size(11);
+		connection.rate_limit(0, now + Duration::from_secs(1));
+		assert_eq!(connection.bucket.rate(), 225, "growing committees lower the rate gradually");
+	}
+
+	#[test]
+	fn aggregate_cap_is_shared() {
+		let now = Instant::now();
+		let limits = limits(100, now);
+		limits.set_committee_size(2);
+		let mut first = AdaptiveTokenBucket::new_with_now(limits.clone(), now);
+		let mut second = AdaptiveTokenBucket::new_with_now(limits, now);
+		assert_eq!(first.rate_limit(100, now), None);
+		assert_eq!(
+			second.rate_limit(100, now),
+			Some(Duration::from_secs(1)),
+			"the second connection should wait for the aggregate cap"
+		);
+	}
+}
@@ -1,6 +1,10 @@
+mod adaptive;
 mod rate_limiter;
 mod token_bucket;
 
-pub use crate::rate_limiter::{RateLimiter, SleepingRateLimiter};
+pub use crate::{
+	adaptive::{AdaptiveConfig, AdaptiveLimits},
+	rate_limiter::{RateLimiter, SleepingRateLimiter},
+};
 
 const LOG_TARGET: &str = "rate-limiter";
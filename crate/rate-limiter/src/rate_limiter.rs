@@ -1,15 +1,36 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use futures::{future::BoxFuture, FutureExt};
 use log::trace;
 use tokio::{io::AsyncRead, time::sleep};
 
-use crate::{token_bucket::TokenBucket, LOG_TARGET};
+use crate::{
+	adaptive::{AdaptiveLimits, AdaptiveTokenBucket},
+	token_bucket::TokenBucket,
+	LOG_TARGET,
+};
+
+#[derive(Clone)]
+enum Limiter {
+	Fixed(TokenBucket),
+	Adaptive(AdaptiveTokenBucket),
+}
+
+impl Limiter {
+	fn rate_limit(&mut self, requested: usize, now: Instant) -> Option<Duration> {
+		match self {
+			Limiter::Fixed(token_bucket) => token_bucket.rate_limit(requested, now),
+			Limiter::Adaptive(token_bucket) => token_bucket.rate_limit(requested, now),
+		}
+	}
+}
 
 /// Allows to limit access to some resource. Given a preferred rate (units of something) and last used amount of units of some
 /// resource, it calculates how long we should delay our next access to that resource in order to satisfy that rate.
+///
+/// Every clone limits its accesses independently, except for the limits shared in the adaptive mode.
 pub struct SleepingRateLimiter {
-	rate_limiter: TokenBucket,
+	rate_limiter: Limiter,
 }
 
 impl Clone for SleepingRateLimiter {
@@ -21,7 +42,13 @@ impl Clone for SleepingRateLimiter {
 impl SleepingRateLimiter {
 	/// Constructs a instance of [SleepingRateLimiter] with given target rate-per-second.
 	pub fn new(rate_per_second: usize) -> Self {
-		Self { rate_limiter: TokenBucket::new(rate_per_second) }
+		Self { rate_limiter: Limiter::Fixed(TokenBucket::new(rate_per_second)) }
+	}
+
+	/// Constructs a instance of [SleepingRateLimiter] whose rate adapts to the committee size and to its own backlog, and
+	/// which counts towards the aggregate cap shared with all the other limiters using the same `limits`.
+	pub fn adaptive(limits: AdaptiveLimits) -> Self {
+		Self { rate_limiter: Limiter::Adaptive(AdaptiveTokenBucket::new(limits)) }
 	}
 
 	/// Given `read_size`, that is an amount of units of some governed resource, delays return of `Self` to satisfy configure
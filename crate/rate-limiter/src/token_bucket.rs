@@ -1,5 +1,5 @@
 use std::{
-	cmp::min,
+	cmp::{max, min},
 	time::{Duration, Instant},
 };
 
@@ -17,8 +17,10 @@ pub struct TokenBucket {
 }
 
 impl TokenBucket {
-	/// Constructs a instance of [TokenBucket] with given target rate-per-second.
+	/// Constructs a instance of [TokenBucket] with given target rate-per-second. A rate of zero is
+	/// raised to one, since an empty bucket would never refill and every request would wait forever.
 	pub fn new(rate_per_second: usize) -> Self {
+		let rate_per_second = max(rate_per_second, 1);
 		Self {
 			rate_per_second,
 			available: rate_per_second,
@@ -27,7 +29,8 @@ impl TokenBucket {
 		}
 	}
 
-	#[cfg(test)]
+	/// Constructs a instance of [TokenBucket] with given target rate-per-second, treating `now` as the moment of the last
+	/// update, which makes its behavior deterministic.
 	pub fn new_with_now(rate_per_second: usize, now: Instant) -> Self {
 		Self { last_update: now, ..Self::new(rate_per_second) }
 	}
@@ -84,6 +87,23 @@ impl TokenBucket {
 		None
 	}
 
+	/// Changes the target rate-per-second, keeping the tokens accumulated so far up to the new
+	/// limit. As in [TokenBucket::new], a rate of zero is raised to one.
+	pub fn set_rate(&mut self, rate_per_second: usize, now: Instant) {
+		self.update_units(max(now, self.last_update));
+		self.rate_per_second = max(rate_per_second, 1);
+		self.available = min(self.available, self.token_limit());
+	}
+
+	/// Current target rate-per-second.
+	pub fn rate(&self) -> usize {
+		self.rate_per_second
+	}
+
+	pub(crate) fn last_update(&self) -> Instant {
+		self.last_update
+	}
+
 	fn token_limit(&self) -> usize {
 		self.rate_per_second
 	}
@@ -176,4 +196,39 @@ mod tests {
 			Some(Duration::from_secs(6))
 		);
 	}
+
+	#[test]
+	fn changing_rate_keeps_pending_requests() {
+		let limit_per_second = 10;
+		let now = Instant::now();
+		let mut rate_limiter = TokenBucket::new_with_now(limit_per_second, now);
+
+		assert_eq!(rate_limiter.rate_limit(30, now), Some(Duration::from_secs(2)));
+
+		rate_limiter.set_rate(20, now);
+		assert_eq!(rate_limiter.rate(), 20);
+		assert_eq!(
+			rate_limiter.rate_limit(10, now),
+			Some(Duration::from_millis(1500)),
+			"the pending 20 and the new 10 should take 1.5 seconds at the new rate"
+		);
+
+		rate_limiter.set_rate(5, now + Duration::from_secs(1));
+		assert_eq!(
+			rate_limiter.rate_limit(5, now + Duration::from_secs(1)),
+			Some(Duration::from_secs(3)),
+			"the remaining 10 and the new 5 should take 3 seconds at the lowered rate"
+		);
+	}
+
+	#[test]
+	fn zero_rate_is_raised_to_one() {
+		let now = Instant::now();
+		let mut rate_limiter = TokenBucket::new_with_now(0, now);
+		assert_eq!(rate_limiter.rate(), 1);
+		assert_eq!(rate_limiter.rate_limit(3, now), Some(Duration::from_secs(2)));
+
+		rate_limiter.set_rate(0, now);
+		assert_eq!(rate_limiter.rate(), 1);
+	}
 }
@@ -0,0 +1,71 @@
+//! Generates the runtime bindings from the metadata of the Selendra runtime.
+//!
+//! The metadata is taken from the `selendra-runtime` crate of this repository, built natively as a
+//! build dependency, rather than from a live node, so the bindings always describe the pallets
+//! `runtime/src/lib.rs` ships.
+
+use std::{env, fs, path::PathBuf};
+
+use selendra_runtime::Runtime;
+use subxt_codegen::{CratePath, DerivesRegistry, TypeSubstitutes};
+use syn::parse_quote;
+
+/// Metadata versions to generate the bindings from, newest first.
+const METADATA_VERSIONS: [u32; 2] = [15, 14];
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("set by cargo"));
+    println!("cargo:rerun-if-changed=../../runtime/src");
+
+    let metadata = runtime_metadata();
+    fs::write(out_dir.join("metadata.scale"), &metadata).expect("OUT_DIR should be writable");
+    fs::write(out_dir.join("selendra.rs"), bindings(&metadata))
+        .expect("OUT_DIR should be writable");
+}
+
+/// Encoded metadata of the runtime, in the newest version both the runtime and subxt support.
+fn runtime_metadata() -> Vec<u8> {
+    METADATA_VERSIONS
+        .into_iter()
+        .find_map(Runtime::metadata_at_version)
+        .map(|metadata| metadata.to_vec())
+        .expect("the runtime should provide metadata in a version subxt supports")
+}
+
+/// Generates the bindings the same way `subxt codegen` would.
+fn bindings(metadata: &[u8]) -> String {
+    let crate_path = CratePath::default();
+    let mut derives = DerivesRegistry::with_default_derives(&crate_path);
+    derives.extend_for_all(
+        [
+            parse_quote!(Clone),
+            parse_quote!(Debug),
+            parse_quote!(PartialEq),
+            parse_quote!(Eq),
+        ],
+        [],
+    );
+    let mut substitutes = TypeSubstitutes::with_default_substitutes(&crate_path);
+    let account_id: syn::Path =
+        parse_quote!(::subxt::utils::Static<::subxt::ext::sp_core::crypto::AccountId32>);
+    substitutes
+        .insert(
+            parse_quote!(sp_core::crypto::AccountId32),
+            account_id.try_into().expect("the path is absolute"),
+        )
+        .expect("AccountId32 can be substituted");
+
+    subxt_codegen::generate_runtime_api_from_bytes(
+        parse_quote!(
+            pub mod api {}
+        ),
+        metadata,
+        derives,
+        substitutes,
+        crate_path,
+        true,
+        false,
+    )
+    .expect("bindings should generate from the runtime metadata")
+    .to_string()
+}
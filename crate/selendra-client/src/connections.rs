@@ -1,8 +1,9 @@
-use std::{thread::sleep, time::Duration};
+use std::{sync::Arc, time::Duration};
 
 use anyhow::anyhow;
 use codec::Decode;
-use log::{debug, info};
+use futures::channel::mpsc::UnboundedReceiver;
+use log::info;
 use primitives::Nonce;
 use serde::{Deserialize, Serialize};
 use subxt::{
@@ -18,15 +19,21 @@ use subxt::{
 };
 
 use crate::{
-    api, runtime_types::sp_weights::weight_v2::Weight, AccountId, AlephConfig, BlockHash, Call,
-    KeyPair, ParamsBuilder, SubxtClient, TxHash, TxStatus,
+    api,
+    failover::{ConnectionEvent, FailoverRpc, ReconnectConfig},
+    runtime_types::sp_weights::weight_v2::Weight,
+    AccountId, AlephConfig, BlockHash, Call, KeyPair, ParamsBuilder, SubxtClient, TxHash, TxStatus,
 };
 
 /// Capable of communicating with a live Aleph chain.
+///
+/// Requests go to the first of its endpoints that works. When an endpoint stops responding, the
+/// connection reconnects with a backoff, failing over to the next endpoint.
 #[derive(Clone)]
 pub struct Connection {
     /// inner subxt type
     pub client: SubxtClient,
+    failover: Arc<FailoverRpc>,
 }
 
 /// Any connection that is signed by some key.
@@ -82,7 +89,7 @@ pub trait ConnectionApi: Sync {
     ///
     /// # Examples
     /// ```ignore
-    ///     let addrs = api::storage().treasury().proposal_count();
+    ///     let addrs = api::storage().indices().accounts(index);
     ///     get_storage_entry_maybe(&addrs, None).await
     /// ```
     async fn get_storage_entry_maybe<
@@ -103,21 +110,25 @@ pub trait ConnectionApi: Sync {
     ///
     /// # Examples
     /// ```ignore
-    ///  let args = ContractCallArgs {
-    ///             origin: address.clone(),
-    ///             dest: address.clone(),
-    ///             value: 0,
-    ///             gas_limit: None,
-    ///             input_data: payload,
-    ///             storage_deposit_limit: None,
-    ///         };
-    /// let params = rpc_params!["ContractsApi_call", Bytes(args.encode())];
+    /// let params = rpc_params!["AlephSessionApi_finality_version", Bytes(Vec::new())];
     /// rpc_call("state_call".to_string(), params).await;
     /// ```
     async fn rpc_call<R: Decode>(&self, func_name: String, params: RpcParams) -> anyhow::Result<R>;
 
     /// Same as [rpc_call] but used for rpc endpoint that does not return values.
     async fn rpc_call_no_return(&self, func_name: String, params: RpcParams) -> anyhow::Result<()>;
+
+    /// Send an unsigned transaction, e.g. one carrying its own signature, to a chain. It waits
+    /// for a given tx `status`.
+    /// * `tx` - encoded transaction payload
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    /// # Returns
+    /// Block hash of block where transaction was put together with transaction hash, or error.
+    async fn send_unsigned_tx<Call: TxPayload + Send + Sync>(
+        &self,
+        tx: Call,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
 }
 
 /// Data regarding submitted transaction.
@@ -161,7 +172,7 @@ pub trait SignedConnectionApi: ConnectionApi {
     /// ```ignore
     ///     let tx = api::tx()
     ///         .balances()
-    ///         .transfer(MultiAddress::Id(dest), amount);
+    ///         .transfer_keep_alive(Static(dest), amount);
     ///     send_tx(tx, status).await
     /// ```
     async fn send_tx<Call: TxPayload + Send + Sync>(
@@ -322,6 +333,31 @@ impl<C: AsConnection + Sync> ConnectionApi for C {
 
         Ok(())
     }
+
+    async fn send_unsigned_tx<Call: TxPayload + Send + Sync>(
+        &self,
+        tx: Call,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        if let Some(details) = tx.validation_details() {
+            info!(
+                target:"aleph-client", "Sending unsigned extrinsic {}.{}",
+                details.pallet_name,
+                details.call_name,
+            );
+        }
+
+        let unsigned: SubmittableExtrinsic = self
+            .as_connection()
+            .as_client()
+            .tx()
+            .create_unsigned(&tx)?
+            .into();
+        let info = unsigned.submit(status).await?;
+        info!(target: "aleph-client", "tx with hash {:?} included in block {:?}", info.tx_hash, info.block_hash);
+
+        Ok(info)
+    }
 }
 
 impl SubmittableExtrinsic {
@@ -432,7 +468,7 @@ impl Connection {
     const RETRY_WAIT_SECS: u64 = 6;
 
     /// Creates new connection from a given url.
-    /// By default, it tries to connect 10 times, waiting 1 second between each unsuccessful attempt.
+    /// By default, it tries to connect 10 times, waiting 6 seconds between each unsuccessful attempt.
     /// * `address` - address in websocket format, e.g. `ws://127.0.0.1:9943`
     pub async fn new(address: &str) -> Connection {
         Self::new_with_retries(address, Self::DEFAULT_RETRIES).await
@@ -441,19 +477,49 @@ impl Connection {
     /// Creates new connection from a given url and given number of connection attempts.
     /// * `address` - address in websocket format, e.g. `ws://127.0.0.1:9943`
     /// * `retries` - number of connection attempts
-    async fn new_with_retries(address: &str, mut retries: u32) -> Connection {
-        loop {
-            debug!(target: "aleph-client", "new_with_retries: address={address} retries_left={retries}");
-            let client = SubxtClient::from_url(&address).await;
-            match (retries, client) {
-                (_, Ok(client)) => return Connection { client },
-                (0, Err(e)) => panic!("new_with_retries failed for address {address}: {e:?}"),
-                _ => {
-                    sleep(Duration::from_secs(Self::RETRY_WAIT_SECS));
-                    retries -= 1;
-                }
-            }
+    async fn new_with_retries(address: &str, retries: u32) -> Connection {
+        let wait = Duration::from_secs(Self::RETRY_WAIT_SECS);
+        let config = ReconnectConfig {
+            initial_backoff: wait,
+            max_backoff: wait,
+            max_attempts: retries + 1,
+        };
+
+        Self::with_endpoints(vec![address.to_string()], config)
+            .await
+            .unwrap_or_else(|e| panic!("new_with_retries failed for address {address}: {e:?}"))
+    }
+
+    /// Creates new connection to the first of given urls that works.
+    /// * `endpoints` - addresses in websocket format, e.g. `ws://127.0.0.1:9943`, in order of preference
+    /// * `config` - how to reconnect when an endpoint stops responding
+    pub async fn with_endpoints(
+        endpoints: Vec<String>,
+        config: ReconnectConfig,
+    ) -> anyhow::Result<Connection> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("At least one endpoint is needed"));
         }
+        let failover = Arc::new(FailoverRpc::new(endpoints, config));
+        let client = SubxtClient::from_rpc_client(failover.clone()).await?;
+
+        Ok(Connection { client, failover })
+    }
+
+    /// Returns a stream of [`ConnectionEvent`]s, e.g. disconnections and failovers, from now on.
+    pub fn connection_events(&self) -> UnboundedReceiver<ConnectionEvent> {
+        self.failover.events()
+    }
+
+    /// Returns the endpoint requests are currently sent to.
+    pub async fn current_endpoint(&self) -> String {
+        self.failover.current_endpoint().await.to_string()
+    }
+
+    /// Checks that every other endpoint finalized the same blocks as the current one, up to the
+    /// lower of their finalized heads. Mismatches are returned and reported as [`ConnectionEvent`]s.
+    pub async fn check_finalized_heads(&self) -> anyhow::Result<Vec<ConnectionEvent>> {
+        self.failover.check_finalized_heads().await
     }
 
     /// Casts self to the underlying RPC client.
@@ -0,0 +1,283 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use ethereum::{
+    AccessListItem, EIP1559Transaction, EIP1559TransactionMessage, LegacyTransaction,
+    LegacyTransactionMessage, TransactionSignature,
+};
+pub use ethereum::{Log, ReceiptV3, TransactionAction, TransactionV2};
+use subxt::ext::{
+    sp_core::{blake2_256, ecdsa, keccak_256, Pair, H160, H256},
+    sp_runtime::{traits::IdentifyAccount, MultiSigner},
+};
+
+use crate::{pallets::evm::EvmTxParams, AccountId};
+
+/// A secp256k1 key of an Ethereum account.
+#[derive(Clone)]
+pub struct EthKeyPair {
+    pair: ecdsa::Pair,
+}
+
+impl FromStr for EthKeyPair {
+    type Err = anyhow::Error;
+
+    /// Accepts a hex encoded private key, with or without `0x`, or a secret URI, e.g. `//Alice`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let seed = s.strip_prefix("0x").unwrap_or(s);
+        if let Ok(seed) = <[u8; 32]>::try_from(hex::decode(seed).unwrap_or_default()) {
+            return Ok(Self::new(ecdsa::Pair::from_seed(&seed)));
+        }
+        let pair = ecdsa::Pair::from_string(s, None)
+            .map_err(|e| anyhow!("Can't create pair from seed value: {:?}", e))?;
+        Ok(Self::new(pair))
+    }
+}
+
+impl EthKeyPair {
+    /// Constructs a new EthKeyPair from an ecdsa key pair.
+    pub fn new(pair: ecdsa::Pair) -> Self {
+        Self { pair }
+    }
+
+    /// Returns a reference to the inner ecdsa key pair.
+    pub fn signer(&self) -> &ecdsa::Pair {
+        &self.pair
+    }
+
+    /// Returns the Ethereum address of the key, i.e. the last 20 bytes of the keccak hash of the
+    /// uncompressed public key.
+    pub fn address(&self) -> H160 {
+        let public = libsecp256k1::PublicKey::parse_slice(self.pair.public().as_ref(), None)
+            .expect("ecdsa public keys are valid secp256k1 points");
+        let hash = keccak_256(&public.serialize()[1..]);
+        H160::from_slice(&hash[12..])
+    }
+
+    /// Returns the account holding the balance of [`EthKeyPair::address`] in pallet evm.
+    pub fn evm_account_id(&self) -> AccountId {
+        evm_account_id(self.address())
+    }
+
+    /// Returns the account the key signs for in pallet custom signatures.
+    pub fn account_id(&self) -> AccountId {
+        MultiSigner::from(self.pair.public()).into_account()
+    }
+
+    /// Signs a message the way `eth_sign` does, prefixing the keccak hash of the message.
+    /// Returns 65 bytes: `r`, `s` and the recovery id.
+    pub fn sign_message(&self, message: &[u8]) -> [u8; 65] {
+        let mut signable = b"\x19Ethereum Signed Message:\n32".to_vec();
+        signable.extend_from_slice(&keccak_256(message));
+
+        self.pair.sign_prehashed(&keccak_256(&signable)).0
+    }
+
+    /// Signs an Ethereum transaction. It is a legacy, EIP-155 transaction if
+    /// `params.max_priority_fee_per_gas` is not set, with `params.max_fee_per_gas` as the gas
+    /// price, and an EIP-1559 one otherwise.
+    /// * `chain_id` - the chain id of the EVM, see [`crate::pallets::evm::EvmApi::chain_id`]
+    /// * `action` - a contract to call, or [`TransactionAction::Create`]
+    /// * `input` - call data, or the init code of a contract to create
+    /// * `params` - gas and fee settings, with the nonce set
+    pub fn sign_transaction(
+        &self,
+        chain_id: u64,
+        action: TransactionAction,
+        input: Vec<u8>,
+        params: &EvmTxParams,
+    ) -> anyhow::Result<TransactionV2> {
+        let nonce = params
+            .nonce
+            .ok_or_else(|| anyhow!("The nonce of an Ethereum transaction has to be set"))?;
+
+        match params.max_priority_fee_per_gas {
+            None => {
+                if !params.access_list.is_empty() {
+                    return Err(anyhow!("Access lists need EIP-1559 fees"));
+                }
+                let message = LegacyTransactionMessage {
+                    nonce,
+                    gas_price: params.max_fee_per_gas,
+                    gas_limit: params.gas_limit.into(),
+                    action,
+                    value: params.value,
+                    input,
+                    chain_id: Some(chain_id),
+                };
+                let (r, s, recovery_id) = self.sign_hash(message.hash());
+                let signature =
+                    TransactionSignature::new(recovery_id as u64 + 35 + 2 * chain_id, r, s)
+                        .ok_or_else(|| anyhow!("Invalid transaction signature"))?;
+
+                Ok(TransactionV2::Legacy(LegacyTransaction {
+                    nonce: message.nonce,
+                    gas_price: message.gas_price,
+                    gas_limit: message.gas_limit,
+                    action: message.action,
+                    value: message.value,
+                    input: message.input,
+                    signature,
+                }))
+            }
+            Some(max_priority_fee_per_gas) => {
+                let message = EIP1559TransactionMessage {
+                    chain_id,
+                    nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas: params.max_fee_per_gas,
+                    gas_limit: params.gas_limit.into(),
+                    action,
+                    value: params.value,
+                    input,
+                    access_list: params
+                        .access_list
+                        .iter()
+                        .map(|(address, storage_keys)| AccessListItem {
+                            address: *address,
+                            storage_keys: storage_keys.clone(),
+                        })
+                        .collect(),
+                };
+                let (r, s, recovery_id) = self.sign_hash(message.hash());
+
+                Ok(TransactionV2::EIP1559(EIP1559Transaction {
+                    chain_id: message.chain_id,
+                    nonce: message.nonce,
+                    max_priority_fee_per_gas: message.max_priority_fee_per_gas,
+                    max_fee_per_gas: message.max_fee_per_gas,
+                    gas_limit: message.gas_limit,
+                    action: message.action,
+                    value: message.value,
+                    input: message.input,
+                    access_list: message.access_list,
+                    odd_y_parity: recovery_id == 1,
+                    r,
+                    s,
+                }))
+            }
+        }
+    }
+
+    fn sign_hash(&self, hash: H256) -> (H256, H256, u8) {
+        let signature = self.pair.sign_prehashed(&hash.0).0;
+        (
+            H256::from_slice(&signature[..32]),
+            H256::from_slice(&signature[32..64]),
+            signature[64],
+        )
+    }
+}
+
+/// Returns the account holding the balance of an Ethereum address in pallet evm.
+/// * `address` - an Ethereum address
+pub fn evm_account_id(address: H160) -> AccountId {
+    let mut data = b"evm:".to_vec();
+    data.extend_from_slice(address.as_bytes());
+    AccountId::from(blake2_256(&data))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethereum::{EIP1559TransactionMessage, LegacyTransactionMessage};
+    use subxt::ext::sp_core::{H160, U256};
+
+    use super::*;
+
+    fn hardhat_key() -> EthKeyPair {
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+            .parse()
+            .unwrap()
+    }
+
+    fn recover(hash: H256, r: H256, s: H256, recovery_id: u8) -> H160 {
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(r.as_bytes());
+        signature[32..].copy_from_slice(s.as_bytes());
+        let public = libsecp256k1::recover(
+            &libsecp256k1::Message::parse(&hash.0),
+            &libsecp256k1::Signature::parse_standard(&signature).unwrap(),
+            &libsecp256k1::RecoveryId::parse(recovery_id).unwrap(),
+        )
+        .unwrap();
+        H160::from_slice(&keccak_256(&public.serialize()[1..])[12..])
+    }
+
+    fn params() -> EvmTxParams {
+        EvmTxParams {
+            value: U256::from(1_000),
+            gas_limit: 21_000,
+            max_fee_per_gas: U256::from(1_000_000_000u64),
+            nonce: Some(U256::from(3)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn derives_the_ethereum_address() {
+        assert_eq!(
+            hardhat_key().address(),
+            H160::from_slice(&hex::decode("f39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap())
+        );
+    }
+
+    #[test]
+    fn signs_legacy_transactions_for_the_chain() {
+        let key = hardhat_key();
+        let to = H160::repeat_byte(7);
+        let transaction = key
+            .sign_transaction(1961, TransactionAction::Call(to), vec![], &params())
+            .unwrap();
+
+        let TransactionV2::Legacy(transaction) = transaction else {
+            panic!("expected a legacy transaction");
+        };
+        assert_eq!(transaction.signature.chain_id(), Some(1961));
+        let message = LegacyTransactionMessage::from(transaction.clone());
+        assert_eq!(
+            recover(
+                message.hash(),
+                *transaction.signature.r(),
+                *transaction.signature.s(),
+                transaction.signature.standard_v(),
+            ),
+            key.address()
+        );
+    }
+
+    #[test]
+    fn signs_eip1559_transactions() {
+        let key = hardhat_key();
+        let mut params = params();
+        params.max_priority_fee_per_gas = Some(U256::from(1));
+        params.access_list = vec![(H160::repeat_byte(7), vec![H256::repeat_byte(1)])];
+        let transaction = key
+            .sign_transaction(1961, TransactionAction::Create, vec![0x60, 0x00], &params)
+            .unwrap();
+
+        let TransactionV2::EIP1559(transaction) = transaction else {
+            panic!("expected an EIP-1559 transaction");
+        };
+        let message = EIP1559TransactionMessage::from(transaction.clone());
+        assert_eq!(message.access_list.len(), 1);
+        assert_eq!(
+            recover(
+                message.hash(),
+                transaction.r,
+                transaction.s,
+                transaction.odd_y_parity as u8,
+            ),
+            key.address()
+        );
+    }
+
+    #[test]
+    fn refuses_legacy_transactions_with_access_lists() {
+        let mut params = params();
+        params.access_list = vec![(H160::repeat_byte(7), vec![])];
+
+        assert!(hardhat_key()
+            .sign_transaction(1961, TransactionAction::Create, vec![], &params)
+            .is_err());
+    }
+}
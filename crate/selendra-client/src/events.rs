@@ -0,0 +1,394 @@
+use std::{cmp::Ordering, collections::VecDeque, time::Duration};
+
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use log::{debug, warn};
+use primitives::BlockNumber;
+use subxt::{
+    blocks::Block,
+    events::{EventDetails, Phase, StaticEvent},
+};
+use tokio::time::sleep;
+
+use crate::{waiting::BlockStatus, AlephConfig, BlockHash, Connection, SubxtClient};
+
+type DecodeFn<E> =
+    Box<dyn Fn(&EventDetails<AlephConfig>) -> Option<Result<E, subxt::Error>> + Send + Sync>;
+
+struct Decoder<E> {
+    pallet: &'static str,
+    event: &'static str,
+    decode: DecodeFn<E>,
+}
+
+/// Event types an [`EventStream`] decodes, each mapped into a common type `E`.
+///
+/// # Examples
+/// ```ignore
+/// enum CommitteeEvent {
+///     Banned(BanValidators),
+///     Changed(ChangeValidators),
+/// }
+///
+/// let filter = EventFilter::new()
+///     .with(CommitteeEvent::Banned)
+///     .with(CommitteeEvent::Changed);
+/// ```
+pub struct EventFilter<E> {
+    decoders: Vec<Decoder<E>>,
+}
+
+impl<E> Default for EventFilter<E> {
+    fn default() -> Self {
+        Self {
+            decoders: Vec::new(),
+        }
+    }
+}
+
+impl<E: 'static> EventFilter<E> {
+    /// Creates a filter that does not let any event through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets events of type `T` through.
+    /// * `into` - maps a decoded event into the type of the stream
+    pub fn with<T: StaticEvent + 'static>(
+        mut self,
+        into: impl Fn(T) -> E + Send + Sync + 'static,
+    ) -> Self {
+        self.decoders.push(Decoder {
+            pallet: T::PALLET,
+            event: T::EVENT,
+            decode: Box::new(move |details| {
+                details
+                    .as_event::<T>()
+                    .transpose()
+                    .map(|event| event.map(&into))
+            }),
+        });
+        self
+    }
+
+    fn decoder(&self, pallet: &str, event: &str) -> Option<&Decoder<E>> {
+        self.decoders
+            .iter()
+            .find(|decoder| decoder.pallet == pallet && decoder.event == event)
+    }
+
+    fn decode(&self, details: &EventDetails<AlephConfig>) -> Option<Result<E, subxt::Error>> {
+        let decoder = self.decoder(details.pallet_name(), details.variant_name())?;
+        (decoder.decode)(details)
+    }
+}
+
+/// An event emitted in a block.
+#[derive(Clone, Debug)]
+pub struct BlockEvent<E> {
+    /// Number of the block.
+    pub block_number: BlockNumber,
+    /// Hash of the block.
+    pub block_hash: BlockHash,
+    /// Index of the extrinsic that emitted the event, `None` for events emitted while
+    /// initializing or finalizing the block.
+    pub extrinsic_index: Option<u32>,
+    /// The event.
+    pub event: E,
+}
+
+/// A subscription to events of given types, block after block.
+///
+/// Every block, starting from the given one or from the first one the subscription brings, is
+/// visited exactly once and in order: when the subscription breaks, e.g. because the connection
+/// failed over to another endpoint, the stream subscribes again and fetches the blocks it missed
+/// by number before moving on. With [`BlockStatus::Best`], blocks replaced by a reorg
+/// are not visited again.
+///
+/// # Examples
+/// ```ignore
+/// let mut events = EventStream::new(connection, filter, BlockStatus::Finalized)
+///     .from_block(last_seen + 1)
+///     .into_stream();
+/// while let Some(event) = events.next().await {
+///     let event = event?;
+///     info!("#{} {:?}", event.block_number, event.extrinsic_index);
+/// }
+/// ```
+pub struct EventStream<E> {
+    connection: Connection,
+    filter: EventFilter<E>,
+    status: BlockStatus,
+    next_block: Option<BlockNumber>,
+}
+
+impl<E: Send + 'static> EventStream<E> {
+    const RETRY_WAIT_SECS: u64 = 3;
+
+    /// Creates a stream of events that pass a filter.
+    /// * `connection` - a connection to subscribe with
+    /// * `filter` - types of events to decode
+    /// * `status` - a [`BlockStatus`] of blocks to read events from
+    pub fn new(connection: Connection, filter: EventFilter<E>, status: BlockStatus) -> Self {
+        Self {
+            connection,
+            filter,
+            status,
+            next_block: None,
+        }
+    }
+
+    /// Starts the stream at a given block, instead of the first one the subscription brings.
+    /// * `block` - number of the first block to read events from
+    pub fn from_block(mut self, block: BlockNumber) -> Self {
+        self.next_block = Some(block);
+        self
+    }
+
+    /// Starts the subscription. Connection problems are not returned, the stream subscribes again
+    /// until it succeeds; errors are about events that could not be decoded.
+    pub fn into_stream(self) -> BoxStream<'static, anyhow::Result<BlockEvent<E>>> {
+        let driver = Driver {
+            stream: self,
+            subscription: None,
+            live: None,
+            pending: VecDeque::new(),
+        };
+
+        stream::unfold(driver, |mut driver| async move {
+            let event = driver.next_event().await;
+            Some((event, driver))
+        })
+        .boxed()
+    }
+}
+
+/// What to do with the newest block brought by the subscription.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    /// The block was already visited, e.g. it is a best block replacing a visited one.
+    Skip,
+    /// The block is the next one to visit.
+    Visit,
+    /// Blocks before it were not visited yet, the one with the given number goes first.
+    Backfill(BlockNumber),
+}
+
+fn step(next_block: Option<BlockNumber>, live: BlockNumber) -> Step {
+    let next = next_block.unwrap_or(live);
+    match live.cmp(&next) {
+        Ordering::Less => Step::Skip,
+        Ordering::Equal => Step::Visit,
+        Ordering::Greater => Step::Backfill(next),
+    }
+}
+
+type Subscription = BoxStream<'static, Result<Block<AlephConfig, SubxtClient>, subxt::Error>>;
+
+struct Driver<E> {
+    stream: EventStream<E>,
+    subscription: Option<Subscription>,
+    /// The newest block brought by the subscription, if blocks before it still have to be visited.
+    live: Option<Block<AlephConfig, SubxtClient>>,
+    pending: VecDeque<anyhow::Result<BlockEvent<E>>>,
+}
+
+impl<E: Send + 'static> Driver<E> {
+    async fn next_event(&mut self) -> anyhow::Result<BlockEvent<E>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+
+            match self.next_block().await {
+                Ok(block) => self.read_events(block).await,
+                Err(e) => {
+                    warn!(target: "aleph-client", "Event subscription broke: {}", e);
+                    self.subscription = None;
+                    self.live = None;
+                    sleep(Duration::from_secs(EventStream::<E>::RETRY_WAIT_SECS)).await;
+                }
+            }
+        }
+    }
+
+    async fn subscribe(&self) -> Result<Subscription, subxt::Error> {
+        let blocks = self.stream.connection.as_client().blocks();
+        Ok(match self.stream.status {
+            BlockStatus::Best => blocks.subscribe_best().await?.boxed(),
+            BlockStatus::Finalized => blocks.subscribe_finalized().await?.boxed(),
+        })
+    }
+
+    /// Returns the block after the last visited one.
+    async fn next_block(&mut self) -> Result<Block<AlephConfig, SubxtClient>, subxt::Error> {
+        loop {
+            if let Some(live) = self.live.take() {
+                match step(self.stream.next_block, live.number()) {
+                    Step::Skip => continue,
+                    Step::Visit => {
+                        self.stream.next_block = Some(live.number() + 1);
+                        return Ok(live);
+                    }
+                    Step::Backfill(next) => {
+                        self.live = Some(live);
+                        let block = self.block_at(next).await?;
+                        self.stream.next_block = Some(next + 1);
+                        return Ok(block);
+                    }
+                }
+            }
+
+            if self.subscription.is_none() {
+                self.subscription = Some(self.subscribe().await?);
+            }
+            let subscription = self.subscription.as_mut().expect("just subscribed");
+            match subscription.next().await {
+                Some(block) => self.live = Some(block?),
+                None => return Err(subxt::Error::Other("The subscription ended".to_string())),
+            }
+        }
+    }
+
+    async fn block_at(
+        &self,
+        number: BlockNumber,
+    ) -> Result<Block<AlephConfig, SubxtClient>, subxt::Error> {
+        debug!(target: "aleph-client", "Backfilling events of block #{}", number);
+        let client = self.stream.connection.as_client();
+        let hash = client
+            .rpc()
+            .block_hash(Some(number.into()))
+            .await?
+            .ok_or_else(|| subxt::Error::Other(format!("Block #{number} not found")))?;
+
+        client.blocks().at(hash).await
+    }
+
+    async fn read_events(&mut self, block: Block<AlephConfig, SubxtClient>) {
+        let block_number = block.number();
+        let block_hash = block.hash();
+        let events = match block.events().await {
+            Ok(events) => events,
+            Err(e) => {
+                self.pending.push_back(Err(anyhow::anyhow!(
+                    "Could not read events of block #{block_number}: {e}"
+                )));
+                return;
+            }
+        };
+
+        for details in events.iter() {
+            let decoded = details.and_then(|details| {
+                let extrinsic_index = match details.phase() {
+                    Phase::ApplyExtrinsic(index) => Some(index),
+                    _ => None,
+                };
+                Ok(self
+                    .stream
+                    .filter
+                    .decode(&details)
+                    .transpose()?
+                    .map(|event| BlockEvent {
+                        block_number,
+                        block_hash,
+                        extrinsic_index,
+                        event,
+                    }))
+            });
+            match decoded {
+                Ok(Some(event)) => self.pending.push_back(Ok(event)),
+                Ok(None) => {}
+                Err(e) => self.pending.push_back(Err(anyhow::anyhow!(
+                    "Could not decode an event of block #{block_number}: {e}"
+                ))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use primitives::BlockNumber;
+
+    use super::{step, EventFilter, Step};
+    use crate::api::system::events::{ExtrinsicFailed, ExtrinsicSuccess};
+
+    fn filter() -> EventFilter<&'static str> {
+        EventFilter::new()
+            .with(|_: ExtrinsicSuccess| "success")
+            .with(|_: ExtrinsicFailed| "failed")
+    }
+
+    #[test]
+    fn filter_picks_decoder_by_pallet_and_event() {
+        let filter = filter();
+        let decoder = filter
+            .decoder("System", "ExtrinsicFailed")
+            .expect("the event passes the filter");
+        assert_eq!(
+            (decoder.pallet, decoder.event),
+            ("System", "ExtrinsicFailed")
+        );
+        let decoder = filter
+            .decoder("System", "ExtrinsicSuccess")
+            .expect("the event passes the filter");
+        assert_eq!(decoder.event, "ExtrinsicSuccess");
+    }
+
+    #[test]
+    fn filter_stops_other_events() {
+        let filter = filter();
+        assert!(filter.decoder("System", "NewAccount").is_none());
+        assert!(filter.decoder("Balances", "ExtrinsicSuccess").is_none());
+        assert!(EventFilter::<()>::new()
+            .decoder("System", "ExtrinsicSuccess")
+            .is_none());
+    }
+
+    /// Blocks the stream visits, in order, when the subscription brings the given ones.
+    fn visited(from_block: Option<BlockNumber>, subscription: &[BlockNumber]) -> Vec<BlockNumber> {
+        let mut next_block = from_block;
+        let mut visited = Vec::new();
+        for live in subscription {
+            loop {
+                match step(next_block, *live) {
+                    Step::Skip => break,
+                    Step::Visit => {
+                        visited.push(*live);
+                        next_block = Some(live + 1);
+                        break;
+                    }
+                    Step::Backfill(next) => {
+                        visited.push(next);
+                        next_block = Some(next + 1);
+                    }
+                }
+            }
+        }
+        visited
+    }
+
+    #[test]
+    fn starts_at_first_subscribed_block() {
+        assert_eq!(visited(None, &[4, 5, 6]), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn backfills_blocks_before_first_subscribed_one() {
+        assert_eq!(visited(Some(2), &[5, 6]), vec![2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn backfills_blocks_missed_while_resubscribing() {
+        assert_eq!(visited(None, &[4, 5, 9, 10]), vec![4, 5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn skips_already_visited_blocks() {
+        assert_eq!(visited(None, &[4, 5, 5, 4, 6]), vec![4, 5, 6]);
+        assert_eq!(visited(Some(8), &[4, 7, 8]), vec![8]);
+    }
+}
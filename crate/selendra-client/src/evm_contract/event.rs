@@ -0,0 +1,294 @@
+//! This module provides utilities corresponding to the events emitted by an EVM contract, i.e. its
+//! logs.
+//!
+//! There are three ways that you can get contract events:
+//!  1. By fetching events corresponding to a particular transaction. For this, you will need to
+//!     provide a connection, some contracts and transaction coordinate to [get_contract_events]
+//!     function. Similarly to [crate::utility::BlocksApi::get_tx_events], it will fetch block
+//!     events, filter them and decode all relevant ones.
+//!  2. By decoding the logs of a receipt, see [crate::pallets::ethereum::EthereumApi::receipt],
+//!     with [translate_logs].
+//!  3. By listening to all contract events. For this, you will need to provide a connection, some
+//!     contracts and an `UnboundedSender` to the [listen_contract_events] function. In a loop,
+//!     it will inspect every finalized block and look for contract events.
+
+use std::{collections::HashMap, error::Error};
+
+use anyhow::{anyhow, Result};
+use codec::{Decode, Encode};
+use ethabi::RawLog;
+use futures::{channel::mpsc::UnboundedSender, StreamExt};
+use primitives::BlockNumber;
+use subxt::{
+    events::EventDetails,
+    ext::sp_core::{H160, H256},
+};
+
+use super::{EvmContract, Token};
+use crate::{
+    api::evm::events::Log as EvmLog,
+    eth::Log,
+    events::{EventFilter, EventStream},
+    utility::BlocksApi,
+    waiting::BlockStatus,
+    AlephConfig, Connection, TxInfo,
+};
+
+/// Represents details about the block containing the event.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlockDetails {
+    /// the block number
+    pub block_number: BlockNumber,
+    /// the block hash
+    pub block_hash: H256,
+}
+
+/// Represents a single event emitted by a contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractEvent {
+    /// The address of the contract that emitted the event.
+    pub contract: H160,
+    /// The name of the event.
+    pub name: String,
+    /// Data contained in the event, indexed parameters included.
+    pub data: HashMap<String, Token>,
+    /// details about the block containing the event
+    pub block_details: Option<BlockDetails>,
+}
+
+/// Fetch and decode all events that correspond to the transaction identified by `tx_info`, made to
+/// one of `contracts`. Calls made through pallet evm and through pallet ethereum are covered alike,
+/// since both emit `evm.Log` events.
+///
+/// ```no_run
+/// # use selendra_client::{Connection, TxStatus};
+/// # use selendra_client::eth::EthKeyPair;
+/// # use selendra_client::evm_contract::EvmContract;
+/// # use selendra_client::evm_contract::event::get_contract_events;
+/// # use selendra_client::sp_core::H160;
+/// # use anyhow::Result;
+/// # async fn example(conn: Connection, signer: EthKeyPair, address: H160, path: &str) -> Result<()> {
+/// let contract = EvmContract::new(address, path)?;
+///
+/// let (_, tx_info) = contract
+///     .exec(&conn, &signer, "some_method", &[], Default::default(), TxStatus::Finalized)
+///     .await?;
+///
+/// println!("Received events {:?}", get_contract_events(&conn, &[&contract], tx_info).await);
+///
+/// #   Ok(())
+/// # }
+/// ```
+pub async fn get_contract_events(
+    conn: &Connection,
+    contracts: &[&EvmContract],
+    tx_info: TxInfo,
+) -> Result<Vec<ContractEvent>> {
+    let events = conn.get_tx_events(tx_info).await?;
+    translate_events(events.iter(), contracts, None)
+        .into_iter()
+        .collect()
+}
+
+/// Starts an event listening loop. Will send contract event and every error encountered while
+/// decoding through the provided [UnboundedSender].
+///
+/// Only logs coming from the address of one of the `contracts` will be decoded. Blocks are read
+/// with an [EventStream], so no block is skipped when the connection fails over.
+///
+/// The loop will terminate once `sender` is closed.
+///
+/// You most likely want to `tokio::spawn` the resulting future, so that it runs concurrently.
+pub async fn listen_contract_events(
+    conn: &Connection,
+    contracts: &[&EvmContract],
+    sender: UnboundedSender<Result<ContractEvent>>,
+) -> Result<()> {
+    let filter = EventFilter::new().with(|event: EvmLog| event);
+    let mut events = EventStream::new(conn.clone(), filter, BlockStatus::Finalized).into_stream();
+
+    while let Some(event) = events.next().await {
+        if sender.is_closed() {
+            break;
+        }
+
+        let event = event.and_then(|event| {
+            let block_details = BlockDetails {
+                block_number: event.block_number,
+                block_hash: event.block_hash,
+            };
+            let log = Log::decode(&mut &event.event.log.encode()[..])?;
+            translate_log(&log, contracts, Some(block_details))
+        });
+        if !matches!(&event, Err(e) if e.is::<NotWatched>()) {
+            sender.unbounded_send(event)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Try to convert `evm.Log` events among `events` to `ContractEvent` using matching contract from
+/// `contracts`. Logs of other contracts are skipped.
+pub fn translate_events<
+    Err: Error + Into<anyhow::Error> + Send + Sync + 'static,
+    E: Iterator<Item = Result<EventDetails<AlephConfig>, Err>>,
+>(
+    events: E,
+    contracts: &[&EvmContract],
+    block_details: Option<BlockDetails>,
+) -> Vec<Result<ContractEvent>> {
+    events
+        .filter_map(|maybe_event| {
+            maybe_event
+                .map(|e| e.as_event::<EvmLog>().ok().flatten())
+                .transpose()
+        })
+        .map(|maybe_event| {
+            let event = maybe_event.map_err(anyhow::Error::from)?;
+            Ok(Log::decode(&mut &event.log.encode()[..])?)
+        })
+        .filter_map(|maybe_log: Result<Log>| match maybe_log {
+            Ok(log) => match translate_log(&log, contracts, block_details.clone()) {
+                Err(e) if e.is::<NotWatched>() => None,
+                event => Some(event),
+            },
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
+/// Try to convert `logs`, e.g. of an [crate::pallets::ethereum::EthReceipt], to `ContractEvent`
+/// using matching contract from `contracts`. Logs of other contracts are skipped.
+pub fn translate_logs(
+    logs: &[Log],
+    contracts: &[&EvmContract],
+    block_details: Option<BlockDetails>,
+) -> Vec<Result<ContractEvent>> {
+    logs.iter()
+        .filter_map(
+            |log| match translate_log(log, contracts, block_details.clone()) {
+                Err(e) if e.is::<NotWatched>() => None,
+                event => Some(event),
+            },
+        )
+        .collect()
+}
+
+/// The log was not emitted by any of the given contracts.
+#[derive(Debug)]
+struct NotWatched;
+
+impl std::fmt::Display for NotWatched {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The log wasn't emitted by any of the provided contracts")
+    }
+}
+
+impl Error for NotWatched {}
+
+/// Try to convert `log` to `ContractEvent` using matching contract from `contracts`. The event is
+/// recognized by its signature, i.e. the first topic, so anonymous events are not supported.
+fn translate_log(
+    log: &Log,
+    contracts: &[&EvmContract],
+    block_details: Option<BlockDetails>,
+) -> Result<ContractEvent> {
+    let matching_contract = contracts
+        .iter()
+        .find(|contract| contract.address() == &log.address)
+        .ok_or(NotWatched)?;
+
+    let signature = log
+        .topics
+        .first()
+        .ok_or_else(|| anyhow!("Anonymous events are not supported"))?;
+    let event = matching_contract
+        .abi()
+        .events()
+        .find(|event| !event.anonymous && &event.signature() == signature)
+        .ok_or_else(|| anyhow!("Unknown event with signature {:?}", signature))?;
+    let decoded = event.parse_log(RawLog {
+        topics: log.topics.clone(),
+        data: log.data.clone(),
+    })?;
+
+    Ok(ContractEvent {
+        contract: log.address,
+        name: event.name.clone(),
+        data: decoded
+            .params
+            .into_iter()
+            .map(|param| (param.name, param.value))
+            .collect(),
+        block_details,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use subxt::ext::sp_core::U256;
+
+    use super::*;
+    use crate::evm_contract::Abi;
+
+    const ERC20_ABI: &str = r#"[{
+        "type": "event",
+        "name": "Transfer",
+        "anonymous": false,
+        "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false}
+        ]
+    }]"#;
+
+    fn contract() -> EvmContract {
+        EvmContract::from_abi(
+            H160::repeat_byte(1),
+            Abi::load(ERC20_ABI.as_bytes()).unwrap(),
+        )
+    }
+
+    fn transfer_log(address: H160) -> Log {
+        let signature = contract().abi().event("Transfer").unwrap().signature();
+        Log {
+            address,
+            topics: vec![
+                signature,
+                H256::from(H160::repeat_byte(2)),
+                H256::from(H160::repeat_byte(3)),
+            ],
+            data: ethabi::encode(&[Token::Uint(U256::from(1_000))]),
+        }
+    }
+
+    #[test]
+    fn decodes_logs_into_named_fields() {
+        let contract = contract();
+        let events = translate_logs(&[transfer_log(*contract.address())], &[&contract], None);
+
+        let event = events.into_iter().next().unwrap().unwrap();
+        assert_eq!(event.name, "Transfer");
+        assert_eq!(
+            event.data.get("from"),
+            Some(&Token::Address(H160::repeat_byte(2)))
+        );
+        assert_eq!(
+            event.data.get("to"),
+            Some(&Token::Address(H160::repeat_byte(3)))
+        );
+        assert_eq!(
+            event.data.get("value"),
+            Some(&Token::Uint(U256::from(1_000)))
+        );
+    }
+
+    #[test]
+    fn skips_logs_of_other_contracts() {
+        let contract = contract();
+        let events = translate_logs(&[transfer_log(H160::repeat_byte(9))], &[&contract], None);
+
+        assert!(events.is_empty());
+    }
+}
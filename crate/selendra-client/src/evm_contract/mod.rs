@@ -0,0 +1,277 @@
+//! Contains types and functions simplifying common operations on EVM contracts, described by
+//! their Solidity ABI.
+//!
+//! For example, you could write this wrapper around (some of) the functionality of ERC-20
+//! contracts using the building blocks provided by this module:
+//!
+//! ```no_run
+//! # use anyhow::{anyhow, Result};
+//! # use selendra_client::{Connection, TxInfo, TxStatus};
+//! # use selendra_client::eth::EthKeyPair;
+//! # use selendra_client::evm_contract::{EvmContract, Token};
+//! # use selendra_client::sp_core::{H160, H256, U256};
+//! #
+//! #[derive(Debug)]
+//! struct Erc20Instance {
+//!     contract: EvmContract,
+//! }
+//!
+//! impl Erc20Instance {
+//!     fn new(address: H160, abi_path: &str) -> Result<Self> {
+//!         Ok(Self {
+//!             contract: EvmContract::new(address, abi_path)?,
+//!         })
+//!     }
+//!
+//!     async fn transfer(&self, conn: &Connection, signer: &EthKeyPair, to: H160, amount: U256) -> Result<(H256, TxInfo)> {
+//!         self.contract.exec(
+//!             conn,
+//!             signer,
+//!             "transfer",
+//!             &[Token::Address(to), Token::Uint(amount)],
+//!             Default::default(),
+//!             TxStatus::Finalized,
+//!         ).await
+//!     }
+//!
+//!     async fn balance_of(&self, conn: &Connection, account: H160) -> Result<U256> {
+//!         self.contract
+//!             .read(conn, "balanceOf", &[Token::Address(account)], Default::default())
+//!             .await?
+//!             .pop()
+//!             .and_then(Token::into_uint)
+//!             .ok_or_else(|| anyhow!("balanceOf returns a uint256"))
+//!     }
+//! }
+//! ```
+
+pub mod event;
+
+use std::{
+    fmt::{Debug, Formatter},
+    fs::File,
+};
+
+use anyhow::{anyhow, Context, Result};
+use codec::{Decode, Encode, Error as CodecError, Input};
+pub use ethabi::{Contract as Abi, Token};
+use log::info;
+use subxt::{
+    ext::{
+        sp_core::{Bytes, H160, H256, U256},
+        sp_runtime::DispatchError,
+    },
+    rpc_params,
+};
+
+use crate::{
+    eth::{EthKeyPair, TransactionAction},
+    pallets::{ethereum::EthereumUserApi, evm::EvmTxParams},
+    AsConnection, BlockHash, ConnectionApi, TxInfo, TxStatus,
+};
+
+/// Represents an EVM contract deployed on the chain.
+pub struct EvmContract {
+    address: H160,
+    abi: Abi,
+}
+
+/// Builder for read only contract call
+#[derive(Debug, Clone)]
+pub struct ReadonlyCallParams {
+    at: Option<BlockHash>,
+    sender: Option<H160>,
+    gas_limit: U256,
+}
+
+impl Default for ReadonlyCallParams {
+    fn default() -> Self {
+        Self {
+            at: None,
+            sender: None,
+            gas_limit: Self::DEFAULT_GAS_LIMIT.into(),
+        }
+    }
+}
+
+impl ReadonlyCallParams {
+    const DEFAULT_GAS_LIMIT: u64 = 10_000_000;
+
+    /// Creates a new instance of `ReadonlyCallParams`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the block hash to execute the call at. If not set, by default the latest block is used.
+    pub fn at(mut self, at: BlockHash) -> Self {
+        self.at = Some(at);
+        self
+    }
+
+    /// Overrides `sender` of the contract call as if it was executed by them. If not set,
+    /// by default the zero address is used.
+    pub fn sender(mut self, sender: H160) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Sets the gas the call may use, 10 million by default.
+    pub fn gas_limit(mut self, gas_limit: U256) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+}
+
+impl EvmContract {
+    /// Creates a new contract instance under `address` with the ABI read from `abi_path`.
+    pub fn new(address: H160, abi_path: &str) -> Result<Self> {
+        let file = File::open(abi_path).context(format!("Cannot open the ABI at {abi_path}"))?;
+        Ok(Self::from_abi(address, Abi::load(file)?))
+    }
+
+    /// Creates a new contract instance under `address` with a given ABI.
+    pub fn from_abi(address: H160, abi: Abi) -> Self {
+        Self { address, abi }
+    }
+
+    /// The address of this contract instance.
+    pub fn address(&self) -> &H160 {
+        &self.address
+    }
+
+    /// The ABI of this contract instance.
+    pub fn abi(&self) -> &Abi {
+        &self.abi
+    }
+
+    /// Reads the value of a read-only, 0-argument call via `eth_call`.
+    pub async fn read0<C: ConnectionApi>(&self, conn: &C, function: &str) -> Result<Vec<Token>> {
+        self.read(conn, function, &[], Default::default()).await
+    }
+
+    /// Reads the value of a read-only call via `eth_call`, i.e. the `call` method of the
+    /// `EthereumRuntimeRPCApi` runtime api, without submitting a transaction.
+    pub async fn read<C: ConnectionApi>(
+        &self,
+        conn: &C,
+        function: &str,
+        args: &[Token],
+        params: ReadonlyCallParams,
+    ) -> Result<Vec<Token>> {
+        let function = self.abi.function(function)?;
+        let data = function.encode_input(args)?;
+        info!(target: "aleph-client", "Reading {} of {:?}", function.name, self.address);
+
+        let call_args = (
+            params.sender.unwrap_or_default(),
+            self.address,
+            data,
+            U256::zero(),
+            params.gas_limit,
+            Option::<U256>::None,
+            Option::<U256>::None,
+            Option::<U256>::None,
+            false,
+            Option::<Vec<(H160, Vec<H256>)>>::None,
+        );
+        let rpc_params = rpc_params![
+            "EthereumRuntimeRPCApi_call",
+            Bytes(call_args.encode()),
+            params.at
+        ];
+        let outcome: CallOutcome = conn.rpc_call("state_call".to_string(), rpc_params).await?;
+
+        match outcome {
+            CallOutcome::Succeeded(output) => Ok(function.decode_output(&output)?),
+            CallOutcome::Reverted(output) => Err(anyhow!(
+                "{} reverted: {}",
+                function.name,
+                revert_reason(&output)
+            )),
+            CallOutcome::Failed(reason) => Err(anyhow!("{} failed: {}", function.name, reason)),
+        }
+    }
+
+    /// Executes a call, signing it as an Ethereum transaction and submitting it with
+    /// `ethereum.transact`. Returns the Ethereum hash of the transaction, next to where it was
+    /// included.
+    pub async fn exec<C: AsConnection + Sync>(
+        &self,
+        conn: &C,
+        signer: &EthKeyPair,
+        function: &str,
+        args: &[Token],
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> Result<(H256, TxInfo)> {
+        let data = self.abi.function(function)?.encode_input(args)?;
+
+        conn.sign_and_transact(
+            signer,
+            TransactionAction::Call(self.address),
+            data,
+            params,
+            status,
+        )
+        .await
+    }
+}
+
+impl Debug for EvmContract {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvmContract")
+            .field("address", &self.address)
+            .field("abi", &self.abi)
+            .finish()
+    }
+}
+
+/// What `EthereumRuntimeRPCApi::call` returned, decoded only as far as needed.
+enum CallOutcome {
+    Succeeded(Vec<u8>),
+    Reverted(Vec<u8>),
+    Failed(String),
+}
+
+impl Decode for CallOutcome {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+        // `Result<pallet_evm::CallInfo, DispatchError>`
+        if input.read_byte()? == 1 {
+            let error = DispatchError::decode(input)?;
+            return Ok(Self::Failed(format!("{error:?}")));
+        }
+        // `CallInfo` starts with `ExitReason`, followed by the returned data. Errors carry data
+        // of different shapes, but nothing after them is needed then.
+        match input.read_byte()? {
+            0 => {
+                let _succeed = input.read_byte()?;
+                Ok(Self::Succeeded(Vec::decode(input)?))
+            }
+            2 => {
+                let _revert = input.read_byte()?;
+                Ok(Self::Reverted(Vec::decode(input)?))
+            }
+            1 => Ok(Self::Failed(format!("EVM error {}", input.read_byte()?))),
+            _ => Ok(Self::Failed(format!(
+                "fatal EVM error {}",
+                input.read_byte()?
+            ))),
+        }
+    }
+}
+
+/// Decodes the reason of a `revert("...")`, i.e. data encoded as a call of `Error(string)`.
+fn revert_reason(output: &[u8]) -> String {
+    const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+    match output.strip_prefix(&ERROR_SELECTOR[..]) {
+        Some(data) => match ethabi::decode(&[ethabi::ParamType::String], data) {
+            Ok(mut tokens) => tokens
+                .pop()
+                .and_then(Token::into_string)
+                .unwrap_or_default(),
+            Err(_) => format!("0x{}", hex::encode(output)),
+        },
+        None => format!("0x{}", hex::encode(output)),
+    }
+}
@@ -0,0 +1,636 @@
+use std::{
+    collections::HashMap,
+    io::{Error as IoError, ErrorKind},
+    ops::Deref,
+    sync::{Arc, Mutex as SyncMutex},
+    time::Duration,
+};
+
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    lock::Mutex,
+    stream, StreamExt,
+};
+use jsonrpsee::{
+    client_transport::ws::{Uri, WsTransportClientBuilder},
+    core::client::ClientBuilder,
+};
+use log::{info, warn};
+use primitives::BlockNumber;
+use serde_json::value::RawValue;
+use subxt::{
+    error::RpcError,
+    rpc::{Rpc, RpcClient, RpcClientT, RpcFuture, RpcSubscription, RpcSubscriptionStream},
+    rpc_params, Config,
+};
+use tokio::time::sleep;
+
+use crate::{AlephConfig, BlockHash};
+
+type Header = <AlephConfig as Config>::Header;
+
+/// How a [`Connection`](crate::Connection) reconnects after losing an endpoint.
+#[derive(Clone, Debug)]
+pub struct ReconnectConfig {
+    /// How long to wait after every endpoint failed once.
+    pub initial_backoff: Duration,
+    /// The wait doubles after every round of failures, up to this value.
+    pub max_backoff: Duration,
+    /// How many times to try connecting, to any endpoint, before a request fails.
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_attempts: 10,
+        }
+    }
+}
+
+/// A change of the state of a [`Connection`](crate::Connection).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// Requests are now sent to the endpoint.
+    Connected {
+        /// The endpoint.
+        endpoint: String,
+    },
+    /// The endpoint could not be reached, or stopped responding.
+    Disconnected {
+        /// The endpoint.
+        endpoint: String,
+        /// What went wrong.
+        reason: String,
+    },
+    /// The endpoint finalized a different block than the one requests are sent to.
+    FinalizedHeadMismatch {
+        /// The endpoint.
+        endpoint: String,
+        /// Number of the block both endpoints finalized.
+        number: BlockNumber,
+        /// Hash of the block according to the endpoint requests are sent to.
+        expected: BlockHash,
+        /// Hash of the block according to the endpoint.
+        actual: BlockHash,
+    },
+}
+
+struct State {
+    current: usize,
+    client: Option<RpcClient>,
+    /// Bumped on every connection, so that one failure is not handled twice.
+    generation: u64,
+}
+
+/// Opens RPC connections to endpoints.
+trait Connect: Send + Sync + 'static {
+    fn connect<'a>(&'a self, endpoint: &'a str) -> RpcFuture<'a, RpcClient>;
+}
+
+/// Connects over a websocket, without fetching anything from the endpoint.
+struct WsConnect;
+
+impl Connect for WsConnect {
+    fn connect<'a>(&'a self, endpoint: &'a str) -> RpcFuture<'a, RpcClient> {
+        Box::pin(async move {
+            let url: Uri = endpoint
+                .parse()
+                .map_err(|e| RpcError::ClientError(Box::new(e)))?;
+            let (sender, receiver) = WsTransportClientBuilder::default()
+                .build(url)
+                .await
+                .map_err(|e| RpcError::ClientError(Box::new(e)))?;
+            let client = ClientBuilder::default()
+                .max_notifs_per_subscription(4096)
+                .build_with_tokio(sender, receiver);
+
+            Ok(Rpc::<AlephConfig>::new(Arc::new(client)).deref().clone())
+        })
+    }
+}
+
+struct Inner {
+    endpoints: Vec<String>,
+    config: ReconnectConfig,
+    connector: Box<dyn Connect>,
+    state: Mutex<State>,
+    /// Held while connecting, so that only one request at a time goes through the endpoints.
+    connecting: Mutex<()>,
+    others: Mutex<HashMap<usize, RpcClient>>,
+    listeners: SyncMutex<Vec<UnboundedSender<ConnectionEvent>>>,
+}
+
+/// An RPC client that sends requests to the first endpoint that works, reconnecting and failing
+/// over to the next endpoint whenever the current one stops responding.
+#[derive(Clone)]
+pub(crate) struct FailoverRpc {
+    inner: Arc<Inner>,
+}
+
+impl FailoverRpc {
+    pub(crate) fn new(endpoints: Vec<String>, config: ReconnectConfig) -> Self {
+        Self::with_connector(endpoints, config, WsConnect)
+    }
+
+    fn with_connector(
+        endpoints: Vec<String>,
+        config: ReconnectConfig,
+        connector: impl Connect,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                endpoints,
+                config,
+                connector: Box::new(connector),
+                state: Mutex::new(State {
+                    current: 0,
+                    client: None,
+                    generation: 0,
+                }),
+                connecting: Mutex::new(()),
+                others: Mutex::new(HashMap::new()),
+                listeners: SyncMutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub(crate) fn events(&self) -> UnboundedReceiver<ConnectionEvent> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.inner
+            .listeners
+            .lock()
+            .expect("listeners lock is not poisoned")
+            .push(sender);
+        receiver
+    }
+
+    pub(crate) async fn current_endpoint(&self) -> &str {
+        &self.inner.endpoints[self.inner.state.lock().await.current]
+    }
+
+    fn notify(&self, event: ConnectionEvent) {
+        self.inner
+            .listeners
+            .lock()
+            .expect("listeners lock is not poisoned")
+            .retain(|listener| listener.unbounded_send(event.clone()).is_ok());
+    }
+
+    /// Returns the client of the current endpoint, if connected.
+    async fn connected(&self) -> Option<(RpcClient, u64)> {
+        let state = self.inner.state.lock().await;
+        state
+            .client
+            .as_ref()
+            .map(|client| (client.clone(), state.generation))
+    }
+
+    /// Returns a client of the current endpoint, connecting to the endpoints in turn if needed.
+    async fn client(&self) -> Result<(RpcClient, u64), RpcError> {
+        if let Some(connected) = self.connected().await {
+            return Ok(connected);
+        }
+        let _connecting = self.inner.connecting.lock().await;
+        // Another request may have connected while this one waited.
+        if let Some(connected) = self.connected().await {
+            return Ok(connected);
+        }
+
+        let mut backoff = self.inner.config.initial_backoff;
+        for attempt in 1..=self.inner.config.max_attempts {
+            let current = self.inner.state.lock().await.current;
+            let endpoint = &self.inner.endpoints[current];
+            match self.inner.connector.connect(endpoint).await {
+                Ok(client) => {
+                    info!(target: "aleph-client", "Connected to {}", endpoint);
+                    let mut state = self.inner.state.lock().await;
+                    state.client = Some(client.clone());
+                    state.generation += 1;
+                    self.notify(ConnectionEvent::Connected {
+                        endpoint: endpoint.clone(),
+                    });
+                    return Ok((client, state.generation));
+                }
+                Err(e) => {
+                    warn!(target: "aleph-client", "Connecting to {} failed (attempt {}): {:?}", endpoint, attempt, e);
+                    self.notify(ConnectionEvent::Disconnected {
+                        endpoint: endpoint.clone(),
+                        reason: e.to_string(),
+                    });
+                    let next = (current + 1) % self.inner.endpoints.len();
+                    self.inner.state.lock().await.current = next;
+                    if next == 0 {
+                        sleep(backoff).await;
+                        backoff = (backoff * 2).min(self.inner.config.max_backoff);
+                    }
+                }
+            }
+        }
+
+        Err(RpcError::ClientError(Box::new(IoError::new(
+            ErrorKind::NotConnected,
+            format!(
+                "None of the endpoints {:?} is reachable",
+                self.inner.endpoints
+            ),
+        ))))
+    }
+
+    /// Tells whether an error came from the endpoint being gone, rather than from the request.
+    async fn is_gone(client: &RpcClient) -> bool {
+        client.request_raw("system_health", None).await.is_err()
+    }
+
+    /// Moves on to the next endpoint, unless another request already reconnected.
+    async fn drop_client(&self, generation: u64, reason: String) {
+        let mut state = self.inner.state.lock().await;
+        if state.generation != generation || state.client.is_none() {
+            return;
+        }
+
+        let endpoint = self.inner.endpoints[state.current].clone();
+        warn!(target: "aleph-client", "Lost connection to {}: {}", endpoint, reason);
+        state.client = None;
+        state.current = (state.current + 1) % self.inner.endpoints.len();
+        self.notify(ConnectionEvent::Disconnected { endpoint, reason });
+    }
+
+    /// Subscribes through the current endpoint, failing over to the next ones if needed.
+    async fn subscribe_once(
+        &self,
+        sub: &str,
+        params: Option<Box<RawValue>>,
+        unsub: &str,
+    ) -> Result<(RpcSubscription, RpcClient, u64), RpcError> {
+        let mut attempts = 0;
+        loop {
+            let (client, generation) = self.client().await?;
+            let error = match client.subscribe_raw(sub, params.clone(), unsub).await {
+                Ok(subscription) => return Ok((subscription, client, generation)),
+                Err(error) => error,
+            };
+            attempts += 1;
+            if attempts >= self.inner.config.max_attempts || !Self::is_gone(&client).await {
+                return Err(error);
+            }
+            self.drop_client(generation, error.to_string()).await;
+        }
+    }
+
+    /// Compares the finalized chain of every other endpoint with the current one, up to the lower
+    /// of their finalized heads. Returns the mismatches found.
+    pub(crate) async fn check_finalized_heads(&self) -> anyhow::Result<Vec<ConnectionEvent>> {
+        let (client, _) = self.client().await?;
+        let current = self.inner.state.lock().await.current;
+        let (number, _) = finalized_head(&client).await?;
+
+        let mut mismatches = Vec::new();
+        let mut others = self.inner.others.lock().await;
+        for (index, endpoint) in self.inner.endpoints.iter().enumerate() {
+            if index == current {
+                continue;
+            }
+            let other = match others.get(&index) {
+                Some(other) => other.clone(),
+                None => {
+                    let other = self.inner.connector.connect(endpoint).await?;
+                    others.insert(index, other.clone());
+                    other
+                }
+            };
+            let checked = match finalized_head(&other).await {
+                Ok((other_number, _)) => {
+                    let number = number.min(other_number);
+                    let expected = block_hash(&client, number).await?;
+                    let actual = block_hash(&other, number).await?;
+                    Ok((number, expected, actual))
+                }
+                Err(e) => Err(e),
+            };
+            match checked {
+                Ok((number, expected, actual)) if expected != actual => {
+                    let mismatch = ConnectionEvent::FinalizedHeadMismatch {
+                        endpoint: endpoint.clone(),
+                        number,
+                        expected,
+                        actual,
+                    };
+                    warn!(target: "aleph-client", "{:?}", mismatch);
+                    self.notify(mismatch.clone());
+                    mismatches.push(mismatch);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    others.remove(&index);
+                    return Err(e.context(format!("Could not check {endpoint}")));
+                }
+            }
+        }
+
+        Ok(mismatches)
+    }
+}
+
+async fn finalized_head(client: &RpcClient) -> anyhow::Result<(BlockNumber, BlockHash)> {
+    let hash: BlockHash = client
+        .request("chain_getFinalizedHead", rpc_params![])
+        .await?;
+    let header: Header = client.request("chain_getHeader", rpc_params![hash]).await?;
+
+    Ok((header.number, hash))
+}
+
+async fn block_hash(client: &RpcClient, number: BlockNumber) -> anyhow::Result<BlockHash> {
+    let hash: Option<BlockHash> = client
+        .request("chain_getBlockHash", rpc_params![number])
+        .await?;
+
+    hash.ok_or_else(|| anyhow::anyhow!("Block #{number} not found"))
+}
+
+impl RpcClientT for FailoverRpc {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<RawValue>>,
+    ) -> RpcFuture<'a, Box<RawValue>> {
+        Box::pin(async move {
+            let mut attempts = 0;
+            loop {
+                let (client, generation) = self.client().await?;
+                let error = match client.request_raw(method, params.clone()).await {
+                    Ok(response) => return Ok(response),
+                    Err(error) => error,
+                };
+                attempts += 1;
+                if attempts >= self.inner.config.max_attempts || !Self::is_gone(&client).await {
+                    return Err(error);
+                }
+                self.drop_client(generation, error.to_string()).await;
+            }
+        })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<RawValue>>,
+        unsub: &'a str,
+    ) -> RpcFuture<'a, RpcSubscription> {
+        Box::pin(async move {
+            let (subscription, client, generation) =
+                self.subscribe_once(sub, params.clone(), unsub).await?;
+            let resubscribing = Resubscribing {
+                rpc: self.clone(),
+                sub: sub.to_string(),
+                params,
+                unsub: unsub.to_string(),
+                stream: subscription.stream,
+                client,
+                generation,
+            };
+
+            Ok(RpcSubscription {
+                stream: resubscribing.into_stream(),
+                id: subscription.id,
+            })
+        })
+    }
+}
+
+/// A subscription that is made again, through the next endpoint, when its endpoint goes away.
+///
+/// Notifications sent while reconnecting are lost, and the subscription id stays the one of the
+/// first subscription.
+struct Resubscribing {
+    rpc: FailoverRpc,
+    sub: String,
+    params: Option<Box<RawValue>>,
+    unsub: String,
+    stream: RpcSubscriptionStream,
+    client: RpcClient,
+    generation: u64,
+}
+
+impl Resubscribing {
+    fn into_stream(self) -> RpcSubscriptionStream {
+        Box::pin(stream::unfold(Some(self), |resubscribing| async move {
+            let mut this = resubscribing?;
+            loop {
+                let item = this.stream.next().await;
+                if let Some(Ok(notification)) = item {
+                    return Some((Ok(notification), Some(this)));
+                }
+                if !FailoverRpc::is_gone(&this.client).await {
+                    return item.map(|error| (error, Some(this)));
+                }
+                let reason = match item {
+                    Some(Err(error)) => error.to_string(),
+                    _ => "Subscription closed".to_string(),
+                };
+                this.rpc.drop_client(this.generation, reason).await;
+                match this
+                    .rpc
+                    .subscribe_once(&this.sub, this.params.clone(), &this.unsub)
+                    .await
+                {
+                    Ok((subscription, client, generation)) => {
+                        this.stream = subscription.stream;
+                        this.client = client;
+                        this.generation = generation;
+                    }
+                    Err(error) => return Some((Err(error), None)),
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{Error as IoError, ErrorKind},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use futures::{
+        channel::mpsc::{self, UnboundedSender},
+        StreamExt,
+    };
+    use serde_json::value::RawValue;
+    use subxt::{
+        error::RpcError,
+        rpc::{Rpc, RpcClient, RpcClientT, RpcFuture, RpcSubscription, RpcSubscriptionStream},
+    };
+
+    use super::{Connect, ConnectionEvent, FailoverRpc, ReconnectConfig};
+    use crate::AlephConfig;
+
+    type Notification = Result<Box<RawValue>, RpcError>;
+
+    /// An endpoint that answers every request, and every subscription, with its name.
+    struct FakeEndpoint {
+        name: String,
+        up: AtomicBool,
+        subscribers: Mutex<Vec<UnboundedSender<Notification>>>,
+    }
+
+    impl FakeEndpoint {
+        fn new(name: &str) -> Arc<Self> {
+            Arc::new(FakeEndpoint {
+                name: name.to_string(),
+                up: AtomicBool::new(true),
+                subscribers: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn go_down(&self) {
+            self.up.store(false, Ordering::SeqCst);
+            self.subscribers.lock().unwrap().clear();
+        }
+
+        fn check_up(&self) -> Result<(), RpcError> {
+            match self.up.load(Ordering::SeqCst) {
+                true => Ok(()),
+                false => Err(RpcError::ClientError(Box::new(IoError::new(
+                    ErrorKind::ConnectionReset,
+                    format!("{} is down", self.name),
+                )))),
+            }
+        }
+
+        fn name(&self) -> Box<RawValue> {
+            RawValue::from_string(format!("\"{}\"", self.name)).unwrap()
+        }
+    }
+
+    impl RpcClientT for FakeEndpoint {
+        fn request_raw<'a>(
+            &'a self,
+            _method: &'a str,
+            _params: Option<Box<RawValue>>,
+        ) -> RpcFuture<'a, Box<RawValue>> {
+            Box::pin(async move {
+                self.check_up()?;
+                Ok(self.name())
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            _sub: &'a str,
+            _params: Option<Box<RawValue>>,
+            _unsub: &'a str,
+        ) -> RpcFuture<'a, RpcSubscription> {
+            Box::pin(async move {
+                self.check_up()?;
+                let (sender, receiver) = mpsc::unbounded();
+                sender.unbounded_send(Ok(self.name())).unwrap();
+                self.subscribers.lock().unwrap().push(sender);
+                Ok(RpcSubscription {
+                    stream: Box::pin(receiver),
+                    id: Some(self.name.clone()),
+                })
+            })
+        }
+    }
+
+    struct FakeConnect(HashMap<String, Arc<FakeEndpoint>>);
+
+    impl Connect for FakeConnect {
+        fn connect<'a>(&'a self, endpoint: &'a str) -> RpcFuture<'a, RpcClient> {
+            Box::pin(async move {
+                let endpoint = self.0[endpoint].clone();
+                endpoint.check_up()?;
+                Ok((*Rpc::<AlephConfig>::new(endpoint)).clone())
+            })
+        }
+    }
+
+    fn setup() -> (FailoverRpc, Arc<FakeEndpoint>, Arc<FakeEndpoint>) {
+        let first = FakeEndpoint::new("first");
+        let second = FakeEndpoint::new("second");
+        let connector = FakeConnect(HashMap::from([
+            ("first".to_string(), first.clone()),
+            ("second".to_string(), second.clone()),
+        ]));
+        let config = ReconnectConfig {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            max_attempts: 3,
+        };
+        let rpc = FailoverRpc::with_connector(
+            vec!["first".to_string(), "second".to_string()],
+            config,
+            connector,
+        );
+
+        (rpc, first, second)
+    }
+
+    async fn answer(rpc: &FailoverRpc) -> Result<String, RpcError> {
+        rpc.request_raw("system_name", None)
+            .await
+            .map(|response| response.get().to_string())
+    }
+
+    async fn next(subscription: &mut RpcSubscriptionStream) -> String {
+        let notification = subscription.next().await.unwrap().unwrap();
+        notification.get().to_string()
+    }
+
+    #[tokio::test]
+    async fn requests_fail_over_when_endpoint_goes_down() {
+        let (rpc, first, _second) = setup();
+        let mut events = rpc.events();
+
+        assert_eq!(answer(&rpc).await.unwrap(), "\"first\"");
+        first.go_down();
+        assert_eq!(answer(&rpc).await.unwrap(), "\"second\"");
+        assert_eq!(rpc.current_endpoint().await, "second");
+
+        let events: Vec<_> = std::iter::from_fn(|| events.try_next().ok().flatten()).collect();
+        assert!(matches!(
+            &events[..],
+            [
+                ConnectionEvent::Connected { endpoint: connected },
+                ConnectionEvent::Disconnected { endpoint: disconnected, .. },
+                ConnectionEvent::Connected { endpoint: failed_over },
+            ] if connected == "first" && disconnected == "first" && failed_over == "second"
+        ));
+    }
+
+    #[tokio::test]
+    async fn requests_fail_when_every_endpoint_is_down() {
+        let (rpc, first, second) = setup();
+
+        first.go_down();
+        second.go_down();
+
+        assert!(answer(&rpc).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn subscription_is_made_again_when_endpoint_goes_down() {
+        let (rpc, first, _second) = setup();
+
+        let mut subscription = rpc
+            .subscribe_raw("chain_subscribeNewHeads", None, "chain_unsubscribeNewHeads")
+            .await
+            .unwrap()
+            .stream;
+        assert_eq!(next(&mut subscription).await, "\"first\"");
+
+        first.go_down();
+
+        assert_eq!(next(&mut subscription).await, "\"second\"");
+    }
+}
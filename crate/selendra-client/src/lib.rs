@@ -12,7 +12,6 @@
 use std::str::FromStr;
 
 use anyhow::anyhow;
-pub use contract_transcode;
 pub use subxt::ext::{
     codec, sp_core,
     sp_core::{
@@ -25,20 +24,31 @@ use subxt::{
     config::polkadot::PolkadotExtrinsicParams,
     ext::{
         sp_core::{ed25519, sr25519, H256},
-        sp_runtime::{MultiAddress, MultiSignature},
+        sp_runtime::MultiSignature,
     },
     Config, OnlineClient, PolkadotConfig,
 };
 
-use crate::api::runtime_types::aleph_runtime::RuntimeCall as Call;
+use crate::api::runtime_types::selendra_runtime::RuntimeCall as Call;
 
 #[allow(clippy::all)]
 #[doc(hidden)]
 mod selendra;
 
 mod connections;
+/// Ethereum keys and transactions API.
+pub mod eth;
+/// Subscribing to events API.
+pub mod events;
+/// EVM contracts API.
+pub mod evm_contract;
+mod failover;
+/// Signing transactions without a connection to the chain API.
+pub mod offline;
 /// API for pallets.
 pub mod pallets;
+/// Concurrent transaction submission API.
+pub mod pipeline;
 mod runtime_types;
 /// Block / session / era API.
 pub mod utility;
@@ -68,6 +78,7 @@ pub use connections::{
     AsConnection, AsSigned, Connection, ConnectionApi, RootConnection, SignedConnection,
     SignedConnectionApi, SignedConnectionApiExt, SubmittableExtrinsic, SudoCall, TxInfo,
 };
+pub use failover::{ConnectionEvent, ReconnectConfig};
 
 /// An alias for a configuration of live chain, e.g. block index type, hash type.
 pub enum AlephConfig {}
@@ -75,7 +86,7 @@ pub enum AlephConfig {}
 impl Config for AlephConfig {
     type Hash = <PolkadotConfig as Config>::Hash;
     type AccountId = AccountId;
-    type Address = MultiAddress<Self::AccountId, u32>;
+    type Address = Self::AccountId;
     type Signature = MultiSignature;
     type Hasher = <PolkadotConfig as Config>::Hasher;
     type Header = <PolkadotConfig as Config>::Header;
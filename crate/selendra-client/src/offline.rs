@@ -0,0 +1,337 @@
+use std::{fs, path::Path};
+
+use anyhow::anyhow;
+use codec::Decode;
+use log::info;
+use primitives::{Balance, BlockNumber, Nonce};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use subxt::{
+    config::{extrinsic_params::Era, polkadot::PlainTip},
+    ext::sp_core::Bytes,
+    rpc::types::RuntimeVersion,
+    tx::{SubmittableExtrinsic as SubxtSubmittable, TxPayload},
+    Metadata, OfflineClient,
+};
+
+use crate::{
+    pipeline::EncodedCall, utility::BlocksApi, AccountId, AlephConfig, AsConnection, BlockHash,
+    Call, KeyPair, ParamsBuilder, SubmittableExtrinsic, TxInfo, TxStatus,
+};
+
+/// Metadata of the runtime the bindings were generated from.
+const METADATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/metadata.scale"));
+
+/// Returns metadata of the runtime the bindings were generated from, without asking any node.
+pub fn metadata() -> anyhow::Result<Metadata> {
+    Ok(Metadata::decode(&mut &METADATA[..])?)
+}
+
+/// Validity period of a transaction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mortality {
+    /// For how many blocks, since the checkpoint, the transaction is valid.
+    pub period: u64,
+    /// Number of a block the period starts at.
+    pub checkpoint_number: BlockNumber,
+    /// Hash of a block the period starts at.
+    pub checkpoint_hash: BlockHash,
+}
+
+/// Options of an [`UnsignedPayload`] the chain cannot tell.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct UnsignedPayloadParams {
+    /// A tip for the block author.
+    pub tip: Balance,
+    /// Nonce to sign with; the next one of the signer if not set.
+    pub nonce: Option<Nonce>,
+    /// For how many blocks, since the last finalized one, the transaction is valid; immortal if
+    /// not set.
+    pub mortal_for: Option<u64>,
+}
+
+/// Everything needed to sign a transaction without a connection to the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedPayload {
+    /// The account expected to sign the transaction.
+    pub signer: AccountId,
+    /// The encoded call.
+    pub call_data: Bytes,
+    /// Nonce of the signer.
+    pub nonce: Nonce,
+    /// A tip for the block author.
+    pub tip: Balance,
+    /// Validity period of the transaction; immortal if not set.
+    pub mortality: Option<Mortality>,
+    /// Hash of the genesis block of the chain.
+    pub genesis_hash: BlockHash,
+    /// Spec version of the runtime.
+    pub spec_version: u32,
+    /// Transaction version of the runtime.
+    pub transaction_version: u32,
+}
+
+/// A transaction signed offline, ready to be submitted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedPayload {
+    /// What has been signed.
+    pub unsigned: UnsignedPayload,
+    /// The encoded signed extrinsic.
+    pub extrinsic: Bytes,
+}
+
+impl UnsignedPayload {
+    /// Decodes the call, so that it can be reviewed before signing.
+    pub fn call(&self) -> anyhow::Result<Call> {
+        Ok(Call::decode(&mut &self.call_data[..])?)
+    }
+
+    /// Signs the payload. Does not need a connection to the chain.
+    /// * `signer` - a key pair of [`UnsignedPayload::signer`]
+    pub fn sign(&self, signer: &KeyPair) -> anyhow::Result<SignedPayload> {
+        if signer.account_id() != &self.signer {
+            return Err(anyhow!(
+                "The payload is to be signed by {}, not by {}",
+                self.signer,
+                signer.account_id()
+            ));
+        }
+
+        let client = OfflineClient::<AlephConfig>::new(
+            self.genesis_hash,
+            RuntimeVersion {
+                spec_version: self.spec_version,
+                transaction_version: self.transaction_version,
+                other: Default::default(),
+            },
+            metadata()?,
+        );
+        let mut params = ParamsBuilder::new().tip(PlainTip::new(self.tip));
+        if let Some(mortality) = self.mortality {
+            params = params.era(
+                Era::mortal(mortality.period, mortality.checkpoint_number.into()),
+                mortality.checkpoint_hash,
+            );
+        }
+        let signed = client.tx().create_signed_with_nonce(
+            &EncodedCall(self.call_data.to_vec()),
+            &signer.inner,
+            self.nonce.into(),
+            params,
+        )?;
+
+        Ok(SignedPayload {
+            unsigned: self.clone(),
+            extrinsic: signed.into_encoded().into(),
+        })
+    }
+}
+
+/// Payloads stored as JSON files, with binary fields as `0x`-prefixed hex.
+pub trait PayloadFile: Serialize + DeserializeOwned {
+    /// Serializes the payload to JSON.
+    fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserializes a payload from JSON.
+    fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Writes the payload to a JSON file.
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+
+    /// Reads a payload from a JSON file.
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl PayloadFile for UnsignedPayload {}
+impl PayloadFile for SignedPayload {}
+
+/// Online side of signing transactions offline.
+#[async_trait::async_trait]
+pub trait OfflineSigningApi {
+    /// Builds a payload to be signed by [`UnsignedPayload::sign`].
+    /// * `signer` - an account to sign the transaction
+    /// * `tx` - encoded transaction payload
+    /// * `params` - a tip, a nonce and mortality of the transaction
+    async fn unsigned_payload<Call: TxPayload + Send + Sync>(
+        &self,
+        signer: &AccountId,
+        tx: Call,
+        params: UnsignedPayloadParams,
+    ) -> anyhow::Result<UnsignedPayload>;
+
+    /// Submits a transaction signed by [`UnsignedPayload::sign`].
+    /// * `signed` - the signed payload
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    async fn submit_signed_payload(
+        &self,
+        signed: &SignedPayload,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: AsConnection + Sync> OfflineSigningApi for C {
+    async fn unsigned_payload<Call: TxPayload + Send + Sync>(
+        &self,
+        signer: &AccountId,
+        tx: Call,
+        params: UnsignedPayloadParams,
+    ) -> anyhow::Result<UnsignedPayload> {
+        let client = self.as_connection().as_client();
+        let nonce = match params.nonce {
+            Some(nonce) => nonce,
+            None => client.tx().account_nonce(signer).await?.try_into()?,
+        };
+        let mortality = match params.mortal_for {
+            Some(period) => {
+                let checkpoint_hash = self.get_finalized_block_hash().await?;
+                let checkpoint_number = self
+                    .get_block_number(checkpoint_hash)
+                    .await?
+                    .ok_or_else(|| anyhow!("Finalized block {} not found", checkpoint_hash))?;
+                Some(Mortality {
+                    period,
+                    checkpoint_number,
+                    checkpoint_hash,
+                })
+            }
+            None => None,
+        };
+        let runtime_version = client.runtime_version();
+
+        Ok(UnsignedPayload {
+            signer: signer.clone(),
+            call_data: client.tx().call_data(&tx)?.into(),
+            nonce,
+            tip: params.tip,
+            mortality,
+            genesis_hash: client.genesis_hash(),
+            spec_version: runtime_version.spec_version,
+            transaction_version: runtime_version.transaction_version,
+        })
+    }
+
+    async fn submit_signed_payload(
+        &self,
+        signed: &SignedPayload,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let client = self.as_connection().as_client();
+        let runtime_version = client.runtime_version();
+        if signed.unsigned.genesis_hash != client.genesis_hash() {
+            return Err(anyhow!("The payload was signed for a different chain"));
+        }
+        if signed.unsigned.transaction_version != runtime_version.transaction_version {
+            return Err(anyhow!(
+                "The payload was signed for transaction version {}, but the chain uses {}",
+                signed.unsigned.transaction_version,
+                runtime_version.transaction_version
+            ));
+        }
+
+        let submittable: SubmittableExtrinsic =
+            SubxtSubmittable::from_bytes(client.clone(), signed.extrinsic.to_vec()).into();
+        let info = submittable.submit(status).await?;
+        info!(target: "aleph-client", "tx with hash {:?} included in block {:?}", info.tx_hash, info.block_hash);
+
+        Ok(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{Compact, Decode, Encode};
+    use subxt::ext::sp_core::{sr25519, Pair, H256};
+
+    use super::*;
+    use crate::{api, api::runtime_types::frame_system::pallet::Call as SystemCall};
+
+    fn alice() -> KeyPair {
+        KeyPair::new(sr25519::Pair::from_string("//Alice", None).unwrap())
+    }
+
+    fn remark_payload(signer: &KeyPair, mortality: Option<Mortality>) -> UnsignedPayload {
+        let client = OfflineClient::<AlephConfig>::new(
+            H256::repeat_byte(1),
+            RuntimeVersion {
+                spec_version: 10_000,
+                transaction_version: 1,
+                other: Default::default(),
+            },
+            metadata().unwrap(),
+        );
+        let call_data = client
+            .tx()
+            .call_data(&api::tx().system().remark(b"offline".to_vec()))
+            .unwrap();
+
+        UnsignedPayload {
+            signer: signer.account_id().clone(),
+            call_data: call_data.into(),
+            nonce: 7,
+            tip: 1_000,
+            mortality,
+            genesis_hash: H256::repeat_byte(1),
+            spec_version: 10_000,
+            transaction_version: 1,
+        }
+    }
+
+    #[test]
+    fn unsigned_payload_round_trips() {
+        let mortality = Mortality {
+            period: 64,
+            checkpoint_number: 1_234,
+            checkpoint_hash: H256::repeat_byte(2),
+        };
+        let payload = remark_payload(&alice(), Some(mortality));
+
+        let json = payload.to_json().unwrap();
+        assert!(json.contains(&format!("\"0x{}\"", hex::encode(&payload.call_data[..]))));
+        assert_eq!(UnsignedPayload::from_json(&json).unwrap(), payload);
+        assert_eq!(
+            payload.call().unwrap(),
+            Call::System(SystemCall::remark {
+                remark: b"offline".to_vec()
+            })
+        );
+    }
+
+    #[test]
+    fn signed_payload_round_trips() {
+        let signer = alice();
+        let payload = remark_payload(&signer, None);
+
+        let signed = payload.sign(&signer).unwrap();
+        assert_eq!(
+            SignedPayload::from_json(&signed.to_json().unwrap()).unwrap(),
+            signed
+        );
+
+        let mut extrinsic = &signed.extrinsic[..];
+        let length = Compact::<u32>::decode(&mut extrinsic).unwrap().0 as usize;
+        assert_eq!(length, extrinsic.len());
+        // A signed extrinsic of version 4, with the call at the end.
+        assert_eq!(extrinsic[0], 0x84);
+        assert_eq!(
+            AccountId::decode(&mut &extrinsic[1..]).unwrap(),
+            payload.signer
+        );
+        assert!(extrinsic.ends_with(&payload.call().unwrap().encode()));
+    }
+
+    #[test]
+    fn refuses_to_sign_for_someone_else() {
+        let payload = remark_payload(&alice(), None);
+        let bob = KeyPair::new(sr25519::Pair::from_string("//Bob", None).unwrap());
+
+        assert!(payload.sign(&bob).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use subxt::rpc_params;
 use crate::{
     api,
     api::runtime_types::{
-        pallet_aleph::pallet::Call::set_emergency_finalizer, primitives::app::Public,
+        pallet_aleph::pallet::Call::set_emergency_finalizer, selendra_primitives::app::Public,
         sp_core::ed25519::Public as EdPublic,
     },
     connections::TxInfo,
@@ -67,6 +67,18 @@ pub trait AlephRpc {
         hash: BlockHash,
         key_pair: AlephKeyPair,
     ) -> anyhow::Result<()>;
+
+    /// Finalize the block with given hash and number using a signature of its hash made elsewhere,
+    /// e.g. by a remote signer.
+    /// * `number` - number of the block
+    /// * `hash` - hash of the block
+    /// * `signature` - ed25519 signature of the encoded `hash` by the emergency finalizer
+    async fn emergency_finalize_with_signature(
+        &self,
+        number: BlockNumber,
+        hash: BlockHash,
+        signature: [u8; 64],
+    ) -> anyhow::Result<()>;
 }
 
 #[async_trait::async_trait]
@@ -130,9 +142,20 @@ impl<C: ConnectionApi> AlephRpc for C {
         hash: BlockHash,
         key_pair: AlephKeyPair,
     ) -> anyhow::Result<()> {
-        let method = "alephNode_emergencyFinalize";
         let signature = key_pair.sign(&hash.encode());
-        let raw_signature = Bytes::from(signature.0.to_vec());
+
+        self.emergency_finalize_with_signature(number, hash, signature.0)
+            .await
+    }
+
+    async fn emergency_finalize_with_signature(
+        &self,
+        number: BlockNumber,
+        hash: BlockHash,
+        signature: [u8; 64],
+    ) -> anyhow::Result<()> {
+        let method = "alephNode_emergencyFinalize";
+        let raw_signature = Bytes::from(signature.to_vec());
         let params = rpc_params![raw_signature, hash, number];
 
         let _: () = self.rpc_call_no_return(method.to_string(), params).await?;
@@ -1,4 +1,4 @@
-use subxt::utils::{MultiAddress, Static};
+use subxt::utils::Static;
 
 use crate::{
     connections::TxInfo, pallet_balances::{pallet::Call::transfer_keep_alive, types::BalanceLock}, pallets::utility::UtilityApi, selendra::{self, api}, AccountId, AsConnection, Balance, BlockHash, Call::Balances, ConnectionApi, ParamsBuilder, SignedConnectionApi, TxStatus
@@ -131,7 +131,7 @@ impl<S: SignedConnectionApi> BalanceUserApi for S {
     ) -> anyhow::Result<TxInfo> {
         let tx = api::tx()
             .balances()
-            .transfer_keep_alive(MultiAddress::Id(dest.into()), amount);
+            .transfer_keep_alive(Static(dest), amount);
         self.send_tx(tx, status).await
     }
 
@@ -144,7 +144,7 @@ impl<S: SignedConnectionApi> BalanceUserApi for S {
     ) -> anyhow::Result<TxInfo> {
         let tx = api::tx()
             .balances()
-            .transfer_keep_alive(MultiAddress::Id(dest.into()), amount);
+            .transfer_keep_alive(Static(dest), amount);
 
         self.send_tx_with_params(tx, ParamsBuilder::new().tip(tip), status)
             .await
@@ -163,7 +163,7 @@ impl<S: SignedConnectionApi> BalanceUserBatchExtApi for S {
             .iter()
             .map(|dest| {
                 Balances(transfer_keep_alive {
-                    dest: MultiAddress::Id(dest.clone().into()),
+                    dest: Static(dest.clone()),
                     value: amount,
                 })
             })
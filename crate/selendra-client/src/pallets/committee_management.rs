@@ -7,12 +7,12 @@ use subxt::{
 };
 
 use crate::{
-    aleph_runtime::RuntimeCall::CommitteeManagement,
+    selendra_runtime::RuntimeCall::CommitteeManagement,
     api,
     pallet_committee_management::pallet::Call::{
         ban_from_committee, set_ban_config, set_lenient_threshold,
     },
-    primitives::{BanConfig, BanInfo, BanReason},
+    selendra_primitives::{BanConfig, BanInfo, BanReason},
     AccountId, AsConnection, BlockHash, ConnectionApi, EraIndex, RootConnection, SessionCount,
     SessionIndex, SudoCall, TxInfo, TxStatus,
 };
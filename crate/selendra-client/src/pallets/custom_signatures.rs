@@ -0,0 +1,115 @@
+use codec::Encode;
+use primitives::Nonce;
+use subxt::utils::Static;
+
+use crate::{
+    api, eth::EthKeyPair, pallets::system::SystemApi, selendra_runtime::RuntimeCall, AccountId,
+    AsConnection, Balance, ConnectionApi, TxInfo, TxStatus,
+};
+
+/// Pallet custom signatures read-only api.
+#[async_trait::async_trait]
+pub trait CustomSignaturesApi {
+    /// Returns the magic number prepended to every payload signed for `eth_call.call`.
+    async fn call_magic_number(&self) -> anyhow::Result<u16>;
+
+    /// Returns the fee charged from the signer of every `eth_call.call`.
+    async fn call_fee(&self) -> anyhow::Result<Balance>;
+
+    /// Returns the payload the signer has to sign for dispatching a given call.
+    /// * `call` - a call to dispatch on behalf of the signer
+    /// * `nonce` - current nonce of the signer
+    async fn signing_payload(&self, call: &RuntimeCall, nonce: Nonce) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Pallet custom signatures api.
+#[async_trait::async_trait]
+pub trait CustomSignaturesUserApi {
+    /// Issues `eth_call.call`, an unsigned extrinsic which dispatches `call` as `signer`, if
+    /// `signature` is an Ethereum signature of the [`CustomSignaturesApi::signing_payload`].
+    /// * `call` - a call to dispatch on behalf of the signer
+    /// * `signer` - the account derived from the Ethereum key
+    /// * `signature` - a 65 bytes Ethereum signature
+    /// * `nonce` - current nonce of the signer
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    async fn call(
+        &self,
+        call: RuntimeCall,
+        signer: AccountId,
+        signature: Vec<u8>,
+        nonce: Nonce,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// Issues `eth_call.call`, signing the call with an Ethereum key and the current nonce of
+    /// [`EthKeyPair::account_id`].
+    /// * `call` - a call to dispatch on behalf of the signer
+    /// * `signer` - an Ethereum key
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    async fn call_signed(
+        &self,
+        call: RuntimeCall,
+        signer: &EthKeyPair,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: AsConnection + Sync> CustomSignaturesApi for C {
+    async fn call_magic_number(&self) -> anyhow::Result<u16> {
+        let address = api::constants().eth_call().call_magic_number();
+        self.as_connection()
+            .as_client()
+            .constants()
+            .at(&address)
+            .map_err(|e| e.into())
+    }
+
+    async fn call_fee(&self) -> anyhow::Result<Balance> {
+        let address = api::constants().eth_call().call_fee();
+        self.as_connection()
+            .as_client()
+            .constants()
+            .at(&address)
+            .map_err(|e| e.into())
+    }
+
+    async fn signing_payload(&self, call: &RuntimeCall, nonce: Nonce) -> anyhow::Result<Vec<u8>> {
+        let magic_number = self.call_magic_number().await?;
+
+        Ok((magic_number, nonce, call).encode())
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: AsConnection + Sync> CustomSignaturesUserApi for C {
+    async fn call(
+        &self,
+        call: RuntimeCall,
+        signer: AccountId,
+        signature: Vec<u8>,
+        nonce: Nonce,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx()
+            .eth_call()
+            .call(call, Static(signer), signature, nonce);
+
+        self.send_unsigned_tx(tx, status).await
+    }
+
+    async fn call_signed(
+        &self,
+        call: RuntimeCall,
+        signer: &EthKeyPair,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let account = signer.account_id();
+        let nonce = self.account_nonce(&account).await?;
+        let payload = self.signing_payload(&call, nonce).await?;
+        let signature = signer.sign_message(&payload);
+
+        self.call(call, account, signature.to_vec(), nonce, status)
+            .await
+    }
+}
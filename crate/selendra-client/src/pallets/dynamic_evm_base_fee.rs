@@ -0,0 +1,54 @@
+use subxt::ext::sp_core::U256;
+
+use crate::{
+    api, pallet_dynamic_evm_base_fee::pallet::Call::set_base_fee_per_gas, AsConnection, BlockHash,
+    Call::DynamicEvmBaseFee, RootConnection, SudoCall, TxInfo, TxStatus,
+};
+
+/// Pallet dynamic evm base fee read-only api.
+#[async_trait::async_trait]
+pub trait DynamicEvmBaseFeeApi {
+    /// Returns `dynamic_evm_base_fee.base_fee_per_gas` storage, i.e. the current base fee of EVM
+    /// transactions.
+    /// * `at` - optional hash of a block to query state from
+    async fn base_fee_per_gas(&self, at: Option<BlockHash>) -> U256;
+}
+
+/// Pallet dynamic evm base fee api that requires sudo.
+#[async_trait::async_trait]
+pub trait DynamicEvmBaseFeeSudoApi {
+    /// Issues `dynamic_evm_base_fee.set_base_fee_per_gas`, which overrides the base fee until it
+    /// gets adjusted again.
+    /// * `fee` - new base fee per gas, within the limits configured in the runtime
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    async fn set_base_fee_per_gas(&self, fee: U256, status: TxStatus) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: AsConnection + Sync> DynamicEvmBaseFeeApi for C {
+    async fn base_fee_per_gas(&self, at: Option<BlockHash>) -> U256 {
+        let addrs = api::storage().dynamic_evm_base_fee().base_fee_per_gas();
+
+        // Unlike most values, the default of this one is set by the runtime.
+        let storage = self.as_connection().as_client().storage();
+        let block = match at {
+            Some(block_hash) => storage.at(block_hash),
+            None => storage.at_latest().await.expect("Should access storage"),
+        };
+
+        block
+            .fetch_or_default(&addrs)
+            .await
+            .expect("Should access storage")
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl DynamicEvmBaseFeeSudoApi for RootConnection {
+    async fn set_base_fee_per_gas(&self, fee: U256, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let call = DynamicEvmBaseFee(set_base_fee_per_gas { fee: fee.into() });
+
+        self.sudo_unchecked(call, status).await
+    }
+}
@@ -2,10 +2,10 @@ use subxt::utils::Static;
 
 use crate::{
     api,
-    api::runtime_types::primitives::{CommitteeSeats, EraValidators},
+    api::runtime_types::selendra_primitives::{CommitteeSeats, EraValidators},
     connections::{AsConnection, TxInfo},
     pallet_elections::pallet::Call::{change_validators, set_elections_openness},
-    primitives::ElectionOpenness,
+    selendra_primitives::ElectionOpenness,
     AccountId, BlockHash,
     Call::Elections,
     ConnectionApi, RootConnection, SudoCall, TxStatus,
@@ -0,0 +1,168 @@
+use codec::{Decode, Encode};
+use subxt::ext::sp_core::{H160, H256, U256};
+
+use crate::{
+    api,
+    eth::{EthKeyPair, Log, ReceiptV3, TransactionAction, TransactionV2},
+    pallets::{
+        evm::{EvmApi, EvmTxParams},
+        system::SystemApi,
+    },
+    AsConnection, BlockHash, ConnectionApi, TxInfo, TxStatus,
+};
+
+/// Outcome of an Ethereum transaction, put together from `ethereum.current_receipts` and
+/// `ethereum.current_transaction_statuses` of the block that included it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EthReceipt {
+    /// The Ethereum hash of the transaction.
+    pub transaction_hash: H256,
+    /// Position of the transaction among the Ethereum transactions of the block.
+    pub transaction_index: u32,
+    /// The sender.
+    pub from: H160,
+    /// The callee, `None` for contract creations.
+    pub to: Option<H160>,
+    /// The created contract, if any.
+    pub contract_address: Option<H160>,
+    /// Whether the execution succeeded.
+    pub succeeded: bool,
+    /// Gas used by the transactions of the block up to, and including, this one.
+    pub cumulative_gas_used: U256,
+    /// Logs emitted by the execution.
+    pub logs: Vec<Log>,
+}
+
+/// Pallet ethereum read-only api.
+#[async_trait::async_trait]
+pub trait EthereumApi {
+    /// Returns receipts of all Ethereum transactions included in a given block.
+    /// * `at` - hash of the block
+    async fn receipts(&self, at: BlockHash) -> anyhow::Result<Vec<EthReceipt>>;
+
+    /// Returns the receipt of an Ethereum transaction, if a given block included it.
+    /// * `transaction_hash` - the Ethereum hash of the transaction
+    /// * `at` - hash of the block
+    async fn receipt(
+        &self,
+        transaction_hash: H256,
+        at: BlockHash,
+    ) -> anyhow::Result<Option<EthReceipt>>;
+}
+
+/// Pallet ethereum api.
+#[async_trait::async_trait]
+pub trait EthereumUserApi {
+    /// API for `ethereum.transact` call. The transaction carries its own signature, so it is sent
+    /// as an unsigned extrinsic.
+    /// * `transaction` - a signed Ethereum transaction
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    async fn transact(
+        &self,
+        transaction: TransactionV2,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// Signs an Ethereum transaction with the chain id of the chain and, unless `params` has one
+    /// set, the current nonce of the signer, then submits it with `ethereum.transact`.
+    /// Returns the Ethereum hash of the transaction, next to where it was included.
+    /// * `signer` - an Ethereum key
+    /// * `action` - a contract to call, or [`TransactionAction::Create`]
+    /// * `input` - call data, or the init code of a contract to create
+    /// * `params` - gas and fee settings
+    /// * `status` - a [`TxStatus`] for a tx to wait for
+    async fn sign_and_transact(
+        &self,
+        signer: &EthKeyPair,
+        action: TransactionAction,
+        input: Vec<u8>,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<(H256, TxInfo)>;
+}
+
+#[async_trait::async_trait]
+impl<C: ConnectionApi> EthereumApi for C {
+    async fn receipts(&self, at: BlockHash) -> anyhow::Result<Vec<EthReceipt>> {
+        let receipts = self
+            .get_storage_entry_maybe(&api::storage().ethereum().current_receipts(), Some(at))
+            .await
+            .unwrap_or_default();
+        let statuses = self
+            .get_storage_entry_maybe(
+                &api::storage().ethereum().current_transaction_statuses(),
+                Some(at),
+            )
+            .await
+            .unwrap_or_default();
+        // The generated types mirror the ones of the `ethereum` crate, which are easier to use.
+        let receipts = Vec::<ReceiptV3>::decode(&mut &receipts.encode()[..])?;
+
+        Ok(
+            statuses
+                .into_iter()
+                .zip(receipts)
+                .map(|(status, receipt)| {
+                    let (ReceiptV3::Legacy(data)
+                    | ReceiptV3::EIP2930(data)
+                    | ReceiptV3::EIP1559(data)) = receipt;
+                    EthReceipt {
+                        transaction_hash: status.transaction_hash,
+                        transaction_index: status.transaction_index,
+                        from: status.from,
+                        to: status.to,
+                        contract_address: status.contract_address,
+                        succeeded: data.status_code == 1,
+                        cumulative_gas_used: data.used_gas,
+                        logs: data.logs,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    async fn receipt(
+        &self,
+        transaction_hash: H256,
+        at: BlockHash,
+    ) -> anyhow::Result<Option<EthReceipt>> {
+        Ok(self
+            .receipts(at)
+            .await?
+            .into_iter()
+            .find(|receipt| receipt.transaction_hash == transaction_hash))
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: AsConnection + Sync> EthereumUserApi for C {
+    async fn transact(
+        &self,
+        transaction: TransactionV2,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let transaction = Decode::decode(&mut &transaction.encode()[..])?;
+        let tx = api::tx().ethereum().transact(transaction);
+
+        self.send_unsigned_tx(tx, status).await
+    }
+
+    async fn sign_and_transact(
+        &self,
+        signer: &EthKeyPair,
+        action: TransactionAction,
+        input: Vec<u8>,
+        mut params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<(H256, TxInfo)> {
+        if params.nonce.is_none() {
+            let nonce = self.account_nonce(&signer.evm_account_id()).await?;
+            params.nonce = Some(nonce.into());
+        }
+        let chain_id = self.chain_id(None).await;
+        let transaction = signer.sign_transaction(chain_id, action, input, &params)?;
+        let hash = transaction.hash();
+
+        Ok((hash, self.transact(transaction, status).await?))
+    }
+}
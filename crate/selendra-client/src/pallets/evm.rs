@@ -0,0 +1,188 @@
+use subxt::ext::sp_core::{H160, H256, U256};
+
+use crate::{api, Balance, BlockHash, ConnectionApi, SignedConnectionApi, TxInfo, TxStatus};
+
+/// Gas and fee settings of a call executed by pallet evm.
+#[derive(Clone, Debug, Default)]
+pub struct EvmTxParams {
+    /// The value to transfer to the callee, in wei.
+    pub value: U256,
+    /// The maximum amount of gas the execution may use.
+    pub gas_limit: u64,
+    /// The maximum fee per unit of gas, including the base fee.
+    pub max_fee_per_gas: U256,
+    /// The maximum tip per unit of gas for the block author; legacy pricing if not set.
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// The nonce of the source account; the current one if not set.
+    pub nonce: Option<U256>,
+    /// Addresses and storage keys the execution is going to access.
+    pub access_list: Vec<(H160, Vec<H256>)>,
+}
+
+/// Pallet evm read-only api.
+#[async_trait::async_trait]
+pub trait EvmApi {
+    /// Returns `evm.account_codes` storage, i.e. the code deployed at a given address.
+    /// * `address` - an EVM address
+    /// * `at` - optional hash of a block to query state from
+    async fn account_code(&self, address: H160, at: Option<BlockHash>) -> Vec<u8>;
+
+    /// Returns `evm.account_storages` storage, i.e. a word of the storage of a given contract.
+    /// * `address` - an EVM address of a contract
+    /// * `index` - a storage slot
+    /// * `at` - optional hash of a block to query state from
+    async fn account_storage(&self, address: H160, index: H256, at: Option<BlockHash>) -> H256;
+
+    /// Returns `evm_chain_id.chain_id` storage, i.e. the chain id used by EVM transactions.
+    /// * `at` - optional hash of a block to query state from
+    async fn chain_id(&self, at: Option<BlockHash>) -> u64;
+}
+
+/// Pallet evm api.
+#[async_trait::async_trait]
+pub trait EvmUserApi {
+    /// API for `evm.withdraw` call.
+    async fn withdraw(
+        &self,
+        address: H160,
+        value: Balance,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for `evm.call` call.
+    async fn call(
+        &self,
+        source: H160,
+        target: H160,
+        input: Vec<u8>,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for `evm.create` call.
+    async fn create(
+        &self,
+        source: H160,
+        init: Vec<u8>,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for `evm.create2` call.
+    async fn create2(
+        &self,
+        source: H160,
+        init: Vec<u8>,
+        salt: H256,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: ConnectionApi> EvmApi for C {
+    async fn account_code(&self, address: H160, at: Option<BlockHash>) -> Vec<u8> {
+        let addrs = api::storage().evm().account_codes(address);
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .unwrap_or_default()
+    }
+
+    async fn account_storage(&self, address: H160, index: H256, at: Option<BlockHash>) -> H256 {
+        let addrs = api::storage().evm().account_storages(address, index);
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .unwrap_or_default()
+    }
+
+    async fn chain_id(&self, at: Option<BlockHash>) -> u64 {
+        let addrs = api::storage().evm_chain_id().chain_id();
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: SignedConnectionApi> EvmUserApi for S {
+    async fn withdraw(
+        &self,
+        address: H160,
+        value: Balance,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().evm().withdraw(address, value);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn call(
+        &self,
+        source: H160,
+        target: H160,
+        input: Vec<u8>,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().evm().call(
+            source,
+            target,
+            input,
+            params.value.into(),
+            params.gas_limit,
+            params.max_fee_per_gas.into(),
+            params.max_priority_fee_per_gas.map(Into::into),
+            params.nonce.map(Into::into),
+            params.access_list,
+        );
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn create(
+        &self,
+        source: H160,
+        init: Vec<u8>,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().evm().create(
+            source,
+            init,
+            params.value.into(),
+            params.gas_limit,
+            params.max_fee_per_gas.into(),
+            params.max_priority_fee_per_gas.map(Into::into),
+            params.nonce.map(Into::into),
+            params.access_list,
+        );
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn create2(
+        &self,
+        source: H160,
+        init: Vec<u8>,
+        salt: H256,
+        params: EvmTxParams,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().evm().create2(
+            source,
+            init,
+            salt,
+            params.value.into(),
+            params.gas_limit,
+            params.max_fee_per_gas.into(),
+            params.max_priority_fee_per_gas.map(Into::into),
+            params.nonce.map(Into::into),
+            params.access_list,
+        );
+
+        self.send_tx(tx, status).await
+    }
+}
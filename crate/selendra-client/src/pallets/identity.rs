@@ -0,0 +1,136 @@
+use subxt::{ext::sp_core::H256, utils::Static};
+
+use crate::{
+    api, api::runtime_types, pallet_identity::pallet::Call::add_registrar, AccountId, Balance,
+    BlockHash, Call::Identity, ConnectionApi, RootConnection, SignedConnectionApi, SudoCall,
+    TxInfo, TxStatus,
+};
+
+/// An alias for the identity information an account can set.
+pub type IdentityInfo = runtime_types::pallet_identity::legacy::IdentityInfo;
+/// An alias for the identity of an account, together with the judgements of registrars.
+pub type Registration = runtime_types::pallet_identity::types::Registration<Balance, IdentityInfo>;
+/// An alias for a judgement of a registrar.
+pub type Judgement = runtime_types::pallet_identity::types::Judgement<Balance>;
+
+/// Pallet identity read-only api.
+#[async_trait::async_trait]
+pub trait IdentityApi {
+    /// Returns the identity of a given account from `identity.identity_of` storage.
+    /// * `account` - an account to query identity for
+    /// * `at` - optional hash of a block to query state from
+    async fn identity_of(&self, account: AccountId, at: Option<BlockHash>) -> Option<Registration>;
+
+    /// Returns the parent of a given sub-account from `identity.super_of` storage.
+    /// * `account` - a sub-account
+    /// * `at` - optional hash of a block to query state from
+    async fn super_of(&self, account: AccountId, at: Option<BlockHash>) -> Option<AccountId>;
+}
+
+/// Pallet identity api.
+#[async_trait::async_trait]
+pub trait IdentityUserApi {
+    /// API for [`set_identity`](https://paritytech.github.io/polkadot-sdk/master/pallet_identity/pallet/struct.Pallet.html#method.set_identity) call.
+    async fn set_identity(&self, info: IdentityInfo, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`clear_identity`](https://paritytech.github.io/polkadot-sdk/master/pallet_identity/pallet/struct.Pallet.html#method.clear_identity) call.
+    async fn clear_identity(&self, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`request_judgement`](https://paritytech.github.io/polkadot-sdk/master/pallet_identity/pallet/struct.Pallet.html#method.request_judgement) call.
+    async fn request_judgement(
+        &self,
+        registrar: u32,
+        max_fee: Balance,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for [`provide_judgement`](https://paritytech.github.io/polkadot-sdk/master/pallet_identity/pallet/struct.Pallet.html#method.provide_judgement) call.
+    /// * `identity` - hash of the identity being judged, so that it cannot change in the meantime
+    async fn provide_judgement(
+        &self,
+        registrar: u32,
+        target: AccountId,
+        judgement: Judgement,
+        identity: H256,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+}
+
+/// Pallet identity api that requires sudo.
+#[async_trait::async_trait]
+pub trait IdentitySudoApi {
+    /// API for [`add_registrar`](https://paritytech.github.io/polkadot-sdk/master/pallet_identity/pallet/struct.Pallet.html#method.add_registrar) call.
+    async fn add_registrar(&self, account: AccountId, status: TxStatus) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: ConnectionApi> IdentityApi for C {
+    async fn identity_of(&self, account: AccountId, at: Option<BlockHash>) -> Option<Registration> {
+        let addrs = api::storage().identity().identity_of(Static(account));
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .map(|(registration, _username)| registration)
+    }
+
+    async fn super_of(&self, account: AccountId, at: Option<BlockHash>) -> Option<AccountId> {
+        let addrs = api::storage().identity().super_of(Static(account));
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .map(|(parent, _name)| parent.0)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: SignedConnectionApi> IdentityUserApi for S {
+    async fn set_identity(&self, info: IdentityInfo, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().identity().set_identity(info);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn clear_identity(&self, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().identity().clear_identity();
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn request_judgement(
+        &self,
+        registrar: u32,
+        max_fee: Balance,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().identity().request_judgement(registrar, max_fee);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn provide_judgement(
+        &self,
+        registrar: u32,
+        target: AccountId,
+        judgement: Judgement,
+        identity: H256,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx =
+            api::tx()
+                .identity()
+                .provide_judgement(registrar, Static(target), judgement, identity);
+
+        self.send_tx(tx, status).await
+    }
+}
+
+#[async_trait::async_trait]
+impl IdentitySudoApi for RootConnection {
+    async fn add_registrar(&self, account: AccountId, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let call = Identity(add_registrar {
+            account: Static(account),
+        });
+
+        self.sudo_unchecked(call, status).await
+    }
+}
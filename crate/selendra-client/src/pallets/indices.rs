@@ -0,0 +1,78 @@
+use subxt::utils::Static;
+
+use crate::{
+    api, AccountId, AccountIndex, BlockHash, ConnectionApi, SignedConnectionApi, TxInfo, TxStatus,
+};
+
+/// Pallet indices read-only api.
+#[async_trait::async_trait]
+pub trait IndicesApi {
+    /// Returns the owner of a given index from `indices.accounts` storage, if it is claimed.
+    /// * `index` - an account index
+    /// * `at` - optional hash of a block to query state from
+    async fn index_owner(&self, index: AccountIndex, at: Option<BlockHash>) -> Option<AccountId>;
+}
+
+/// Pallet indices api.
+#[async_trait::async_trait]
+pub trait IndicesUserApi {
+    /// API for [`claim`](https://paritytech.github.io/polkadot-sdk/master/pallet_indices/pallet/struct.Pallet.html#method.claim) call.
+    async fn claim_index(&self, index: AccountIndex, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`transfer`](https://paritytech.github.io/polkadot-sdk/master/pallet_indices/pallet/struct.Pallet.html#method.transfer) call.
+    async fn transfer_index(
+        &self,
+        new: AccountId,
+        index: AccountIndex,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for [`free`](https://paritytech.github.io/polkadot-sdk/master/pallet_indices/pallet/struct.Pallet.html#method.free) call.
+    async fn free_index(&self, index: AccountIndex, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`freeze`](https://paritytech.github.io/polkadot-sdk/master/pallet_indices/pallet/struct.Pallet.html#method.freeze) call.
+    async fn freeze_index(&self, index: AccountIndex, status: TxStatus) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: ConnectionApi> IndicesApi for C {
+    async fn index_owner(&self, index: AccountIndex, at: Option<BlockHash>) -> Option<AccountId> {
+        let addrs = api::storage().indices().accounts(index);
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .map(|(owner, _, _)| owner.0)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: SignedConnectionApi> IndicesUserApi for S {
+    async fn claim_index(&self, index: AccountIndex, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().indices().claim(index);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn transfer_index(
+        &self,
+        new: AccountId,
+        index: AccountIndex,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().indices().transfer(Static(new), index);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn free_index(&self, index: AccountIndex, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().indices().free(index);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn freeze_index(&self, index: AccountIndex, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().indices().freeze(index);
+
+        self.send_tx(tx, status).await
+    }
+}
@@ -6,17 +6,28 @@ pub mod author;
 pub mod balances;
 /// Pallet committee-management API
 pub mod committee_management;
-/// Pallet contracts API
-pub mod contract;
+/// Pallet custom signatures API
+pub mod custom_signatures;
+/// Pallet dynamic evm base fee API
+pub mod dynamic_evm_base_fee;
 /// Pallet elections API
 pub mod elections;
-
+/// Pallet ethereum API
+pub mod ethereum;
+/// Pallet evm API
+pub mod evm;
 /// Pallet transaction payment API
 pub mod fee;
+/// Pallet identity API
+pub mod identity;
+/// Pallet indices API
+pub mod indices;
 /// Pallet multisig API
 pub mod multisig;
 /// Pallet Proxy API
 pub mod proxy;
+/// Pallet recovery API
+pub mod recovery;
 /// Pallet session API
 pub mod session;
 /// Pallet staking API
@@ -25,5 +36,5 @@ pub mod staking;
 pub mod system;
 /// Pallet timestamp API
 pub mod timestamp;
-/// Pallet treasury API
-pub mod utility;
\ No newline at end of file
+/// Pallet utility API
+pub mod utility;
@@ -5,7 +5,7 @@ use codec::{Decode, Encode};
 use subxt::utils::Static;
 
 use crate::{
-    account_from_keypair, aleph_runtime::RuntimeCall, api, api::runtime_types, connections::TxInfo,
+    account_from_keypair, selendra_runtime::RuntimeCall, api, api::runtime_types, connections::TxInfo,
     sp_core::blake2_256, sp_runtime::traits::TrailingZeroInput, sp_weights::weight_v2::Weight,
     AccountId, Balance, BlockHash, BlockNumber, ConnectionApi, SignedConnectionApi, TxStatus,
 };
@@ -1,7 +1,7 @@
-use subxt::utils::MultiAddress;
+use subxt::utils::Static;
 
 use crate::{
-    aleph_runtime::{ProxyType, RuntimeCall},
+    selendra_runtime::{utility::ProxyType, RuntimeCall},
     api, AccountId, SignedConnectionApi, TxInfo, TxStatus,
 };
 
@@ -43,9 +43,7 @@ impl<S: SignedConnectionApi> ProxyUserApi for S {
         call: RuntimeCall,
         status: TxStatus,
     ) -> anyhow::Result<TxInfo> {
-        let tx = api::tx()
-            .proxy()
-            .proxy(MultiAddress::Id(real.into()), None, call);
+        let tx = api::tx().proxy().proxy(Static(real), None, call);
 
         self.send_tx(tx, status).await
     }
@@ -58,7 +56,7 @@ impl<S: SignedConnectionApi> ProxyUserApi for S {
     ) -> anyhow::Result<TxInfo> {
         let tx = api::tx()
             .proxy()
-            .add_proxy(MultiAddress::Id(delegate.into()), proxy_type, delay);
+            .add_proxy(Static(delegate), proxy_type, delay);
 
         self.send_tx(tx, status).await
     }
@@ -69,10 +67,9 @@ impl<S: SignedConnectionApi> ProxyUserApi for S {
         delay: u32,
         status: TxStatus,
     ) -> anyhow::Result<TxInfo> {
-        let tx =
-            api::tx()
-                .proxy()
-                .remove_proxy(MultiAddress::Id(delegate.into()), proxy_type, delay);
+        let tx = api::tx()
+            .proxy()
+            .remove_proxy(Static(delegate), proxy_type, delay);
 
         self.send_tx(tx, status).await
     }
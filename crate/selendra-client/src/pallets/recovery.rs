@@ -0,0 +1,194 @@
+use subxt::utils::Static;
+
+use crate::{
+    api, api::runtime_types, bounded_collections::bounded_vec::BoundedVec,
+    selendra_runtime::RuntimeCall, AccountId, Balance, BlockHash, BlockNumber, ConnectionApi,
+    SignedConnectionApi, TxInfo, TxStatus,
+};
+
+/// An alias for the recovery configuration of an account.
+pub type RecoveryConfig = runtime_types::pallet_recovery::RecoveryConfig<
+    BlockNumber,
+    Balance,
+    BoundedVec<Static<AccountId>>,
+>;
+/// An alias for a recovery process in progress.
+pub type ActiveRecovery = runtime_types::pallet_recovery::ActiveRecovery<
+    BlockNumber,
+    Balance,
+    BoundedVec<Static<AccountId>>,
+>;
+
+/// Pallet recovery read-only api.
+#[async_trait::async_trait]
+pub trait RecoveryApi {
+    /// Returns `recovery.recoverable` storage, i.e. how a given account can be recovered.
+    /// * `account` - a recoverable account
+    /// * `at` - optional hash of a block to query state from
+    async fn recovery_config(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Option<RecoveryConfig>;
+
+    /// Returns `recovery.active_recoveries` storage, i.e. the vouches a rescuer has collected.
+    /// * `lost` - an account being recovered
+    /// * `rescuer` - an account trying to recover it
+    /// * `at` - optional hash of a block to query state from
+    async fn active_recovery(
+        &self,
+        lost: AccountId,
+        rescuer: AccountId,
+        at: Option<BlockHash>,
+    ) -> Option<ActiveRecovery>;
+
+    /// Returns `recovery.proxy` storage, i.e. the account a given rescuer has recovered.
+    /// * `rescuer` - an account that may have recovered another one
+    /// * `at` - optional hash of a block to query state from
+    async fn recovered_by(&self, rescuer: AccountId, at: Option<BlockHash>) -> Option<AccountId>;
+}
+
+/// Pallet recovery api.
+#[async_trait::async_trait]
+pub trait RecoveryUserApi {
+    /// API for [`create_recovery`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.create_recovery) call.
+    /// The friends do not need to be sorted.
+    async fn create_recovery(
+        &self,
+        friends: Vec<AccountId>,
+        threshold: u16,
+        delay_period: BlockNumber,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for [`initiate_recovery`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.initiate_recovery) call.
+    async fn initiate_recovery(&self, lost: AccountId, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`vouch_recovery`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.vouch_recovery) call.
+    async fn vouch_recovery(
+        &self,
+        lost: AccountId,
+        rescuer: AccountId,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for [`claim_recovery`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.claim_recovery) call.
+    async fn claim_recovery(&self, lost: AccountId, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`as_recovered`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.as_recovered) call.
+    async fn as_recovered(
+        &self,
+        lost: AccountId,
+        call: RuntimeCall,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo>;
+
+    /// API for [`close_recovery`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.close_recovery) call.
+    async fn close_recovery(&self, rescuer: AccountId, status: TxStatus) -> anyhow::Result<TxInfo>;
+
+    /// API for [`remove_recovery`](https://paritytech.github.io/polkadot-sdk/master/pallet_recovery/pallet/struct.Pallet.html#method.remove_recovery) call.
+    async fn remove_recovery(&self, status: TxStatus) -> anyhow::Result<TxInfo>;
+}
+
+#[async_trait::async_trait]
+impl<C: ConnectionApi> RecoveryApi for C {
+    async fn recovery_config(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Option<RecoveryConfig> {
+        let addrs = api::storage().recovery().recoverable(Static(account));
+
+        self.get_storage_entry_maybe(&addrs, at).await
+    }
+
+    async fn active_recovery(
+        &self,
+        lost: AccountId,
+        rescuer: AccountId,
+        at: Option<BlockHash>,
+    ) -> Option<ActiveRecovery> {
+        let addrs = api::storage()
+            .recovery()
+            .active_recoveries(Static(lost), Static(rescuer));
+
+        self.get_storage_entry_maybe(&addrs, at).await
+    }
+
+    async fn recovered_by(&self, rescuer: AccountId, at: Option<BlockHash>) -> Option<AccountId> {
+        let addrs = api::storage().recovery().proxy(Static(rescuer));
+
+        self.get_storage_entry_maybe(&addrs, at)
+            .await
+            .map(|lost| lost.0)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: SignedConnectionApi> RecoveryUserApi for S {
+    async fn create_recovery(
+        &self,
+        mut friends: Vec<AccountId>,
+        threshold: u16,
+        delay_period: BlockNumber,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        // The pallet accepts only sorted friends without duplicates.
+        friends.sort();
+        friends.dedup();
+        let friends = friends.into_iter().map(Static).collect();
+        let tx = api::tx()
+            .recovery()
+            .create_recovery(friends, threshold, delay_period);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn initiate_recovery(&self, lost: AccountId, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().recovery().initiate_recovery(Static(lost));
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn vouch_recovery(
+        &self,
+        lost: AccountId,
+        rescuer: AccountId,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx()
+            .recovery()
+            .vouch_recovery(Static(lost), Static(rescuer));
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn claim_recovery(&self, lost: AccountId, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().recovery().claim_recovery(Static(lost));
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn as_recovered(
+        &self,
+        lost: AccountId,
+        call: RuntimeCall,
+        status: TxStatus,
+    ) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().recovery().as_recovered(Static(lost), call);
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn close_recovery(&self, rescuer: AccountId, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().recovery().close_recovery(Static(rescuer));
+
+        self.send_tx(tx, status).await
+    }
+
+    async fn remove_recovery(&self, status: TxStatus) -> anyhow::Result<TxInfo> {
+        let tx = api::tx().recovery().remove_recovery();
+
+        self.send_tx(tx, status).await
+    }
+}
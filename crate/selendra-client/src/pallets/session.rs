@@ -1,7 +1,7 @@
 use subxt::utils::Static;
 
 use crate::{
-    api, connections::TxInfo, primitives::AlephNodeSessionKeys as SessionKeys, AccountId,
+    api, connections::TxInfo, selendra_primitives::AlephNodeSessionKeys as SessionKeys, AccountId,
     BlockHash, ConnectionApi, SessionIndex, SignedConnectionApi, TxStatus,
 };
 
@@ -2,7 +2,7 @@ use codec::Encode;
 use subxt::{
     ext::sp_runtime::Perbill as SPerbill,
     storage::StorageKey,
-    utils::{KeyedVec, MultiAddress, Static},
+    utils::{KeyedVec, Static},
 };
 
 use crate::{
@@ -460,7 +460,7 @@ impl<S: SignedConnectionApi> StakingUserApi for S {
     ) -> anyhow::Result<TxInfo> {
         let tx = api::tx()
             .staking()
-            .nominate(vec![MultiAddress::Id(Static(nominee_account_id))]);
+            .nominate(vec![Static(nominee_account_id)]);
 
         self.send_tx(tx, status).await
     }
@@ -615,7 +615,7 @@ impl StakingApiExt for RootConnection {
                 });
 
                 Sudo(sudo_as {
-                    who: MultiAddress::Id(Static(s.clone())),
+                    who: Static(s.clone()),
                     call: Box::new(b),
                 })
             })
@@ -633,10 +633,10 @@ impl StakingApiExt for RootConnection {
             .iter()
             .map(|(nominator, nominee)| {
                 let call = Staking(nominate {
-                    targets: vec![MultiAddress::Id(Static(nominee.clone()))],
+                    targets: vec![Static(nominee.clone())],
                 });
                 Sudo(sudo_as {
-                    who: MultiAddress::Id(Static(nominator.clone())),
+                    who: Static(nominator.clone()),
                     call: Box::new(call),
                 })
             })
@@ -0,0 +1,517 @@
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use futures::{
+    channel::mpsc,
+    future::BoxFuture,
+    stream::{BoxStream, FuturesUnordered},
+    FutureExt, StreamExt,
+};
+use log::{debug, info, warn};
+use primitives::Nonce;
+use subxt::{
+    error::TransactionError,
+    tx::{SubmittableExtrinsic as SubxtSubmittable, TxPayload},
+    Metadata,
+};
+
+use crate::{
+    api, pallets::system::SystemApi, AlephConfig, AsConnection, ParamsBuilder, SignedConnection,
+    SignedConnectionApi, SubxtClient, TxInfo, TxStatus,
+};
+
+/// Identifies a transaction submitted through a [`TxPipeline`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TxId(pub u64);
+
+/// Limits of a [`TxPipeline`].
+#[derive(Clone, Debug)]
+pub struct TxPipelineConfig {
+    /// How many transactions may be submitted and not yet done at the same time.
+    pub max_in_flight: usize,
+    /// How many times a transaction is resubmitted after a recoverable failure.
+    pub max_retries: u32,
+}
+
+impl Default for TxPipelineConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: 256,
+            max_retries: 3,
+        }
+    }
+}
+
+/// What happened to a transaction submitted through a [`TxPipeline`].
+#[derive(Debug)]
+pub enum TxEvent {
+    /// The transaction reached the [`TxStatus`] it was submitted with.
+    Done {
+        /// The transaction.
+        id: TxId,
+        /// The nonce it was signed with.
+        nonce: Nonce,
+        /// Where it was included.
+        info: TxInfo,
+    },
+    /// The transaction did not make it into a block, and is going to be signed with a fresh nonce
+    /// and submitted again.
+    Retrying {
+        /// The transaction.
+        id: TxId,
+        /// Why it did not make it.
+        error: anyhow::Error,
+    },
+    /// The transaction failed for good.
+    Failed {
+        /// The transaction.
+        id: TxId,
+        /// Why it failed.
+        error: anyhow::Error,
+    },
+}
+
+/// Submits many transactions signed by the same account concurrently.
+///
+/// Nonces are assigned locally instead of being fetched for every transaction, so hundreds of
+/// transactions can land in the same block. After a transaction is dropped or turns out to be
+/// stale, the nonce is synced with the chain again and the transaction is resubmitted, while
+/// nonces left unused by failed transactions are filled with `system.remark`s, so the ones after
+/// them do not get stuck.
+///
+/// # Examples
+/// ```ignore
+/// let (pipeline, mut events) = TxPipeline::new(connection, TxPipelineConfig::default());
+/// for dest in destinations {
+///     pipeline.submit(api::tx().balances().transfer_keep_alive(Static(dest), amount), TxStatus::InBlock)?;
+/// }
+/// drop(pipeline);
+/// while let Some(event) = events.next().await {
+///     info!("{:?}", event);
+/// }
+/// ```
+pub struct TxPipeline {
+    connection: SignedConnection,
+    requests: mpsc::UnboundedSender<Request>,
+    next_id: AtomicU64,
+}
+
+impl TxPipeline {
+    /// Creates a pipeline of transactions signed by the signer of the connection.
+    ///
+    /// Transactions are submitted only while the returned stream is polled. The stream ends once
+    /// the pipeline is dropped and all its transactions are done.
+    pub fn new(
+        connection: SignedConnection,
+        config: TxPipelineConfig,
+    ) -> (Self, BoxStream<'static, TxEvent>) {
+        let (requests, requests_from_pipeline) = mpsc::unbounded();
+        let driver = Driver {
+            connection: connection.clone(),
+            config,
+            requests: requests_from_pipeline,
+            queue: VecDeque::new(),
+            in_flight: FuturesUnordered::new(),
+            nonces: Nonces::default(),
+            events: VecDeque::new(),
+        };
+        let events = futures::stream::unfold(driver, |mut driver| async move {
+            let event = driver.next_event().await?;
+            Some((event, driver))
+        })
+        .boxed();
+
+        (
+            Self {
+                connection,
+                requests,
+                next_id: AtomicU64::new(0),
+            },
+            events,
+        )
+    }
+
+    /// Queues a transaction with default params.
+    /// * `tx` - encoded transaction payload
+    /// * `status` - a [`TxStatus`] the transaction has to reach to be done
+    pub fn submit<Call: TxPayload>(&self, tx: Call, status: TxStatus) -> anyhow::Result<TxId> {
+        self.submit_with_params(tx, Default::default(), status)
+    }
+
+    /// Queues a transaction.
+    /// * `tx` - encoded transaction payload
+    /// * `params` - tx params e.g. tip, used for every submission of the transaction
+    /// * `status` - a [`TxStatus`] the transaction has to reach to be done
+    pub fn submit_with_params<Call: TxPayload>(
+        &self,
+        tx: Call,
+        params: ParamsBuilder,
+        status: TxStatus,
+    ) -> anyhow::Result<TxId> {
+        let call = EncodedCall(self.connection.as_client().tx().call_data(&tx)?);
+        let id = TxId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.requests
+            .unbounded_send(Request {
+                id: Some(id),
+                call,
+                params,
+                status,
+                attempts: 0,
+            })
+            .map_err(|_| anyhow::anyhow!("The events of the pipeline are no longer polled"))?;
+
+        Ok(id)
+    }
+
+    /// Returns the connection the transactions are signed and submitted with.
+    pub fn connection(&self) -> &SignedConnection {
+        &self.connection
+    }
+}
+
+/// Call data encoded once, so that a transaction can be signed again with a different nonce.
+#[derive(Clone)]
+pub(crate) struct EncodedCall(pub(crate) Vec<u8>);
+
+impl TxPayload for EncodedCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+struct Request {
+    /// `None` for transactions filling a nonce left unused.
+    id: Option<TxId>,
+    call: EncodedCall,
+    params: ParamsBuilder,
+    status: TxStatus,
+    attempts: u32,
+}
+
+struct Failure {
+    error: subxt::Error,
+    /// Whether some transaction, not necessarily this one, used the nonce.
+    nonce_used: bool,
+    /// Whether submitting again with a fresh nonce may help.
+    recoverable: bool,
+}
+
+impl Failure {
+    /// The transaction was rejected when submitting.
+    fn rejected(error: subxt::Error) -> Self {
+        let message = error.to_string();
+        // The nonce was used by a transaction from outside of the pipeline.
+        let recoverable = message.contains("outdated")
+            || message.contains("Stale")
+            || message.contains("Priority is too low");
+        Self {
+            error,
+            nonce_used: false,
+            recoverable,
+        }
+    }
+
+    /// The transaction was accepted, but something went wrong later.
+    fn after_submission(error: subxt::Error) -> Self {
+        let (nonce_used, recoverable) = match &error {
+            subxt::Error::Transaction(TransactionError::Dropped | TransactionError::Invalid) => {
+                (false, true)
+            }
+            subxt::Error::Transaction(TransactionError::Usurped) => (true, true),
+            _ => (true, false),
+        };
+        Self {
+            error,
+            nonce_used,
+            recoverable,
+        }
+    }
+}
+
+/// Tracks nonces of the signer, so that every transaction in flight has a different one.
+#[derive(Default)]
+struct Nonces {
+    next: Option<Nonce>,
+    in_flight: BTreeSet<Nonce>,
+}
+
+impl Nonces {
+    fn needs_sync(&self) -> bool {
+        self.next.is_none()
+    }
+
+    fn sync(&mut self, next: Nonce) {
+        self.next = Some(next);
+    }
+
+    /// Forgets the next nonce, so that it is fetched from the chain before it is used.
+    fn resync(&mut self) {
+        self.next = None;
+    }
+
+    /// Takes the lowest nonce, from the next one on, that no transaction in flight uses.
+    fn take(&mut self) -> Nonce {
+        let mut nonce = self.next.expect("nonces should be synced before use");
+        while self.in_flight.contains(&nonce) {
+            nonce += 1;
+        }
+        self.in_flight.insert(nonce);
+        self.next = Some(nonce + 1);
+        nonce
+    }
+
+    /// Returns a nonce that has not been used, e.g. because signing failed.
+    fn give_back(&mut self, nonce: Nonce) {
+        self.in_flight.remove(&nonce);
+        self.next = Some(self.next.map_or(nonce, |next| next.min(nonce)));
+    }
+
+    fn done(&mut self, nonce: Nonce) {
+        self.in_flight.remove(&nonce);
+    }
+}
+
+type InFlight = BoxFuture<'static, (Request, Nonce, Result<TxInfo, Failure>)>;
+
+struct Driver {
+    connection: SignedConnection,
+    config: TxPipelineConfig,
+    requests: mpsc::UnboundedReceiver<Request>,
+    queue: VecDeque<Request>,
+    in_flight: FuturesUnordered<InFlight>,
+    nonces: Nonces,
+    events: VecDeque<TxEvent>,
+}
+
+impl Driver {
+    async fn next_event(&mut self) -> Option<TxEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(event);
+            }
+            self.start_queued().await;
+            if !self.events.is_empty() {
+                continue;
+            }
+            futures::select! {
+                request = self.requests.next() => {
+                    if let Some(request) = request {
+                        self.queue.push_back(request);
+                    }
+                },
+                (request, nonce, result) = self.in_flight.select_next_some() => {
+                    self.finish(request, nonce, result);
+                },
+                complete => return None,
+            }
+        }
+    }
+
+    async fn start_queued(&mut self) {
+        while self.in_flight.len() < self.config.max_in_flight {
+            let Some(request) = self.queue.pop_front() else {
+                return;
+            };
+            if self.nonces.needs_sync() {
+                match self
+                    .connection
+                    .account_nonce(self.connection.account_id())
+                    .await
+                {
+                    Ok(nonce) => {
+                        debug!(target: "aleph-client", "Pipeline nonce synced to {}", nonce);
+                        self.nonces.sync(nonce);
+                    }
+                    Err(error) => {
+                        self.fail(request, error);
+                        continue;
+                    }
+                }
+            }
+
+            let nonce = self.nonces.take();
+            let signed = self.connection.as_client().tx().create_signed_with_nonce(
+                &request.call,
+                &self.connection.signer().inner,
+                nonce.into(),
+                request.params,
+            );
+            match signed {
+                Ok(signed) => self.in_flight.push(watch(signed, request, nonce).boxed()),
+                Err(error) => {
+                    self.nonces.give_back(nonce);
+                    self.fail(request, error.into());
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, mut request: Request, nonce: Nonce, result: Result<TxInfo, Failure>) {
+        self.nonces.done(nonce);
+        let failure = match result {
+            Ok(info) => {
+                if let Some(id) = request.id {
+                    self.events.push_back(TxEvent::Done { id, nonce, info });
+                }
+                return;
+            }
+            Err(failure) => failure,
+        };
+
+        if !failure.nonce_used || failure.recoverable {
+            self.nonces.resync();
+        }
+        if failure.recoverable && request.attempts < self.config.max_retries {
+            warn!(target: "aleph-client", "Resubmitting tx {:?} with nonce {}: {}", request.id, nonce, failure.error);
+            request.attempts += 1;
+            if let Some(id) = request.id {
+                self.events.push_back(TxEvent::Retrying {
+                    id,
+                    error: failure.error.into(),
+                });
+            }
+            self.queue.push_front(request);
+            return;
+        }
+
+        if !failure.nonce_used && self.queue.is_empty() && !self.in_flight.is_empty() {
+            // Nothing else is going to take the nonce, and the transactions after it would wait
+            // for it forever.
+            self.fill_gap();
+        }
+        self.fail(request, failure.error.into());
+    }
+
+    fn fill_gap(&mut self) {
+        let remark = api::tx().system().remark(Vec::new());
+        match self.connection.as_client().tx().call_data(&remark) {
+            Ok(call) => {
+                info!(target: "aleph-client", "Filling an unused nonce with a remark");
+                self.queue.push_back(Request {
+                    id: None,
+                    call: EncodedCall(call),
+                    params: Default::default(),
+                    status: TxStatus::Submitted,
+                    attempts: 0,
+                });
+            }
+            Err(e) => warn!(target: "aleph-client", "Could not fill an unused nonce: {}", e),
+        }
+    }
+
+    fn fail(&mut self, request: Request, error: anyhow::Error) {
+        warn!(target: "aleph-client", "Tx {:?} failed: {}", request.id, error);
+        if let Some(id) = request.id {
+            self.events.push_back(TxEvent::Failed { id, error });
+        }
+    }
+}
+
+async fn watch(
+    signed: SubxtSubmittable<AlephConfig, SubxtClient>,
+    request: Request,
+    nonce: Nonce,
+) -> (Request, Nonce, Result<TxInfo, Failure>) {
+    let result = match request.status {
+        TxStatus::Submitted => signed
+            .submit()
+            .await
+            .map(|tx_hash| TxInfo {
+                block_hash: Default::default(),
+                tx_hash,
+            })
+            .map_err(Failure::rejected),
+        status => match signed.submit_and_watch().await {
+            Ok(progress) => {
+                let included = match status {
+                    TxStatus::Finalized => progress.wait_for_finalized_success().await,
+                    _ => match progress.wait_for_in_block().await {
+                        Ok(in_block) => in_block.wait_for_success().await,
+                        Err(e) => Err(e),
+                    },
+                };
+                included
+                    .map(TxInfo::from)
+                    .map_err(Failure::after_submission)
+            }
+            Err(e) => Err(Failure::rejected(e)),
+        },
+    };
+
+    (request, nonce, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use subxt::error::TransactionError;
+
+    use super::{Failure, Nonces};
+
+    #[test]
+    fn assigns_consecutive_nonces() {
+        let mut nonces = Nonces::default();
+        assert!(nonces.needs_sync());
+        nonces.sync(7);
+        assert_eq!(
+            (0..3).map(|_| nonces.take()).collect::<Vec<_>>(),
+            vec![7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn resync_skips_nonces_in_flight() {
+        let mut nonces = Nonces::default();
+        nonces.sync(0);
+        for _ in 0..5 {
+            nonces.take();
+        }
+        // Transactions with nonces 1 and 3 were dropped, the chain knows only about 0.
+        nonces.done(1);
+        nonces.done(3);
+        nonces.resync();
+        nonces.sync(1);
+        assert_eq!(nonces.take(), 1);
+        assert_eq!(nonces.take(), 3);
+        assert_eq!(nonces.take(), 5);
+    }
+
+    #[test]
+    fn gives_back_unused_nonces() {
+        let mut nonces = Nonces::default();
+        nonces.sync(0);
+        let first = nonces.take();
+        let second = nonces.take();
+        nonces.give_back(first);
+        assert_eq!(nonces.take(), first);
+        assert_eq!(nonces.take(), second + 1);
+    }
+
+    #[test]
+    fn classifies_failures() {
+        let stale = Failure::rejected(subxt::Error::Other(
+            "Invalid Transaction: Transaction is outdated".to_string(),
+        ));
+        assert!(stale.recoverable && !stale.nonce_used);
+
+        let bad_proof = Failure::rejected(subxt::Error::Other(
+            "Invalid Transaction: Transaction has a bad signature".to_string(),
+        ));
+        assert!(!bad_proof.recoverable && !bad_proof.nonce_used);
+
+        let dropped = Failure::after_submission(TransactionError::Dropped.into());
+        assert!(dropped.recoverable && !dropped.nonce_used);
+
+        let usurped = Failure::after_submission(TransactionError::Usurped.into());
+        assert!(usurped.recoverable && usurped.nonce_used);
+
+        let timeout = Failure::after_submission(TransactionError::FinalityTimeout.into());
+        assert!(!timeout.recoverable && timeout.nonce_used);
+    }
+}
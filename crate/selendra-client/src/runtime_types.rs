@@ -1,12 +1,15 @@
+use subxt::ext::sp_core;
+
 pub use crate::selendra::api::runtime_types::*;
 use crate::{
     api::runtime_types::{
-        primitives::app::Public as AlephPublic,
+        selendra_primitives::app::Public as AlephPublic,
         sp_consensus_aura::sr25519::app_sr25519::Public as AuraPublic,
         sp_core::{ed25519::Public as EdPublic, sr25519::Public as SrPublic},
     },
     pallet_staking::EraRewardPoints,
-    primitives::AlephNodeSessionKeys as SessionKeys,
+    primitive_types::U256,
+    selendra_primitives::AlephNodeSessionKeys as SessionKeys,
     sp_weights::weight_v2::Weight,
 };
 
@@ -58,4 +61,15 @@ impl Weight {
             proof_size,
         }
     }
-}
\ No newline at end of file
+}
+impl From<sp_core::U256> for U256 {
+    fn from(value: sp_core::U256) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<U256> for sp_core::U256 {
+    fn from(value: U256) -> Self {
+        Self(value.0)
+    }
+}
@@ -1,10 +1,20 @@
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 
-use finality_aleph::UnitCreationDelay;
+use finality_aleph::{UnitCreationDelay, DEFAULT_BACKUP_RETAINED_SESSIONS};
 use log::warn;
-use sc_cli::clap::{self, ArgGroup, Parser};
+use sc_cli::clap::{self, ArgGroup, Parser, ValueEnum};
 use selendra_primitives::{DEFAULT_MAX_NON_FINALIZED_BLOCKS, DEFAULT_UNIT_CREATION_DELAY};
 
+/// Kinds of storage AlephBFT backups can be kept in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BackupStorageKind {
+	/// Files in a directory on the local filesystem.
+	Filesystem,
+	/// The auxiliary storage of the node's database. Survives crashes of the node, but unlike
+	/// the filesystem storage the latest records might be lost on power loss.
+	Database,
+}
+
 #[derive(Debug, Parser, Clone)]
 #[command(group(ArgGroup::new("backup")))]
 pub struct AlephCli {
@@ -35,6 +45,20 @@ pub struct AlephCli {
 	#[arg(long, value_name = "PATH", group = "backup")]
 	backup_path: Option<PathBuf>,
 
+	/// Where to keep backups. The filesystem storage keeps them under `--backup-path`, the
+	/// database storage keeps them in the node's database, so they survive as long as it does.
+	#[arg(long, value_enum, default_value_t = BackupStorageKind::Filesystem)]
+	backup_storage: BackupStorageKind,
+
+	/// How many of the most recent sessions keep their backups, including the current one.
+	/// Backups of older sessions are removed at the beginning of every session.
+	#[arg(
+		long,
+		default_value_t = DEFAULT_BACKUP_RETAINED_SESSIONS,
+		value_parser = clap::value_parser!(u32).range(1..)
+	)]
+	backup_retained_sessions: u32,
+
 	/// The maximum number of nonfinalized blocks, after which block production should be locally
 	/// stopped. DO NOT CHANGE THIS, PRODUCING MORE OR FEWER BLOCKS MIGHT BE CONSIDERED MALICIOUS
 	/// BEHAVIOUR AND PUNISHED ACCORDINGLY!
@@ -51,10 +75,57 @@ pub struct AlephCli {
 	#[arg(long, default_value_t = 64 * 1024)]
 	alephbft_bit_rate_per_connection: u64,
 
+	/// Maximum combined bit-rate in bytes per second of all the connections of the alephbft
+	/// validator network. Enables adaptive rate limiting: the rate of every connection follows the
+	/// committee size and its backlog, never dropping below `--alephbft-bit-rate-per-connection`.
+	#[arg(long)]
+	alephbft_aggregate_bit_rate: Option<u64>,
+
 	/// Don't spend some extra time to collect more debugging data (e.g. validator network details).
 	/// By default collecting is enabled, as the impact on performance is negligible, if any.
 	#[arg(long, default_value_t = false)]
 	no_collection_of_extra_debugging_data: bool,
+
+	/// How many blocks above the last finalized one block sync keeps track of. Has to be at least
+	/// the session period. By default it is enough to fit two sessions, but at least 1800.
+	#[arg(long)]
+	sync_forest_max_depth: Option<u32>,
+
+	/// How many sessions the justification verifier keeps the authorities of. Has to be at
+	/// least 2, defaults to 3.
+	#[arg(long)]
+	sync_verifier_cache_size: Option<usize>,
+
+	/// How many chain events the data store of a session waits for at most before pruning
+	/// proposals. Defaults to 80000.
+	#[arg(long)]
+	data_store_max_triggers_pending: Option<usize>,
+
+	/// How many proposals the data store of a session waits to become available at most.
+	/// Defaults to 80000.
+	#[arg(long)]
+	data_store_max_proposals_pending: Option<usize>,
+
+	/// How many messages the data store of a session holds back at most until their data is
+	/// available. Defaults to 40000.
+	#[arg(long)]
+	data_store_max_messages_pending: Option<usize>,
+
+	/// How many proposals known to be available the data store of a session remembers.
+	/// Defaults to 8000.
+	#[arg(long)]
+	data_store_available_proposals_cache_capacity: Option<NonZeroUsize>,
+
+	/// How many blocks known to be imported or finalized the data store of a session remembers.
+	/// Defaults to 2000.
+	#[arg(long)]
+	data_store_block_cache_capacity: Option<NonZeroUsize>,
+
+	/// Trace the lifecycle of blocks, from being authored until finalization, and export the
+	/// traces to the OpenTelemetry collector at this OTLP/HTTP endpoint, e.g.
+	/// `http://localhost:4318`. Tracing is disabled if not provided.
+	#[arg(long, value_name = "URL")]
+	otlp_endpoint: Option<String>,
 }
 
 impl AlephCli {
@@ -82,6 +153,14 @@ impl AlephCli {
 		self.no_backup
 	}
 
+	pub fn backup_storage(&self) -> BackupStorageKind {
+		self.backup_storage
+	}
+
+	pub fn backup_retained_sessions(&self) -> u32 {
+		self.backup_retained_sessions
+	}
+
 	pub fn max_nonfinalized_blocks(&self) -> u32 {
 		if self.max_nonfinalized_blocks != DEFAULT_MAX_NON_FINALIZED_BLOCKS {
 			warn!("Running block production with a value of max-nonfinalized-blocks {}, which is not the default of 20. THIS MIGHT BE CONSIDERED MALICIOUS BEHAVIOUR AND RESULT IN PENALTIES!", self.max_nonfinalized_blocks);
@@ -97,7 +176,43 @@ impl AlephCli {
 		self.alephbft_bit_rate_per_connection
 	}
 
+	pub fn alephbft_aggregate_bit_rate(&self) -> Option<u64> {
+		self.alephbft_aggregate_bit_rate
+	}
+
 	pub fn no_collection_of_extra_debugging_data(&self) -> bool {
 		self.no_collection_of_extra_debugging_data
 	}
+
+	pub fn sync_forest_max_depth(&self) -> Option<u32> {
+		self.sync_forest_max_depth
+	}
+
+	pub fn sync_verifier_cache_size(&self) -> Option<usize> {
+		self.sync_verifier_cache_size
+	}
+
+	pub fn data_store_max_triggers_pending(&self) -> Option<usize> {
+		self.data_store_max_triggers_pending
+	}
+
+	pub fn data_store_max_proposals_pending(&self) -> Option<usize> {
+		self.data_store_max_proposals_pending
+	}
+
+	pub fn data_store_max_messages_pending(&self) -> Option<usize> {
+		self.data_store_max_messages_pending
+	}
+
+	pub fn data_store_available_proposals_cache_capacity(&self) -> Option<NonZeroUsize> {
+		self.data_store_available_proposals_cache_capacity
+	}
+
+	pub fn data_store_block_cache_capacity(&self) -> Option<NonZeroUsize> {
+		self.data_store_block_cache_capacity
+	}
+
+	pub fn otlp_endpoint(&self) -> Option<String> {
+		self.otlp_endpoint.clone()
+	}
 }
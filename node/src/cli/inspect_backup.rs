@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use finality_aleph::BackupStorage;
+use sc_cli::{
+	clap::{self, Parser},
+	CliConfiguration, DatabaseParams, SharedParams,
+};
+
+use crate::cli::aleph_cli::BackupStorageKind;
+
+/// Summarizes the AlephBFT backups of a node without modifying them.
+#[derive(Debug, Parser, Clone)]
+pub struct InspectBackupCmd {
+	/// Where the node keeps its backups, i.e. its `--backup-storage`. Backups in the database
+	/// are read from the database of the chain selected with `--chain` and `--base-path`.
+	#[arg(long, value_enum, default_value_t = BackupStorageKind::Filesystem)]
+	backup_storage: BackupStorageKind,
+
+	/// The path the node saves backups to, i.e. its `--backup-path`, which by default is
+	/// `backup-stash` in the base path of the chain. Required for backups on the filesystem.
+	#[arg(long, value_name = "PATH", required_if_eq("backup_storage", "filesystem"))]
+	backup_path: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl InspectBackupCmd {
+	pub fn backup_storage(&self) -> BackupStorageKind {
+		self.backup_storage
+	}
+
+	pub fn backup_path(&self) -> Option<PathBuf> {
+		self.backup_path.clone()
+	}
+
+	pub fn run(&self, storage: &dyn BackupStorage) -> sc_cli::Result<()> {
+		let summaries = storage.inspect()?;
+		if summaries.is_empty() {
+			println!("No session backups found");
+		}
+		for summary in summaries {
+			println!("{summary}");
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InspectBackupCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
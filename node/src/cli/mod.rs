@@ -1,7 +1,9 @@
 #![allow(missing_docs)]
 
 pub mod aleph_cli;
+pub mod inspect_backup;
 use aleph_cli::AlephCli;
+use inspect_backup::InspectBackupCmd;
 
 use crate::eth::EthConfiguration;
 
@@ -59,4 +61,7 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	FrontierDb(fc_cli::FrontierDbCmd),
+
+	/// Print the number of AlephBFT units backed up for every session.
+	InspectBackup(InspectBackupCmd),
 }
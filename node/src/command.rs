@@ -1,3 +1,4 @@
+use finality_aleph::{DatabaseBackupStorage, FsBackupStorage};
 use futures::TryFutureExt;
 use log::info;
 // Substrate
@@ -12,7 +13,7 @@ use selendra_primitives::HEAP_PAGES;
 
 use crate::{
 	chain_spec::{selendra_config, SelendraNodeChainSpec},
-	cli::{Cli, Subcommand},
+	cli::{aleph_cli::BackupStorageKind, Cli, Subcommand},
 	eth::db_config_dir,
 	new_partial, service, ConfigValidator, ServiceComponents,
 };
@@ -71,6 +72,19 @@ pub fn run() -> sc_cli::Result<()> {
 
 	match &cli.subcommand {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
+		Some(Subcommand::InspectBackup(cmd)) => match (cmd.backup_storage(), cmd.backup_path()) {
+			(BackupStorageKind::Filesystem, Some(path)) => cmd.run(&FsBackupStorage::new(path)),
+			(BackupStorageKind::Filesystem, None) => {
+				Err("Backups on the filesystem require `--backup-path`.".into())
+			},
+			(BackupStorageKind::Database, _) => {
+				let runner = cli.create_runner(cmd)?;
+				runner.sync_run(|mut config| {
+					let (client, _, _, _, _) = service::new_chain_ops(&mut config, &cli.eth)?;
+					cmd.run(&DatabaseBackupStorage::new(client))
+				})
+			},
+		},
 		Some(Subcommand::CheckBlock(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|mut config| {
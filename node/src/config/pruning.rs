@@ -7,6 +7,8 @@ use crate::Cli;
 
 /// Anything greater than 900, which is one normal length session, should be enough.
 /// We need to be able to read back state from previous session to retrieve the list of authorities for a session.
+/// The session period can change at runtime, so once the client is running the state pruning is
+/// also checked against the longest session known to the runtime, refusing to start if it is too short.
 const MINIMAL_STATE_PRUNING: u32 = 901;
 const_assert!(MINIMAL_STATE_PRUNING > DEFAULT_SESSION_PERIOD);
 
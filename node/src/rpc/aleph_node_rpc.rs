@@ -1,18 +1,30 @@
 use std::{collections::HashMap, sync::Arc};
 
 use finality_aleph::{
-	AlephJustification, BlockId, Justification, JustificationTranslator, ValidatorAddressCache,
-	ValidatorAddressingInfo,
+	backwards_compatible_decode, AlephJustification, BlockId,
+	BlockchainEvents as ChainStatusEvents, ChainStatusNotification, ChainStatusNotifier,
+	HealthMonitor, HealthStatus, Justification, JustificationTranslator, SessionBoundaryInfo,
+	ValidatorAddressCache, ValidatorAddressingInfo, ValidatorNetworkKey,
 };
-use futures::channel::mpsc;
+use futures::{channel::mpsc, stream, FutureExt, StreamExt};
 use jsonrpsee::{
 	core::{error::Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
-	types::error::{CallError, ErrorObject},
+	types::{
+		error::{CallError, ErrorObject},
+		SubscriptionResult,
+	},
+	SubscriptionSink,
 };
-use parity_scale_codec::Decode;
-use sc_client_api::StorageProvider;
-use selendra_primitives::{AccountId, Block, BlockHash, BlockNumber, Signature};
+use log::warn;
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::{BlockBackend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use sc_rpc_api::DenyUnsafe;
+use selendra_primitives::{
+	AccountId, Block, BlockHash, BlockNumber, Header, Signature, ALEPH_ENGINE_ID,
+};
+use serde::{Deserialize, Serialize};
 use sp_arithmetic::traits::Zero;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::SyncOracle;
@@ -23,6 +35,8 @@ use sp_runtime::{
 	DigestItem,
 };
 
+const LOG_TARGET: &str = "selendra-rpc";
+
 /// System RPC errors.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -56,6 +70,9 @@ pub enum Error {
 	/// Network info caching is not enabled.
 	#[error("Unable to get any data, because network info caching is not enabled.")]
 	NetworkInfoCachingNotEnabled,
+	/// Failed to generate a new validator network key.
+	#[error("Failed to rotate the validator network key: {0}.")]
+	NetworkKeyRotationFailed(String),
 }
 
 // Base code for all system errors.
@@ -80,6 +97,8 @@ const FAILED_HEADER_DECODING_ERROR: i32 = BASE_ERROR + 8;
 const UNKNOWN_HASH_ERROR: i32 = BASE_ERROR + 9;
 /// Network info caching is not enabled.
 const NETWORK_INFO_CACHING_NOT_ENABLED_ERROR: i32 = BASE_ERROR + 10;
+/// Failed to generate a new validator network key.
+const NETWORK_KEY_ROTATION_FAILED_ERROR: i32 = BASE_ERROR + 11;
 
 impl From<Error> for JsonRpseeError {
 	fn from(e: Error) -> Self {
@@ -140,6 +159,11 @@ impl From<Error> for JsonRpseeError {
 				"Unable to get any data, because network info caching is not enabled.",
 				None::<()>,
 			)),
+			Error::NetworkKeyRotationFailed(e) => CallError::Custom(ErrorObject::owned(
+				NETWORK_KEY_ROTATION_FAILED_ERROR,
+				format!("Failed to rotate the validator network key: {e}."),
+				None::<()>,
+			)),
 		}
 		.into()
 	}
@@ -167,6 +191,69 @@ pub trait AlephNodeApi<BE> {
 
 	#[method(name = "unstable_validatorNetworkInfo")]
 	fn validator_network_info(&self) -> RpcResult<HashMap<AccountId, ValidatorAddressingInfo>>;
+
+	/// Get the health of the node derived from the progress of finalization and the connectivity
+	/// of its committees, together with the reasons it is unhealthy, if it is.
+	#[method(name = "health")]
+	fn health(&self) -> RpcResult<HealthStatus>;
+
+	/// Subscribe to justifications of blocks as they get finalized. Blocks finalized together
+	/// with one of their descendants are reported too, from the lowest, if they are justified.
+	#[subscription(
+		name = "subscribeJustifications" => "justifications",
+		unsubscribe = "unsubscribeJustifications",
+		item = JustificationNotification
+	)]
+	fn subscribe_justifications(&self);
+}
+
+/// Validator network key RPC API, next to the other key management methods.
+#[rpc(client, server, namespace = "author")]
+pub trait ValidatorNetworkKeyApi {
+	/// Generate a new key identifying the node in the validator network and return its public
+	/// key. The node keeps using the old key until it restarts.
+	#[method(name = "rotateValidatorNetworkKey")]
+	fn rotate_validator_network_key(&self) -> RpcResult<Bytes>;
+}
+
+/// A finalized block together with the proof of its finality.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JustificationNotification {
+	/// Header of the finalized block.
+	pub header: Header,
+	/// SCALE encoded `AlephJustification` of the block.
+	pub justification: Bytes,
+	/// Session the block belongs to.
+	pub session: u32,
+	/// Bitmap of committee members that signed the block, least significant bit first,
+	/// indexed by their position in the session committee. Empty for emergency finalization.
+	pub signers: Bytes,
+}
+
+impl JustificationNotification {
+	fn new(
+		header: Header,
+		justification: AlephJustification,
+		session_info: &SessionBoundaryInfo,
+	) -> Self {
+		let session = session_info.session_id_from_block_num(*header.number()).0;
+		let signers = justification.signers().map(pack_bitmap).unwrap_or_default();
+		JustificationNotification {
+			header,
+			justification: justification.encode().into(),
+			session,
+			signers: signers.into(),
+		}
+	}
+}
+
+fn pack_bitmap(bits: Vec<bool>) -> Vec<u8> {
+	let mut bytes = vec![0u8; (bits.len() + 7) / 8];
+	for (index, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+		bytes[index / 8] |= 1 << (index % 8);
+	}
+	bytes
 }
 
 /// Aleph Node API implementation
@@ -176,6 +263,9 @@ pub struct AlephNode<Client, SO> {
 	client: Arc<Client>,
 	sync_oracle: SO,
 	validator_address_cache: Option<ValidatorAddressCache>,
+	session_info: SessionBoundaryInfo,
+	health_monitor: HealthMonitor,
+	executor: SubscriptionTaskExecutor,
 }
 
 impl<Client, SO> AlephNode<Client, SO>
@@ -188,6 +278,9 @@ where
 		client: Arc<Client>,
 		sync_oracle: SO,
 		validator_address_cache: Option<ValidatorAddressCache>,
+		session_info: SessionBoundaryInfo,
+		health_monitor: HealthMonitor,
+		executor: SubscriptionTaskExecutor,
 	) -> Self {
 		AlephNode {
 			import_justification_tx,
@@ -195,14 +288,70 @@ where
 			client,
 			sync_oracle,
 			validator_address_cache,
+			session_info,
+			health_monitor,
+			executor,
+		}
+	}
+}
+
+fn read_justification<Client: BlockBackend<Block>>(
+	client: &Client,
+	hash: BlockHash,
+) -> Option<AlephJustification> {
+	let encoded_justification = match client.justifications(hash) {
+		Ok(justifications) => justifications?.into_justification(ALEPH_ENGINE_ID)?,
+		Err(e) => {
+			warn!(target: LOG_TARGET, "Failed to read justifications of block {hash}: {e}");
+			return None;
+		},
+	};
+	match backwards_compatible_decode(encoded_justification) {
+		Ok(justification) => Some(justification),
+		Err(e) => {
+			warn!(target: LOG_TARGET, "Failed to decode justification of block {hash}: {e}");
+			None
+		},
+	}
+}
+
+/// Notifications about the justified blocks finalized together with the given one, starting from
+/// the lowest one above `last_reported`.
+fn newly_justified<Client: HeaderBackend<Block> + BlockBackend<Block>>(
+	client: &Client,
+	mut header: Header,
+	last_reported: BlockNumber,
+	session_info: &SessionBoundaryInfo,
+) -> Vec<JustificationNotification> {
+	let mut notifications = Vec::new();
+	while *header.number() > last_reported {
+		let parent = *header.parent_hash();
+		if let Some(justification) = read_justification(client, header.hash()) {
+			notifications.push(JustificationNotification::new(header, justification, session_info));
 		}
+		header = match client.header(parent) {
+			Ok(Some(header)) => header,
+			Ok(None) => break,
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Failed to read header of finalized block {parent}: {e}");
+				break;
+			},
+		};
 	}
+	notifications.reverse();
+	notifications
 }
 
 impl<Client, BE, SO> AlephNodeApiServer<BE> for AlephNode<Client, SO>
 where
 	BE: sc_client_api::Backend<Block> + 'static,
-	Client: HeaderBackend<Block> + StorageProvider<Block, BE> + 'static,
+	Client: HeaderBackend<Block>
+		+ StorageProvider<Block, BE>
+		+ BlockBackend<Block>
+		+ BlockchainEvents<Block>
+		+ Send
+		+ Sync
+		+ 'static,
 	SO: SyncOracle + Send + Sync + 'static,
 {
 	fn emergency_finalize(
@@ -266,6 +415,74 @@ where
 			.map(|c| c.snapshot())
 			.ok_or(Error::NetworkInfoCachingNotEnabled.into())
 	}
+
+	fn health(&self) -> RpcResult<HealthStatus> {
+		Ok(self.health_monitor.status())
+	}
+
+	fn subscribe_justifications(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		let client = self.client.clone();
+		let session_info = self.session_info.clone();
+		let notifier = ChainStatusEvents::chain_status_notifier(self.client.as_ref());
+		let last_reported = self.client.info().finalized_number;
+		let stream =
+			stream::unfold((notifier, last_reported), move |(mut notifier, last_reported)| {
+				let client = client.clone();
+				let session_info = session_info.clone();
+				async move {
+					loop {
+						match notifier.next().await {
+							Ok(ChainStatusNotification::BlockFinalized(header)) => {
+								let number = *header.number();
+								let notifications = newly_justified(
+									client.as_ref(),
+									header,
+									last_reported,
+									&session_info,
+								);
+								return Some((
+									stream::iter(notifications),
+									(notifier, number.max(last_reported)),
+								));
+							},
+							Ok(ChainStatusNotification::BlockImported(_)) => {},
+							Err(e) => {
+								warn!(target: LOG_TARGET, "Justification subscription stopped: {e}");
+								return None;
+							},
+						}
+					}
+				}
+			})
+			.flatten();
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("selendra-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+/// Validator network key API implementation
+pub struct ValidatorNetworkKeyRotation {
+	key: ValidatorNetworkKey,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl ValidatorNetworkKeyRotation {
+	pub fn new(key: ValidatorNetworkKey, deny_unsafe: DenyUnsafe) -> Self {
+		ValidatorNetworkKeyRotation { key, deny_unsafe }
+	}
+}
+
+impl ValidatorNetworkKeyApiServer for ValidatorNetworkKeyRotation {
+	fn rotate_validator_network_key(&self) -> RpcResult<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+		let key = self.key.rotate().map_err(|e| Error::NetworkKeyRotationFailed(e.to_string()))?;
+		Ok(key.encode().into())
+	}
 }
 
 fn read_storage<
@@ -301,3 +518,18 @@ fn read_storage<
 		Error::FailedStorageDecoding(pallet, pallet_item, block_hash.to_string(), e).into()
 	})
 }
+
+#[cfg(test)]
+mod tests {
+	use super::pack_bitmap;
+
+	#[test]
+	fn packs_bitmap_least_significant_bit_first() {
+		assert_eq!(pack_bitmap(vec![]), Vec::<u8>::new());
+		assert_eq!(pack_bitmap(vec![true, false, true]), vec![0b101]);
+		let mut bits = vec![false; 10];
+		bits[0] = true;
+		bits[9] = true;
+		assert_eq!(pack_bitmap(bits), vec![0b1, 0b10]);
+	}
+}
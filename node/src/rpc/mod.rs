@@ -9,7 +9,7 @@ use jsonrpsee::RpcModule;
 use sc_client_api::{
 	backend::{Backend, StorageProvider},
 	client::BlockchainEvents,
-	AuxStore, UsageProvider,
+	AuxStore, BlockBackend, UsageProvider,
 };
 use sc_rpc::SubscriptionTaskExecutor;
 use sc_rpc_api::DenyUnsafe;
@@ -23,7 +23,10 @@ use sp_inherents::CreateInherentDataProviders;
 use sp_runtime::traits::Block as BlockT;
 
 // Aleph
-use finality_aleph::{Justification, JustificationTranslator, ValidatorAddressCache};
+use finality_aleph::{
+	HealthMonitor, Justification, JustificationTranslator, SessionBoundaryInfo,
+	ValidatorAddressCache, ValidatorNetworkKey,
+};
 
 // Runtime
 use selendra_primitives::{AccountId, Balance, Block, Nonce};
@@ -31,7 +34,10 @@ use selendra_primitives::{AccountId, Balance, Block, Nonce};
 pub mod aleph_node_rpc;
 mod eth;
 pub use self::eth::{create_eth, overrides_handle, EthDeps};
-pub use aleph_node_rpc::{AlephNode, AlephNodeApiServer};
+pub use aleph_node_rpc::{
+	AlephNode, AlephNodeApiServer, JustificationNotification, ValidatorNetworkKeyApiServer,
+	ValidatorNetworkKeyRotation,
+};
 
 /// Full client dependencies.
 pub struct FullDeps<C, P, A: ChainApi, CT, CIDP, SO> {
@@ -49,6 +55,13 @@ pub struct FullDeps<C, P, A: ChainApi, CT, CIDP, SO> {
 	pub sync_oracle: SO,
 	/// validator address cache
 	pub validator_address_cache: Option<ValidatorAddressCache>,
+	/// session boundaries used to assign finalized blocks to sessions, following changes of the
+	/// session period
+	pub session_info: SessionBoundaryInfo,
+	/// key identifying the node in the validator network
+	pub validator_network_key: ValidatorNetworkKey,
+	/// health of the node reported by finality-aleph
+	pub health_monitor: HealthMonitor,
 	/// Ethereum-compatibility specific dependencies.
 	pub eth: EthDeps<Block, C, P, A, CT, CIDP>,
 }
@@ -81,6 +94,7 @@ where
 		+ HeaderBackend<Block>
 		+ HeaderMetadata<Block, Error = BlockChainError>
 		+ BlockchainEvents<Block>
+		+ BlockBackend<Block>
 		+ UsageProvider<Block>
 		+ StorageProvider<Block, BE>
 		+ AuxStore
@@ -112,6 +126,9 @@ where
 		justification_translator,
 		sync_oracle,
 		validator_address_cache,
+		session_info,
+		validator_network_key,
+		health_monitor,
 		eth,
 	} = deps;
 
@@ -125,10 +142,15 @@ where
 			client,
 			sync_oracle,
 			validator_address_cache,
+			session_info,
+			health_monitor,
+			subscription_task_executor.clone(),
 		)
 		.into_rpc(),
 	)?;
 
+	io.merge(ValidatorNetworkKeyRotation::new(validator_network_key, deny_unsafe).into_rpc())?;
+
 	// Ethereum compatibility RPCs
 	let io = create_eth::<_, _, _, _, _, _, _, DefaultEthConfig<C, BE>>(
 		io,
@@ -1,17 +1,16 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 #![allow(missing_docs)]
 
-use std::{
-	path::{Path, PathBuf},
-	sync::Arc,
-};
+use std::{path::Path, sync::Arc};
 
 use client_runtime_api::mock_runtime::RuntimeApi;
 use finality_aleph::{
-	build_network, get_aleph_block_import, run_validator_node, AlephConfig, BlockImporter,
-	BuildNetworkOutput, ChannelProvider, FavouriteSelectChainProvider, Justification,
-	JustificationTranslator, MillisecsPerBlock, RateLimiterConfig, RedirectingBlockImport,
-	SessionPeriod, SubstrateChainStatus, SyncOracle, ValidatorAddressCache,
+	build_network, get_aleph_block_import, run_validator_node, AlephConfig, BackupStorage,
+	BlockImporter, BlockTracer, BuildNetworkOutput, ChannelProvider, DatabaseBackupStorage,
+	FavouriteSelectChainProvider, FsBackupStorage, HealthMonitor, HealthThresholds, Justification,
+	JustificationTranslator, MillisecsPerBlock, OtlpExporter, RateLimiterConfig,
+	RedirectingBlockImport, SessionBoundaryInfo, SessionPeriod, SubstrateChainStatus, SyncConfig,
+	SyncOracle, ValidatorAddressCache, ValidatorNetworkKey,
 };
 use log::warn;
 use pallet_aleph_runtime_api::AlephSessionApi;
@@ -22,7 +21,8 @@ use sc_consensus::{ImportQueue, Link};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_slots::BackoffAuthoringBlocksStrategy;
 use sc_service::{
-	error::Error as ServiceError, Configuration, KeystoreContainer, TFullClient, TaskManager,
+	config::PruningMode, error::Error as ServiceError, Configuration, KeystoreContainer,
+	TFullClient, TaskManager,
 };
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use selendra_primitives::{Block, DEFAULT_BACKUP_FOLDER, MAXIMUM_BLOCK_LENGTH as MAX_BLOCK_SIZE};
@@ -33,7 +33,7 @@ use sp_consensus_aura::{sr25519::AuthorityPair as AuraPair, Slot};
 use sp_core::U256;
 
 use crate::{
-	cli::aleph_cli::AlephCli,
+	cli::aleph_cli::{AlephCli, BackupStorageKind},
 	eth::{
 		db_config_dir, new_frontier_partial, spawn_frontier_tasks, BackendType, EthConfiguration,
 		FrontierBackend, FrontierBlockImport, FrontierPartialComponents,
@@ -88,19 +88,55 @@ impl<N: BaseArithmetic> BackoffAuthoringBlocksStrategy<N> for LimitNonfinalized
 	}
 }
 
-fn backup_path(aleph_config: &AlephCli, base_path: &Path) -> Option<PathBuf> {
+/// File in the base path where the block sync keeps the reputation of its peers.
+const SYNC_REPUTATION_FILE: &str = "sync-reputation";
+
+/// File in the base path remembering which keystore key identifies the node in the validator
+/// network.
+const VALIDATOR_NETWORK_KEY_FILE: &str = "validator-network-key";
+
+fn backup_storage(
+	aleph_config: &AlephCli,
+	base_path: &Path,
+	client: Arc<FullClient>,
+) -> Option<Arc<dyn BackupStorage>> {
 	if aleph_config.no_backup() {
 		return None;
 	}
-	if let Some(path) = aleph_config.backup_path() {
-		Some(path)
-	} else {
-		let path = base_path.join(DEFAULT_BACKUP_FOLDER);
-		eprintln!("No backup path provided, using default path: {path:?} for AlephBFT backups. Please do not remove this folder");
-		Some(path)
+	match aleph_config.backup_storage() {
+		BackupStorageKind::Database => {
+			if aleph_config.backup_path().is_some() {
+				warn!("Keeping AlephBFT backups in the database, the provided backup path is ignored.");
+			}
+			Some(Arc::new(DatabaseBackupStorage::new(client)))
+		},
+		BackupStorageKind::Filesystem => {
+			let path = if let Some(path) = aleph_config.backup_path() {
+				path
+			} else {
+				let path = base_path.join(DEFAULT_BACKUP_FOLDER);
+				eprintln!("No backup path provided, using default path: {path:?} for AlephBFT backups. Please do not remove this folder");
+				path
+			};
+			Some(Arc::new(FsBackupStorage::new(path)))
+		},
 	}
 }
 
+fn block_tracer(
+	aleph_config: &AlephCli,
+	task_manager: &TaskManager,
+) -> Result<BlockTracer, ServiceError> {
+	let endpoint = match aleph_config.otlp_endpoint() {
+		Some(endpoint) => endpoint,
+		None => return Ok(BlockTracer::noop()),
+	};
+	let (exporter, sender) = OtlpExporter::new(&endpoint)
+		.map_err(|e| ServiceError::Other(format!("invalid OTLP endpoint: {e}")))?;
+	task_manager.spawn_handle().spawn("aleph-otlp-exporter", None, sender);
+	Ok(BlockTracer::new(Arc::new(exporter)))
+}
+
 pub fn new_partial(
 	config: &Configuration,
 	eth_config: &EthConfiguration,
@@ -232,7 +268,7 @@ pub fn new_partial(
 }
 
 struct AlephRuntimeVars {
-	pub session_period: SessionPeriod,
+	pub session_info: SessionBoundaryInfo,
 	pub millisecs_per_block: MillisecsPerBlock,
 }
 
@@ -245,6 +281,11 @@ fn get_aleph_runtime_vars(client: &Arc<FullClient>) -> AlephRuntimeVars {
 			.session_period(finalized)
 			.expect("should always be available"),
 	);
+	// Runtimes that do not support session period changes only expose the current period.
+	let session_info = match client.runtime_api().session_period_history(finalized) {
+		Ok(history) if history.is_valid() => SessionBoundaryInfo::with_history(history),
+		_ => SessionBoundaryInfo::new(session_period),
+	};
 
 	let millisecs_per_block = MillisecsPerBlock(
 		client
@@ -253,7 +294,29 @@ fn get_aleph_runtime_vars(client: &Arc<FullClient>) -> AlephRuntimeVars {
 			.expect("should always be available"),
 	);
 
-	AlephRuntimeVars { session_period, millisecs_per_block }
+	AlephRuntimeVars { session_info, millisecs_per_block }
+}
+
+/// The session period can change at runtime, so the pruning validated against the default
+/// session period when parsing the command line might not be enough to read the authorities of
+/// every session. Checks it against the longest session known to the runtime.
+fn check_state_pruning(
+	config: &Configuration,
+	session_info: &SessionBoundaryInfo,
+) -> Result<(), ServiceError> {
+	let session_period = session_info.longest_session_period();
+	if let Some(PruningMode::Constrained(constraints)) = &config.state_pruning {
+		match constraints.max_blocks {
+			Some(max_blocks) if max_blocks <= session_period.0 => {
+				return Err(ServiceError::Other(format!(
+					"State pruning keeps {} blocks, which is not more than the session period of {} blocks, so session authorities could not be read. Increase `--state-pruning`.",
+					max_blocks, session_period.0
+				)))
+			},
+			_ => {},
+		}
+	}
+	Ok(())
 }
 
 fn get_validator_address_cache(aleph_config: &AlephCli) -> Option<ValidatorAddressCache> {
@@ -284,9 +347,45 @@ fn get_rate_limit_config(aleph_config: &AlephCli) -> RateLimiterConfig {
 			.alephbft_bit_rate_per_connection()
 			.try_into()
 			.unwrap_or(usize::MAX),
+		alephbft_aggregate_bit_rate: aleph_config
+			.alephbft_aggregate_bit_rate()
+			.map(|rate| rate.try_into().unwrap_or(usize::MAX)),
 	}
 }
 
+fn get_sync_config(
+	aleph_config: &AlephCli,
+	session_info: &SessionBoundaryInfo,
+) -> Result<SyncConfig, ServiceError> {
+	let mut sync_config = SyncConfig::for_session_info(session_info);
+	if let Some(max_depth) = aleph_config.sync_forest_max_depth() {
+		sync_config.forest_max_depth = max_depth;
+	}
+	if let Some(size) = aleph_config.sync_verifier_cache_size() {
+		sync_config.verifier_cache_size = size;
+	}
+	let data_store = &mut sync_config.data_store;
+	if let Some(limit) = aleph_config.data_store_max_triggers_pending() {
+		data_store.max_triggers_pending = limit;
+	}
+	if let Some(limit) = aleph_config.data_store_max_proposals_pending() {
+		data_store.max_proposals_pending = limit;
+	}
+	if let Some(limit) = aleph_config.data_store_max_messages_pending() {
+		data_store.max_messages_pending = limit;
+	}
+	if let Some(capacity) = aleph_config.data_store_available_proposals_cache_capacity() {
+		data_store.available_proposals_cache_capacity = capacity;
+	}
+	if let Some(capacity) = aleph_config.data_store_block_cache_capacity() {
+		data_store.chain_info_cache.block_cache_capacity = capacity;
+	}
+	sync_config
+		.validate(session_info)
+		.map_err(|e| ServiceError::Other(format!("invalid sync config: {e}")))?;
+	Ok(sync_config)
+}
+
 struct NoopLink;
 
 impl Link<Block> for NoopLink {}
@@ -303,13 +402,27 @@ pub async fn new_authority(
 
 	let mut service_components = new_partial(&config, &eth_config)?;
 
-	let backup_path = backup_path(&aleph_config, config.base_path.path());
+	let backup_storage =
+		backup_storage(&aleph_config, config.base_path.path(), service_components.client.clone());
+	let sync_reputation_path = config.base_path.path().join(SYNC_REPUTATION_FILE);
+	let validator_network_key = ValidatorNetworkKey::new(
+		service_components.keystore_container.local_keystore(),
+		Some(config.base_path.path().join(VALIDATOR_NETWORK_KEY_FILE)),
+	);
 
 	let backoff_authoring_blocks = Some(LimitNonfinalized(aleph_config.max_nonfinalized_blocks()));
 	let prometheus_registry = config.prometheus_registry().cloned();
+	let chain_status = SubstrateChainStatus::new(service_components.backend.clone())
+		.map_err(|e| ServiceError::Other(format!("failed to set up chain status: {e}")))?;
+	let health_monitor = HealthMonitor::new(
+		prometheus_registry.as_ref(),
+		HealthThresholds::default(),
+		&chain_status,
+	);
 	let (sync_oracle, major_sync) = SyncOracle::new();
 	let proposer_factory = get_proposer_factory(&service_components, &config);
 	let slot_duration = sc_consensus_aura::slot_duration(&*service_components.client)?;
+	let block_tracer = block_tracer(&aleph_config, &service_components.task_manager)?;
 	let (block_import, block_rx) = RedirectingBlockImport::new(service_components.client.clone());
 
 	let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _>(
@@ -376,16 +489,23 @@ pub async fn new_authority(
 	// for ethereum-compatibility rpc.
 	config.rpc_id_provider = Some(Box::new(fc_rpc::EthereumSubIdProvider));
 
-	let chain_status = SubstrateChainStatus::new(service_components.backend.clone())
-		.map_err(|e| ServiceError::Other(format!("failed to set up chain status: {e}")))?;
 	let validator_address_cache = get_validator_address_cache(&aleph_config);
 	let role = config.role.clone();
 
+	// The session info is shared between the RPC and finality-aleph, which keeps it up to date
+	// with changes of the session period.
+	let AlephRuntimeVars { millisecs_per_block, session_info } =
+		get_aleph_runtime_vars(&service_components.client);
+	check_state_pruning(&config, &session_info)?;
+
 	let rpc_builder = {
 		let client = service_components.client.clone();
 		let pool = service_components.transaction_pool.clone();
 		let sync_oracle = sync_oracle.clone();
 		let validator_address_cache = validator_address_cache.clone();
+		let validator_network_key = validator_network_key.clone();
+		let health_monitor = health_monitor.clone();
+		let session_info = session_info.clone();
 		let import_justification_tx =
 			service_components.justification_channel_provider.get_sender();
 		let chain_status = chain_status.clone();
@@ -452,6 +572,9 @@ pub async fn new_authority(
 				justification_translator: JustificationTranslator::new(chain_status.clone()),
 				sync_oracle: sync_oracle.clone(),
 				validator_address_cache: validator_address_cache.clone(),
+				session_info: session_info.clone(),
+				validator_network_key: validator_network_key.clone(),
+				health_monitor: health_monitor.clone(),
 				eth: eth_deps,
 			};
 
@@ -504,9 +627,7 @@ pub async fn new_authority(
 		.spawn_blocking("aura", None, aura);
 
 	let rate_limiter_config = get_rate_limit_config(&aleph_config);
-
-	let AlephRuntimeVars { millisecs_per_block, session_period } =
-		get_aleph_runtime_vars(&service_components.client);
+	let sync_config = get_sync_config(&aleph_config, &session_info)?;
 
 	let aleph_config = AlephConfig {
 		authentication_network,
@@ -515,7 +636,7 @@ pub async fn new_authority(
 		chain_status,
 		import_queue_handle,
 		select_chain_provider: service_components.select_chain_provider,
-		session_period,
+		session_info,
 		millisecs_per_block,
 		spawn_handle: service_components.task_manager.spawn_handle().into(),
 		keystore: service_components.keystore_container.local_keystore(),
@@ -523,13 +644,19 @@ pub async fn new_authority(
 		block_rx,
 		registry: prometheus_registry,
 		unit_creation_delay: aleph_config.unit_creation_delay(),
-		backup_saving_path: backup_path,
+		backup_storage,
+		backup_retained_sessions: aleph_config.backup_retained_sessions(),
 		external_addresses: aleph_config.external_addresses(),
 		validator_port: aleph_config.validator_port(),
 		rate_limiter_config,
 		sync_oracle,
 		validator_address_cache,
 		transaction_pool: service_components.transaction_pool,
+		sync_reputation_path: Some(sync_reputation_path),
+		sync_config,
+		validator_network_key,
+		health_monitor,
+		block_tracer,
 	};
 
 	service_components.task_manager.spawn_essential_handle().spawn_blocking(
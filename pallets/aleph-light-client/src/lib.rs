@@ -0,0 +1,272 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![doc = include_str!("../README.md")]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+mod weights;
+
+use aleph_light_client::{backwards_compatible_decode, session_handover, SessionVerifier};
+pub use pallet::*;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use selendra_primitives::{BlockHash, BlockNumber, Header, SessionAuthorityData, SessionIndex};
+use sp_runtime::traits::Header as HeaderT;
+use sp_std::prelude::*;
+pub use weights::WeightInfo;
+
+pub(crate) const LOG_TARGET: &str = "pallet-aleph-light-client";
+
+/// Data kept about every imported header of the tracked chain.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ImportedHeader {
+	/// Number of the header.
+	pub number: BlockNumber,
+	/// State root of the header, against which storage proofs can be checked.
+	pub state_root: BlockHash,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::{ensure_root, ensure_signed, pallet_prelude::OriginFor};
+
+	use super::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type RuntimeEvent: From<Event> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Number of the most recently imported headers whose state roots are kept in storage.
+		#[pallet::constant]
+		type HeadersToKeep: Get<u32>;
+		/// Upper bound on the size of the tracked chain's committee. Larger committees are
+		/// rejected, so that the weight of verifying a justification is bounded.
+		#[pallet::constant]
+		type MaxAuthorities: Get<u32>;
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub (super) fn deposit_event)]
+	pub enum Event {
+		/// Tracking was (re)started from a trusted header.
+		Initialized { number: BlockNumber, hash: BlockHash },
+		/// A header was accepted as finalized on the tracked chain.
+		HeaderImported { number: BlockNumber, hash: BlockHash },
+		/// Authorities of the given session of the tracked chain are now in use.
+		AuthoritiesChanged { session: SessionIndex },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The pallet has not been initialized with a trusted header yet.
+		NotInitialized,
+		/// Session period of the tracked chain cannot be zero.
+		InvalidSessionPeriod,
+		/// The header is neither in the given session nor the last block before it.
+		InvalidSessionStart,
+		/// The committee is larger than `MaxAuthorities`.
+		TooManyAuthorities,
+		/// The header is not newer than the best finalized header.
+		OldHeader,
+		/// The header belongs to a session whose authorities are not known.
+		UnknownSession,
+		/// The justification could not be decoded.
+		MalformedJustification,
+		/// The justification is not valid for the header.
+		InvalidJustification,
+		/// The last block of a session requires a proof of the next session's authorities.
+		MissingHandoverProof,
+		/// The proof of the next session's authorities is invalid.
+		InvalidHandoverProof,
+	}
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	/// Number of blocks in `CurrentSession`. The session period of the tracked chain might
+	/// change, so it is updated on every handover.
+	#[pallet::storage]
+	#[pallet::getter(fn session_period)]
+	pub type SessionPeriod<T: Config> = StorageValue<_, BlockNumber, OptionQuery>;
+
+	/// Number of the first block of `CurrentSession`.
+	#[pallet::storage]
+	#[pallet::getter(fn current_session_start)]
+	pub type CurrentSessionStart<T: Config> = StorageValue<_, BlockNumber, ValueQuery>;
+
+	/// The session whose authorities currently finalize blocks of the tracked chain.
+	#[pallet::storage]
+	#[pallet::getter(fn current_session)]
+	pub type CurrentSession<T: Config> = StorageValue<_, SessionIndex, ValueQuery>;
+
+	/// Authority data of `CurrentSession`.
+	#[pallet::storage]
+	#[pallet::getter(fn current_authority_data)]
+	pub type CurrentAuthorityData<T: Config> = StorageValue<_, SessionAuthorityData, OptionQuery>;
+
+	/// Number and hash of the best finalized header of the tracked chain.
+	#[pallet::storage]
+	#[pallet::getter(fn best_finalized)]
+	pub type BestFinalized<T: Config> = StorageValue<_, (BlockNumber, BlockHash), OptionQuery>;
+
+	/// Recently imported headers of the tracked chain.
+	#[pallet::storage]
+	#[pallet::getter(fn imported_header)]
+	pub type ImportedHeaders<T: Config> =
+		StorageMap<_, Identity, BlockHash, ImportedHeader, OptionQuery>;
+
+	/// Ring buffer of hashes in `ImportedHeaders`, used for pruning.
+	#[pallet::storage]
+	pub type ImportedHashes<T: Config> = StorageMap<_, Identity, u32, BlockHash, OptionQuery>;
+
+	/// Position in `ImportedHashes` to be overwritten next.
+	#[pallet::storage]
+	pub type ImportedHashesPointer<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	impl<T: Config> Pallet<T> {
+		fn ensure_committee_size(authority_data: &SessionAuthorityData) -> DispatchResult {
+			ensure!(
+				authority_data.authorities().len() <= T::MaxAuthorities::get() as usize,
+				Error::<T>::TooManyAuthorities
+			);
+			Ok(())
+		}
+
+		fn import_header(header: &Header) {
+			let hash = header.hash();
+			let number = *header.number();
+			let pointer = ImportedHashesPointer::<T>::get();
+			if let Some(pruned) = ImportedHashes::<T>::get(pointer) {
+				ImportedHeaders::<T>::remove(pruned);
+			}
+			ImportedHashes::<T>::insert(pointer, hash);
+			ImportedHashesPointer::<T>::put((pointer + 1) % T::HeadersToKeep::get().max(1));
+			ImportedHeaders::<T>::insert(
+				hash,
+				ImportedHeader { number, state_root: *header.state_root() },
+			);
+			BestFinalized::<T>::put((number, hash));
+		}
+
+		fn set_session(
+			session: SessionIndex,
+			first_block: BlockNumber,
+			session_period: BlockNumber,
+			authority_data: SessionAuthorityData,
+		) {
+			CurrentSession::<T>::put(session);
+			CurrentSessionStart::<T>::put(first_block);
+			SessionPeriod::<T>::put(session_period);
+			CurrentAuthorityData::<T>::put(authority_data);
+			Self::deposit_event(Event::AuthoritiesChanged { session });
+		}
+
+		pub(crate) fn do_submit_finality_proof(
+			header: Header,
+			justification: Vec<u8>,
+			handover_proof: Option<Vec<Vec<u8>>>,
+		) -> DispatchResult {
+			let session_period = SessionPeriod::<T>::get().ok_or(Error::<T>::NotInitialized)?;
+			let authority_data =
+				CurrentAuthorityData::<T>::get().ok_or(Error::<T>::NotInitialized)?;
+			let (best_number, _) = BestFinalized::<T>::get().ok_or(Error::<T>::NotInitialized)?;
+
+			let number = *header.number();
+			ensure!(number > best_number, Error::<T>::OldHeader);
+			let first_block = CurrentSessionStart::<T>::get();
+			let last_block = first_block.saturating_add(session_period - 1);
+			ensure!((first_block..=last_block).contains(&number), Error::<T>::UnknownSession);
+			let session = CurrentSession::<T>::get();
+
+			let justification = backwards_compatible_decode(&justification)
+				.map_err(|_| Error::<T>::MalformedJustification)?;
+			let hash = header.hash();
+			SessionVerifier::from(authority_data)
+				.verify_justification(&justification, hash)
+				.map_err(|e| {
+					log::debug!(target: LOG_TARGET, "Rejected justification for {hash:?}: {e}");
+					Error::<T>::InvalidJustification
+				})?;
+
+			let handover = match number == last_block {
+				true => {
+					let proof = handover_proof.ok_or(Error::<T>::MissingHandoverProof)?;
+					let handover = session_handover(*header.state_root(), proof).map_err(|e| {
+						log::debug!(target: LOG_TARGET, "Rejected handover proof for {hash:?}: {e}");
+						Error::<T>::InvalidHandoverProof
+					})?;
+					Self::ensure_committee_size(&handover.authority_data)?;
+					Some(handover)
+				},
+				false => None,
+			};
+
+			Self::import_header(&header);
+			Self::deposit_event(Event::HeaderImported { number, hash });
+			if let Some(handover) = handover {
+				let next_session_period = handover.next_session_period(session, session_period);
+				Self::set_session(
+					session + 1,
+					last_block + 1,
+					next_session_period,
+					handover.authority_data,
+				);
+			}
+			Ok(())
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Starts tracking the chain from a trusted header, discarding previous tracking state
+		/// apart from the pruning buffer. `authority_data`, `first_block` and `session_period`
+		/// describe the session with index `session`, which has to be the header's session or,
+		/// if the header is the last block of its session, the next one.
+		#[pallet::call_index(0)]
+		#[pallet::weight((T::WeightInfo::initialize(), DispatchClass::Operational))]
+		pub fn initialize(
+			origin: OriginFor<T>,
+			header: Box<Header>,
+			authority_data: SessionAuthorityData,
+			session: SessionIndex,
+			first_block: BlockNumber,
+			session_period: BlockNumber,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(session_period > 0, Error::<T>::InvalidSessionPeriod);
+			Self::ensure_committee_size(&authority_data)?;
+
+			let number = *header.number();
+			let hash = header.hash();
+			ensure!(
+				number.saturating_add(1) >= first_block
+					&& number < first_block.saturating_add(session_period),
+				Error::<T>::InvalidSessionStart
+			);
+
+			Self::import_header(&header);
+			Self::deposit_event(Event::Initialized { number, hash });
+			Self::set_session(session, first_block, session_period, authority_data);
+			Ok(())
+		}
+
+		/// Imports a header of the tracked chain finalized by the given justification. For the
+		/// last block of a session `handover_proof` has to contain a storage proof of the next
+		/// session's authority data against the header's state root.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::submit_finality_proof(T::MaxAuthorities::get()))]
+		pub fn submit_finality_proof(
+			origin: OriginFor<T>,
+			header: Box<Header>,
+			justification: Vec<u8>,
+			handover_proof: Option<Vec<Vec<u8>>>,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::do_submit_finality_proof(*header, justification, handover_proof)
+		}
+	}
+}
@@ -0,0 +1,168 @@
+#![cfg(test)]
+
+use frame_support::{construct_runtime, parameter_types, weights::Weight};
+use parity_scale_codec::Encode;
+use selendra_primitives::{
+	AuthorityId, AuthorityPair, BlockHash, BlockNumber, Header, SessionAuthorityData,
+	SessionPeriodChange,
+};
+use sp_core::{hashing::twox_128, Pair, H256};
+use sp_runtime::{
+	traits::{BlakeTwo256, Header as HeaderT, IdentityLookup},
+	BuildStorage, Digest, StateVersion,
+};
+use sp_state_machine::{prove_read, InMemoryBackend};
+
+use super::*;
+use crate as pallet_aleph_light_client;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+pub(crate) type AccountId = u64;
+
+pub(crate) const SESSION_PERIOD: BlockNumber = 5;
+
+construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		AlephLightClient: pallet_aleph_light_client,
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub BlockWeights: frame_system::limits::BlockWeights =
+		frame_system::limits::BlockWeights::simple_max(Weight::from_parts(1024, 0));
+	pub const HeadersToKeep: u32 = 3;
+	pub const MaxAuthorities: u32 = 10;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type RuntimeTask = RuntimeTask;
+	type Nonce = u64;
+	type Block = Block;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = BlockHashCount;
+	type DbWeight = ();
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type HeadersToKeep = HeadersToKeep;
+	type MaxAuthorities = MaxAuthorities;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = <frame_system::GenesisConfig<Test> as BuildStorage>::build_storage(
+		&frame_system::GenesisConfig::default(),
+	)
+	.expect("Storage should be build.");
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Committee of the tracked chain in a single session.
+pub struct Committee {
+	pub pairs: Vec<AuthorityPair>,
+}
+
+impl Committee {
+	pub fn new(seed: u8, size: u8) -> Self {
+		Committee {
+			pairs: (0..size)
+				.map(|i| AuthorityPair::from_seed(&[seed.wrapping_add(i); 32]))
+				.collect(),
+		}
+	}
+
+	pub fn authorities(&self) -> Vec<AuthorityId> {
+		self.pairs.iter().map(|pair| pair.public()).collect()
+	}
+
+	pub fn authority_data(&self) -> SessionAuthorityData {
+		SessionAuthorityData::new(self.authorities(), None)
+	}
+
+	/// Justification signed by the given members of the committee.
+	pub fn justify(&self, header: &Header, signers: &[usize]) -> Vec<u8> {
+		let message = header.hash().encode();
+		let signatures = signers
+			.iter()
+			.fold(aleph_light_client::SignatureSet::with_size(self.pairs.len()), |set, index| {
+				set.add_signature(&self.pairs[*index].sign(&message), *index)
+			});
+		aleph_light_client::versioned_encode(
+			&aleph_light_client::AlephJustification::CommitteeMultisignature(signatures),
+		)
+	}
+
+	/// Justification signed by the whole committee.
+	pub fn justify_all(&self, header: &Header) -> Vec<u8> {
+		let signers: Vec<_> = (0..self.pairs.len()).collect();
+		self.justify(header, &signers)
+	}
+}
+
+fn aleph_storage_key(item: &[u8]) -> Vec<u8> {
+	[twox_128(b"Aleph"), twox_128(item)].concat()
+}
+
+/// State of the tracked chain at the end of a session, committing to the next committee.
+pub fn handover_state(next: &Committee) -> (BlockHash, Vec<Vec<u8>>) {
+	handover_state_with_period_change(next, None)
+}
+
+/// State of the tracked chain at the end of a session, committing to the next committee and
+/// possibly to a scheduled change of the session period.
+pub fn handover_state_with_period_change(
+	next: &Committee,
+	period_change: Option<SessionPeriodChange>,
+) -> (BlockHash, Vec<Vec<u8>>) {
+	let authorities_key = aleph_storage_key(b"NextAuthorities");
+	let period_change_key = aleph_storage_key(b"SessionPeriodScheduledChange");
+	let mut storage = vec![(authorities_key.clone(), Some(next.authorities().encode()))];
+	if let Some(period_change) = period_change {
+		storage.push((period_change_key.clone(), Some(period_change.encode())));
+	}
+	let backend = InMemoryBackend::<BlakeTwo256>::from((vec![(None, storage)], StateVersion::V1));
+	let root = *backend.root();
+	let keys = [authorities_key, aleph_storage_key(b"QueuedEmergencyFinalizer"), period_change_key];
+	let proof = prove_read(backend, &keys).expect("proving should work");
+	(root, proof.into_iter_nodes().collect())
+}
+
+pub fn header(number: BlockNumber, parent_hash: BlockHash, state_root: BlockHash) -> Header {
+	Header::new(number, BlockHash::zero(), state_root, parent_hash, Digest::default())
+}
+
+/// Headers following `parent`, up to and including `last`, with default state roots.
+pub fn headers_after(parent: &Header, last: BlockNumber) -> Vec<Header> {
+	let mut parent_hash = parent.hash();
+	(*parent.number() + 1..=last)
+		.map(|number| {
+			let header = header(number, parent_hash, BlockHash::zero());
+			parent_hash = header.hash();
+			header
+		})
+		.collect()
+}
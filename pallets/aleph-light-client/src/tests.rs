@@ -0,0 +1,339 @@
+#![cfg(test)]
+
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use frame_system::RawOrigin;
+use selendra_primitives::{BlockHash, SessionPeriodChange};
+use sp_runtime::{traits::Header as HeaderT, DispatchError};
+
+use crate::{
+	mock::*, BestFinalized, CurrentSession, CurrentSessionStart, Error, ImportedHeaders,
+	SessionPeriod,
+};
+
+fn initialize(committee: &Committee) -> selendra_primitives::Header {
+	let genesis = header(0, BlockHash::zero(), BlockHash::zero());
+	assert_ok!(AlephLightClient::initialize(
+		RawOrigin::Root.into(),
+		Box::new(genesis.clone()),
+		committee.authority_data(),
+		0,
+		0,
+		SESSION_PERIOD,
+	));
+	genesis
+}
+
+fn submit(
+	header: &selendra_primitives::Header,
+	justification: Vec<u8>,
+	handover_proof: Option<Vec<Vec<u8>>>,
+) -> frame_support::dispatch::DispatchResult {
+	AlephLightClient::submit_finality_proof(
+		RawOrigin::Signed(1).into(),
+		Box::new(header.clone()),
+		justification,
+		handover_proof,
+	)
+}
+
+#[test]
+fn only_root_can_initialize() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = header(0, BlockHash::zero(), BlockHash::zero());
+		assert_noop!(
+			AlephLightClient::initialize(
+				RawOrigin::Signed(1).into(),
+				Box::new(genesis),
+				committee.authority_data(),
+				0,
+				0,
+				SESSION_PERIOD,
+			),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn rejects_proofs_before_initialization() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = header(0, BlockHash::zero(), BlockHash::zero());
+		let block = headers_after(&genesis, 1).remove(0);
+		assert_noop!(
+			submit(&block, committee.justify_all(&block), None),
+			Error::<Test>::NotInitialized
+		);
+	});
+}
+
+#[test]
+fn imports_justified_header() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let block = headers_after(&genesis, 2).pop().unwrap();
+
+		assert_ok!(submit(&block, committee.justify(&block, &[0, 2, 3]), None));
+
+		assert_eq!(BestFinalized::<Test>::get(), Some((2, block.hash())));
+		assert_eq!(ImportedHeaders::<Test>::get(block.hash()).map(|header| header.number), Some(2));
+	});
+}
+
+#[test]
+fn rejects_insufficient_justification() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let block = headers_after(&genesis, 2).pop().unwrap();
+
+		assert_noop!(
+			submit(&block, committee.justify(&block, &[0, 2]), None),
+			Error::<Test>::InvalidJustification
+		);
+	});
+}
+
+#[test]
+fn rejects_justification_by_other_committee() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let block = headers_after(&genesis, 2).pop().unwrap();
+
+		assert_noop!(
+			submit(&block, Committee::new(100, 4).justify_all(&block), None),
+			Error::<Test>::InvalidJustification
+		);
+	});
+}
+
+#[test]
+fn rejects_malformed_justification() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let block = headers_after(&genesis, 2).pop().unwrap();
+
+		assert_noop!(submit(&block, vec![1, 2, 3], None), Error::<Test>::MalformedJustification);
+	});
+}
+
+#[test]
+fn rejects_old_header() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let mut blocks = headers_after(&genesis, 3);
+		let newer = blocks.pop().unwrap();
+		let older = blocks.pop().unwrap();
+
+		assert_ok!(submit(&newer, committee.justify_all(&newer), None));
+		assert_noop!(submit(&older, committee.justify_all(&older), None), Error::<Test>::OldHeader);
+	});
+}
+
+#[test]
+fn requires_handover_proof_at_session_end() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let last = headers_after(&genesis, SESSION_PERIOD - 1).pop().unwrap();
+
+		assert_noop!(
+			submit(&last, committee.justify_all(&last), None),
+			Error::<Test>::MissingHandoverProof
+		);
+	});
+}
+
+#[test]
+fn rejects_header_from_unknown_session() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let block = headers_after(&genesis, SESSION_PERIOD + 1).pop().unwrap();
+
+		assert_noop!(
+			submit(&block, committee.justify_all(&block), None),
+			Error::<Test>::UnknownSession
+		);
+	});
+}
+
+#[test]
+fn hands_over_to_next_committee() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let next_committee = Committee::new(50, 7);
+		let genesis = initialize(&committee);
+
+		let mut blocks = headers_after(&genesis, SESSION_PERIOD - 2);
+		let parent = blocks.pop().unwrap();
+		let (state_root, proof) = handover_state(&next_committee);
+		let last = header(SESSION_PERIOD - 1, parent.hash(), state_root);
+
+		assert_noop!(
+			submit(&last, committee.justify_all(&last), Some(vec![vec![1, 2, 3]])),
+			Error::<Test>::InvalidHandoverProof
+		);
+		assert_ok!(submit(&last, committee.justify_all(&last), Some(proof)));
+		assert_eq!(CurrentSession::<Test>::get(), 1);
+
+		let block = headers_after(&last, SESSION_PERIOD + 1).pop().unwrap();
+		assert_noop!(
+			submit(&block, committee.justify_all(&block), None),
+			Error::<Test>::InvalidJustification
+		);
+		assert_ok!(submit(&block, next_committee.justify(&block, &[0, 1, 2, 4, 6]), None));
+	});
+}
+
+#[test]
+fn rejects_initialization_outside_of_session() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = header(0, BlockHash::zero(), BlockHash::zero());
+		let block = headers_after(&genesis, SESSION_PERIOD + 2).pop().unwrap();
+		assert_noop!(
+			AlephLightClient::initialize(
+				RawOrigin::Root.into(),
+				Box::new(block),
+				committee.authority_data(),
+				0,
+				0,
+				SESSION_PERIOD,
+			),
+			Error::<Test>::InvalidSessionStart
+		);
+	});
+}
+
+#[test]
+fn initializes_from_last_block_of_session() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = header(0, BlockHash::zero(), BlockHash::zero());
+		let last = headers_after(&genesis, SESSION_PERIOD - 1).pop().unwrap();
+		assert_ok!(AlephLightClient::initialize(
+			RawOrigin::Root.into(),
+			Box::new(last.clone()),
+			committee.authority_data(),
+			1,
+			SESSION_PERIOD,
+			SESSION_PERIOD,
+		));
+
+		let block = headers_after(&last, SESSION_PERIOD + 1).pop().unwrap();
+		assert_ok!(submit(&block, committee.justify_all(&block), None));
+	});
+}
+
+#[test]
+fn rejects_too_large_committee() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, MaxAuthorities::get() as u8 + 1);
+		let genesis = header(0, BlockHash::zero(), BlockHash::zero());
+		assert_noop!(
+			AlephLightClient::initialize(
+				RawOrigin::Root.into(),
+				Box::new(genesis),
+				committee.authority_data(),
+				0,
+				0,
+				SESSION_PERIOD,
+			),
+			Error::<Test>::TooManyAuthorities
+		);
+	});
+}
+
+#[test]
+fn rejects_handover_to_too_large_committee() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let next_committee = Committee::new(50, MaxAuthorities::get() as u8 + 1);
+		let genesis = initialize(&committee);
+
+		let parent = headers_after(&genesis, SESSION_PERIOD - 2).pop().unwrap();
+		let (state_root, proof) = handover_state(&next_committee);
+		let last = header(SESSION_PERIOD - 1, parent.hash(), state_root);
+
+		assert_noop!(
+			submit(&last, committee.justify_all(&last), Some(proof)),
+			Error::<Test>::TooManyAuthorities
+		);
+	});
+}
+
+#[test]
+fn follows_session_period_changes() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let next_committee = Committee::new(50, 4);
+		let new_period = SESSION_PERIOD + 3;
+		let genesis = initialize(&committee);
+
+		let parent = headers_after(&genesis, SESSION_PERIOD - 2).pop().unwrap();
+		let (state_root, proof) = handover_state_with_period_change(
+			&next_committee,
+			Some(SessionPeriodChange { period: new_period, session: 1 }),
+		);
+		let last = header(SESSION_PERIOD - 1, parent.hash(), state_root);
+		assert_ok!(submit(&last, committee.justify_all(&last), Some(proof)));
+		assert_eq!(CurrentSession::<Test>::get(), 1);
+		assert_eq!(CurrentSessionStart::<Test>::get(), SESSION_PERIOD);
+		assert_eq!(SessionPeriod::<Test>::get(), Some(new_period));
+
+		// With the old period this would already belong to the next session.
+		let mut blocks = headers_after(&last, SESSION_PERIOD + new_period - 1);
+		let last = blocks.pop().unwrap();
+		let block = blocks.pop().unwrap();
+		assert_ok!(submit(&block, next_committee.justify_all(&block), None));
+		assert_noop!(
+			submit(&last, next_committee.justify_all(&last), None),
+			Error::<Test>::MissingHandoverProof
+		);
+	});
+}
+
+#[test]
+fn ignores_period_changes_for_later_sessions() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let next_committee = Committee::new(50, 4);
+		let genesis = initialize(&committee);
+
+		let parent = headers_after(&genesis, SESSION_PERIOD - 2).pop().unwrap();
+		let (state_root, proof) = handover_state_with_period_change(
+			&next_committee,
+			Some(SessionPeriodChange { period: SESSION_PERIOD + 3, session: 2 }),
+		);
+		let last = header(SESSION_PERIOD - 1, parent.hash(), state_root);
+		assert_ok!(submit(&last, committee.justify_all(&last), Some(proof)));
+		assert_eq!(SessionPeriod::<Test>::get(), Some(SESSION_PERIOD));
+	});
+}
+
+#[test]
+fn prunes_old_headers() {
+	new_test_ext().execute_with(|| {
+		let committee = Committee::new(0, 4);
+		let genesis = initialize(&committee);
+		let blocks = headers_after(&genesis, 3);
+		for block in &blocks {
+			assert_ok!(submit(block, committee.justify_all(block), None));
+		}
+
+		assert!(ImportedHeaders::<Test>::get(genesis.hash()).is_none());
+		for block in &blocks {
+			assert!(ImportedHeaders::<Test>::get(block.hash()).is_some());
+		}
+		assert_eq!(
+			BestFinalized::<Test>::get().map(|(number, _)| number),
+			Some(*blocks[2].number())
+		);
+	});
+}
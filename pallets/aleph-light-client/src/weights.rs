@@ -0,0 +1,40 @@
+use frame_support::weights::{constants::WEIGHT_REF_TIME_PER_MICROS, Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for `pallet-aleph-light-client`.
+pub trait WeightInfo {
+	fn initialize() -> Weight;
+	fn submit_finality_proof(authorities: u32) -> Weight;
+}
+
+/// Conservative estimates until the pallet is benchmarked. The cost of a finality proof is
+/// dominated by verifying an ed25519 signature of every committee member and the handover proof.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn initialize() -> Weight {
+		Weight::from_parts(20 * WEIGHT_REF_TIME_PER_MICROS, 0)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(8))
+	}
+
+	fn submit_finality_proof(authorities: u32) -> Weight {
+		Weight::from_parts(200 * WEIGHT_REF_TIME_PER_MICROS, 16 * 1024)
+			.saturating_add(
+				Weight::from_parts(60 * WEIGHT_REF_TIME_PER_MICROS, 0)
+					.saturating_mul(authorities.into()),
+			)
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(8))
+	}
+}
+
+impl WeightInfo for () {
+	fn initialize() -> Weight {
+		Weight::zero()
+	}
+
+	fn submit_finality_proof(_authorities: u32) -> Weight {
+		Weight::zero()
+	}
+}
@@ -3,7 +3,7 @@
 
 use selendra_primitives::{
 	AccountId, ApiError, AuthorityId, SessionAuthorityData, SessionCommittee, SessionIndex,
-	SessionValidatorError, Version,
+	SessionPeriodHistory, SessionValidatorError, Version,
 };
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_std::vec::Vec;
@@ -14,7 +14,11 @@ sp_api::decl_runtime_apis! {
 		fn authorities() -> Vec<AuthorityId>;
 		fn next_session_authority_data() -> Result<SessionAuthorityData, ApiError>;
 		fn authority_data() -> SessionAuthorityData;
+		/// The number of blocks in the current session.
 		fn session_period() -> u32;
+		/// All the changes of the session period, including a scheduled one, from which
+		/// the boundaries of any session can be computed.
+		fn session_period_history() -> SessionPeriodHistory;
 		fn millisecs_per_block() -> u64;
 		fn finality_version() -> Version;
 		fn next_session_finality_version() -> Version;
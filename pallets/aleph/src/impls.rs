@@ -1,8 +1,17 @@
-use selendra_primitives::{FinalityCommitteeManager, SessionIndex};
-use sp_std::vec::Vec;
+use frame_support::{
+	pallet_prelude::Get,
+	sp_runtime::{traits::UniqueSaturatedInto, Permill},
+	traits::EstimateNextSessionRotation,
+	weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use pallet_session::ShouldEndSession;
+use selendra_primitives::{BlockNumber, FinalityCommitteeManager, SessionIndex};
+use sp_std::{marker::PhantomData, vec::Vec};
 
 use crate::{
 	Config, Event, FinalityScheduledVersionChange, FinalityVersion, NextFinalityCommittee, Pallet,
+	SessionPeriodChanges, SessionPeriodScheduledChange, LOG_TARGET,
 };
 
 impl<T> pallet_session::SessionManager<T::AccountId> for Pallet<T>
@@ -24,6 +33,7 @@ where
 	fn start_session(start_index: SessionIndex) {
 		<T as Config>::SessionManager::start_session(start_index);
 		Self::update_version_change_history();
+		Self::update_session_period_history();
 	}
 }
 
@@ -51,6 +61,70 @@ where
 			}
 		}
 	}
+
+	// Check if a scheduled session period change has moved into the past, and if so record it
+	// as applied.
+	fn update_session_period_history() {
+		let current_session = Self::current_session();
+
+		if let Some(scheduled_change) = <SessionPeriodScheduledChange<T>>::get() {
+			if scheduled_change.session == current_session {
+				// Scheduling is refused when there is no room left, so this cannot fail. If it
+				// did, keeping the change scheduled keeps the history intact anyway.
+				if <SessionPeriodChanges<T>>::try_append(scheduled_change).is_err() {
+					log::error!(target: LOG_TARGET, "Too many session period changes.");
+					return;
+				}
+				<SessionPeriodScheduledChange<T>>::kill();
+
+				Self::deposit_event(Event::SessionPeriodChange(scheduled_change));
+			}
+		}
+	}
+}
+
+/// Ends sessions according to the session period history, including the scheduled change.
+impl<T: Config> ShouldEndSession<BlockNumberFor<T>> for Pallet<T> {
+	fn should_end_session(now: BlockNumberFor<T>) -> bool {
+		let now: BlockNumber = now.unique_saturated_into();
+		let history = Self::session_period_history();
+		history.first_block_of_session(history.session_of_block(now)) == now
+	}
+}
+
+impl<T: Config> EstimateNextSessionRotation<BlockNumberFor<T>> for Pallet<T> {
+	fn average_session_length() -> BlockNumberFor<T> {
+		Self::current_session_period().into()
+	}
+
+	fn estimate_current_session_progress(now: BlockNumberFor<T>) -> (Option<Permill>, Weight) {
+		let now: BlockNumber = now.unique_saturated_into();
+		let history = Self::session_period_history();
+		let session = history.session_of_block(now);
+		let current = now - history.first_block_of_session(session) + 1;
+		(
+			Some(Permill::from_rational(current, history.period_of_session(session))),
+			T::DbWeight::get().reads(2),
+		)
+	}
+
+	fn estimate_next_session_rotation(
+		now: BlockNumberFor<T>,
+	) -> (Option<BlockNumberFor<T>>, Weight) {
+		let now: BlockNumber = now.unique_saturated_into();
+		let history = Self::session_period_history();
+		let next_session = history.session_of_block(now) + 1;
+		(Some(history.first_block_of_session(next_session).into()), T::DbWeight::get().reads(2))
+	}
+}
+
+/// The number of blocks in the current session, for pallets that need it as a parameter.
+pub struct CurrentSessionPeriod<T>(PhantomData<T>);
+
+impl<T: Config> Get<u32> for CurrentSessionPeriod<T> {
+	fn get() -> u32 {
+		Pallet::<T>::current_session_period()
+	}
 }
 
 impl<T: Config> FinalityCommitteeManager<T::AccountId> for Pallet<T> {
@@ -13,9 +13,11 @@ use frame_support::{
 	sp_runtime::BoundToRuntimeAppPublic,
 	traits::{OneSessionHandler, StorageVersion},
 };
+pub use impls::CurrentSessionPeriod;
 pub use pallet::*;
 use selendra_primitives::{
-	SessionIndex, Version, VersionChange, DEFAULT_FINALITY_VERSION, LEGACY_FINALITY_VERSION,
+	SessionIndex, SessionPeriodChange, SessionPeriodHistory, Version, VersionChange,
+	DEFAULT_FINALITY_VERSION, LEGACY_FINALITY_VERSION,
 };
 use sp_std::prelude::*;
 
@@ -47,6 +49,13 @@ pub mod pallet {
 		type SessionInfoProvider: SessionInfoProvider<BlockNumberFor<Self>>;
 		type SessionManager: SessionManager<<Self as frame_system::Config>::AccountId>;
 		type NextSessionAuthorityProvider: NextSessionAuthorityProvider<Self>;
+		/// The session period before any changes were applied.
+		#[pallet::constant]
+		type SessionPeriod: Get<u32>;
+		/// How many session period changes can take effect over the lifetime of the chain. The
+		/// whole history is read in every block, so it has to stay small.
+		#[pallet::constant]
+		type MaxSessionPeriodChanges: Get<u32>;
 	}
 
 	#[pallet::event]
@@ -55,6 +64,8 @@ pub mod pallet {
 		ChangeEmergencyFinalizer(T::AuthorityId),
 		ScheduleFinalityVersionChange(VersionChange),
 		FinalityVersionChange(VersionChange),
+		ScheduleSessionPeriodChange(SessionPeriodChange),
+		SessionPeriodChange(SessionPeriodChange),
 	}
 
 	#[pallet::pallet]
@@ -111,6 +122,18 @@ pub mod pallet {
 	pub(super) type FinalityScheduledVersionChange<T: Config> =
 		StorageValue<_, VersionChange, OptionQuery>;
 
+	/// Session period changes that already took effect, in order.
+	#[pallet::storage]
+	#[pallet::getter(fn session_period_changes)]
+	pub(super) type SessionPeriodChanges<T: Config> =
+		StorageValue<_, BoundedVec<SessionPeriodChange, T::MaxSessionPeriodChanges>, ValueQuery>;
+
+	/// Scheduled session period change.
+	#[pallet::storage]
+	#[pallet::getter(fn session_period_change)]
+	pub(super) type SessionPeriodScheduledChange<T: Config> =
+		StorageValue<_, SessionPeriodChange, OptionQuery>;
+
 	impl<T: Config> Pallet<T> {
 		pub(crate) fn initialize_authorities(
 			authorities: &[T::AuthorityId],
@@ -213,6 +236,61 @@ pub mod pallet {
 
 			Self::finality_version()
 		}
+
+		// Works like scheduling finality version changes, a scheduled change can be replaced
+		// until it is less than 2 sessions in the future. To cancel a scheduled change, reschedule
+		// it with the current period.
+		pub(crate) fn do_schedule_session_period_change(
+			session_period_change: SessionPeriodChange,
+		) -> Result<(), &'static str> {
+			let current_session = Self::current_session();
+
+			let session_to_schedule = session_period_change.session;
+
+			if session_period_change.period == 0 {
+				return Err("Session period has to be positive!");
+			} else if <SessionPeriodChanges<T>>::decode_len().unwrap_or(0)
+				>= T::MaxSessionPeriodChanges::get() as usize
+			{
+				return Err("No more session period changes are allowed!");
+			} else if session_to_schedule < current_session {
+				return Err("Cannot schedule session period changes for sessions in the past!");
+			} else if session_to_schedule < current_session + 2 {
+				return Err(
+					"Tried to schedule a session period change less than 2 sessions in advance!",
+				);
+			}
+
+			<SessionPeriodScheduledChange<T>>::put(session_period_change);
+
+			Ok(())
+		}
+
+		/// All the session period changes, including the scheduled one.
+		pub fn session_period_history() -> SessionPeriodHistory {
+			let mut history = SessionPeriodHistory::new(T::SessionPeriod::get());
+			let changes = Self::session_period_changes().into_iter();
+			for change in changes.chain(Self::session_period_change()) {
+				if let Err(e) = history.push(change) {
+					log::error!(target: LOG_TARGET, "Invalid session period change: {}", e);
+				}
+			}
+			history
+		}
+
+		/// The number of blocks in the current session.
+		pub fn current_session_period() -> u32 {
+			Self::session_period_history().period_of_session(Self::current_session())
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_: BlockNumberFor<T>) -> Weight {
+			// `ShouldEndSession` reads `SessionPeriodChanges` and `SessionPeriodScheduledChange`
+			// in every block.
+			T::DbWeight::get().reads(2)
+		}
 	}
 
 	#[pallet::call]
@@ -255,6 +333,31 @@ pub mod pallet {
 			Self::deposit_event(Event::ScheduleFinalityVersionChange(version_change));
 			Ok(())
 		}
+
+		/// Schedules a change of the session period, i.e. the number of blocks in a session,
+		/// starting from the provided session. If such a scheduled change is already set, it is
+		/// replaced with the provided one.
+		/// Any rescheduling needs to occur at least 2 sessions in advance of the provided session.
+		/// In order to cancel a scheduled change, a new change should be scheduled with the
+		/// current session period.
+		#[pallet::call_index(2)]
+		#[pallet::weight((T::BlockWeights::get().max_block, DispatchClass::Operational))]
+		pub fn schedule_session_period_change(
+			origin: OriginFor<T>,
+			period: u32,
+			session: SessionIndex,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let session_period_change = SessionPeriodChange { period, session };
+
+			if let Err(e) = Self::do_schedule_session_period_change(session_period_change) {
+				return Err(DispatchError::Other(e));
+			}
+
+			Self::deposit_event(Event::ScheduleSessionPeriodChange(session_period_change));
+			Ok(())
+		}
 	}
 
 	impl<T: Config> BoundToRuntimeAppPublic for Pallet<T> {
@@ -155,6 +155,8 @@ impl Config for Test {
 	type SessionInfoProvider = SessionInfoImpl;
 	type SessionManager = ();
 	type NextSessionAuthorityProvider = Session;
+	type SessionPeriod = ConstU32<1>;
+	type MaxSessionPeriodChanges = ConstU32<2>;
 }
 
 pub fn to_authority(id: &u64) -> AuthorityId {
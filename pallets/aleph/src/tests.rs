@@ -1,7 +1,11 @@
 #![cfg(test)]
 
-use frame_support::{storage_alias, traits::OneSessionHandler};
-use selendra_primitives::VersionChange;
+use frame_support::{
+	storage_alias,
+	traits::{EstimateNextSessionRotation, OneSessionHandler},
+};
+use pallet_session::ShouldEndSession;
+use selendra_primitives::{SessionPeriodChange, VersionChange};
 
 use crate::{mock::*, NextFinalityCommittee};
 
@@ -157,3 +161,72 @@ fn test_finality_version_scheduling() {
 		assert!(scheduling_result.is_err());
 	})
 }
+
+#[test]
+fn test_session_period_scheduling() {
+	new_test_ext(&[(1u64, 1u64), (2u64, 2u64)]).execute_with(|| {
+		initialize_session();
+
+		run_session(1);
+
+		let zero_period = SessionPeriodChange { period: 0, session: 4 };
+		assert!(Aleph::do_schedule_session_period_change(zero_period).is_err());
+
+		let change_to_schedule = SessionPeriodChange { period: 3, session: 4 };
+		let scheduling_result = Aleph::do_schedule_session_period_change(change_to_schedule);
+		assert_eq!(scheduling_result, Ok(()));
+		assert_eq!(Aleph::session_period_change(), Some(change_to_schedule));
+		assert_eq!(Aleph::session_period_history().changes().len(), 2);
+		assert_eq!(Aleph::current_session_period(), 1);
+
+		run_session(4);
+
+		assert_eq!(Aleph::session_period_change(), None);
+		assert_eq!(Aleph::session_period_changes().into_inner(), vec![change_to_schedule]);
+		assert_eq!(Aleph::current_session_period(), 3);
+
+		let change_to_schedule = SessionPeriodChange { period: 1, session: 5 };
+		let scheduling_result = Aleph::do_schedule_session_period_change(change_to_schedule);
+		assert!(scheduling_result.is_err());
+	})
+}
+
+#[test]
+fn test_session_period_changes_are_bounded() {
+	new_test_ext(&[(1u64, 1u64), (2u64, 2u64)]).execute_with(|| {
+		initialize_session();
+
+		for (period, session) in [(2, 2), (3, 4)] {
+			let change = SessionPeriodChange { period, session };
+			assert_eq!(Aleph::do_schedule_session_period_change(change), Ok(()));
+			run_session(session);
+			assert_eq!(Aleph::current_session_period(), period);
+		}
+
+		let change = SessionPeriodChange { period: 4, session: 7 };
+		assert!(Aleph::do_schedule_session_period_change(change).is_err());
+		assert_eq!(Aleph::session_period_history().changes().len(), 3);
+	})
+}
+
+#[test]
+fn test_session_boundaries_follow_period_changes() {
+	new_test_ext(&[(1u64, 1u64), (2u64, 2u64)]).execute_with(|| {
+		initialize_session();
+
+		let change_to_schedule = SessionPeriodChange { period: 3, session: 4 };
+		assert_eq!(Aleph::do_schedule_session_period_change(change_to_schedule), Ok(()));
+
+		let history = Aleph::session_period_history();
+		assert_eq!(history.first_block_of_session(4), 4);
+		assert_eq!(history.last_block_of_session(4), 6);
+		assert_eq!(history.session_of_block(8), 5);
+
+		assert!(Aleph::should_end_session(3));
+		assert!(Aleph::should_end_session(4));
+		assert!(!Aleph::should_end_session(5));
+		assert!(Aleph::should_end_session(7));
+		assert_eq!(Aleph::estimate_next_session_rotation(5).0, Some(7));
+		assert_eq!(Aleph::estimate_next_session_rotation(2).0, Some(3));
+	})
+}
@@ -338,6 +338,108 @@ pub struct VersionChange {
 	pub session: SessionIndex,
 }
 
+/// A change of the session period, in effect from `session` onwards.
+#[derive(Clone, Copy, Debug, Decode, Encode, PartialEq, Eq, TypeInfo)]
+pub struct SessionPeriodChange {
+	pub period: u32,
+	pub session: SessionIndex,
+}
+
+/// All the changes of the session period, sorted by session. The first change is always for
+/// session 0 and contains the initial session period, so session boundaries can be computed
+/// for any session or block.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, TypeInfo)]
+pub struct SessionPeriodHistory {
+	changes: Vec<SessionPeriodChange>,
+}
+
+impl SessionPeriodHistory {
+	/// A history with no changes after the initial session period.
+	pub fn new(initial_period: u32) -> Self {
+		SessionPeriodHistory {
+			changes: sp_std::vec![SessionPeriodChange { period: initial_period, session: 0 }],
+		}
+	}
+
+	pub fn changes(&self) -> &[SessionPeriodChange] {
+		&self.changes
+	}
+
+	/// Records a change of the session period. The change has to be for a session later than
+	/// all the recorded ones, and the period has to be positive.
+	pub fn push(&mut self, change: SessionPeriodChange) -> Result<(), &'static str> {
+		if change.period == 0 {
+			return Err("Session period has to be positive!");
+		}
+		if let Some(last) = self.changes.last() {
+			if change.session <= last.session {
+				return Err("Session period changes have to be recorded in order!");
+			}
+		}
+		self.changes.push(change);
+		Ok(())
+	}
+
+	/// Whether the history could have been created using `new` and `push`. Histories obtained
+	/// by decoding should be checked before use.
+	pub fn is_valid(&self) -> bool {
+		matches!(self.changes.first(), Some(SessionPeriodChange { session: 0, .. }))
+			&& self.changes.iter().all(|change| change.period > 0)
+			&& self.changes.windows(2).all(|pair| pair[0].session < pair[1].session)
+	}
+
+	/// The changes together with the number of the first block they are in effect for.
+	fn segments(&self) -> impl Iterator<Item = (BlockNumber, SessionPeriodChange)> + '_ {
+		let mut first_block: BlockNumber = 0;
+		let mut previous: Option<SessionPeriodChange> = None;
+		self.changes.iter().map(move |change| {
+			if let Some(previous) = previous {
+				first_block = first_block.saturating_add(
+					change.session.saturating_sub(previous.session).saturating_mul(previous.period),
+				);
+			}
+			previous = Some(*change);
+			(first_block, *change)
+		})
+	}
+
+	fn segment_of_session(&self, session: SessionIndex) -> (BlockNumber, SessionPeriodChange) {
+		self.segments()
+			.take_while(|(_, change)| change.session <= session)
+			.last()
+			.unwrap_or((0, SessionPeriodChange { period: DEFAULT_SESSION_PERIOD, session: 0 }))
+	}
+
+	fn segment_of_block(&self, number: BlockNumber) -> (BlockNumber, SessionPeriodChange) {
+		self.segments()
+			.take_while(|(first_block, _)| *first_block <= number)
+			.last()
+			.unwrap_or((0, SessionPeriodChange { period: DEFAULT_SESSION_PERIOD, session: 0 }))
+	}
+
+	/// The number of blocks in the given session.
+	pub fn period_of_session(&self, session: SessionIndex) -> u32 {
+		self.segment_of_session(session).1.period
+	}
+
+	/// The number of the first block of the given session.
+	pub fn first_block_of_session(&self, session: SessionIndex) -> BlockNumber {
+		let (first_block, change) = self.segment_of_session(session);
+		first_block.saturating_add((session - change.session).saturating_mul(change.period))
+	}
+
+	/// The number of the last block of the given session.
+	pub fn last_block_of_session(&self, session: SessionIndex) -> BlockNumber {
+		self.first_block_of_session(session.saturating_add(1)).saturating_sub(1)
+	}
+
+	/// The session the block with the given number belongs to.
+	pub fn session_of_block(&self, number: BlockNumber) -> SessionIndex {
+		let (first_block, change) = self.segment_of_block(number);
+		change.session.saturating_add((number - first_block) / change.period.max(1))
+	}
+}
+
 pub trait BanHandler {
 	type AccountId;
 	/// returns whether the account can be banned
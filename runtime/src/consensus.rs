@@ -1,4 +1,6 @@
-use crate::{AlephId, AuraId, Elections, Runtime, RuntimeEvent, Session, Staking};
+use crate::{
+	validator::SessionPeriod, AlephId, AuraId, Elections, Runtime, RuntimeEvent, Session, Staking,
+};
 
 pub use frame_support::{parameter_types, traits::EstimateNextSessionRotation, weights::Weight};
 use sp_core::ConstBool;
@@ -9,6 +11,7 @@ use selendra_primitives::{BlockNumber, SessionIndex, SessionInfoProvider};
 
 parameter_types! {
 	pub const MaxAuthorities: u32 = 100;
+	pub const MaxSessionPeriodChanges: u32 = 100;
 }
 
 impl pallet_aura::Config for Runtime {
@@ -42,4 +45,6 @@ impl pallet_aleph::Config for Runtime {
 		Runtime,
 	>;
 	type NextSessionAuthorityProvider = Session;
+	type SessionPeriod = SessionPeriod;
+	type MaxSessionPeriodChanges = MaxSessionPeriodChanges;
 }
@@ -16,7 +16,6 @@ use frame_support::weights::constants::ExtrinsicBaseWeight;
 use frame_support::weights::WeightToFeePolynomial;
 use selendra_primitives::impls::DealWithFees;
 use sp_runtime::FixedPointNumber;
-use validator::SessionPeriod;
 use validator::MAX_NOMINATORS;
 
 use parity_scale_codec::{Decode, Encode};
@@ -60,7 +59,8 @@ use selendra_primitives::{
 	constants::time::*,
 	AccountId, AlephNodeSessionKeys as SessionKeys, ApiError as AlephApiError, AuraId,
 	AuthorityId as AlephId, Balance, BlockNumber, Hash, Nonce, SessionAuthorityData,
-	SessionCommittee, SessionIndex, SessionValidatorError, Signature, Version as FinalityVersion,
+	SessionCommittee, SessionIndex, SessionPeriodHistory, SessionValidatorError, Signature,
+	Version as FinalityVersion,
 };
 
 #[sp_version::runtime_version]
@@ -68,7 +68,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("selendra"),
 	impl_name: create_runtime_str!("selendra"),
 	authoring_version: 1,
-	spec_version: 20001,
+	spec_version: 20002,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -491,7 +491,11 @@ impl_runtime_apis! {
 		}
 
 		fn session_period() -> u32 {
-			SessionPeriod::get()
+			Aleph::current_session_period()
+		}
+
+		fn session_period_history() -> SessionPeriodHistory {
+			Aleph::session_period_history()
 		}
 
 		fn authorities() -> Vec<AlephId> {
@@ -16,16 +16,12 @@ use selendra_primitives::{
 	DEFAULT_SESSION_PERIOD,
 };
 
-parameter_types! {
-	pub const Offset: u32 = 0;
-}
-
 impl pallet_session::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type ValidatorId = <Self as frame_system::Config>::AccountId;
 	type ValidatorIdOf = pallet_staking::StashOf<Self>;
-	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, Offset>;
-	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, Offset>;
+	type ShouldEndSession = Aleph;
+	type NextSessionRotation = Aleph;
 	type SessionManager = Aleph;
 	type SessionHandler = (Aura, Aleph);
 	type Keys = SessionKeys;
@@ -178,6 +174,7 @@ impl pallet_staking::Config for Runtime {
 }
 
 parameter_types! {
+	/// The session period the chain started with, see `pallet_aleph` for changing it.
 	pub const SessionPeriod: u32 = DEFAULT_SESSION_PERIOD;
 	pub const MaximumBanReasonLength: u32 = DEFAULT_BAN_REASON_LENGTH;
 	pub const MaxWinners: u32 = DEFAULT_MAX_WINNERS;
@@ -199,5 +196,5 @@ impl pallet_committee_management::Config for Runtime {
 	type ValidatorRewardsHandler = Staking;
 	type ValidatorExtractor = Staking;
 	type FinalityCommitteeManager = Aleph;
-	type SessionPeriod = SessionPeriod;
+	type SessionPeriod = pallet_aleph::CurrentSessionPeriod<Runtime>;
 }